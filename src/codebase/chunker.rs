@@ -2,8 +2,10 @@ use std::path::Path;
 
 use crate::types::{ChunkType, CodeChunk, Language};
 
+use super::docs::{fenced_lines, parse_outline};
 use super::parser::languages::get_language_support;
 use super::scanner::detect_language;
+use super::structured;
 
 const MAX_CHUNK_CHARS: usize = 4000;
 const MIN_CHUNK_CHARS: usize = 10;
@@ -17,6 +19,14 @@ pub fn chunk_file(path: &Path, content: &str, project_id: &str) -> Vec<CodeChunk
         return vec![];
     }
 
+    if language.is_document() {
+        return chunk_by_sections(content, &file_path, project_id, language);
+    }
+
+    if language.is_structured() {
+        return chunk_by_outline(content, &file_path, project_id, language);
+    }
+
     if let Some(support) = get_language_support(language.clone()) {
         chunk_by_ast(
             content,
@@ -47,46 +57,279 @@ fn chunk_by_ast(
         None => return chunk_by_structure(content, file_path, project_id, language),
     };
 
-    let mut chunks = Vec::new();
-    let root = tree.root_node();
+    let mut chunker = AstChunker {
+        content,
+        file_path,
+        project_id,
+        language: language.clone(),
+        chunks: Vec::new(),
+    };
+    chunker.chunk_children(tree.root_node(), &[]);
 
-    let mut cursor = root.walk();
-    for child in root.children(&mut cursor) {
-        let start_byte = child.start_byte();
-        let end_byte = child.end_byte();
-        let node_text = &content[start_byte..end_byte];
+    if chunker.chunks.is_empty() {
+        return chunk_by_structure(content, file_path, project_id, language);
+    }
 
-        if node_text.len() < MIN_CHUNK_CHARS {
-            continue;
+    chunker.chunks
+}
+
+/// Recursive AST chunker. Definitions (functions, classes, impls) get their
+/// own chunk; oversized containers are descended into so every method becomes
+/// a chunk carrying the container signature as context. Neighbouring
+/// non-definition nodes (imports, fields, statements) are grouped.
+struct AstChunker<'a> {
+    content: &'a str,
+    file_path: &'a str,
+    project_id: &'a str,
+    language: Language,
+    chunks: Vec<CodeChunk>,
+}
+
+impl AstChunker<'_> {
+    fn chunk_children(&mut self, parent: tree_sitter::Node, context: &[String]) {
+        let mut cursor = parent.walk();
+        let children: Vec<tree_sitter::Node> = parent.named_children(&mut cursor).collect();
+
+        // Pending group of non-definition siblings, and where its trailing
+        // comments start (they belong to the next definition if adjacent)
+        let mut group: Option<(usize, usize)> = None;
+        let mut trailing_comments: Option<usize> = None;
+
+        for (idx, child) in children.iter().enumerate() {
+            if !is_definition(child) {
+                group = match group {
+                    Some((first, _))
+                        if child.end_byte() - children[first].start_byte() <= MAX_CHUNK_CHARS =>
+                    {
+                        Some((first, idx))
+                    }
+                    Some(range) => {
+                        self.emit_group(&children, range, context);
+                        trailing_comments = None;
+                        Some((idx, idx))
+                    }
+                    None => Some((idx, idx)),
+                };
+                if is_leading_trivia(child.kind()) {
+                    trailing_comments.get_or_insert(idx);
+                } else {
+                    trailing_comments = None;
+                }
+                continue;
+            }
+
+            // Doc comments and attributes directly above a definition travel with it
+            let mut start = idx;
+            if let (Some((first, _)), Some(comments)) = (group, trailing_comments) {
+                let adjacent =
+                    children[idx - 1].end_position().row + 1 >= child.start_position().row;
+                if adjacent {
+                    start = comments;
+                    group = (comments > first).then(|| (first, comments - 1));
+                }
+            }
+            if let Some(range) = group.take() {
+                self.emit_group(&children, range, context);
+            }
+            trailing_comments = None;
+
+            self.emit_definition(children[start], *child, context);
         }
 
-        if node_text.len() <= MAX_CHUNK_CHARS {
-            chunks.push(create_chunk(
-                node_text,
-                file_path,
-                project_id,
-                language.clone(),
-                child.start_position().row as u32 + 1,
-                child.end_position().row as u32 + 1,
-                detect_chunk_type(&child),
-            ));
-        } else {
-            let sub_chunks = split_large_node(
-                node_text,
-                file_path,
-                project_id,
-                language.clone(),
-                child.start_position().row as u32 + 1,
-            );
-            chunks.extend(sub_chunks);
+        if let Some(range) = group {
+            self.emit_group(&children, range, context);
         }
     }
 
-    if chunks.is_empty() {
-        return chunk_by_structure(content, file_path, project_id, language);
+    fn emit_group(
+        &mut self,
+        children: &[tree_sitter::Node],
+        (first, last): (usize, usize),
+        context: &[String],
+    ) {
+        let (start, end) = (children[first], children[last]);
+        let text = &self.content[start.start_byte()..end.end_byte()];
+        if text.trim().len() < MIN_CHUNK_CHARS {
+            return;
+        }
+        self.push(text, start, end, ChunkType::Other, None, context);
     }
 
-    chunks
+    fn emit_definition(
+        &mut self,
+        start: tree_sitter::Node,
+        node: tree_sitter::Node,
+        context: &[String],
+    ) {
+        let inner = unwrap_definition(node);
+        let name = definition_name(&inner, self.content);
+        let text = &self.content[start.start_byte()..node.end_byte()];
+        if text.trim().len() < MIN_CHUNK_CHARS {
+            return;
+        }
+        if text.len() <= MAX_CHUNK_CHARS {
+            self.push(text, start, node, detect_chunk_type(&inner), name, context);
+            return;
+        }
+
+        if is_container(inner.kind()) {
+            if let Some(body) = container_body(&inner) {
+                // Doc comments / attributes above a large container get a chunk of their own
+                let preamble = &self.content[start.start_byte()..node.start_byte()];
+                if preamble.trim().len() >= MIN_CHUNK_CHARS {
+                    let header_end = body.start_position().row;
+                    let lines: Vec<&str> = self.content.lines().collect();
+                    let first = start.start_position().row;
+                    let text = lines[first..=header_end.min(lines.len() - 1)].join("\n");
+                    let mut chunk =
+                        self.chunk(&text, first, header_end, detect_chunk_type(&inner), context);
+                    chunk.name = name.clone();
+                    self.chunks.push(chunk);
+                }
+
+                let header = self.content[node.start_byte()..body.start_byte()]
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let mut nested = context.to_vec();
+                nested.push(header.chars().take(MAX_CONTEXT_CHARS).collect());
+                self.chunk_children(body, &nested);
+                return;
+            }
+        }
+
+        // Oversized function: fall back to line windows
+        for mut chunk in split_large_node(
+            text,
+            self.file_path,
+            self.project_id,
+            self.language.clone(),
+            start.start_position().row as u32 + 1,
+        ) {
+            chunk.name = name.clone();
+            chunk.context = context_string(context);
+            self.chunks.push(chunk);
+        }
+    }
+
+    fn push(
+        &mut self,
+        text: &str,
+        start: tree_sitter::Node,
+        end: tree_sitter::Node,
+        chunk_type: ChunkType,
+        name: Option<String>,
+        context: &[String],
+    ) {
+        if text.len() > MAX_CHUNK_CHARS {
+            for mut chunk in split_large_node(
+                text,
+                self.file_path,
+                self.project_id,
+                self.language.clone(),
+                start.start_position().row as u32 + 1,
+            ) {
+                chunk.context = context_string(context);
+                self.chunks.push(chunk);
+            }
+            return;
+        }
+        let mut chunk = self.chunk(
+            text,
+            start.start_position().row,
+            end.end_position().row,
+            chunk_type,
+            context,
+        );
+        chunk.name = name;
+        self.chunks.push(chunk);
+    }
+
+    fn chunk(
+        &self,
+        text: &str,
+        start_row: usize,
+        end_row: usize,
+        chunk_type: ChunkType,
+        context: &[String],
+    ) -> CodeChunk {
+        let mut chunk = create_chunk(
+            text,
+            self.file_path,
+            self.project_id,
+            self.language.clone(),
+            start_row as u32 + 1,
+            end_row as u32 + 1,
+            chunk_type,
+        );
+        chunk.context = context_string(context);
+        chunk
+    }
+}
+
+const MAX_CONTEXT_CHARS: usize = 200;
+
+fn context_string(context: &[String]) -> Option<String> {
+    (!context.is_empty()).then(|| context.join("\n"))
+}
+
+/// Containers whose members are chunked individually when the whole is too large.
+fn is_container(kind: &str) -> bool {
+    matches!(
+        kind,
+        "impl_item"
+            | "trait_item"
+            | "mod_item"
+            | "class_definition"
+            | "class_declaration"
+            | "abstract_class_declaration"
+            | "class"
+            | "interface_declaration"
+            | "enum_declaration"
+            | "record_declaration"
+            | "internal_module"
+            | "module"
+            | "mixin_declaration"
+            | "extension_declaration"
+    )
+}
+
+fn is_leading_trivia(kind: &str) -> bool {
+    kind.contains("comment") || matches!(kind, "attribute_item" | "decorator" | "annotation")
+}
+
+fn is_definition(node: &tree_sitter::Node) -> bool {
+    let inner = unwrap_definition(*node);
+    is_container(inner.kind()) || detect_chunk_type(&inner) != ChunkType::Other
+}
+
+/// Look through `export` / decorator wrappers to the declaration inside.
+fn unwrap_definition(node: tree_sitter::Node) -> tree_sitter::Node {
+    match node.kind() {
+        "export_statement" | "decorated_definition" => node
+            .child_by_field_name("declaration")
+            .or_else(|| node.child_by_field_name("definition"))
+            .unwrap_or(node),
+        _ => node,
+    }
+}
+
+fn container_body<'t>(node: &tree_sitter::Node<'t>) -> Option<tree_sitter::Node<'t>> {
+    node.child_by_field_name("body").or_else(|| {
+        let mut cursor = node.walk();
+        node.named_children(&mut cursor)
+            .filter(|n| {
+                n.kind().ends_with("_body") || matches!(n.kind(), "declaration_list" | "block")
+            })
+            .last()
+    })
+}
+
+fn definition_name(node: &tree_sitter::Node, content: &str) -> Option<String> {
+    node.child_by_field_name("name")
+        .or_else(|| node.child_by_field_name("type"))
+        .and_then(|n| n.utf8_text(content.as_bytes()).ok())
+        .map(str::to_string)
 }
 
 fn chunk_by_structure(
@@ -141,6 +384,159 @@ fn chunk_by_structure(
     chunks
 }
 
+/// Chunk documentation by heading hierarchy. The heading breadcrumb becomes the
+/// chunk name; oversized sections are split on blank lines outside fenced code.
+fn chunk_by_sections(
+    content: &str,
+    file_path: &str,
+    project_id: &str,
+    language: Language,
+) -> Vec<CodeChunk> {
+    let lines: Vec<&str> = content.lines().collect();
+    let fenced = fenced_lines(&lines, &language);
+    let sections = parse_outline(content, &language);
+
+    let mut spans: Vec<Span> = Vec::new();
+    let preamble_end = sections
+        .first()
+        .map(|s| s.start_line as usize - 1)
+        .unwrap_or(lines.len());
+    if preamble_end > 0 {
+        spans.push((None, 0, preamble_end, ChunkType::Section));
+    }
+    for section in &sections {
+        let body = &lines[section.heading_end_line as usize..section.body_end_line as usize];
+        if body.iter().all(|l| l.trim().is_empty()) {
+            continue;
+        }
+        spans.push((
+            Some(section.breadcrumb_name()),
+            section.start_line as usize - 1,
+            section.body_end_line as usize,
+            ChunkType::Section,
+        ));
+    }
+
+    chunk_spans(&lines, &fenced, spans, file_path, project_id, language)
+}
+
+/// Chunk config and schema files by top-level key, table or definition.
+/// Falls back to paragraph chunking when no structure is recognised.
+fn chunk_by_outline(
+    content: &str,
+    file_path: &str,
+    project_id: &str,
+    language: Language,
+) -> Vec<CodeChunk> {
+    let sections = structured::outline(content, &language);
+    if sections.is_empty() {
+        return chunk_by_structure(content, file_path, project_id, language);
+    }
+
+    let lines: Vec<&str> = content.lines().collect();
+    let fenced = vec![false; lines.len()];
+    let spans = sections
+        .into_iter()
+        .map(|s| {
+            (
+                s.name,
+                s.start_line as usize - 1,
+                s.end_line as usize,
+                s.chunk_type,
+            )
+        })
+        .collect();
+
+    chunk_spans(&lines, &fenced, spans, file_path, project_id, language)
+}
+
+/// Named line range: (name, first line index, end line index exclusive, type).
+type Span = (Option<String>, usize, usize, ChunkType);
+
+/// Turn named line ranges into chunks, splitting oversized ranges on blank lines.
+fn chunk_spans(
+    lines: &[&str],
+    fenced: &[bool],
+    spans: Vec<Span>,
+    file_path: &str,
+    project_id: &str,
+    language: Language,
+) -> Vec<CodeChunk> {
+    let mut chunks = Vec::new();
+    for (name, start, end, chunk_type) in spans {
+        for (block_start, block_end) in split_section(lines, fenced, start, end) {
+            let text = lines[block_start..block_end].join("\n");
+            if text.trim().len() < MIN_CHUNK_CHARS {
+                continue;
+            }
+            let mut chunk = create_chunk(
+                &text,
+                file_path,
+                project_id,
+                language.clone(),
+                block_start as u32 + 1,
+                block_end as u32,
+                chunk_type.clone(),
+            );
+            chunk.name = name.clone();
+            chunks.push(chunk);
+        }
+    }
+
+    chunks
+}
+
+/// Group a section's paragraphs into blocks of at most `MAX_CHUNK_CHARS`.
+/// Returns `(start, end)` line index ranges, end exclusive.
+fn split_section(lines: &[&str], fenced: &[bool], start: usize, end: usize) -> Vec<(usize, usize)> {
+    let mut units = Vec::new();
+    let mut unit_start = start;
+    for idx in start..end {
+        if lines[idx].trim().is_empty() && !fenced[idx] {
+            if idx > unit_start {
+                units.push((unit_start, idx));
+            }
+            unit_start = idx + 1;
+        }
+    }
+    if end > unit_start {
+        units.push((unit_start, end));
+    }
+
+    let size = |(a, b): (usize, usize)| lines[a..b].iter().map(|l| l.len() + 1).sum::<usize>();
+
+    let mut blocks = Vec::new();
+    let mut current: Option<(usize, usize)> = None;
+    for unit in units {
+        if size(unit) > MAX_CHUNK_CHARS {
+            // A single paragraph or code block that is too large on its own
+            if let Some(block) = current.take() {
+                blocks.push(block);
+            }
+            let mut s = unit.0;
+            while s < unit.1 {
+                let e = (s + MAX_CHUNK_LINES).min(unit.1);
+                blocks.push((s, e));
+                s = e;
+            }
+            continue;
+        }
+        current = match current {
+            Some((cs, _)) if size((cs, unit.1)) <= MAX_CHUNK_CHARS => Some((cs, unit.1)),
+            Some(block) => {
+                blocks.push(block);
+                Some(unit)
+            }
+            None => Some(unit),
+        };
+    }
+    if let Some(block) = current {
+        blocks.push(block);
+    }
+
+    blocks
+}
+
 fn split_large_node(
     text: &str,
     file_path: &str,
@@ -164,7 +560,7 @@ fn split_large_node(
                 project_id,
                 language.clone(),
                 base_line + current_start as u32,
-                base_line + end as u32,
+                base_line + end as u32 - 1,
                 ChunkType::Other,
             ));
         }
@@ -195,6 +591,7 @@ fn create_chunk(
         end_line,
         chunk_type,
         name: None,
+        context: None,
         embedding: None,
         content_hash,
         project_id: Some(project_id.to_string()),
@@ -204,12 +601,76 @@ fn create_chunk(
 
 fn detect_chunk_type(node: &tree_sitter::Node) -> ChunkType {
     match node.kind() {
-        "function_item" | "function_definition" | "function_declaration" | "method_definition" => {
-            ChunkType::Function
-        }
+        "function_item"
+        | "function_definition"
+        | "function_declaration"
+        | "method_definition"
+        | "method_declaration"
+        | "constructor_declaration" => ChunkType::Function,
         "struct_item" | "class_definition" | "class_declaration" => ChunkType::Class,
         "impl_item" | "trait_item" | "interface_declaration" => ChunkType::Class,
         "mod_item" | "module" => ChunkType::Module,
         _ => ChunkType::Other,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_markdown_chunks_by_section_with_breadcrumb() {
+        let content = "# Guide\n\nIntro paragraph.\n\n## Build\n\n```sh\n# compile\ncargo build --release\n```\n\n## Empty\n\n### Leaf\n\nLeaf body text.\n";
+        let chunks = chunk_file(&PathBuf::from("README.md"), content, "p");
+
+        let names: Vec<_> = chunks.iter().map(|c| c.name.clone().unwrap()).collect();
+        assert_eq!(
+            names,
+            vec!["Guide", "Guide > Build", "Guide > Empty > Leaf"]
+        );
+        assert!(chunks.iter().all(|c| c.chunk_type == ChunkType::Section));
+        assert_eq!(chunks[0].language, Language::Markdown);
+
+        // Fenced block stays inside its section
+        assert!(chunks[1].content.contains("# compile"));
+        assert!(chunks[1].content.contains("cargo build"));
+        assert_eq!((chunks[1].start_line, chunks[1].end_line), (5, 10));
+    }
+
+    #[test]
+    fn test_large_impl_chunks_per_method_with_context() {
+        let mut content = String::from("use std::fmt;\n\nstruct Validator;\n\nimpl Validator {\n");
+        for i in 0..60 {
+            content.push_str(&format!(
+                "    /// Check number {i}\n    fn check_{i}(&self, input: &str) -> bool {{\n        input.len() > {i} && input.starts_with(\"prefix\")\n    }}\n\n"
+            ));
+        }
+        content.push_str("}\n");
+
+        let chunks = chunk_file(&PathBuf::from("validator.rs"), &content, "p");
+        let methods: Vec<_> = chunks
+            .iter()
+            .filter(|c| c.chunk_type == ChunkType::Function)
+            .collect();
+
+        // Every method is covered, none truncated or merged into line windows
+        assert_eq!(methods.len(), 60);
+        assert_eq!(methods[0].name.as_deref(), Some("check_0"));
+        assert_eq!(methods[0].context.as_deref(), Some("impl Validator"));
+        assert!(methods[0].content.starts_with("/// Check number 0"));
+        assert_eq!((methods[0].start_line, methods[0].end_line), (6, 9));
+        assert_eq!(methods[59].name.as_deref(), Some("check_59"));
+    }
+
+    #[test]
+    fn test_toml_chunks_by_table() {
+        let content = "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n\n[dependencies]\nserde = { version = \"1\", features = [\"derive\"] }\n";
+        let chunks = chunk_file(&PathBuf::from("Cargo.toml"), content, "p");
+
+        let names: Vec<_> = chunks.iter().map(|c| c.name.clone().unwrap()).collect();
+        assert_eq!(names, vec!["package", "dependencies"]);
+        assert_eq!(chunks[0].language, Language::Toml);
+        assert_eq!((chunks[1].start_line, chunks[1].end_line), (5, 6));
+    }
+}
@@ -0,0 +1,246 @@
+//! Near-duplicate code detection over chunk embeddings.
+//!
+//! Embeddings find chunks that mean the same thing; a token-level check
+//! then keeps only pairs that are also written the same way, so two
+//! unrelated parsers that merely both "parse a header" are not clones.
+
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use crate::types::CodeChunk;
+
+/// Cosine similarity of two vectors, 0 for mismatched or zero vectors.
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let (mut dot, mut norm_a, mut norm_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+/// Code tokens: identifiers, numbers and single punctuation characters.
+/// Whitespace and layout are ignored.
+pub fn tokens(text: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        let word = c.is_alphanumeric() || c == '_';
+        match (word, start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                out.push(&text[s..i]);
+                start = None;
+            }
+            _ => {}
+        }
+        if !word && !c.is_whitespace() {
+            out.push(&text[i..i + c.len_utf8()]);
+        }
+    }
+    if let Some(s) = start {
+        out.push(&text[s..]);
+    }
+    out
+}
+
+/// Token multiset counts, for [`token_similarity`].
+pub fn token_counts<'a>(tokens: &[&'a str]) -> HashMap<&'a str, u32> {
+    let mut counts = HashMap::new();
+    for token in tokens {
+        *counts.entry(*token).or_default() += 1;
+    }
+    counts
+}
+
+/// Weighted Jaccard similarity of two token multisets, from 0 to 1. It is
+/// at most the ratio of the smaller token count to the larger.
+pub fn token_similarity(a: &HashMap<&str, u32>, b: &HashMap<&str, u32>) -> f32 {
+    let (mut shared, mut total) = (0u32, 0u32);
+    for (token, &count) in a {
+        let other = b.get(token).copied().unwrap_or(0);
+        shared += count.min(other);
+        total += count.max(other);
+    }
+    total += b
+        .iter()
+        .filter(|(token, _)| !a.contains_key(*token))
+        .map(|(_, &count)| count)
+        .sum::<u32>();
+    if total == 0 {
+        return 0.0;
+    }
+    shared as f32 / total as f32
+}
+
+/// Chunks that are copies of each other, with the mean similarity of the
+/// confirmed pairs that joined them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CloneGroup {
+    /// Indexes into the input chunks, in input order
+    pub members: Vec<usize>,
+    pub similarity: f32,
+    pub token_similarity: f32,
+}
+
+/// Group chunks whose embeddings are at least `threshold` similar and whose
+/// tokens are at least `min_token_similarity` similar. Chunks without an
+/// embedding, shorter than `min_lines`, or overlapping in the same file are
+/// never paired. Largest groups first.
+pub fn find_clone_groups(
+    chunks: &[CodeChunk],
+    threshold: f32,
+    min_token_similarity: f32,
+    min_lines: u32,
+) -> Vec<CloneGroup> {
+    let candidates: Vec<(usize, &[f32], Vec<&str>)> = chunks
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| c.end_line + 1 - c.start_line.min(c.end_line) >= min_lines)
+        .filter_map(|(i, c)| Some((i, c.embedding.as_deref()?, tokens(&c.content))))
+        .filter(|(_, _, tokens)| !tokens.is_empty())
+        .collect();
+    // Shortest first: a chunk only needs comparing with the longer chunks
+    // whose length still allows `min_token_similarity`
+    let mut order: Vec<usize> = (0..candidates.len()).collect();
+    order.sort_by_key(|&i| candidates[i].2.len());
+    let counts: Vec<HashMap<&str, u32>> = candidates.iter().map(|c| token_counts(&c.2)).collect();
+
+    let pairs: Vec<(usize, usize, f32, f32)> = (0..order.len())
+        .into_par_iter()
+        .flat_map_iter(|a| {
+            let (i, len) = (order[a], candidates[order[a]].2.len());
+            let (candidates, counts, chunks) = (&candidates, &counts, chunks);
+            order[a + 1..]
+                .iter()
+                .take_while(move |&&j| {
+                    len as f32 >= min_token_similarity * candidates[j].2.len() as f32
+                })
+                .filter_map(move |&j| {
+                    let (x, y) = (&chunks[candidates[i].0], &chunks[candidates[j].0]);
+                    if x.file_path == y.file_path
+                        && x.start_line <= y.end_line
+                        && y.start_line <= x.end_line
+                    {
+                        return None;
+                    }
+                    let similarity = cosine(candidates[i].1, candidates[j].1);
+                    if similarity < threshold {
+                        return None;
+                    }
+                    let tokens = token_similarity(&counts[i], &counts[j]);
+                    (tokens >= min_token_similarity).then_some((i, j, similarity, tokens))
+                })
+        })
+        .collect();
+
+    let mut parent: Vec<usize> = (0..candidates.len()).collect();
+    for &(i, j, _, _) in &pairs {
+        let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+        parent[a.max(b)] = a.min(b);
+    }
+
+    let mut groups: HashMap<usize, (Vec<usize>, f32, f32, usize)> = HashMap::new();
+    for &(i, j, similarity, tokens) in &pairs {
+        let entry = groups.entry(root(&mut parent, i)).or_default();
+        entry.0.extend([candidates[i].0, candidates[j].0]);
+        entry.1 += similarity;
+        entry.2 += tokens;
+        entry.3 += 1;
+    }
+    let mut groups: Vec<CloneGroup> = groups
+        .into_values()
+        .map(|(mut members, similarity, tokens, pairs)| {
+            members.sort_unstable();
+            members.dedup();
+            CloneGroup {
+                members,
+                similarity: similarity / pairs as f32,
+                token_similarity: tokens / pairs as f32,
+            }
+        })
+        .collect();
+    groups.sort_by(|a, b| {
+        b.members
+            .len()
+            .cmp(&a.members.len())
+            .then(b.similarity.total_cmp(&a.similarity))
+            .then(a.members.cmp(&b.members))
+    });
+    groups
+}
+
+/// Union-find root, halving the path on the way.
+fn root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ChunkType, Language};
+
+    fn chunk(path: &str, lines: (u32, u32), content: &str, embedding: Vec<f32>) -> CodeChunk {
+        CodeChunk {
+            id: None,
+            file_path: path.to_string(),
+            content: content.to_string(),
+            language: Language::Rust,
+            start_line: lines.0,
+            end_line: lines.1,
+            chunk_type: ChunkType::Function,
+            name: None,
+            context: None,
+            embedding: Some(embedding),
+            content_hash: String::new(),
+            project_id: Some("p".to_string()),
+            indexed_at: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_tokens() {
+        assert_eq!(
+            tokens("fn add(a: u32) -> u32 {\n    a + 1\n}"),
+            vec!["fn", "add", "(", "a", ":", "u32", ")", "-", ">", "u32", "{", "a", "+", "1", "}"]
+        );
+        let (a, b) = (tokens("a + b"), tokens("a + c"));
+        assert_eq!(token_similarity(&token_counts(&a), &token_counts(&b)), 0.5);
+        assert_eq!(cosine(&[1.0, 0.0], &[2.0, 0.0]), 1.0);
+        assert_eq!(cosine(&[1.0, 0.0], &[0.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    fn test_find_clone_groups() {
+        let add = "fn add(a: u32, b: u32) -> u32 {\n    let sum = a + b;\n    sum\n}";
+        let renamed = "fn plus(a: u32, b: u32) -> u32 {\n    let sum = a + b;\n    sum\n}";
+        let other = "fn greet(name: &str) -> String {\n    format!(\"hi {}\", name)\n}";
+        let chunks = vec![
+            chunk("a.rs", (1, 4), add, vec![1.0, 0.0, 0.0]),
+            chunk("b.rs", (10, 13), renamed, vec![0.99, 0.1, 0.0]),
+            // Same meaning by embedding, different code
+            chunk("c.rs", (1, 3), other, vec![0.98, 0.0, 0.1]),
+            // Overlaps the first chunk in the same file
+            chunk("a.rs", (2, 4), add, vec![1.0, 0.0, 0.0]),
+            chunk("d.rs", (1, 4), add, vec![0.0, 1.0, 0.0]),
+        ];
+        let groups = find_clone_groups(&chunks, 0.9, 0.6, 3);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].members, vec![0, 1, 3]);
+        assert!(groups[0].similarity > 0.9);
+        assert!(groups[0].token_similarity > 0.8);
+
+        assert!(find_clone_groups(&chunks, 0.9, 0.6, 5).is_empty());
+    }
+}
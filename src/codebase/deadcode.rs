@@ -0,0 +1,592 @@
+//! Unreferenced symbol detection over the stored symbol graph.
+//!
+//! A symbol is unreferenced when no calls, imports, implements, extends or
+//! references edge points at it. Code reached from outside the project
+//! (entry points, tests, public API, trait impls, framework hooks) has no
+//! such edges either, so it is excluded by looking at names, declaration
+//! lines and the attributes or decorators above them.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+use crate::codebase::detect_language;
+use crate::graph::is_test_symbol;
+use crate::types::{
+    record_id_string, CodeRelationType, CodeSymbol, Language, SymbolRelation, SymbolType,
+};
+
+/// Names run by a runtime rather than called from project code.
+pub const DEFAULT_ENTRY_POINTS: &[&str] = &["main", "init", "constructor"];
+
+/// Edges that count as a use of their target.
+pub const USE_RELATIONS: &[CodeRelationType] = &[
+    CodeRelationType::Calls,
+    CodeRelationType::Imports,
+    CodeRelationType::Implements,
+    CodeRelationType::Extends,
+    CodeRelationType::References,
+];
+
+/// Attributes and decorators that say nothing about how code is reached.
+const INERT_ANNOTATIONS: &[&str] = &[
+    "allow",
+    "cfg",
+    "cfg_attr",
+    "classmethod",
+    "dataclass",
+    "deny",
+    "deprecated",
+    "Deprecated",
+    "derive",
+    "doc",
+    "expect",
+    "inline",
+    "must_use",
+    "non_exhaustive",
+    "repr",
+    "staticmethod",
+    "SuppressWarnings",
+    "warn",
+];
+
+const TEST_ANNOTATIONS: &[&str] = &[
+    "test",
+    "rstest",
+    "Test",
+    "ParameterizedTest",
+    "RepeatedTest",
+    "TestFactory",
+];
+
+#[derive(Debug, Clone)]
+pub struct DeadCodeOptions {
+    /// Names or qualified names that are always reachable
+    pub entry_points: Vec<String>,
+    pub include_tests: bool,
+    pub include_public: bool,
+    pub include_annotated: bool,
+}
+
+impl Default for DeadCodeOptions {
+    fn default() -> Self {
+        Self {
+            entry_points: DEFAULT_ENTRY_POINTS.iter().map(|s| s.to_string()).collect(),
+            include_tests: false,
+            include_public: false,
+            include_annotated: false,
+        }
+    }
+}
+
+/// Why an unreferenced symbol was left out of the report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Exclusion {
+    EntryPoint,
+    Test,
+    TraitImpl,
+    Public,
+    Annotated,
+}
+
+/// How likely an unreferenced symbol is really unused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+impl Confidence {
+    /// From the share of in-project references that resolved to a single
+    /// definition. Ambiguous references may point at any candidate, so the
+    /// more of them, the less a missing edge means.
+    pub fn from_resolution(resolved: usize, ambiguous: usize) -> Self {
+        let total = resolved + ambiguous;
+        if total == 0 {
+            return Confidence::Low;
+        }
+        match resolved as f32 / total as f32 {
+            ratio if ratio >= 0.95 => Confidence::High,
+            ratio if ratio >= 0.8 => Confidence::Medium,
+            _ => Confidence::Low,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DeadCodeReport {
+    /// Indexes into the analysed symbols, in input order
+    pub unreferenced: Vec<(usize, Confidence)>,
+    pub excluded: BTreeMap<Exclusion, usize>,
+}
+
+/// Find symbols nothing in the project uses.
+///
+/// `relations` holds the project's [`USE_RELATIONS`] and `contains` edges;
+/// `sources` maps file paths to their text, for declaration lines and
+/// annotations (files without one fall back to the signature).
+/// `ambiguous` names had references that could not be pinned to one
+/// definition, so symbols with those names get low confidence.
+pub fn find_unreferenced(
+    symbols: &[CodeSymbol],
+    relations: &[SymbolRelation],
+    sources: &HashMap<String, String>,
+    ambiguous: &HashSet<String>,
+    confidence: Confidence,
+    options: &DeadCodeOptions,
+) -> DeadCodeReport {
+    let ids: Vec<String> = symbols
+        .iter()
+        .map(|s| format!("code_symbols:{}", s.unique_key()))
+        .collect();
+    let by_id: HashMap<&str, usize> = ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| (id.as_str(), i))
+        .collect();
+
+    let mut used = HashSet::new();
+    let mut container: HashMap<String, String> = HashMap::new();
+    let mut members: HashMap<String, Vec<String>> = HashMap::new();
+    let mut supertypes: HashMap<String, Vec<String>> = HashMap::new();
+    for relation in relations {
+        let (from, to) = (
+            record_id_string(&relation.from_symbol),
+            record_id_string(&relation.to_symbol),
+        );
+        match relation.relation_type {
+            CodeRelationType::Contains => {
+                members.entry(from.clone()).or_default().push(to.clone());
+                container.insert(to, from);
+                continue;
+            }
+            CodeRelationType::Implements | CodeRelationType::Extends => {
+                supertypes.entry(from.clone()).or_default().push(to.clone());
+            }
+            _ => {}
+        }
+        // Recursion is not a use
+        if from != to {
+            used.insert(to);
+        }
+    }
+
+    // A type and its impl blocks are one group, used when any part or any
+    // member of it is
+    let group_of = |i: usize| {
+        symbols[i]
+            .qualified_name
+            .as_deref()
+            .unwrap_or(&symbols[i].name)
+    };
+    let mut used_groups = HashSet::new();
+    for (i, id) in ids.iter().enumerate() {
+        if is_type(symbols[i].symbol_type)
+            && (used.contains(id)
+                || members
+                    .get(id)
+                    .into_iter()
+                    .flatten()
+                    .any(|m| used.contains(m)))
+        {
+            used_groups.insert(group_of(i));
+        }
+    }
+
+    let mut report = DeadCodeReport::default();
+    let mut reported_groups = HashSet::new();
+    for (i, symbol) in symbols.iter().enumerate() {
+        if !is_candidate(symbol.symbol_type) || used.contains(&ids[i]) {
+            continue;
+        }
+        if is_type(symbol.symbol_type)
+            && (used_groups.contains(group_of(i)) || !reported_groups.insert(group_of(i)))
+        {
+            continue;
+        }
+
+        let language = detect_language(Path::new(&symbol.file_path));
+        let (annotations, declaration) = declaration_of(symbol, sources);
+        let parent = container.get(&ids[i]).and_then(|id| by_id.get(id.as_str()));
+
+        // Whether the symbol overrides a member of a supertype of its type;
+        // `None` when those members are not indexed (TypeScript interfaces)
+        let overrides = match container
+            .get(&ids[i])
+            .and_then(|owner| supertypes.get(owner))
+        {
+            None => Some(false),
+            // Everything in `impl Trait for T` belongs to the trait
+            Some(_) if language == Language::Rust => Some(true),
+            Some(supers) => {
+                let mut known = supers
+                    .iter()
+                    .flat_map(|s| members.get(s))
+                    .flatten()
+                    .peekable();
+                known.peek().is_some().then(|| {
+                    known.any(|m| {
+                        by_id
+                            .get(m.as_str())
+                            .is_some_and(|&j| symbols[j].name == symbol.name)
+                    })
+                })
+            }
+        };
+        let (test_annotations, annotated): (Vec<&str>, Vec<&str>) = annotations
+            .iter()
+            .map(|a| a.rsplit([':', '.']).next().unwrap_or(a))
+            .filter(|a| !INERT_ANNOTATIONS.contains(a))
+            .partition(|a| TEST_ANNOTATIONS.contains(a));
+
+        let exclusion = if options
+            .entry_points
+            .iter()
+            .any(|e| *e == symbol.name || symbol.qualified_name.as_ref() == Some(e))
+            || symbol.name.len() > 4 && symbol.name.starts_with("__") && symbol.name.ends_with("__")
+        {
+            Some(Exclusion::EntryPoint)
+        } else if !options.include_tests && (is_test_symbol(symbol) || !test_annotations.is_empty())
+        {
+            Some(Exclusion::Test)
+        } else if overrides == Some(true) || annotated.contains(&"Override") {
+            Some(Exclusion::TraitImpl)
+        } else if !options.include_public
+            && is_public(
+                language,
+                &declaration,
+                &symbol.name,
+                parent.map(|&p| &symbols[p]),
+                sources,
+            )
+        {
+            Some(Exclusion::Public)
+        } else if !options.include_annotated && !annotated.is_empty() {
+            Some(Exclusion::Annotated)
+        } else {
+            None
+        };
+        match exclusion {
+            Some(exclusion) => *report.excluded.entry(exclusion).or_default() += 1,
+            None => {
+                let confidence = if ambiguous.contains(&symbol.name) || overrides.is_none() {
+                    Confidence::Low
+                } else {
+                    confidence
+                };
+                report.unreferenced.push((i, confidence));
+            }
+        }
+    }
+    report
+}
+
+fn is_type(symbol_type: SymbolType) -> bool {
+    matches!(
+        symbol_type,
+        SymbolType::Class
+            | SymbolType::Struct
+            | SymbolType::Enum
+            | SymbolType::Interface
+            | SymbolType::Trait
+    )
+}
+
+fn is_candidate(symbol_type: SymbolType) -> bool {
+    is_type(symbol_type) || matches!(symbol_type, SymbolType::Function | SymbolType::Method)
+}
+
+/// Annotations and declaration line of a symbol, from its file or, without
+/// one, its signature.
+fn declaration_of(symbol: &CodeSymbol, sources: &HashMap<String, String>) -> (Vec<String>, String) {
+    match sources.get(&symbol.file_path) {
+        Some(text) => declaration(text, symbol.start_line),
+        None => (Vec::new(), symbol.signature.clone().unwrap_or_default()),
+    }
+}
+
+/// Annotation names (`test`, `tokio::test`, `app.route`, `Override`) on the
+/// definition starting at 1-based `start_line`, and its declaration line.
+/// Definitions may start at their first decorator or after their attributes.
+fn declaration(text: &str, start_line: u32) -> (Vec<String>, String) {
+    let lines: Vec<&str> = text.lines().collect();
+    let start = (start_line as usize).saturating_sub(1).min(lines.len());
+    let mut annotations = Vec::new();
+    for line in lines[..start].iter().rev() {
+        let line = line.trim();
+        match annotation(line) {
+            Some(name) => annotations.push(name),
+            None if is_comment(line) => {}
+            None => break,
+        }
+    }
+    let mut declaration = String::new();
+    for line in &lines[start..] {
+        let line = line.trim();
+        match annotation(line) {
+            Some(name) => annotations.push(name),
+            None if is_comment(line) || line.is_empty() => {}
+            None => {
+                declaration = line.to_string();
+                break;
+            }
+        }
+    }
+    (annotations, declaration)
+}
+
+fn annotation(line: &str) -> Option<String> {
+    let body = line.strip_prefix("#[").or_else(|| line.strip_prefix('@'))?;
+    let name: String = body
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || matches!(c, '_' | ':' | '.'))
+        .collect();
+    (!name.is_empty()).then_some(name)
+}
+
+fn is_comment(line: &str) -> bool {
+    line.starts_with("//")
+        || line.starts_with("/*")
+        || line.starts_with('*')
+        || line.starts_with('#') && !line.starts_with("#[")
+}
+
+/// Whether a definition is part of its file's public API, by the
+/// language's visibility markers. Members are public only inside a public
+/// type.
+fn is_public(
+    language: Language,
+    declaration: &str,
+    name: &str,
+    parent: Option<&CodeSymbol>,
+    sources: &HashMap<String, String>,
+) -> bool {
+    let exported = match language {
+        // `pub(crate)` and friends stay inside the crate
+        Language::Rust => declaration.starts_with("pub "),
+        Language::TypeScript | Language::JavaScript => {
+            declaration.starts_with("export ")
+                || parent.is_some()
+                    && !declaration.starts_with("private ")
+                    && !declaration.starts_with("protected ")
+                    && !declaration.starts_with('#')
+        }
+        Language::Java => {
+            declaration.starts_with("public ") || declaration.starts_with("protected ")
+        }
+        Language::Go => name.starts_with(|c: char| c.is_uppercase()),
+        Language::Dart => !name.starts_with('_'),
+        _ => false,
+    };
+    match parent {
+        Some(parent) if exported && language != Language::Rust => {
+            let (_, declaration) = declaration_of(parent, sources);
+            is_public(language, &declaration, &parent.name, None, sources)
+        }
+        _ => exported,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{edge, symbol};
+
+    const SOURCE: &str = r#"fn main() { used(); }
+fn used() { used(); }
+fn unused() {}
+pub fn api() {}
+#[get("/")]
+fn handler() {}
+/// Docs
+#[inline]
+fn helper() {}
+trait Store { fn get(&self); }
+struct Mem;
+impl Store for Mem {
+    fn get(&self) {}
+}
+impl Mem {
+    fn extra(&self) {}
+}
+#[test]
+fn checks() {}
+"#;
+
+    #[test]
+    fn test_find_unreferenced() {
+        use CodeRelationType::{Calls, Contains, Implements};
+        use SymbolType::{Class, Function, Struct, Trait};
+        let symbols = vec![
+            symbol("main", "main", Function, (1, 1)),
+            symbol("used", "used", Function, (2, 2)),
+            symbol("unused", "unused", Function, (3, 3)),
+            symbol("api", "api", Function, (4, 4)),
+            symbol("handler", "handler", Function, (6, 6)),
+            symbol("helper", "helper", Function, (9, 9)),
+            symbol("store", "Store", Trait, (10, 10)),
+            symbol("mem", "Mem", Struct, (11, 11)),
+            symbol("impl", "Mem", Class, (12, 14)),
+            symbol("get", "get", Function, (13, 13)),
+            symbol("inherent", "Mem", Class, (15, 17)),
+            symbol("extra", "extra", Function, (16, 16)),
+            symbol("checks", "checks", Function, (19, 19)),
+        ];
+        let relations = vec![
+            edge("main", "used", Calls),
+            edge("used", "used", Calls),
+            edge("impl", "store", Implements),
+            edge("impl", "get", Contains),
+            edge("inherent", "extra", Contains),
+        ];
+        let sources = HashMap::from([("lib.rs".to_string(), SOURCE.to_string())]);
+        let report = find_unreferenced(
+            &symbols,
+            &relations,
+            &sources,
+            &HashSet::from(["helper".to_string()]),
+            Confidence::High,
+            &DeadCodeOptions::default(),
+        );
+
+        let found: Vec<(&str, Confidence)> = report
+            .unreferenced
+            .iter()
+            .map(|&(i, c)| (symbols[i].name.as_str(), c))
+            .collect();
+        // Mem's struct and impl blocks are reported once, the struct first
+        assert_eq!(
+            found,
+            vec![
+                ("unused", Confidence::High),
+                ("helper", Confidence::Low),
+                ("Mem", Confidence::High),
+                ("extra", Confidence::High),
+            ]
+        );
+        assert_eq!(
+            report.excluded,
+            BTreeMap::from([
+                (Exclusion::EntryPoint, 1),
+                (Exclusion::Test, 1),
+                (Exclusion::TraitImpl, 1),
+                (Exclusion::Public, 1),
+                (Exclusion::Annotated, 1),
+            ])
+        );
+
+        let options = DeadCodeOptions {
+            include_public: true,
+            include_annotated: true,
+            ..Default::default()
+        };
+        let report = find_unreferenced(
+            &symbols,
+            &relations,
+            &sources,
+            &HashSet::new(),
+            Confidence::Medium,
+            &options,
+        );
+        assert_eq!(report.unreferenced.len(), 6);
+        assert!(report
+            .unreferenced
+            .iter()
+            .all(|&(_, c)| c == Confidence::Medium));
+    }
+
+    #[test]
+    fn test_declaration() {
+        let text = "/// Doc\n#[tokio::main]\nasync fn main() {}\n\n@app.route('/x')\ndef view():\n    pass\n";
+        assert_eq!(
+            declaration(text, 3),
+            (
+                vec!["tokio::main".to_string()],
+                "async fn main() {}".to_string()
+            )
+        );
+        // Python definitions start at their first decorator
+        assert_eq!(
+            declaration(text, 5),
+            (vec!["app.route".to_string()], "def view():".to_string())
+        );
+    }
+
+    #[test]
+    fn test_is_public() {
+        let sources = HashMap::new();
+        assert!(is_public(Language::Rust, "pub fn a()", "a", None, &sources));
+        assert!(!is_public(
+            Language::Rust,
+            "pub(crate) fn a()",
+            "a",
+            None,
+            &sources
+        ));
+        assert!(!is_public(Language::Rust, "fn a()", "a", None, &sources));
+        assert!(is_public(
+            Language::TypeScript,
+            "export function a()",
+            "a",
+            None,
+            &sources
+        ));
+        assert!(is_public(
+            Language::Go,
+            "func Serve()",
+            "Serve",
+            None,
+            &sources
+        ));
+        assert!(!is_public(
+            Language::Python,
+            "def a():",
+            "a",
+            None,
+            &sources
+        ));
+
+        let mut class = CodeSymbol::new(
+            "Box".to_string(),
+            SymbolType::Class,
+            "box.ts".to_string(),
+            1,
+            3,
+            "p".to_string(),
+        );
+        class.signature = Some("class Box".to_string());
+        assert!(!is_public(
+            Language::TypeScript,
+            "open() {",
+            "open",
+            Some(&class),
+            &sources
+        ));
+        class.signature = Some("export class Box".to_string());
+        assert!(is_public(
+            Language::TypeScript,
+            "open() {",
+            "open",
+            Some(&class),
+            &sources
+        ));
+        assert!(!is_public(
+            Language::TypeScript,
+            "private open() {",
+            "open",
+            Some(&class),
+            &sources
+        ));
+    }
+
+    #[test]
+    fn test_confidence() {
+        assert_eq!(Confidence::from_resolution(0, 0), Confidence::Low);
+        assert_eq!(Confidence::from_resolution(99, 1), Confidence::High);
+        assert_eq!(Confidence::from_resolution(85, 15), Confidence::Medium);
+        assert_eq!(Confidence::from_resolution(1, 1), Confidence::Low);
+    }
+}
@@ -0,0 +1,462 @@
+//! Heading-aware outline parsing for documentation files.
+//!
+//! Markdown (ATX and setext headings), reStructuredText (underlined and
+//! overlined titles) and plain text are reduced to a flat list of sections
+//! carrying their heading breadcrumb. Fenced code blocks never produce
+//! headings, so they stay inside the section that contains them.
+
+use std::path::Path;
+
+use crate::types::symbol::{CodeReference, CodeRelationType, CodeSymbol, SymbolType};
+use crate::types::Language;
+
+use super::scanner::{detect_language, is_doc_file};
+
+/// Separator used when joining heading breadcrumbs into a chunk name.
+pub const BREADCRUMB_SEPARATOR: &str = " > ";
+
+/// A heading and the lines it governs (all line numbers are 1-based).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocSection {
+    pub title: String,
+    pub level: usize,
+    /// Titles of all enclosing headings, outermost first, ending with `title`.
+    pub breadcrumb: Vec<String>,
+    /// First line of the heading (overline for reStructuredText titles).
+    pub start_line: u32,
+    /// Last line of the heading itself (underline for setext/rst titles).
+    pub heading_end_line: u32,
+    /// Last line of this section's own body, before the next heading of any level.
+    pub body_end_line: u32,
+    /// Last line including nested subsections.
+    pub end_line: u32,
+}
+
+impl DocSection {
+    pub fn breadcrumb_name(&self) -> String {
+        self.breadcrumb.join(BREADCRUMB_SEPARATOR)
+    }
+}
+
+/// Parse the heading outline of a document.
+pub fn parse_outline(content: &str, language: &Language) -> Vec<DocSection> {
+    let lines: Vec<&str> = content.lines().collect();
+    let headings = match language {
+        Language::Markdown => markdown_headings(&lines),
+        Language::ReStructuredText => rst_headings(&lines),
+        _ => vec![],
+    };
+
+    build_sections(&headings, lines.len())
+}
+
+/// Extract heading symbols and backtick references for a documentation file.
+///
+/// Each heading becomes a `Module` symbol whose signature is the breadcrumb.
+/// Inline code spans in the section body become `References` edges so docs
+/// link to the code symbols they mention.
+pub fn parse_document(
+    path: &Path,
+    content: &str,
+    project_id: &str,
+) -> (Vec<CodeSymbol>, Vec<CodeReference>) {
+    let language = detect_language(path);
+    let file_path = path.to_string_lossy().to_string();
+    let lines: Vec<&str> = content.lines().collect();
+    let fenced = fenced_lines(&lines, &language);
+
+    let mut symbols = Vec::new();
+    let mut references = Vec::new();
+
+    for section in parse_outline(content, &language) {
+        symbols.push(
+            CodeSymbol::new(
+                section.title.clone(),
+                SymbolType::Module,
+                file_path.clone(),
+                section.start_line,
+                section.end_line,
+                project_id.to_string(),
+            )
+            .with_signature(section.breadcrumb_name()),
+        );
+
+        let mut seen = std::collections::HashSet::new();
+        let body_start = section.start_line as usize - 1;
+        let body_end = section.body_end_line as usize;
+        for idx in body_start..body_end.min(lines.len()) {
+            if fenced[idx] {
+                continue;
+            }
+            for (span, column) in inline_code_spans(lines[idx]) {
+                let Some(target) = code_identifier(span) else {
+                    continue;
+                };
+                if !seen.insert(target.clone()) {
+                    continue;
+                }
+                references.push(
+                    CodeReference::builder()
+                        .name(target.clone())
+                        .from_symbol(section.title.clone())
+                        .from_symbol_line(section.start_line)
+                        .to_symbol(target)
+                        .relation_type(CodeRelationType::References)
+                        .file_path(file_path.clone())
+                        .line(idx as u32 + 1)
+                        .column(column as u32)
+                        .build(),
+                );
+            }
+        }
+    }
+
+    (symbols, references)
+}
+
+/// Per-line flag telling whether the line belongs to a fenced code block
+/// (fence delimiters included). Only Markdown has fences.
+pub fn fenced_lines(lines: &[&str], language: &Language) -> Vec<bool> {
+    let mut flags = vec![false; lines.len()];
+    if *language != Language::Markdown {
+        return flags;
+    }
+
+    let mut open: Option<(char, usize)> = None;
+    for (idx, line) in lines.iter().enumerate() {
+        let fence = fence_marker(line);
+        match (open, fence) {
+            (None, Some(marker)) => {
+                open = Some(marker);
+                flags[idx] = true;
+            }
+            (Some((ch, len)), Some((fch, flen))) if ch == fch && flen >= len => {
+                open = None;
+                flags[idx] = true;
+            }
+            (Some(_), _) => flags[idx] = true,
+            (None, None) => {}
+        }
+    }
+
+    flags
+}
+
+struct Heading {
+    title: String,
+    level: usize,
+    /// 0-based index of the first heading line.
+    start: usize,
+    /// 0-based index of the last heading line (underline for setext/rst).
+    end: usize,
+}
+
+fn build_sections(headings: &[Heading], total_lines: usize) -> Vec<DocSection> {
+    let mut sections = Vec::with_capacity(headings.len());
+    let mut stack: Vec<(usize, String)> = Vec::new();
+
+    for (i, heading) in headings.iter().enumerate() {
+        while stack.last().is_some_and(|(lvl, _)| *lvl >= heading.level) {
+            stack.pop();
+        }
+        stack.push((heading.level, heading.title.clone()));
+
+        let body_end = headings
+            .get(i + 1)
+            .map(|next| next.start)
+            .unwrap_or(total_lines);
+        let end = headings[i + 1..]
+            .iter()
+            .find(|next| next.level <= heading.level)
+            .map(|next| next.start)
+            .unwrap_or(total_lines);
+
+        sections.push(DocSection {
+            title: heading.title.clone(),
+            level: heading.level,
+            breadcrumb: stack.iter().map(|(_, t)| t.clone()).collect(),
+            start_line: heading.start as u32 + 1,
+            heading_end_line: heading.end as u32 + 1,
+            body_end_line: body_end.max(heading.end + 1) as u32,
+            end_line: end.max(heading.end + 1) as u32,
+        });
+    }
+
+    sections
+}
+
+fn markdown_headings(lines: &[&str]) -> Vec<Heading> {
+    let fenced = fenced_lines(lines, &Language::Markdown);
+    let mut headings: Vec<Heading> = Vec::new();
+
+    for (idx, line) in lines.iter().enumerate() {
+        if fenced[idx] || leading_spaces(line) > 3 {
+            continue;
+        }
+        let trimmed = line.trim();
+
+        if let Some((level, title)) = atx_heading(trimmed) {
+            headings.push(Heading {
+                title,
+                level,
+                start: idx,
+                end: idx,
+            });
+            continue;
+        }
+
+        // Setext: a paragraph line underlined with `===` (h1) or `---` (h2)
+        let underline_level = if is_repeated(trimmed, '=') {
+            Some(1)
+        } else if is_repeated(trimmed, '-') {
+            Some(2)
+        } else {
+            None
+        };
+        if let (Some(level), Some(prev_idx)) = (underline_level, idx.checked_sub(1)) {
+            let prev = lines[prev_idx].trim();
+            let prev_is_heading = headings.last().is_some_and(|h| h.end == prev_idx);
+            if !prev.is_empty()
+                && !fenced[prev_idx]
+                && !prev_is_heading
+                && leading_spaces(lines[prev_idx]) <= 3
+            {
+                headings.push(Heading {
+                    title: prev.to_string(),
+                    level,
+                    start: prev_idx,
+                    end: idx,
+                });
+            }
+        }
+    }
+
+    headings
+}
+
+fn atx_heading(trimmed: &str) -> Option<(usize, String)> {
+    let level = trimmed.chars().take_while(|&c| c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let title = rest.trim().trim_end_matches('#').trim_end();
+    if title.is_empty() {
+        return None;
+    }
+    Some((level, title.to_string()))
+}
+
+fn rst_headings(lines: &[&str]) -> Vec<Heading> {
+    // reStructuredText levels are assigned in order of first appearance
+    // of each (adornment char, has overline) style.
+    let mut styles: Vec<(char, bool)> = Vec::new();
+    let mut headings = Vec::new();
+    let mut idx = 0;
+
+    while idx + 1 < lines.len() {
+        let title_line = lines[idx];
+        let title = title_line.trim();
+        let under = lines[idx + 1].trim_end();
+
+        let candidate = !title.is_empty()
+            && !title_line.starts_with([' ', '\t'])
+            && rst_adornment(under).is_some_and(|_| under.chars().count() >= title.chars().count());
+
+        if !candidate {
+            idx += 1;
+            continue;
+        }
+
+        let ch = rst_adornment(under).unwrap_or('=');
+        let overline = idx
+            .checked_sub(1)
+            .is_some_and(|prev| lines[prev].trim_end() == under);
+        let style = (ch, overline);
+        let level = match styles.iter().position(|s| *s == style) {
+            Some(pos) => pos + 1,
+            None => {
+                styles.push(style);
+                styles.len()
+            }
+        };
+
+        headings.push(Heading {
+            title: title.to_string(),
+            level,
+            start: if overline { idx - 1 } else { idx },
+            end: idx + 1,
+        });
+        idx += 2;
+    }
+
+    headings
+}
+
+fn rst_adornment(line: &str) -> Option<char> {
+    const ADORNMENTS: &str = "=-`:'\"~^_*+#<>.";
+    let first = line.chars().next()?;
+    if line.chars().count() >= 2 && ADORNMENTS.contains(first) && is_repeated(line, first) {
+        Some(first)
+    } else {
+        None
+    }
+}
+
+fn fence_marker(line: &str) -> Option<(char, usize)> {
+    if leading_spaces(line) > 3 {
+        return None;
+    }
+    let trimmed = line.trim_start();
+    let ch = trimmed.chars().next()?;
+    if ch != '`' && ch != '~' {
+        return None;
+    }
+    let len = trimmed.chars().take_while(|&c| c == ch).count();
+    (len >= 3).then_some((ch, len))
+}
+
+fn is_repeated(s: &str, ch: char) -> bool {
+    !s.is_empty() && s.chars().all(|c| c == ch)
+}
+
+fn leading_spaces(line: &str) -> usize {
+    line.chars().take_while(|&c| c == ' ').count()
+}
+
+/// Inline code spans on a single line as `(content, column)` pairs.
+/// Handles multi-backtick delimiters (` ``a`b`` `), as used by reStructuredText literals.
+fn inline_code_spans(line: &str) -> Vec<(&str, usize)> {
+    let bytes = line.as_bytes();
+    let mut spans = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] != b'`' {
+            i += 1;
+            continue;
+        }
+        let open_len = bytes[i..].iter().take_while(|&&b| b == b'`').count();
+        let content_start = i + open_len;
+        let mut j = content_start;
+        let mut closed = None;
+        while j < bytes.len() {
+            if bytes[j] == b'`' {
+                let run = bytes[j..].iter().take_while(|&&b| b == b'`').count();
+                if run == open_len {
+                    closed = Some(j);
+                    break;
+                }
+                j += run;
+            } else {
+                j += 1;
+            }
+        }
+        match closed {
+            Some(end) => {
+                spans.push((line[content_start..end].trim(), content_start));
+                i = end + open_len;
+            }
+            None => break,
+        }
+    }
+
+    spans
+}
+
+/// Reduce an inline code span to the identifier it most likely names:
+/// `TokenValidator::check()` → `check`, `config.load` → `load`.
+/// File names, shell commands and free text are rejected.
+fn code_identifier(span: &str) -> Option<String> {
+    let span = span.split('(').next().unwrap_or(span).trim();
+    if span.is_empty()
+        || !span
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == ':' || c == '.')
+    {
+        return None;
+    }
+    if Path::new(span).extension().is_some()
+        && (detect_language(Path::new(span)) != Language::Unknown || is_doc_file(Path::new(span)))
+    {
+        return None;
+    }
+
+    let last = span.rsplit([':', '.']).find(|s| !s.is_empty())?;
+    let mut chars = last.chars();
+    let first = chars.next()?;
+    if last.len() < 2 || !(first.is_alphabetic() || first == '_') {
+        return None;
+    }
+    Some(last.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_markdown_outline_breadcrumbs() {
+        let content =
+            "# Guide\nintro\n\n## Install\nstep\n\n### Docker\nrun it\n\n## Usage\nuse it\n";
+        let sections = parse_outline(content, &Language::Markdown);
+
+        let names: Vec<_> = sections.iter().map(|s| s.breadcrumb_name()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "Guide",
+                "Guide > Install",
+                "Guide > Install > Docker",
+                "Guide > Usage"
+            ]
+        );
+
+        let install = &sections[1];
+        assert_eq!(install.start_line, 4);
+        assert_eq!(install.body_end_line, 6);
+        assert_eq!(install.end_line, 9);
+    }
+
+    #[test]
+    fn test_markdown_fenced_hash_is_not_heading() {
+        let content =
+            "# Build\n\n```sh\n# not a heading\ncargo build\n```\n\nSetext\n------\ntext\n";
+        let sections = parse_outline(content, &Language::Markdown);
+
+        let titles: Vec<_> = sections.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, vec!["Build", "Setext"]);
+        assert_eq!(sections[0].body_end_line, 7);
+        assert_eq!(sections[1].level, 2);
+    }
+
+    #[test]
+    fn test_rst_levels_follow_first_appearance() {
+        let content = "=====\nTitle\n=====\n\nSection\n-------\n\nbody\n\nOther\n-----\n";
+        let sections = parse_outline(content, &Language::ReStructuredText);
+
+        let names: Vec<_> = sections.iter().map(|s| s.breadcrumb_name()).collect();
+        assert_eq!(names, vec!["Title", "Title > Section", "Title > Other"]);
+        assert_eq!(sections[0].start_line, 1);
+    }
+
+    #[test]
+    fn test_document_symbols_and_backtick_references() {
+        let content = "# Storage\n\nSee `SymbolIndex::resolve()` and `config.rs`.\n\n```rust\nlet x = `ignored`;\n```\n";
+        let path = PathBuf::from("docs/README.md");
+        let (symbols, refs) = parse_document(&path, content, "test");
+
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].symbol_type, SymbolType::Module);
+        assert_eq!(symbols[0].signature.as_deref(), Some("Storage"));
+
+        let targets: Vec<_> = refs.iter().map(|r| r.to_symbol.as_str()).collect();
+        assert_eq!(targets, vec!["resolve"]);
+        assert_eq!(refs[0].from_symbol, "Storage");
+        assert_eq!(refs[0].relation_type, CodeRelationType::References);
+    }
+}
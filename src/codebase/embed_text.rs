@@ -0,0 +1,229 @@
+//! Embedding input for code chunks and symbols.
+//!
+//! Raw code embeds poorly on its own: `fn check(&self)` says nothing about JWT
+//! expiry. The builder prepends a short header (file path, language, enclosing
+//! container, doc comment) to the text sent to the embedding queue. Stored chunk
+//! content is never modified.
+
+use crate::types::symbol::CodeSymbol;
+use crate::types::CodeChunk;
+
+/// Which context lines are prepended to code embedding input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmbeddingTextConfig {
+    pub path: bool,
+    pub language: bool,
+    pub container: bool,
+    pub doc: bool,
+}
+
+impl Default for EmbeddingTextConfig {
+    fn default() -> Self {
+        Self {
+            path: true,
+            language: true,
+            container: true,
+            doc: true,
+        }
+    }
+}
+
+impl EmbeddingTextConfig {
+    /// Embed raw content only (previous behaviour).
+    pub fn none() -> Self {
+        Self {
+            path: false,
+            language: false,
+            container: false,
+            doc: false,
+        }
+    }
+}
+
+/// Parses `path,language,container,doc` (any subset), `all` or `none`.
+impl std::str::FromStr for EmbeddingTextConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut config = Self::none();
+        for field in s.split(',').map(|f| f.trim().to_lowercase()) {
+            match field.as_str() {
+                "" | "none" => {}
+                "all" => config = Self::default(),
+                "path" => config.path = true,
+                "language" => config.language = true,
+                "container" => config.container = true,
+                "doc" => config.doc = true,
+                other => {
+                    return Err(format!(
+                        "Unknown embedding context field: '{}'. Valid values: path, language, container, doc, all, none",
+                        other
+                    ))
+                }
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// Longest doc comment carried into the header; the rest adds little signal.
+const MAX_DOC_CHARS: usize = 500;
+
+/// Embedding input for a chunk. The chunk's own content is included verbatim.
+pub fn chunk_text(config: &EmbeddingTextConfig, chunk: &CodeChunk) -> String {
+    let container = chunk.context.as_deref().map(|c| c.replace('\n', " > "));
+    build(
+        config,
+        &chunk.file_path,
+        chunk.language.as_str(),
+        container.as_deref(),
+        None,
+        &chunk.content,
+    )
+}
+
+/// Embedding input for a symbol, or `None` if it has no signature to embed.
+pub fn symbol_text(
+    config: &EmbeddingTextConfig,
+    symbol: &CodeSymbol,
+    container: Option<&str>,
+    doc: Option<&str>,
+) -> Option<String> {
+    let signature = symbol.signature.as_deref()?;
+    let language =
+        crate::codebase::scanner::detect_language(std::path::Path::new(&symbol.file_path));
+    Some(build(
+        config,
+        &symbol.file_path,
+        language.as_str(),
+        container,
+        doc,
+        signature,
+    ))
+}
+
+/// Signature (or name) of the innermost other symbol whose range contains `symbol`.
+pub fn enclosing_container<'a>(symbols: &'a [CodeSymbol], symbol: &CodeSymbol) -> Option<&'a str> {
+    symbols
+        .iter()
+        .filter(|s| {
+            s.start_line <= symbol.start_line
+                && s.end_line >= symbol.end_line
+                && (s.start_line, s.end_line, &s.name)
+                    != (symbol.start_line, symbol.end_line, &symbol.name)
+        })
+        .min_by_key(|s| s.end_line - s.start_line)
+        .map(|s| s.signature.as_deref().unwrap_or(&s.name))
+}
+
+fn build(
+    config: &EmbeddingTextConfig,
+    path: &str,
+    language: &str,
+    container: Option<&str>,
+    doc: Option<&str>,
+    body: &str,
+) -> String {
+    let mut text = String::with_capacity(body.len() + 128);
+
+    if config.path {
+        text.push_str("File: ");
+        text.push_str(path);
+        text.push('\n');
+    }
+    if config.language && language != "unknown" {
+        text.push_str("Language: ");
+        text.push_str(language);
+        text.push('\n');
+    }
+    if let Some(container) = container.filter(|_| config.container) {
+        text.push_str("In: ");
+        text.push_str(container);
+        text.push('\n');
+    }
+    if let Some(doc) = doc.filter(|d| config.doc && !d.trim().is_empty()) {
+        text.push_str("Doc: ");
+        text.extend(doc.trim().chars().take(MAX_DOC_CHARS));
+        text.push('\n');
+    }
+
+    text.push_str(body);
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::symbol::SymbolType;
+
+    #[test]
+    fn test_symbol_text_includes_context() {
+        let symbol = CodeSymbol::new(
+            "check".to_string(),
+            SymbolType::Function,
+            "auth/jwt.rs".to_string(),
+            10,
+            20,
+            "p".to_string(),
+        )
+        .with_signature("fn check(&self) -> bool".to_string());
+
+        let text = symbol_text(
+            &EmbeddingTextConfig::default(),
+            &symbol,
+            Some("impl TokenValidator"),
+            Some("Rejects expired tokens."),
+        )
+        .unwrap();
+        assert_eq!(
+            text,
+            "File: auth/jwt.rs\nLanguage: rust\nIn: impl TokenValidator\nDoc: Rejects expired tokens.\nfn check(&self) -> bool"
+        );
+
+        let raw = symbol_text(&EmbeddingTextConfig::none(), &symbol, None, None).unwrap();
+        assert_eq!(raw, "fn check(&self) -> bool");
+    }
+
+    #[test]
+    fn test_config_parsing() {
+        let config: EmbeddingTextConfig = "path, doc".parse().unwrap();
+        assert!(config.path && config.doc && !config.language && !config.container);
+        assert_eq!(
+            "none".parse::<EmbeddingTextConfig>().unwrap(),
+            EmbeddingTextConfig::none()
+        );
+        assert_eq!(
+            "all".parse::<EmbeddingTextConfig>().unwrap(),
+            EmbeddingTextConfig::default()
+        );
+        assert!("bogus".parse::<EmbeddingTextConfig>().is_err());
+    }
+
+    #[test]
+    fn test_enclosing_container_is_innermost() {
+        let make = |name: &str, start, end, sig: Option<&str>| {
+            let s = CodeSymbol::new(
+                name.to_string(),
+                SymbolType::Class,
+                "a.rs".to_string(),
+                start,
+                end,
+                "p".to_string(),
+            );
+            match sig {
+                Some(sig) => s.with_signature(sig.to_string()),
+                None => s,
+            }
+        };
+        let symbols = vec![
+            make("outer", 1, 100, None),
+            make("Inner", 10, 50, Some("impl Inner")),
+            make("method", 20, 30, None),
+        ];
+        assert_eq!(
+            enclosing_container(&symbols, &symbols[2]),
+            Some("impl Inner")
+        );
+        assert_eq!(enclosing_container(&symbols, &symbols[0]), None);
+    }
+}
@@ -0,0 +1,367 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use gix::bstr::ByteSlice;
+
+use gix::diff::blob::{diff, intern::InternedInput, Algorithm};
+
+use super::parser::CodeParser;
+use super::paths::relative_path;
+use super::scanner::{is_code_file, is_ignored_file, is_indexable_file};
+use super::source::{classify, Source};
+use crate::types::{AppError, Datetime, GitCommit, SkipReason};
+use crate::Result;
+
+/// Commit checked out in a worktree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GitHead {
+    pub commit: String,
+    /// Branch name; `None` on a detached HEAD
+    pub branch: Option<String>,
+}
+
+fn git_error(e: impl std::fmt::Display) -> AppError {
+    AppError::Indexing(format!("git: {}", e))
+}
+
+/// Path of the project `root` inside the repository worktree.
+fn repo_prefix(repo: &gix::Repository, root: &Path) -> Option<PathBuf> {
+    let workdir = repo.workdir()?.canonicalize().ok()?;
+    let root = root.canonicalize().ok()?;
+    root.strip_prefix(&workdir).ok().map(Path::to_path_buf)
+}
+
+/// Commit and branch checked out in the repository containing `root`, or
+/// `None` outside a git repository (or before the first commit).
+pub fn head(root: &Path) -> Option<GitHead> {
+    let repo = gix::discover(root).ok()?;
+    let commit = repo.head_id().ok()?.to_string();
+    let branch = repo
+        .head_name()
+        .ok()
+        .flatten()
+        .map(|name| name.shorten().to_string());
+    Some(GitHead { commit, branch })
+}
+
+fn tree_of<'repo>(repo: &'repo gix::Repository, rev: &str) -> Result<gix::Tree<'repo>> {
+    repo.rev_parse_single(rev)
+        .map_err(git_error)?
+        .object()
+        .map_err(git_error)?
+        .peel_to_tree()
+        .map_err(git_error)
+}
+
+/// Files added, modified or deleted between two commits, as paths under
+/// `root`. Changes outside the project are left out.
+pub fn changed_files(root: &Path, from: &str, to: &str) -> Result<Vec<PathBuf>> {
+    let repo = gix::discover(root).map_err(git_error)?;
+    let prefix = repo_prefix(&repo, root)
+        .ok_or_else(|| git_error("project root is outside the repository worktree"))?;
+    let old = tree_of(&repo, from)?;
+    let new = tree_of(&repo, to)?;
+    Ok(diff_paths(&repo, &prefix, &old, &new)?
+        .into_iter()
+        .map(|path| root.join(path))
+        .collect())
+}
+
+/// Up to `depth` commits reachable from HEAD, newest first, skipping those in
+/// `known`. Files are the project-relative paths each commit changed against
+/// its first parent, symbols the code definitions whose lines it touched;
+/// merges list neither, as their changes are counted on the merged branch.
+pub fn history(
+    root: &Path,
+    project_id: &str,
+    depth: usize,
+    known: &HashSet<String>,
+) -> Result<Vec<GitCommit>> {
+    let repo = gix::discover(root).map_err(git_error)?;
+    let prefix = repo_prefix(&repo, root)
+        .ok_or_else(|| git_error("project root is outside the repository worktree"))?;
+    let Ok(head) = repo.head_id() else {
+        // Unborn branch: no commits yet
+        return Ok(vec![]);
+    };
+    let walk = head
+        .ancestors()
+        .sorting(gix::revision::walk::Sorting::ByCommitTime(
+            Default::default(),
+        ))
+        .all()
+        .map_err(git_error)?;
+
+    let mut commits = Vec::new();
+    for info in walk.take(depth) {
+        let info = info.map_err(git_error)?;
+        let hash = info.id.to_string();
+        if known.contains(&hash) {
+            continue;
+        }
+        let commit = info.object().map_err(git_error)?;
+        let author = commit.author().map_err(git_error)?;
+        let seconds = author.time().map_err(git_error)?.seconds;
+
+        let tree = commit.tree().map_err(git_error)?;
+        let changes = match info.parent_ids.as_slice() {
+            [] => diff_changes(&repo, &prefix, &repo.empty_tree(), &tree)?,
+            [parent] => {
+                let parent = repo
+                    .find_commit(*parent)
+                    .map_err(git_error)?
+                    .tree()
+                    .map_err(git_error)?;
+                diff_changes(&repo, &prefix, &parent, &tree)?
+            }
+            _ => Vec::new(),
+        };
+        let mut symbols: Vec<String> = changes
+            .iter()
+            .flat_map(|change| modified_symbols(&repo, change, project_id))
+            .collect();
+        symbols.sort();
+        symbols.dedup();
+
+        commits.push(GitCommit {
+            id: None,
+            project_id: project_id.to_string(),
+            hash,
+            author: author.name.to_string(),
+            email: author.email.to_string(),
+            date: Datetime::from(chrono::DateTime::from_timestamp(seconds, 0).unwrap_or_default()),
+            message: commit
+                .message_raw()
+                .map_err(git_error)?
+                .to_str_lossy()
+                .trim_end()
+                .to_string(),
+            files: changes.into_iter().map(|change| change.path).collect(),
+            symbols,
+        });
+    }
+    Ok(commits)
+}
+
+/// A file that differs between two trees.
+struct FileChange {
+    /// Project-relative path
+    path: String,
+    /// Blob before the change; `None` for additions
+    old: Option<gix::ObjectId>,
+    /// Blob after the change; `None` for deletions
+    new: Option<gix::ObjectId>,
+}
+
+/// Files that differ between two trees, below the project `prefix`.
+fn diff_changes(
+    repo: &gix::Repository,
+    prefix: &Path,
+    old: &gix::Tree<'_>,
+    new: &gix::Tree<'_>,
+) -> Result<Vec<FileChange>> {
+    use gix::object::tree::diff::ChangeDetached;
+
+    let changes = repo
+        .diff_tree_to_tree(old, new, gix::diff::Options::default().with_rewrites(None))
+        .map_err(git_error)?;
+    Ok(changes
+        .iter()
+        .filter(|change| change.entry_mode().is_blob())
+        .filter_map(|change| {
+            let path = Path::new(change.location().to_str().ok()?);
+            let path = relative_path(Path::new(""), path.strip_prefix(prefix).ok()?);
+            let (old, new) = match change {
+                ChangeDetached::Addition { id, .. } => (None, Some(*id)),
+                ChangeDetached::Deletion { id, .. } => (Some(*id), None),
+                ChangeDetached::Modification {
+                    previous_id, id, ..
+                } => (Some(*previous_id), Some(*id)),
+                ChangeDetached::Rewrite { source_id, id, .. } => (Some(*source_id), Some(*id)),
+            };
+            Some(FileChange { path, old, new })
+        })
+        .collect())
+}
+
+fn diff_paths(
+    repo: &gix::Repository,
+    prefix: &Path,
+    old: &gix::Tree<'_>,
+    new: &gix::Tree<'_>,
+) -> Result<Vec<String>> {
+    Ok(diff_changes(repo, prefix, old, new)?
+        .into_iter()
+        .map(|change| change.path)
+        .collect())
+}
+
+/// Names of the symbols in a changed code file whose lines the change
+/// touched, as defined in the new version.
+fn modified_symbols(repo: &gix::Repository, change: &FileChange, project_id: &str) -> Vec<String> {
+    let path = Path::new(&change.path);
+    let Some(new) = change.new else {
+        return Vec::new();
+    };
+    if !is_code_file(path) || is_ignored_file(path) {
+        return Vec::new();
+    }
+    let text = |id: gix::ObjectId| match repo.find_blob(id).map(|blob| classify(&blob.data)) {
+        Ok(Source::Text(text)) => Some(text),
+        _ => None,
+    };
+    let Some(after) = text(new) else {
+        return Vec::new();
+    };
+    let before = change.old.and_then(text).unwrap_or_default();
+
+    // 1-based line ranges of the new version that the change rewrote;
+    // a pure deletion marks the line it happened before
+    let input = InternedInput::new(before.as_str(), after.as_str());
+    let mut touched: Vec<(u32, u32)> = Vec::new();
+    diff(
+        Algorithm::Histogram,
+        &input,
+        |_: std::ops::Range<u32>, after: std::ops::Range<u32>| {
+            touched.push((after.start + 1, after.end.max(after.start + 1)));
+        },
+    );
+
+    let (symbols, _) = CodeParser::parse_file(path, &after, project_id);
+    let mut names: Vec<String> = symbols
+        .into_iter()
+        .filter(|symbol| {
+            touched
+                .iter()
+                .any(|&(start, end)| symbol.start_line <= end && start <= symbol.end_line)
+        })
+        .map(|symbol| symbol.qualified_name.unwrap_or(symbol.name))
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// The indexable files of one commit, read from the object database rather
+/// than the worktree. Paths are given as if checked out under `root`.
+pub struct Snapshot {
+    repo: gix::ThreadSafeRepository,
+    files: BTreeMap<PathBuf, gix::ObjectId>,
+    pub commit: String,
+}
+
+impl Snapshot {
+    /// Resolve `rev` (branch, tag or commit) and list its files below `root`.
+    pub fn open(root: &Path, rev: &str) -> Result<Self> {
+        let repo = gix::discover(root).map_err(git_error)?;
+        let prefix = repo_prefix(&repo, root)
+            .ok_or_else(|| git_error("project root is outside the repository worktree"))?;
+        let (commit, recorder) = {
+            let commit = repo
+                .rev_parse_single(rev)
+                .map_err(git_error)?
+                .object()
+                .map_err(git_error)?
+                .peel_to_commit()
+                .map_err(git_error)?;
+            let mut recorder = gix::traverse::tree::Recorder::default();
+            commit
+                .tree()
+                .map_err(git_error)?
+                .traverse()
+                .breadthfirst(&mut recorder)
+                .map_err(git_error)?;
+            (commit.id.to_string(), recorder)
+        };
+
+        let files = recorder
+            .records
+            .into_iter()
+            .filter(|entry| entry.mode.is_blob())
+            .filter_map(|entry| {
+                let path = Path::new(entry.filepath.to_str().ok()?);
+                let path = root.join(path.strip_prefix(&prefix).ok()?);
+                (is_indexable_file(&path) && !is_ignored_file(&path)).then_some((path, entry.oid))
+            })
+            .collect();
+
+        Ok(Self {
+            commit,
+            files,
+            repo: repo.into_sync(),
+        })
+    }
+
+    /// File paths in the snapshot, sorted.
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.files.keys().cloned().collect()
+    }
+
+    /// Contents of a snapshot file, with the same checks as files on disk.
+    pub fn read(&self, path: &Path, max_size: u64) -> std::io::Result<Source> {
+        let not_found =
+            || std::io::Error::new(std::io::ErrorKind::NotFound, path.display().to_string());
+        let id = self.files.get(path).ok_or_else(not_found)?;
+        let repo = self.repo.to_thread_local();
+        let blob = repo
+            .find_blob(*id)
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+        if max_size > 0 && blob.data.len() as u64 > max_size {
+            return Ok(Source::Skipped(SkipReason::TooLarge));
+        }
+        Ok(classify(&blob.data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestRepo;
+
+    #[test]
+    fn test_head_diff_and_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("repo");
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        let repo = TestRepo::init(&root);
+        std::fs::write(root.join("src/a.rs"), "fn a() {}\n").unwrap();
+        std::fs::write(root.join("src/b.rs"), "fn b() {}\n").unwrap();
+        let commit = repo.commit("first");
+        let first = head(&root).unwrap();
+        assert_eq!(first.commit, commit);
+        assert_eq!(first.branch.as_deref(), Some("main"));
+
+        repo.checkout_new_branch("feature");
+        std::fs::write(root.join("src/a.rs"), "fn a2() {}\n").unwrap();
+        std::fs::remove_file(root.join("src/b.rs")).unwrap();
+        std::fs::write(root.join("src/c.rs"), "fn c() {}\n").unwrap();
+        repo.commit("second");
+        let second = head(&root).unwrap();
+        assert_eq!(second.branch.as_deref(), Some("feature"));
+        assert_ne!(second.commit, first.commit);
+
+        let root = root.canonicalize().unwrap();
+        let mut changed = changed_files(&root, &first.commit, &second.commit).unwrap();
+        changed.sort();
+        assert_eq!(
+            changed,
+            vec![
+                root.join("src/a.rs"),
+                root.join("src/b.rs"),
+                root.join("src/c.rs")
+            ]
+        );
+
+        // main as it was, read from git objects while feature is checked out
+        let snapshot = Snapshot::open(&root, "main").unwrap();
+        assert_eq!(snapshot.commit, first.commit);
+        assert_eq!(
+            snapshot.files(),
+            vec![root.join("src/a.rs"), root.join("src/b.rs")]
+        );
+        assert_eq!(
+            snapshot.read(&root.join("src/a.rs"), 0).unwrap(),
+            Source::Text("fn a() {}\n".to_string())
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use super::git;
+use crate::config::AppState;
+use crate::storage::StorageBackend;
+use crate::Result;
+
+/// Store commits from the repository containing `root` that are not yet
+/// indexed for the project, up to the configured history depth. Returns the
+/// number added; does nothing when history indexing is off.
+pub async fn index_history(state: Arc<AppState>, project_id: &str, root: &Path) -> Result<usize> {
+    let depth = state.config.history_depth;
+    if depth == 0 {
+        return Ok(0);
+    }
+    let known = state.storage.get_commit_hashes(project_id).await?;
+    let commits = {
+        let (root, project_id) = (root.to_path_buf(), project_id.to_string());
+        tokio::task::spawn_blocking(move || git::history(&root, &project_id, depth, &known))
+            .await
+            .map_err(|e| crate::types::AppError::Indexing(e.to_string()))??
+    };
+    let added = state.storage.create_commits_batch(commits).await?;
+    if added > 0 {
+        tracing::info!(project = %project_id, commits = added, "Indexed git history");
+    }
+    Ok(added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{TestContext, TestRepo};
+    use std::fs;
+
+    #[tokio::test]
+    async fn test_history_commits_and_churn() {
+        let ctx = TestContext::with_config(|config| config.history_depth = 10).await;
+        let root = ctx._temp_dir.path().join("proj");
+        fs::create_dir_all(&root).unwrap();
+        let repo = TestRepo::init(&root).with_author("Ada", "ada@example.com");
+        fs::write(root.join("auth.rs"), "fn login() {}\n\nfn logout() {}\n").unwrap();
+        fs::write(root.join("db.rs"), "fn connect() {}\n").unwrap();
+        repo.commit_at("Add auth and db", "2024-01-01T10:00:00+00:00");
+        fs::write(
+            root.join("auth.rs"),
+            "fn login() {\n    check();\n}\n\nfn logout() {}\n",
+        )
+        .unwrap();
+        repo.commit_at(
+            "Validate sessions on login to fix token reuse",
+            "2024-02-01T10:00:00+00:00",
+        );
+
+        let root = root.canonicalize().unwrap();
+        assert_eq!(
+            index_history(ctx.state.clone(), "proj", &root)
+                .await
+                .unwrap(),
+            2
+        );
+        // Already-indexed commits are not stored twice
+        assert_eq!(
+            index_history(ctx.state.clone(), "proj", &root)
+                .await
+                .unwrap(),
+            0
+        );
+
+        let storage = &ctx.state.storage;
+        let found = storage
+            .search_commits("token reuse", Some("proj"), 10)
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].author, "Ada");
+        assert_eq!(found[0].files, vec!["auth.rs".to_string()]);
+        assert_eq!(found[0].symbols, vec!["auth::login".to_string()]);
+
+        let commits = storage
+            .get_file_commits("proj", "auth.rs", 10)
+            .await
+            .unwrap();
+        assert_eq!(commits.len(), 2);
+        assert!(commits[0].message.starts_with("Validate sessions"));
+
+        let churn = storage
+            .get_file_churn("proj", &["auth.rs".to_string(), "db.rs".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(churn["auth.rs"].commits, 2);
+        assert_eq!(churn["auth.rs"].authors, 1);
+        assert_eq!(churn["auth.rs"].last_commit, commits[0].hash);
+        assert_eq!(churn["db.rs"].commits, 1);
+    }
+}
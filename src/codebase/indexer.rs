@@ -1,35 +1,137 @@
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Instant;
 
-use tokio::fs;
+use rayon::prelude::*;
 
-use crate::config::AppState;
+use crate::config::{AppState, IndexBudget, IndexJob, IndexMonitor};
 use crate::storage::StorageBackend;
-use crate::types::{IndexState, IndexStatus};
+use crate::types::{CodeChunk, IndexState, IndexStatus, SkipReason, SkippedFile};
 use crate::Result;
 
 use super::chunker::chunk_file;
+use super::embed_text::{chunk_text, enclosing_container, symbol_text, EmbeddingTextConfig};
+use super::git::{self, Snapshot};
+use super::history::index_history;
 use super::parser::CodeParser;
-use super::relations::{create_symbol_relations, RelationStats};
-use super::scanner::scan_directory;
-use super::symbol_index::SymbolIndex;
+use super::paths::relative_path;
+use super::relations::{
+    containment_relations, create_schema_links, create_symbol_relations, store_relations,
+    RelationStats,
+};
+use super::scanner::{detect_language, scan_directory_until};
+use super::source::{read_source, Source};
 
 use crate::embedding::{EmbeddingRequest, EmbeddingTarget};
-use crate::types::symbol::CodeReference;
+use crate::types::symbol::{CodeReference, CodeSymbol, SymbolRef};
 
 pub async fn index_project(state: Arc<AppState>, project_path: &Path) -> Result<IndexStatus> {
-    let project_id = project_path
+    let project_id = project_id_of(project_path);
+    run_index(state, project_path, project_id, None).await
+}
+
+/// Full pass after ignore rules change, diffing against stored hashes. When
+/// a job is already indexing the project, that job runs the rescan once it
+/// is done and this returns `None`.
+pub async fn rescan_project(
+    state: Arc<AppState>,
+    project_path: &Path,
+) -> Result<Option<IndexStatus>> {
+    let project_id = project_id_of(project_path);
+    let monitor = state.progress.get_or_create(&project_id).await;
+    let Some(job) = monitor.begin_rescan() else {
+        return Ok(None);
+    };
+    run_job(job, state, project_path, project_id, None)
+        .await
+        .map(Some)
+}
+
+fn project_id_of(project_path: &Path) -> String {
+    project_path
         .file_name()
         .and_then(|n| n.to_str())
         .unwrap_or("unknown")
-        .to_string();
+        .to_string()
+}
+
+/// Index `rev` (a branch, tag or commit) of the repository at `project_path`
+/// from git objects, as the separate project `<project>@<rev>`. The worktree
+/// is not touched; content shared with other snapshots reuses cached
+/// embeddings.
+pub async fn index_git_ref(
+    state: Arc<AppState>,
+    project_path: &Path,
+    rev: &str,
+) -> Result<IndexStatus> {
+    let project_id = format!(
+        "{}@{}",
+        project_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown"),
+        rev
+    );
+    run_index(state, project_path, project_id, Some(rev)).await
+}
+
+async fn run_index(
+    state: Arc<AppState>,
+    project_path: &Path,
+    project_id: String,
+    git_ref: Option<&str>,
+) -> Result<IndexStatus> {
+    let monitor = state.progress.get_or_create(&project_id).await;
+    let Some(job) = monitor.begin_job() else {
+        return Err(crate::types::AppError::Indexing(format!(
+            "{} is already being indexed",
+            project_id
+        )));
+    };
+    run_job(job, state, project_path, project_id, git_ref).await
+}
 
-    match do_index_project(state.clone(), project_path, &project_id).await {
-        Ok(status) => Ok(status),
+/// Run indexing passes under `job` until no rescan was deferred to it.
+async fn run_job(
+    mut job: IndexJob,
+    state: Arc<AppState>,
+    project_path: &Path,
+    project_id: String,
+    git_ref: Option<&str>,
+) -> Result<IndexStatus> {
+    loop {
+        let result = index_pass(state.clone(), project_path, &project_id, git_ref).await;
+        if !job.finish_or_rescan() {
+            return result;
+        }
+        tracing::info!(project_id = %project_id, "Ignore rules changed during indexing, rescanning");
+    }
+}
+
+async fn index_pass(
+    state: Arc<AppState>,
+    project_path: &Path,
+    project_id: &str,
+    git_ref: Option<&str>,
+) -> Result<IndexStatus> {
+    match do_index_project(state.clone(), project_path, project_id, git_ref).await {
+        Ok(status) => {
+            // History belongs to the worktree project, not ref snapshots
+            let in_repo = git_ref.is_none() && status.commit.is_some();
+            if let (true, false, Some(root)) =
+                (in_repo, status.status.is_unfinished(), &status.root)
+            {
+                if let Err(e) = index_history(state, project_id, Path::new(root)).await {
+                    tracing::warn!(project_id = %project_id, error = %e, "History indexing failed");
+                }
+            }
+            Ok(status)
+        }
         Err(e) => {
             tracing::error!(project_id = %project_id, error = %e, "Indexing failed");
-            let mut status = IndexStatus::new(project_id.clone());
-            if let Ok(Some(existing)) = state.storage.get_index_status(&project_id).await {
+            let mut status = IndexStatus::new(project_id.to_string());
+            if let Ok(Some(existing)) = state.storage.get_index_status(project_id).await {
                 status = existing;
             }
             status.status = IndexState::Failed;
@@ -41,237 +143,393 @@ pub async fn index_project(state: Arc<AppState>, project_path: &Path) -> Result<
     }
 }
 
+/// Parsed files waiting for the writer; back-pressures the parse stage
+const PIPELINE_DEPTH: usize = 64;
+
+/// A file read, hashed, chunked and parsed on the rayon pool.
+enum PreparedFile {
+    /// Generated file, not indexed
+    Skipped,
+    /// Left out for its size or content; `indexed` if a previous run stored it
+    Excluded {
+        path: String,
+        reason: SkipReason,
+        indexed: bool,
+    },
+    Failed(String),
+    Unchanged,
+    /// Unchanged file stored by an interrupted run whose edges must be rebuilt
+    Relink {
+        path: String,
+        symbols: Vec<CodeSymbol>,
+        references: Vec<CodeReference>,
+    },
+    Changed {
+        path: String,
+        hash: String,
+        chunks: Vec<CodeChunk>,
+        symbols: Vec<CodeSymbol>,
+        references: Vec<CodeReference>,
+    },
+}
+
+/// What the writer stored for one file, for the project-wide relation pass.
+#[derive(Default)]
+struct StoredFile {
+    path: String,
+    chunks: u32,
+    symbols: Vec<CodeSymbol>,
+    references: Vec<CodeReference>,
+    link_candidates: Vec<SymbolRef>,
+}
+
+fn prepare_file(
+    root: &Path,
+    file_path: &Path,
+    project_id: &str,
+    stored_hashes: &HashMap<String, String>,
+    resuming: bool,
+    load: impl FnOnce() -> std::io::Result<Source>,
+) -> PreparedFile {
+    // Skip auto-generated files (no useful semantic content)
+    if crate::codebase::scanner::is_ignored_file(file_path) {
+        tracing::debug!(path = ?file_path, "Skipping generated file");
+        return PreparedFile::Skipped;
+    }
+
+    let path = relative_path(root, file_path);
+    let content = match load() {
+        Ok(Source::Text(c)) => c,
+        Ok(Source::Skipped(reason)) => {
+            tracing::debug!(path = %path, %reason, "Skipping file");
+            return PreparedFile::Excluded {
+                indexed: stored_hashes.contains_key(&path),
+                path,
+                reason,
+            };
+        }
+        Err(e) => {
+            tracing::warn!("Failed to read file {:?}: {}", file_path, e);
+            return PreparedFile::Failed(path);
+        }
+    };
+
+    let hash = blake3::hash(content.as_bytes()).to_hex().to_string();
+    let unchanged = stored_hashes.get(&path) == Some(&hash);
+    if unchanged && !resuming {
+        return PreparedFile::Unchanged;
+    }
+
+    let (symbols, references) = CodeParser::parse_file(Path::new(&path), &content, project_id);
+    if !symbols.is_empty() {
+        tracing::debug!("File {:?}: found {} symbols", file_path, symbols.len());
+    }
+
+    if unchanged {
+        PreparedFile::Relink {
+            path,
+            symbols,
+            references,
+        }
+    } else {
+        PreparedFile::Changed {
+            chunks: chunk_file(Path::new(&path), &content, project_id),
+            path,
+            hash,
+            symbols,
+            references,
+        }
+    }
+}
+
+/// Store one prepared file. The caller holds a `db_semaphore` permit.
+async fn write_file(state: &AppState, project_id: &str, file: PreparedFile) -> StoredFile {
+    match file {
+        PreparedFile::Relink {
+            path,
+            symbols,
+            references,
+        } => {
+            let _ = state
+                .storage
+                .delete_outgoing_relations_by_path(project_id, &path)
+                .await;
+            let old_keys = symbols.iter().map(CodeSymbol::unique_key).collect();
+            StoredFile {
+                path,
+                chunks: 0,
+                link_candidates: schema_link_candidates(&symbols, &old_keys),
+                symbols,
+                references,
+            }
+        }
+        PreparedFile::Changed {
+            path,
+            hash,
+            chunks,
+            symbols,
+            references,
+        } => {
+            let _ = state.storage.delete_chunks_by_path(project_id, &path).await;
+            let chunk_count = chunks.len() as u32;
+            store_chunks(state, chunks).await;
+
+            let old_keys = replace_file_symbols(state, project_id, &path, &symbols).await;
+
+            // Recorded only once the file's data is stored, so an
+            // interrupted run picks up from here
+            let _ = state.storage.set_file_hash(project_id, &path, &hash).await;
+
+            StoredFile {
+                path,
+                chunks: chunk_count,
+                link_candidates: schema_link_candidates(&symbols, &old_keys),
+                symbols,
+                references,
+            }
+        }
+        PreparedFile::Skipped
+        | PreparedFile::Excluded { .. }
+        | PreparedFile::Failed(_)
+        | PreparedFile::Unchanged => StoredFile::default(),
+    }
+}
+
 async fn do_index_project(
     state: Arc<AppState>,
     project_path: &Path,
     project_id: &str,
+    git_ref: Option<&str>,
 ) -> Result<IndexStatus> {
+    // An interrupted run stored some files without linking them; relink those
+    let resuming = matches!(
+        state.storage.get_index_status(project_id).await,
+        Ok(Some(ref s)) if s.status.is_unfinished()
+    );
+    let root = project_path
+        .canonicalize()
+        .unwrap_or_else(|_| project_path.to_path_buf());
     let mut status = IndexStatus::new(project_id.to_string());
+    status.root = Some(root.to_string_lossy().to_string());
     let monitor = state.progress.get_or_create(project_id).await;
 
-    state.storage.delete_project_chunks(project_id).await?;
-    state.storage.delete_project_symbols(project_id).await?;
-    state.storage.delete_file_hashes(project_id).await?;
+    let snapshot = match git_ref {
+        Some(rev) => {
+            let (root, rev) = (root.clone(), rev.to_string());
+            let snapshot = tokio::task::spawn_blocking(move || Snapshot::open(&root, &rev))
+                .await
+                .map_err(|e| crate::types::AppError::Indexing(e.to_string()))??;
+            status.commit = Some(snapshot.commit.clone());
+            status.branch = git_ref.map(str::to_string);
+            Some(Arc::new(snapshot))
+        }
+        None => {
+            if let Some(head) = git::head(&root) {
+                status.commit = Some(head.commit);
+                status.branch = head.branch;
+            }
+            None
+        }
+    };
+
+    let files = match &snapshot {
+        Some(snapshot) => snapshot.files(),
+        None => {
+            let monitor = monitor.clone();
+            let root = root.clone();
+            tokio::task::spawn_blocking(move || {
+                scan_directory_until(&root, || !monitor.is_cancelled())
+            })
+            .await
+            .map_err(|e| crate::types::AppError::Indexing(format!("scan failed: {}", e)))??
+        }
+    };
+    if monitor.is_cancelled() {
+        return finish_cancelled(&state, &monitor, status).await;
+    }
+    let stored_hashes = state.storage.get_file_hashes(project_id).await?;
+
+    // Files indexed previously that no longer exist
+    let scanned: HashSet<String> = files.iter().map(|f| relative_path(&root, f)).collect();
+    let mut removed_files = 0;
+    for path in stored_hashes.keys().filter(|p| !scanned.contains(*p)) {
+        forget_file(&state, project_id, path).await;
+        removed_files += 1;
+    }
 
-    let files = scan_directory(project_path)?;
     status.total_files = files.len() as u32;
     tracing::info!(
         project = %project_id,
         total_files = status.total_files,
+        known_files = stored_hashes.len(),
+        removed_files,
+        resuming,
         "Indexing started"
     );
-    monitor
-        .total_files
-        .store(status.total_files, std::sync::atomic::Ordering::Relaxed);
-    monitor
-        .indexed_files
-        .store(0, std::sync::atomic::Ordering::Relaxed);
+    monitor.start(status.total_files);
 
     state.storage.update_index_status(status.clone()).await?;
 
-    let batch_size = 20;
-    let mut chunk_buffer = Vec::with_capacity(batch_size);
-    let mut symbol_buffer = Vec::with_capacity(batch_size);
-    let mut symbol_index = SymbolIndex::new();
-    let mut relation_buffer: Vec<CodeReference> = Vec::new();
-    let mut total_relation_stats = RelationStats::default();
-
-    const MAX_CHUNKS_PER_FILE: usize = 50;
+    // Parse stage: read, hash, chunk and parse across the rayon pool
+    let (tx, mut rx) = tokio::sync::mpsc::channel(PIPELINE_DEPTH);
+    let parse_stage = {
+        let monitor = monitor.clone();
+        let project_id = project_id.to_string();
+        let pool = parse_pool(&state.config.index_budget);
+        let max_file_size = state.config.max_file_size;
+        tokio::task::spawn_blocking(move || {
+            let parse = || {
+                files.par_iter().for_each_with(tx, |tx, file_path| {
+                    if tx.is_closed() || monitor.is_cancelled() {
+                        return;
+                    }
+                    let started = Instant::now();
+                    let load = || match &snapshot {
+                        Some(snapshot) => snapshot.read(file_path, max_file_size),
+                        None => read_source(file_path, max_file_size),
+                    };
+                    let prepared = prepare_file(
+                        &root,
+                        file_path,
+                        &project_id,
+                        &stored_hashes,
+                        resuming,
+                        load,
+                    );
+                    monitor.parse.record(1, started.elapsed());
+                    let _ = tx.blocking_send(prepared);
+                })
+            };
+            match pool {
+                Some(pool) => pool.install(parse),
+                None => parse(),
+            }
+        })
+    };
 
-    for file_path in &files {
-        // Skip auto-generated files (no useful semantic content)
-        if crate::codebase::scanner::is_ignored_file(file_path) {
-            tracing::debug!(path = ?file_path, "Skipping generated file");
-            status.indexed_files += 1;
-            continue;
+    // Store stage: one write per file in flight per db_semaphore permit
+    let mut writes = tokio::task::JoinSet::new();
+    let mut stored = Vec::new();
+    let mut unchanged_files = 0u32;
+    let mut cancelled = false;
+    while let Some(prepared) = rx.recv().await {
+        if !checkpoint(&state, &monitor, &mut status).await {
+            cancelled = true;
+            break;
         }
-
-        // Warn on large files but still process them (with chunk cap)
-        if let Ok(meta) = fs::metadata(file_path).await {
-            if meta.len() > 1_048_576 {
-                tracing::warn!(
-                    path = ?file_path,
-                    size_kb = meta.len() / 1024,
-                    "Large file detected (>1MB), will cap at {} chunks",
-                    MAX_CHUNKS_PER_FILE
-                );
+        let write = match prepared {
+            PreparedFile::Skipped => None,
+            PreparedFile::Excluded {
+                path,
+                reason,
+                indexed,
+            } => {
+                if indexed {
+                    forget_file(&state, project_id, &path).await;
+                }
+                status.skipped_files.push(SkippedFile { path, reason });
+                None
             }
-        }
+            PreparedFile::Unchanged => {
+                unchanged_files += 1;
+                None
+            }
+            PreparedFile::Failed(path) => {
+                status.failed_files.push(path);
+                None
+            }
+            relink @ PreparedFile::Relink { .. } => {
+                unchanged_files += 1;
+                Some(relink)
+            }
+            changed => Some(changed),
+        };
 
-        let content = match fs::read_to_string(file_path).await {
-            Ok(c) => c,
-            Err(e) => {
-                tracing::warn!("Failed to read file {:?}: {}", file_path, e);
-                status
-                    .failed_files
-                    .push(file_path.to_string_lossy().to_string());
-                status.indexed_files += 1;
+        if let Some(prepared) = write {
+            let permit = state
+                .db_semaphore
+                .clone()
+                .acquire_owned()
+                .await
+                .map_err(|e| crate::types::AppError::Internal(e.to_string()))?;
+            let state = state.clone();
+            let monitor = monitor.clone();
+            let project_id = project_id.to_string();
+            writes.spawn(async move {
+                let _permit = permit;
+                let started = Instant::now();
+                let file = write_file(&state, &project_id, prepared).await;
+                monitor.store.record(1, started.elapsed());
                 monitor
                     .indexed_files
                     .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-                continue;
-            }
-        };
-
-        // Store file-level hash for incremental indexing
-        let file_path_str = file_path.to_string_lossy().to_string();
-        let file_hash = blake3::hash(content.as_bytes()).to_hex().to_string();
-        let _ = state
-            .storage
-            .set_file_hash(project_id, &file_path_str, &file_hash)
-            .await;
-
-        // 1. Chunking (Vector Search) — cap chunks per file to bound memory
-        let mut chunks = chunk_file(file_path, &content, project_id);
-        if chunks.len() > MAX_CHUNKS_PER_FILE {
-            tracing::info!(
-                path = ?file_path,
-                total = chunks.len(),
-                kept = MAX_CHUNKS_PER_FILE,
-                "Capping chunks for large file"
-            );
-            chunks.truncate(MAX_CHUNKS_PER_FILE);
-        }
-        for chunk in chunks {
-            chunk_buffer.push(chunk);
-            status.total_chunks += 1;
-
-            if chunk_buffer.len() >= batch_size {
-                let batch = std::mem::take(&mut chunk_buffer);
-                let _permit = state.db_semaphore.acquire().await;
-                if let Ok(results) = state.storage.create_code_chunks_batch(batch).await {
-                    for (id, chunk) in results {
-                        let _ = state
-                            .embedding_queue
-                            .send(EmbeddingRequest {
-                                text: chunk.content,
-                                responder: None,
-                                target: Some(EmbeddingTarget::Chunk(id)),
-                                retry_count: 0,
-                            })
-                            .await;
-                    }
-                }
+                file
+            });
+            while let Some(done) = writes.try_join_next() {
+                collect_write(done, &mut stored);
             }
+        } else {
+            monitor
+                .indexed_files
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
         }
 
-        // 2. Parsing (Code Graph)
-        let (symbols, references) = CodeParser::parse_file(file_path, &content, project_id);
-
-        if !symbols.is_empty() {
-            tracing::debug!("File {:?}: found {} symbols", file_path, symbols.len());
-        }
-
-        // Add symbols to in-memory index FIRST (for relation resolution)
-        for symbol in &symbols {
-            symbol_index.add(symbol);
-        }
-
-        for symbol in symbols {
-            symbol_buffer.push(symbol);
-            status.total_symbols += 1;
-
-            if symbol_buffer.len() >= batch_size {
-                let batch = std::mem::take(&mut symbol_buffer);
-                let _permit = state.db_semaphore.acquire().await;
-                // 1. Insert batch to get IDs
-                match state.storage.create_code_symbols_batch(batch.clone()).await {
-                    Ok(ids) => {
-                        // 2. Queue for async embedding
-                        for (id, sym) in ids.iter().zip(batch.iter()) {
-                            if let Some(sig) = &sym.signature {
-                                let _ = state
-                                    .embedding_queue
-                                    .send(EmbeddingRequest {
-                                        text: sig.clone(),
-                                        responder: None,
-                                        target: Some(EmbeddingTarget::Symbol(id.clone())),
-                                        retry_count: 0,
-                                    })
-                                    .await;
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        tracing::error!(
-                            count = batch.len(),
-                            error = %e,
-                            "Failed to store symbol batch"
-                        );
-                    }
-                }
-
-                // Relations are deferred to final flush after ALL symbols are indexed
-                // (removing mid-loop flush fixes cross-file forward reference loss)
-            }
-        }
-
-        // Buffer references for deferred processing (after symbols are in DB)
-        relation_buffer.extend(references);
-
-        status.indexed_files += 1;
-        monitor
+        let indexed = monitor
             .indexed_files
-            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-
-        if status.indexed_files.is_multiple_of(10) {
-            let percent = (status.indexed_files as f32 / status.total_files as f32 * 100.0) as u32;
-            tracing::info!(
-                indexed = status.indexed_files,
-                total = status.total_files,
-                percent,
-                chunks = status.total_chunks,
-                symbols = status.total_symbols,
-                failed = status.failed_files.len(),
-                "Indexing progress"
-            );
+            .load(std::sync::atomic::Ordering::Relaxed);
+        if indexed >= status.indexed_files + 10 {
+            status.indexed_files = indexed;
+            log_progress(&status, unchanged_files);
             if let Err(e) = state.storage.update_index_status(status.clone()).await {
                 tracing::warn!("Failed to update intermediate status: {}", e);
             }
         }
     }
-
-    if !chunk_buffer.is_empty() {
-        let _permit = state.db_semaphore.acquire().await;
-        if let Ok(results) = state.storage.create_code_chunks_batch(chunk_buffer).await {
-            for (id, chunk) in results {
-                let _ = state
-                    .embedding_queue
-                    .send(EmbeddingRequest {
-                        text: chunk.content,
-                        responder: None,
-                        target: Some(EmbeddingTarget::Chunk(id)),
-                        retry_count: 0,
-                    })
-                    .await;
-            }
-        }
+    // Files in flight are finished either way, so a cancelled run leaves
+    // every file either fully stored or untouched
+    drop(rx);
+    while let Some(done) = writes.join_next().await {
+        collect_write(done, &mut stored);
     }
+    if let Err(e) = parse_stage.await {
+        return Err(crate::types::AppError::Indexing(format!(
+            "parse stage failed: {}",
+            e
+        )));
+    }
+    status.indexed_files = monitor
+        .indexed_files
+        .load(std::sync::atomic::Ordering::Relaxed);
 
-    if !symbol_buffer.is_empty() {
-        let batch = symbol_buffer;
-        let _permit = state.db_semaphore.acquire().await;
-        let ids = state
-            .storage
-            .create_code_symbols_batch(batch.clone())
-            .await?;
+    // Relations are resolved once every file's symbols are stored
+    // (cross-file forward references), against the whole project
+    let started = Instant::now();
+    let index = state
+        .symbol_indexes
+        .get_or_load(state.storage.as_ref(), project_id)
+        .await?;
+    let mut symbol_index = index.write().await;
 
-        for (id, sym) in ids.iter().zip(batch.iter()) {
-            if let Some(sig) = &sym.signature {
-                let _ = state
-                    .embedding_queue
-                    .send(EmbeddingRequest {
-                        text: sig.clone(),
-                        responder: None,
-                        target: Some(EmbeddingTarget::Symbol(id.clone())),
-                        retry_count: 0,
-                    })
-                    .await;
-            }
-        }
+    for file in &stored {
+        symbol_index.replace_file(&file.path, &file.symbols);
     }
+    if cancelled {
+        // Edges of the stored files are rebuilt when the run is resumed
+        drop(symbol_index);
+        return finish_cancelled(&state, &monitor, status).await;
+    }
+
+    let mut relation_buffer: Vec<CodeReference> = Vec::new();
+    let mut containment_buffer = Vec::new();
+    let mut link_candidates: Vec<SymbolRef> = Vec::new();
+    for file in &mut stored {
+        containment_buffer.extend(containment_relations(project_id, &file.symbols));
+        relation_buffer.append(&mut file.references);
+        link_candidates.append(&mut file.link_candidates);
+    }
+    let symbol_index = symbol_index.downgrade();
 
-    // Final flush of remaining relations
+    let mut total_relation_stats = RelationStats::default();
     if !relation_buffer.is_empty() {
         let stats = create_symbol_relations(
             state.storage.as_ref(),
@@ -285,6 +543,26 @@ async fn do_index_project(
         total_relation_stats.unresolved += stats.unresolved;
     }
 
+    let stats = store_relations(state.storage.as_ref(), containment_buffer).await;
+    total_relation_stats.created += stats.created;
+    total_relation_stats.failed += stats.failed;
+
+    // Link code types to same-named schema definitions (proto, GraphQL, SQL)
+    let stats = create_schema_links(
+        state.storage.as_ref(),
+        project_id,
+        &link_candidates,
+        &symbol_index,
+    )
+    .await;
+    total_relation_stats.created += stats.created;
+    total_relation_stats.failed += stats.failed;
+    drop(symbol_index);
+    monitor
+        .relations
+        .record(stored.len() as u32, started.elapsed());
+    monitor.finish();
+
     // Log relation stats
     if total_relation_stats.created > 0 || total_relation_stats.failed > 0 {
         tracing::info!(
@@ -295,6 +573,16 @@ async fn do_index_project(
         );
     }
 
+    status.total_chunks = state
+        .storage
+        .count_chunks(project_id)
+        .await
+        .unwrap_or_else(|_| stored.iter().map(|f| f.chunks).sum());
+    status.total_symbols = state
+        .storage
+        .count_symbols(project_id)
+        .await
+        .unwrap_or_else(|_| stored.iter().map(|f| f.symbols.len() as u32).sum());
     status.status = IndexState::EmbeddingPending;
     status.completed_at = Some(crate::types::Datetime::default());
 
@@ -303,150 +591,577 @@ async fn do_index_project(
     Ok(status)
 }
 
-/// Incremental re-index for changed files only
+/// Wait while the job is paused, recording the pause in the stored status.
+/// Returns false once the job is cancelled.
+async fn checkpoint(state: &AppState, monitor: &IndexMonitor, status: &mut IndexStatus) -> bool {
+    if !monitor.is_paused() {
+        return !monitor.is_cancelled();
+    }
+
+    status.indexed_files = monitor
+        .indexed_files
+        .load(std::sync::atomic::Ordering::Relaxed);
+    status.status = IndexState::Paused;
+    let _ = state.storage.update_index_status(status.clone()).await;
+    tracing::info!(project = %status.project_id, indexed = status.indexed_files, "Indexing paused");
+
+    let proceed = monitor.checkpoint().await;
+    if proceed {
+        status.status = IndexState::Indexing;
+        let _ = state.storage.update_index_status(status.clone()).await;
+        tracing::info!(project = %status.project_id, "Indexing resumed");
+    }
+    proceed
+}
+
+/// Record a cancelled run. Stored files keep their hashes; the next run
+/// skips them and rebuilds their edges.
+async fn finish_cancelled(
+    state: &AppState,
+    monitor: &IndexMonitor,
+    mut status: IndexStatus,
+) -> Result<IndexStatus> {
+    monitor.finish();
+    status.indexed_files = monitor
+        .indexed_files
+        .load(std::sync::atomic::Ordering::Relaxed);
+    status.status = IndexState::Cancelled;
+    status.completed_at = Some(crate::types::Datetime::default());
+    tracing::info!(
+        project = %status.project_id,
+        indexed = status.indexed_files,
+        total = status.total_files,
+        "Indexing cancelled"
+    );
+    state.storage.update_index_status(status.clone()).await?;
+    Ok(status)
+}
+
+/// Thread pool for the parse stage when the CPU budget is limited.
+fn parse_pool(budget: &IndexBudget) -> Option<rayon::ThreadPool> {
+    if budget.threads.is_none() && !budget.idle_only {
+        return None;
+    }
+    let mut builder = rayon::ThreadPoolBuilder::new().thread_name(|i| format!("index-parse-{}", i));
+    if let Some(threads) = budget.threads {
+        builder = builder.num_threads(threads);
+    }
+    if budget.idle_only {
+        builder = builder.start_handler(|_| lower_thread_priority());
+    }
+    match builder.build() {
+        Ok(pool) => Some(pool),
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to build parser pool, using the global one");
+            None
+        }
+    }
+}
+
+/// Schedule the calling thread only when a CPU would otherwise be idle.
+#[cfg(target_os = "linux")]
+fn lower_thread_priority() {
+    let param = libc::sched_param { sched_priority: 0 };
+    // pid 0 is the calling thread
+    if unsafe { libc::sched_setscheduler(0, libc::SCHED_IDLE, &param) } != 0 {
+        tracing::warn!(
+            error = %std::io::Error::last_os_error(),
+            "Failed to set idle scheduling for parser thread"
+        );
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn lower_thread_priority() {
+    tracing::debug!("Idle-only indexing is only supported on Linux; using the thread cap alone");
+}
+
+fn collect_write(
+    done: std::result::Result<StoredFile, tokio::task::JoinError>,
+    stored: &mut Vec<StoredFile>,
+) {
+    match done {
+        Ok(file) => stored.push(file),
+        Err(e) => tracing::error!(error = %e, "File write task failed"),
+    }
+}
+
+fn log_progress(status: &IndexStatus, unchanged_files: u32) {
+    let percent = (status.indexed_files as f32 / status.total_files as f32 * 100.0) as u32;
+    tracing::info!(
+        indexed = status.indexed_files,
+        total = status.total_files,
+        percent,
+        unchanged = unchanged_files,
+        failed = status.failed_files.len(),
+        "Indexing progress"
+    );
+}
+
+/// Incremental re-index for changed files only, given as paths under the
+/// project `root`. A path that no longer exists and was not an indexed file
+/// is taken to be a deleted directory.
 pub async fn incremental_index(
     state: Arc<AppState>,
     project_id: &str,
+    root: &Path,
     changed_paths: Vec<std::path::PathBuf>,
 ) -> Result<usize> {
     let mut updated = 0;
+    let mut stored_paths: Option<Vec<String>> = None;
 
     for path in changed_paths {
-        let path_str = path.to_string_lossy().to_string();
+        let path_str = relative_path(root, &path);
 
         if !path.exists() {
-            match state
-                .storage
-                .delete_chunks_by_path(project_id, &path_str)
-                .await
-            {
-                Ok(deleted) => {
-                    if deleted > 0 {
-                        tracing::debug!(path = %path_str, deleted, "Removed chunks for deleted file");
-                        updated += 1;
-                    }
-                }
-                Err(e) => {
-                    tracing::warn!(path = %path_str, error = %e, "Failed to delete chunks");
-                }
+            if let Ok(Some(_)) = state.storage.get_file_hash(project_id, &path_str).await {
+                tracing::debug!(path = %path_str, "Removing deleted file from index");
+                forget_file(&state, project_id, &path_str).await;
+                updated += 1;
+                continue;
+            }
+
+            if stored_paths.is_none() {
+                stored_paths = Some(
+                    state
+                        .storage
+                        .get_file_hashes(project_id)
+                        .await?
+                        .into_keys()
+                        .collect(),
+                );
+            }
+            let removed = files_under(stored_paths.as_deref().unwrap_or_default(), &path_str);
+            if !removed.is_empty() {
+                tracing::debug!(path = %path_str, files = removed.len(), "Removing deleted directory from index");
+            }
+            for file in removed {
+                forget_file(&state, project_id, &file).await;
+                updated += 1;
             }
-            // Also delete symbols and file hash
-            let _ = state
-                .storage
-                .delete_symbols_by_path(project_id, &path_str)
-                .await;
-            let _ = state.storage.delete_file_hash(project_id, &path_str).await;
             continue;
         }
 
-        let content = match tokio::fs::read_to_string(&path).await {
-            Ok(c) => c,
+        if reindex_file(&state, project_id, root, &path, None).await? {
+            updated += 1;
+        }
+    }
+
+    Ok(updated)
+}
+
+/// Bring a worktree project up to date after its git HEAD moved (checkout,
+/// commit, pull): reindex the files that differ between the indexed commit
+/// and the new HEAD, plus `paths` changed in the worktree, then record the
+/// new commit. Returns the number of files updated.
+pub async fn sync_git_head(
+    state: Arc<AppState>,
+    project_id: &str,
+    root: &Path,
+    mut paths: Vec<std::path::PathBuf>,
+) -> Result<usize> {
+    let head = {
+        let root = root.to_path_buf();
+        tokio::task::spawn_blocking(move || git::head(&root))
+            .await
+            .map_err(|e| crate::types::AppError::Indexing(e.to_string()))?
+    };
+    let status = state.storage.get_index_status(project_id).await?;
+    let (Some(head), Some(status)) = (head, status) else {
+        return incremental_index(state, project_id, root, paths).await;
+    };
+    // A running full index records the commit it started from itself
+    if status.status.is_unfinished() || status.commit.as_deref() == Some(head.commit.as_str()) {
+        return incremental_index(state, project_id, root, paths).await;
+    }
+
+    if let Some(indexed) = status.commit.clone() {
+        let diff = {
+            let (root, to) = (root.to_path_buf(), head.commit.clone());
+            tokio::task::spawn_blocking(move || git::changed_files(&root, &indexed, &to))
+                .await
+                .map_err(|e| crate::types::AppError::Indexing(e.to_string()))?
+        };
+        match diff {
+            Ok(files) => {
+                tracing::info!(
+                    project = %project_id,
+                    commit = %head.commit,
+                    branch = ?head.branch,
+                    files = files.len(),
+                    "HEAD moved, reindexing changed files"
+                );
+                paths.extend(files);
+            }
             Err(e) => {
-                tracing::warn!(path = %path_str, error = %e, "Failed to read file");
-                continue;
+                // e.g. the indexed commit was garbage-collected after a rebase
+                tracing::warn!(project = %project_id, error = %e, "Cannot diff against indexed commit, rescanning");
+                let status = index_project(state, root).await?;
+                return Ok(status.indexed_files as usize);
             }
+        }
+    }
+    paths.sort();
+    paths.dedup();
+
+    let updated = incremental_index(state.clone(), project_id, root, paths).await?;
+    if let Some(mut status) = state.storage.get_index_status(project_id).await? {
+        status.commit = Some(head.commit);
+        status.branch = head.branch;
+        state.storage.update_index_status(status).await?;
+    }
+    if let Err(e) = index_history(state, project_id, root).await {
+        tracing::warn!(project_id = %project_id, error = %e, "History indexing failed");
+    }
+    Ok(updated)
+}
+
+/// Move a renamed file or directory's index entries to the new path. Chunks
+/// and their embeddings are kept unless the content changed too; symbols are
+/// re-keyed under the new path with inbound edges carried over.
+pub async fn rename_file(
+    state: Arc<AppState>,
+    project_id: &str,
+    root: &Path,
+    from: &Path,
+    to: &Path,
+) -> Result<usize> {
+    let from_str = relative_path(root, from);
+    let moves: Vec<(String, std::path::PathBuf)> =
+        if let Ok(Some(_)) = state.storage.get_file_hash(project_id, &from_str).await {
+            vec![(from_str, to.to_path_buf())]
+        } else {
+            let stored: Vec<String> = state
+                .storage
+                .get_file_hashes(project_id)
+                .await?
+                .into_keys()
+                .collect();
+            files_under(&stored, &from_str)
+                .into_iter()
+                .filter_map(|old| {
+                    let relative = Path::new(&old).strip_prefix(&from_str).ok()?.to_path_buf();
+                    Some((old, to.join(relative)))
+                })
+                .collect()
         };
 
-        let new_hash = blake3::hash(content.as_bytes()).to_hex().to_string();
+    if moves.is_empty() {
+        // Nothing indexed at the old path; treat as a new file
+        return incremental_index(state, project_id, root, vec![to.to_path_buf()]).await;
+    }
+
+    let mut updated = 0;
+    for (old, new) in moves {
+        if !new.is_file() {
+            forget_file(&state, project_id, &old).await;
+            updated += 1;
+            continue;
+        }
+        let moved = state
+            .storage
+            .rename_file(project_id, &old, &relative_path(root, &new))
+            .await?;
+        tracing::debug!(from = %old, to = ?new, chunks = moved, "Moved file in index");
+        reindex_file(&state, project_id, root, &new, Some(&old)).await?;
+        updated += 1;
+    }
+
+    Ok(updated)
+}
+
+/// Stored file paths inside the project-relative directory `dir`.
+fn files_under(stored: &[String], dir: &str) -> Vec<String> {
+    let dir = Path::new(dir);
+    stored
+        .iter()
+        .filter(|p| {
+            let p = Path::new(p.as_str());
+            p != dir && p.starts_with(dir)
+        })
+        .cloned()
+        .collect()
+}
 
-        // Compare file-level hash from dedicated file_hashes table
-        if let Ok(Some(existing_hash)) = state.storage.get_file_hash(project_id, &path_str).await {
-            if existing_hash == new_hash {
-                continue; // File unchanged, skip re-indexing
+/// Re-parse one file and update its chunks, symbols and edges. `previous` is
+/// the path its symbols were stored under before a rename. Returns whether
+/// anything changed.
+async fn reindex_file(
+    state: &Arc<AppState>,
+    project_id: &str,
+    root: &Path,
+    path: &Path,
+    previous: Option<&str>,
+) -> Result<bool> {
+    let path_str = relative_path(root, path);
+    let source = {
+        let path = path.to_path_buf();
+        let max_file_size = state.config.max_file_size;
+        tokio::task::spawn_blocking(move || read_source(&path, max_file_size))
+            .await
+            .map_err(|e| crate::types::AppError::Indexing(e.to_string()))?
+    };
+    let content = match source {
+        Ok(Source::Text(c)) => c,
+        Ok(Source::Skipped(reason)) => {
+            // Grew too large or turned into generated code: drop what was indexed
+            tracing::debug!(path = %path_str, %reason, "Skipping file");
+            if let Some(previous) = previous {
+                forget_file(state, project_id, previous).await;
+            } else if !matches!(
+                state.storage.get_file_hash(project_id, &path_str).await,
+                Ok(Some(_))
+            ) {
+                return Ok(false);
             }
+            forget_file(state, project_id, &path_str).await;
+            return Ok(true);
         }
+        Err(e) => {
+            tracing::warn!(path = %path_str, error = %e, "Failed to read file");
+            return Ok(false);
+        }
+    };
 
+    let new_hash = blake3::hash(content.as_bytes()).to_hex().to_string();
+
+    // Compare file-level hash from dedicated file_hashes table
+    let content_changed = match state.storage.get_file_hash(project_id, &path_str).await {
+        Ok(Some(existing_hash)) => existing_hash != new_hash,
+        _ => true,
+    };
+    if !content_changed && previous.is_none() {
+        return Ok(false); // File unchanged, skip re-indexing
+    }
+
+    let (symbols, references) = CodeParser::parse_file(Path::new(&path_str), &content, project_id);
+
+    let permit = state.db_semaphore.acquire().await;
+    // 1. Chunks - async via queue (consistent with index_project)
+    if content_changed {
         let _ = state
             .storage
             .delete_chunks_by_path(project_id, &path_str)
             .await;
-        let _ = state
-            .storage
-            .delete_symbols_by_path(project_id, &path_str)
-            .await;
+        let chunks = super::chunker::chunk_file(Path::new(&path_str), &content, project_id);
+        store_chunks(state, chunks).await;
+    }
 
-        // 1. Chunks - async via queue (consistent with index_project)
-        let chunks = super::chunker::chunk_file(&path, &content, project_id);
-
-        let _permit = state.db_semaphore.acquire().await;
-        if let Ok(results) = state.storage.create_code_chunks_batch(chunks).await {
-            for (id, chunk) in results {
-                let _ = state
-                    .embedding_queue
-                    .send(EmbeddingRequest {
-                        text: chunk.content,
-                        responder: None,
-                        target: Some(EmbeddingTarget::Chunk(id)),
-                        retry_count: 0,
-                    })
-                    .await;
-            }
-        }
+    // 2. Symbols, updated in place
+    let old_path = previous.unwrap_or(&path_str);
+    let old_keys = replace_file_symbols(state, project_id, old_path, &symbols).await;
+    drop(permit);
+    let contains = store_relations(
+        state.storage.as_ref(),
+        containment_relations(project_id, &symbols),
+    )
+    .await;
+    if contains.failed > 0 {
+        tracing::warn!(
+            path = %path_str,
+            failed = contains.failed,
+            "Failed to store containment relations"
+        );
+    }
 
-        // 2. Symbols
-        let (symbols, references) = CodeParser::parse_file(&path, &content, project_id);
-        if !symbols.is_empty() {
-            let _permit = state.db_semaphore.acquire().await;
-            let created_ids = match state
-                .storage
-                .create_code_symbols_batch(symbols.clone())
-                .await
-            {
-                Ok(ids) => ids,
-                Err(e) => {
-                    tracing::warn!(path = %path_str, error = %e, "Failed to create symbols");
-                    vec![]
-                }
-            };
+    // Create relations using the project-wide symbol index for cross-file resolution
+    let index = state
+        .symbol_indexes
+        .get_or_load(state.storage.as_ref(), project_id)
+        .await?;
+    {
+        let mut index = index.write().await;
+        index.remove_file(old_path);
+        index.replace_file(&path_str, &symbols);
+    }
+    if !references.is_empty() || !symbols.is_empty() {
+        // After a rename, links into the file's schema symbols were carried over
+        let link_old_keys = match previous {
+            Some(_) => symbols.iter().map(CodeSymbol::unique_key).collect(),
+            None => old_keys,
+        };
+        let symbol_index = index.read().await;
+        let stats = create_symbol_relations(
+            state.storage.as_ref(),
+            project_id,
+            &references,
+            &symbol_index,
+        )
+        .await;
+        if stats.failed > 0 {
+            tracing::warn!(
+                path = %path_str,
+                failed = stats.failed,
+                "Failed to store symbol relations"
+            );
+        }
 
-            for (id, sym) in created_ids.iter().zip(symbols.iter()) {
-                if let Some(sig) = &sym.signature {
-                    let _ = state
-                        .embedding_queue
-                        .send(EmbeddingRequest {
-                            text: sig.clone(),
-                            responder: None,
-                            target: Some(EmbeddingTarget::Symbol(id.clone())),
-                            retry_count: 0,
-                        })
-                        .await;
-                }
-            }
+        let links = create_schema_links(
+            state.storage.as_ref(),
+            project_id,
+            &schema_link_candidates(&symbols, &link_old_keys),
+            &symbol_index,
+        )
+        .await;
+        if links.failed > 0 {
+            tracing::warn!(
+                path = %path_str,
+                failed = links.failed,
+                "Failed to store schema links"
+            );
         }
+    }
 
-        // Create relations using project-wide symbol index for cross-file resolution
-        if !references.is_empty() {
-            let mut symbol_index = SymbolIndex::new();
-            // Load ALL project symbols from DB for cross-file resolution
-            if let Ok(all_symbols) = state.storage.get_project_symbols(project_id).await {
-                symbol_index.add_batch(&all_symbols);
-            }
-            // Also add current file's new symbols (may not be in DB yet)
-            symbol_index.add_batch(&symbols);
-            let _stats = create_symbol_relations(
-                state.storage.as_ref(),
-                project_id,
-                &references,
-                &symbol_index,
-            )
-            .await;
+    // Store updated file hash
+    let _ = state
+        .storage
+        .set_file_hash(project_id, &path_str, &new_hash)
+        .await;
+    Ok(true)
+}
+
+/// Remove everything indexed for a file that no longer exists.
+async fn forget_file(state: &AppState, project_id: &str, path: &str) {
+    if let Err(e) = state.storage.delete_chunks_by_path(project_id, path).await {
+        tracing::warn!(path = %path, error = %e, "Failed to delete chunks");
+    }
+    let _ = state.storage.delete_symbols_by_path(project_id, path).await;
+    let _ = state.storage.delete_file_hash(project_id, path).await;
+    if let Some(index) = state.symbol_indexes.get(project_id).await {
+        index.write().await.remove_file(path);
+    }
+}
+
+/// Store a file's freshly parsed symbols in place of its previous version.
+///
+/// Edges created by the file's own code are dropped for the caller to rebuild.
+/// Inbound edges from other files stay attached to symbols that still exist
+/// (IDs are stable); those into removed symbols move to a symbol of the same
+/// name and kind if the file still has one, and are dropped otherwise.
+/// Returns the keys of the previous version's symbols.
+async fn replace_file_symbols(
+    state: &AppState,
+    project_id: &str,
+    path: &str,
+    symbols: &[CodeSymbol],
+) -> HashSet<String> {
+    let old_symbols = state
+        .storage
+        .get_symbols_by_path(project_id, path)
+        .await
+        .unwrap_or_default();
+    if let Err(e) = state
+        .storage
+        .delete_outgoing_relations_by_path(project_id, path)
+        .await
+    {
+        tracing::warn!(path = %path, error = %e, "Failed to delete relations");
+    }
+
+    if !symbols.is_empty() {
+        let texts = symbol_embedding_texts(&state.config.embedding_text, symbols);
+        let batch = symbols.iter().cloned().zip(texts).collect();
+        if let Err(e) = store_symbols(state, batch).await {
+            tracing::warn!(path = %path, error = %e, "Failed to create symbols");
         }
+    }
 
-        // Store updated file hash
-        let _ = state
+    let new_keys: HashSet<String> = symbols.iter().map(CodeSymbol::unique_key).collect();
+    for old in old_symbols
+        .iter()
+        .filter(|s| !new_keys.contains(&s.unique_key()))
+    {
+        let replacement = symbols
+            .iter()
+            .find(|s| s.name == old.name && s.symbol_type == old.symbol_type)
+            .map(|s| format!("code_symbols:{}", s.unique_key()));
+        let id = format!("code_symbols:{}", old.unique_key());
+        if let Err(e) = state
             .storage
-            .set_file_hash(project_id, &path_str, &new_hash)
-            .await;
-        updated += 1;
+            .remove_symbol(&id, replacement.as_deref())
+            .await
+        {
+            tracing::warn!(symbol = %id, error = %e, "Failed to remove stale symbol");
+        }
     }
 
-    Ok(updated)
+    old_symbols.iter().map(CodeSymbol::unique_key).collect()
+}
+
+/// Symbols of a replaced file to pass to `create_schema_links`: its code types,
+/// whose outgoing links were dropped, and schema symbols it did not have
+/// before. Links into existing schema symbols were kept.
+fn schema_link_candidates(symbols: &[CodeSymbol], old_keys: &HashSet<String>) -> Vec<SymbolRef> {
+    symbols
+        .iter()
+        .filter(|s| {
+            !detect_language(Path::new(&s.file_path)).is_schema()
+                || !old_keys.contains(&s.unique_key())
+        })
+        .map(SymbolRef::from_symbol)
+        .collect()
+}
+
+/// Insert a chunk batch and queue each chunk for embedding.
+/// The caller holds a `db_semaphore` permit.
+async fn store_chunks(state: &AppState, batch: Vec<CodeChunk>) {
+    if let Ok(results) = state.storage.create_code_chunks_batch(batch).await {
+        for (id, chunk) in results {
+            let _ = state
+                .embedding_queue
+                .send(EmbeddingRequest {
+                    text: chunk_text(&state.config.embedding_text, &chunk),
+                    responder: None,
+                    target: Some(EmbeddingTarget::Chunk(id)),
+                    retry_count: 0,
+                })
+                .await;
+        }
+    }
+}
+
+/// Insert a symbol batch and queue those with embedding text.
+/// The caller holds a `db_semaphore` permit.
+pub(super) async fn store_symbols(
+    state: &AppState,
+    batch: Vec<(CodeSymbol, Option<String>)>,
+) -> Result<()> {
+    let (symbols, texts): (Vec<_>, Vec<_>) = batch.into_iter().unzip();
+    let ids = state.storage.create_code_symbols_batch(symbols).await?;
+
+    for (id, text) in ids.into_iter().zip(texts) {
+        if let Some(text) = text {
+            let _ = state
+                .embedding_queue
+                .send(EmbeddingRequest {
+                    text,
+                    responder: None,
+                    target: Some(EmbeddingTarget::Symbol(id)),
+                    retry_count: 0,
+                })
+                .await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Embedding text per symbol, computed while the whole file's symbols are at
+/// hand so each can name its enclosing container.
+pub(super) fn symbol_embedding_texts(
+    config: &EmbeddingTextConfig,
+    symbols: &[CodeSymbol],
+) -> Vec<Option<String>> {
+    symbols
+        .iter()
+        .map(|s| symbol_text(config, s, enclosing_container(symbols, s), s.doc.as_deref()))
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_utils::TestContext;
+    use crate::test_utils::{TestContext, TestRepo};
     use std::fs;
 
     #[tokio::test]
@@ -470,6 +1185,10 @@ mod tests {
 
         assert_eq!(status.total_files, 150);
         assert_eq!(status.total_chunks, 150);
+        assert_eq!(
+            status.root.as_deref(),
+            Some(project_dir.canonicalize().unwrap().to_str().unwrap())
+        );
 
         let chunks = ctx
             .state
@@ -478,5 +1197,478 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(chunks.len(), 150);
+        // Stored relative to the project root
+        assert!(chunks.iter().all(|c| c.file_path.starts_with("file_")));
+
+        let monitor = ctx.state.progress.get("test_project").await.unwrap();
+        let pipeline = monitor.pipeline_stats().unwrap();
+        assert_eq!(pipeline["parse"]["files"], 150);
+        assert_eq!(pipeline["store"]["files"], 150);
+    }
+
+    #[tokio::test]
+    async fn test_incremental_reindex_keeps_inbound_edges() {
+        let ctx = TestContext::new().await;
+        let project_dir = ctx._temp_dir.path().join("proj");
+        fs::create_dir_all(project_dir.join("src")).unwrap();
+        let lib = project_dir.join("src/util.rs");
+        let main = project_dir.join("src/main.rs");
+        fs::write(&lib, "pub fn target() {}\npub fn old_name() {}\n").unwrap();
+        fs::write(
+            &main,
+            "use crate::util::{old_name, target};\n\nfn caller() {\n    target();\n    old_name();\n}\n",
+        )
+        .unwrap();
+
+        index_project(ctx.state.clone(), &project_dir)
+            .await
+            .unwrap();
+
+        let id_of = |symbols: &[CodeSymbol], name: &str| {
+            let s = symbols.iter().find(|s| s.name == name).unwrap();
+            format!("code_symbols:{}", s.unique_key())
+        };
+        let lib_path = "src/util.rs".to_string();
+        let before = ctx
+            .state
+            .storage
+            .get_symbols_by_path("proj", &lib_path)
+            .await
+            .unwrap();
+        let target_id = id_of(&before, "target");
+        assert_eq!(
+            ctx.state
+                .storage
+                .get_symbol_callers(&target_id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Shift every line and rename the other function
+        fs::write(
+            &lib,
+            "// header\n\npub fn target() {}\npub fn new_name() {}\n",
+        )
+        .unwrap();
+        incremental_index(ctx.state.clone(), "proj", &project_dir, vec![lib.clone()])
+            .await
+            .unwrap();
+
+        let after = ctx
+            .state
+            .storage
+            .get_symbols_by_path("proj", &lib_path)
+            .await
+            .unwrap();
+        assert_eq!(id_of(&after, "target"), target_id);
+        assert_eq!(after.len(), 2);
+        assert!(after.iter().all(|s| s.name != "old_name"));
+
+        let callers = ctx
+            .state
+            .storage
+            .get_symbol_callers(&target_id)
+            .await
+            .unwrap();
+        assert_eq!(callers.len(), 1);
+        assert_eq!(callers[0].name, "caller");
+    }
+
+    #[tokio::test]
+    async fn test_pause_cancel_and_resume_indexing() {
+        let ctx = TestContext::new().await;
+        let project_dir = ctx._temp_dir.path().join("proj");
+        fs::create_dir_all(&project_dir).unwrap();
+        for i in 0..300 {
+            fs::write(
+                project_dir.join(format!("file_{}.rs", i)),
+                format!("fn f_{}() {{}}", i),
+            )
+            .unwrap();
+        }
+
+        let monitor = ctx.state.progress.get_or_create("proj").await;
+        let job = {
+            let (state, dir) = (ctx.state.clone(), project_dir.clone());
+            tokio::spawn(async move { index_project(state, &dir).await })
+        };
+        while !monitor.pause() {
+            tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+        }
+
+        // A second run is refused while the first one holds the job
+        assert!(index_project(ctx.state.clone(), &project_dir)
+            .await
+            .is_err());
+
+        loop {
+            let status = ctx.state.storage.get_index_status("proj").await.unwrap();
+            if status.is_some_and(|s| s.status == IndexState::Paused) {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        assert!(monitor.cancel());
+
+        let cancelled = job.await.unwrap().unwrap();
+        assert_eq!(cancelled.status, IndexState::Cancelled);
+        assert!(cancelled.indexed_files < 300);
+        assert!(!monitor.is_running());
+        assert!(!monitor.resume());
+
+        let status = index_project(ctx.state.clone(), &project_dir)
+            .await
+            .unwrap();
+        assert_eq!(status.total_files, 300);
+        assert_eq!(status.total_chunks, 300);
+    }
+
+    #[tokio::test]
+    async fn test_rescan_deferred_to_running_job() {
+        let ctx = TestContext::new().await;
+        let project_dir = ctx._temp_dir.path().join("proj");
+        fs::create_dir_all(project_dir.join("generated")).unwrap();
+        for i in 0..300 {
+            fs::write(
+                project_dir.join(format!("file_{}.rs", i)),
+                format!("fn f_{}() {{}}", i),
+            )
+            .unwrap();
+        }
+        fs::write(project_dir.join("generated/gen.rs"), "fn gen() {}").unwrap();
+        fs::write(project_dir.join(".memoryignore"), "generated/\n").unwrap();
+
+        let monitor = ctx.state.progress.get_or_create("proj").await;
+        let job = {
+            let (state, dir) = (ctx.state.clone(), project_dir.clone());
+            tokio::spawn(async move { index_project(state, &dir).await })
+        };
+        while !monitor.pause() {
+            tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+        }
+        loop {
+            let status = ctx.state.storage.get_index_status("proj").await.unwrap();
+            if status.is_some_and(|s| s.status == IndexState::Paused) {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+
+        // The ignore change arrives while the first pass holds the job:
+        // gen.rs becomes visible and file_0.rs, already scanned, is ignored
+        fs::write(project_dir.join(".memoryignore"), "file_0.rs\n").unwrap();
+        assert!(rescan_project(ctx.state.clone(), &project_dir)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(monitor.resume());
+
+        let status = job.await.unwrap().unwrap();
+        assert_eq!(status.total_files, 300);
+        assert!(!monitor.is_running());
+        let symbols = |path: &'static str| {
+            let storage = ctx.state.storage.clone();
+            async move { storage.get_symbols_by_path("proj", path).await.unwrap() }
+        };
+        assert_eq!(symbols("generated/gen.rs").await.len(), 1);
+        assert!(symbols("file_0.rs").await.is_empty());
+
+        // Nothing is left pending for the next job
+        let status = index_project(ctx.state.clone(), &project_dir)
+            .await
+            .unwrap();
+        assert_eq!(status.total_files, 300);
+    }
+
+    #[tokio::test]
+    async fn test_encodings_and_skipped_files() {
+        let ctx = TestContext::new().await;
+        let project_dir = ctx._temp_dir.path().join("proj");
+        fs::create_dir_all(&project_dir).unwrap();
+        fs::write(
+            project_dir.join("latin1.py"),
+            b"# caf\xE9\ndef latin():\n    pass\n",
+        )
+        .unwrap();
+        let utf16: Vec<u8> = [0xFF, 0xFE]
+            .into_iter()
+            .chain(
+                "def wide():\n    pass\n"
+                    .encode_utf16()
+                    .flat_map(u16::to_le_bytes),
+            )
+            .collect();
+        fs::write(project_dir.join("wide.py"), utf16).unwrap();
+        fs::write(
+            project_dir.join("api.pb.go"),
+            "// Code generated by protoc-gen-go. DO NOT EDIT.\npackage api\n\nfunc Generated() {}\n",
+        )
+        .unwrap();
+        fs::write(
+            project_dir.join("huge.rs"),
+            "fn huge() {}\n".repeat(100_000),
+        )
+        .unwrap();
+
+        let status = index_project(ctx.state.clone(), &project_dir)
+            .await
+            .unwrap();
+        assert!(status.failed_files.is_empty());
+
+        let storage = &ctx.state.storage;
+        let symbols = |path: &'static str| async move {
+            storage.get_symbols_by_path("proj", path).await.unwrap()
+        };
+        assert_eq!(symbols("latin1.py").await[0].name, "latin");
+        assert_eq!(symbols("wide.py").await[0].name, "wide");
+        assert!(symbols("api.pb.go").await.is_empty());
+
+        let stored = storage.get_index_status("proj").await.unwrap().unwrap();
+        let mut skipped: Vec<_> = stored
+            .skipped_files
+            .iter()
+            .map(|f| (f.path.as_str(), f.reason))
+            .collect();
+        skipped.sort_by_key(|(path, _)| *path);
+        assert_eq!(
+            skipped,
+            vec![
+                ("api.pb.go", SkipReason::Generated),
+                ("huge.rs", SkipReason::TooLarge)
+            ]
+        );
+
+        // A file that turns into generated code is dropped from the index
+        fs::write(
+            project_dir.join("latin1.py"),
+            "# @generated by a tool\ndef latin():\n    pass\n",
+        )
+        .unwrap();
+        let updated = incremental_index(
+            ctx.state.clone(),
+            "proj",
+            &project_dir,
+            vec![project_dir.join("latin1.py")],
+        )
+        .await
+        .unwrap();
+        assert_eq!(updated, 1);
+        assert!(symbols("latin1.py").await.is_empty());
+        assert!(storage
+            .get_file_hash("proj", "latin1.py")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reindex_only_processes_changed_files() {
+        let ctx = TestContext::new().await;
+        let project_dir = ctx._temp_dir.path().join("proj");
+        fs::create_dir_all(&project_dir).unwrap();
+        let keep = project_dir.join("keep.rs");
+        let change = project_dir.join("change.rs");
+        let remove = project_dir.join("remove.rs");
+        fs::write(&keep, "fn keep() {}\n").unwrap();
+        fs::write(&change, "fn before() {}\n").unwrap();
+        fs::write(&remove, "fn remove() {}\n").unwrap();
+
+        index_project(ctx.state.clone(), &project_dir)
+            .await
+            .unwrap();
+
+        let storage = &ctx.state.storage;
+        let keep_path = "keep.rs".to_string();
+        let kept = storage
+            .get_chunks_by_path("proj", &keep_path)
+            .await
+            .unwrap();
+        let kept_id = format!(
+            "code_chunks:{}",
+            crate::types::record_key_to_string(&kept[0].id.as_ref().unwrap().key)
+        );
+        storage
+            .batch_update_chunk_embeddings(&[(kept_id.clone(), vec![0.5; 768])])
+            .await
+            .unwrap();
+
+        fs::write(&change, "fn after() {}\n").unwrap();
+        fs::remove_file(&remove).unwrap();
+        let status = index_project(ctx.state.clone(), &project_dir)
+            .await
+            .unwrap();
+        assert_eq!(status.total_files, 2);
+        assert_eq!(status.total_chunks, 2);
+
+        let kept = storage
+            .get_chunks_by_path("proj", &keep_path)
+            .await
+            .unwrap();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(
+            format!(
+                "code_chunks:{}",
+                crate::types::record_key_to_string(&kept[0].id.as_ref().unwrap().key)
+            ),
+            kept_id
+        );
+        assert!(kept[0].embedding.is_some());
+
+        let remove_path = "remove.rs".to_string();
+        assert!(storage
+            .get_chunks_by_path("proj", &remove_path)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(storage
+            .get_file_hash("proj", &remove_path)
+            .await
+            .unwrap()
+            .is_none());
+
+        let changed = storage
+            .get_symbols_by_path("proj", "change.rs")
+            .await
+            .unwrap();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].name, "after");
+    }
+
+    #[tokio::test]
+    async fn test_rename_and_directory_delete() {
+        let ctx = TestContext::new().await;
+        let project_dir = ctx._temp_dir.path().join("proj");
+        fs::create_dir_all(project_dir.join("src/sub")).unwrap();
+        let util = project_dir.join("src/util.rs");
+        fs::write(&util, "pub fn target() {}\n").unwrap();
+        fs::write(
+            project_dir.join("src/main.rs"),
+            "use crate::util::target;\n\nfn caller() {\n    target();\n}\n",
+        )
+        .unwrap();
+        fs::write(project_dir.join("src/sub/a.rs"), "fn a() {}\n").unwrap();
+        fs::write(project_dir.join("src/sub/b.rs"), "fn b() {}\n").unwrap();
+
+        index_project(ctx.state.clone(), &project_dir)
+            .await
+            .unwrap();
+
+        let storage = &ctx.state.storage;
+        let chunk_id = |chunks: &[CodeChunk]| {
+            crate::types::record_key_to_string(&chunks[0].id.as_ref().unwrap().key)
+        };
+        let util_path = "src/util.rs".to_string();
+        let before = storage
+            .get_chunks_by_path("proj", &util_path)
+            .await
+            .unwrap();
+
+        let helpers = project_dir.join("src/helpers.rs");
+        fs::rename(&util, &helpers).unwrap();
+        rename_file(ctx.state.clone(), "proj", &project_dir, &util, &helpers)
+            .await
+            .unwrap();
+
+        let helpers_path = "src/helpers.rs".to_string();
+        let after = storage
+            .get_chunks_by_path("proj", &helpers_path)
+            .await
+            .unwrap();
+        assert_eq!(chunk_id(&after), chunk_id(&before));
+        assert!(storage
+            .get_chunks_by_path("proj", &util_path)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(storage
+            .get_file_hash("proj", &util_path)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(storage
+            .get_file_hash("proj", &helpers_path)
+            .await
+            .unwrap()
+            .is_some());
+
+        let symbols = storage
+            .get_symbols_by_path("proj", &helpers_path)
+            .await
+            .unwrap();
+        let target_id = format!("code_symbols:{}", symbols[0].unique_key());
+        let callers = storage.get_symbol_callers(&target_id).await.unwrap();
+        assert_eq!(callers.len(), 1);
+        assert_eq!(callers[0].name, "caller");
+        assert!(storage
+            .get_symbols_by_path("proj", &util_path)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let sub = project_dir.join("src/sub");
+        fs::remove_dir_all(&sub).unwrap();
+        let removed = incremental_index(ctx.state.clone(), "proj", &project_dir, vec![sub.clone()])
+            .await
+            .unwrap();
+        assert_eq!(removed, 2);
+        assert!(storage
+            .get_file_hashes("proj")
+            .await
+            .unwrap()
+            .keys()
+            .all(|p| !p.starts_with("src/sub/")));
+    }
+
+    #[tokio::test]
+    async fn test_git_head_sync_and_ref_snapshot() {
+        let ctx = TestContext::new().await;
+        let project_dir = ctx._temp_dir.path().join("proj");
+        fs::create_dir_all(&project_dir).unwrap();
+        let repo = TestRepo::init(&project_dir);
+        fs::write(project_dir.join("a.rs"), "fn a_main() {}\n").unwrap();
+        fs::write(project_dir.join("b.rs"), "fn b() {}\n").unwrap();
+        repo.commit("first");
+
+        let status = index_project(ctx.state.clone(), &project_dir)
+            .await
+            .unwrap();
+        let first = git::head(&project_dir).unwrap();
+        assert_eq!(status.commit.as_deref(), Some(first.commit.as_str()));
+        assert_eq!(status.branch.as_deref(), Some("main"));
+
+        repo.checkout_new_branch("feature");
+        fs::write(project_dir.join("a.rs"), "fn a_feature() {}\n").unwrap();
+        repo.commit("second");
+
+        // Only the file in the tree diff is reindexed
+        let root = project_dir.canonicalize().unwrap();
+        let updated = sync_git_head(ctx.state.clone(), "proj", &root, Vec::new())
+            .await
+            .unwrap();
+        assert_eq!(updated, 1);
+        let storage = &ctx.state.storage;
+        let symbols = storage.get_symbols_by_path("proj", "a.rs").await.unwrap();
+        assert_eq!(symbols[0].name, "a_feature");
+        let status = storage.get_index_status("proj").await.unwrap().unwrap();
+        assert_eq!(
+            status.commit,
+            git::head(&project_dir).map(|head| head.commit)
+        );
+        assert_eq!(status.branch.as_deref(), Some("feature"));
+
+        // main indexed side by side from git objects
+        let snapshot = index_git_ref(ctx.state.clone(), &project_dir, "main")
+            .await
+            .unwrap();
+        assert_eq!(snapshot.project_id, "proj@main");
+        assert_eq!(snapshot.commit.as_deref(), Some(first.commit.as_str()));
+        assert_eq!(snapshot.total_files, 2);
+        let symbols = storage
+            .get_symbols_by_path("proj@main", "a.rs")
+            .await
+            .unwrap();
+        assert_eq!(symbols[0].name, "a_main");
     }
 }
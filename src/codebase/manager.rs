@@ -9,8 +9,10 @@ use crate::storage::StorageBackend;
 use crate::types::IndexState;
 use crate::Result;
 
-use super::indexer::index_project;
-use super::watcher::FileWatcher;
+use super::indexer::{
+    incremental_index, index_project, rename_file, rescan_project, sync_git_head,
+};
+use super::watcher::{ChangeBatch, FileWatcher};
 
 pub struct CodebaseManager {
     state: Arc<AppState>,
@@ -27,6 +29,9 @@ impl CodebaseManager {
             .unwrap_or("unknown")
             .to_string();
 
+        // Watcher events must share the root stored paths are relative to
+        let project_path = project_path.canonicalize().unwrap_or(project_path);
+
         Self {
             state,
             project_path,
@@ -59,11 +64,16 @@ impl CodebaseManager {
                     || s.status == IndexState::EmbeddingPending =>
             {
                 info!(status = %s.status, "Index exists, will use watcher for updates");
+                // Commits checked out while the server was down
+                self.spawn_git_sync();
             }
             Some(s) if s.status == IndexState::Indexing => {
-                warn!("Previous indexing was interrupted, restarting...");
+                warn!("Previous indexing was interrupted, resuming...");
                 self.spawn_full_index();
             }
+            Some(s) if s.status == IndexState::Paused || s.status == IndexState::Cancelled => {
+                info!(status = %s.status, "Indexing was stopped on request; index_project resumes it");
+            }
             Some(s) if s.status == IndexState::Failed => {
                 warn!("Previous indexing failed, restarting...");
                 self.spawn_full_index();
@@ -97,31 +107,73 @@ impl CodebaseManager {
         });
     }
 
+    fn spawn_git_sync(&self) {
+        let state = self.state.clone();
+        let project_id = self.project_id.clone();
+        let path = self.project_path.clone();
+
+        tokio::spawn(async move {
+            match sync_git_head(state, &project_id, &path, Vec::new()).await {
+                Ok(updated) if updated > 0 => info!(updated, "Caught up with git HEAD"),
+                Ok(_) => {}
+                Err(e) => error!("Git HEAD sync failed: {}", e),
+            }
+        });
+    }
+
     async fn start_watcher(&self) -> Result<()> {
-        let mut watcher = FileWatcher::new(vec![self.project_path.clone()]);
+        let mut watcher = FileWatcher::new(vec![self.project_path.clone()])
+            .with_mode(self.state.config.watch_mode);
 
         let state = self.state.clone();
         let project_id = self.project_id.clone();
+        let project_path = self.project_path.clone();
 
-        watcher.start(move |changed_paths| {
+        watcher.start(move |batch: ChangeBatch| {
             let state = state.clone();
             let project_id = project_id.clone();
+            let project_path = project_path.clone();
 
             tokio::spawn(async move {
+                // Ignore rules changed: a full pass diffs against stored hashes,
+                // indexing newly visible files and dropping newly ignored ones
+                if batch.rescan {
+                    info!("Ignore rules changed, rescanning project");
+                    match rescan_project(state, &project_path).await {
+                        Ok(Some(_)) => {}
+                        Ok(None) => info!("Indexing in progress, rescanning when it finishes"),
+                        Err(e) => error!("Rescan after ignore change failed: {}", e),
+                    }
+                    return;
+                }
+
                 info!(
-                    count = changed_paths.len(),
+                    count = batch.paths.len(),
+                    renames = batch.renames.len(),
                     "File changes detected, running incremental index"
                 );
-                match super::indexer::incremental_index(state, &project_id, changed_paths).await {
-                    Ok(updated) => {
-                        if updated > 0 {
-                            info!(updated, "Incremental index completed");
-                        }
+                let mut updated = 0;
+                for (from, to) in batch.renames {
+                    match rename_file(state.clone(), &project_id, &project_path, &from, &to).await {
+                        Ok(n) => updated += n,
+                        Err(e) => error!(from = ?from, to = ?to, "Rename failed: {}", e),
                     }
+                }
+                // After a checkout, the tree diff names the files to reindex
+                let result = if batch.head_changed {
+                    sync_git_head(state, &project_id, &project_path, batch.paths).await
+                } else {
+                    incremental_index(state, &project_id, &project_path, batch.paths).await
+                };
+                match result {
+                    Ok(n) => updated += n,
                     Err(e) => {
                         error!("Incremental index failed: {}", e);
                     }
                 }
+                if updated > 0 {
+                    info!(updated, "Incremental index completed");
+                }
             });
         })?;
 
@@ -1,16 +1,37 @@
 pub mod chunker;
+pub mod clones;
+pub mod deadcode;
+pub mod docs;
+pub mod embed_text;
+pub mod git;
+pub mod history;
 pub mod indexer;
 pub mod manager;
 pub mod parser;
+pub mod paths;
 pub mod relations;
+pub mod repomap;
 pub mod scanner;
+pub mod scip;
+pub mod source;
+pub mod structured;
 pub mod symbol_index;
 pub mod watcher;
 
-pub use indexer::{incremental_index, index_project};
+pub use history::index_history;
+pub use indexer::{
+    incremental_index, index_git_ref, index_project, rename_file, rescan_project, sync_git_head,
+};
 pub use manager::CodebaseManager;
 pub use parser::CodeParser;
-pub use relations::{create_symbol_relations, RelationStats};
-pub use scanner::{detect_language, is_code_file, scan_directory};
-pub use symbol_index::{ResolutionContext, SymbolIndex};
-pub use watcher::FileWatcher;
+pub use paths::{relative_path, server_path, PathMap};
+pub use relations::{create_schema_links, create_symbol_relations, RelationStats};
+pub use scanner::{
+    detect_language, is_code_file, is_doc_file, is_indexable_file, is_structured_file,
+    scan_directory,
+};
+pub use scip::{import_scip, ScipImportStats};
+pub use symbol_index::{
+    ImportScope, Resolution, ResolutionContext, SymbolIndex, SymbolIndexRegistry,
+};
+pub use watcher::{ChangeBatch, FileWatcher, WatchMode};
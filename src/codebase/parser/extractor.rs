@@ -1,9 +1,11 @@
 use streaming_iterator::StreamingIterator;
 use tree_sitter::{Parser, Query, QueryCursor};
 
-use crate::types::symbol::{CodeReference, CodeSymbol};
+use crate::codebase::structured::{looks_like_sql, sql_table_references};
+use crate::types::symbol::{CodeReference, CodeRelationType, CodeSymbol};
 use crate::types::Language;
 
+use super::imports::extract_imports;
 use super::languages::{get_language_support, LanguageSupport};
 
 pub struct Extractor {
@@ -40,7 +42,9 @@ impl Extractor {
         };
 
         let symbols = self.extract_symbols(&tree, content, file_path, project_id);
-        let references = self.extract_references(&tree, content, file_path, &symbols);
+        let mut references = self.extract_references(&tree, content, file_path, &symbols);
+        references.extend(self.import_references(&tree, content, file_path, &symbols));
+        references.extend(extract_sql_references(&tree, content, file_path, &symbols));
 
         (symbols, references)
     }
@@ -96,6 +100,13 @@ impl Extractor {
                         symbol = symbol.with_signature(sig);
                     }
 
+                    if let Some(doc) = self
+                        .support
+                        .extract_doc(&definition_node, content.as_bytes())
+                    {
+                        symbol = symbol.with_doc(doc);
+                    }
+
                     symbols.push(symbol);
                 }
             }
@@ -134,12 +145,7 @@ impl Extractor {
                     let start_line = node.start_position().row as u32 + 1;
                     let column = node.start_position().column as u32;
 
-                    // Find which symbol contains this reference
-                    let (from_symbol, from_symbol_line) = symbols
-                        .iter()
-                        .find(|s| start_line >= s.start_line && start_line <= s.end_line)
-                        .map(|s| (s.name.clone(), s.start_line))
-                        .unwrap_or_else(|| ("global".to_string(), 0));
+                    let (from_symbol, from_symbol_line) = enclosing_symbol(symbols, start_line);
 
                     let relation_type = self.support.map_relation_type(capture_name);
 
@@ -161,4 +167,107 @@ impl Extractor {
 
         references
     }
+
+    /// One `Imports` reference per imported name. `name` is the local binding,
+    /// `to_symbol` the name in the source module.
+    fn import_references(
+        &self,
+        tree: &tree_sitter::Tree,
+        content: &str,
+        file_path: &str,
+        symbols: &[CodeSymbol],
+    ) -> Vec<CodeReference> {
+        extract_imports(&self.language, tree.root_node(), content)
+            .into_iter()
+            .map(|binding| {
+                let (from_symbol, from_symbol_line) = enclosing_symbol(symbols, binding.line);
+                CodeReference::builder()
+                    .name(binding.local_name)
+                    .from_symbol(from_symbol)
+                    .from_symbol_line(from_symbol_line)
+                    .to_symbol(binding.imported_name)
+                    .relation_type(CodeRelationType::Imports)
+                    .file_path(file_path.to_string())
+                    .line(binding.line)
+                    .column(0)
+                    .module(binding.module)
+                    .build()
+            })
+            .collect()
+    }
+}
+
+/// Find the innermost symbol containing a line, or `global` for top-level code.
+fn enclosing_symbol(symbols: &[CodeSymbol], line: u32) -> (String, u32) {
+    symbols
+        .iter()
+        .filter(|s| line >= s.start_line && line <= s.end_line)
+        .min_by_key(|s| s.end_line - s.start_line)
+        .map(|s| (s.name.clone(), s.start_line))
+        .unwrap_or_else(|| ("global".to_string(), 0))
+}
+
+/// Table references from SQL embedded in string literals, so a query in code
+/// links to the `CREATE TABLE` symbol from a schema file.
+fn extract_sql_references(
+    tree: &tree_sitter::Tree,
+    content: &str,
+    file_path: &str,
+    symbols: &[CodeSymbol],
+) -> Vec<CodeReference> {
+    let mut references = Vec::new();
+    let mut stack = vec![tree.root_node()];
+
+    while let Some(node) = stack.pop() {
+        let is_string = matches!(
+            node.kind(),
+            "string_literal"
+                | "raw_string_literal"
+                | "string"
+                | "template_string"
+                | "interpreted_string_literal"
+                | "text_block"
+        );
+        if !is_string {
+            let mut cursor = node.walk();
+            stack.extend(node.children(&mut cursor));
+            continue;
+        }
+
+        let Ok(text) = node.utf8_text(content.as_bytes()) else {
+            continue;
+        };
+        let text = string_body(text);
+        if !looks_like_sql(text) {
+            continue;
+        }
+
+        let line = node.start_position().row as u32 + 1;
+        let (from_symbol, from_symbol_line) = enclosing_symbol(symbols, line);
+        for table in sql_table_references(text) {
+            references.push(
+                CodeReference::builder()
+                    .name(table.clone())
+                    .from_symbol(from_symbol.clone())
+                    .from_symbol_line(from_symbol_line)
+                    .to_symbol(table)
+                    .relation_type(CodeRelationType::References)
+                    .file_path(file_path.to_string())
+                    .line(line)
+                    .column(node.start_position().column as u32)
+                    .build(),
+            );
+        }
+    }
+
+    references
+}
+
+/// Strip prefixes and delimiters: `r#"SELECT 1"#`, `f'...'`, `"""..."""`.
+fn string_body(literal: &str) -> &str {
+    let is_delimiter = |c: char| matches!(c, '"' | '\'' | '`' | '#');
+    let start = literal.find(['"', '\'', '`']).unwrap_or(0);
+    literal[start..]
+        .trim_start_matches(is_delimiter)
+        .trim_end_matches(is_delimiter)
 }
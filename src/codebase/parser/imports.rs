@@ -0,0 +1,606 @@
+//! Import statements and the module paths they point at.
+//!
+//! Each import becomes one [`ImportBinding`] per name it brings into scope.
+//! Statements are located in the syntax tree and their text parsed per
+//! language; [`module_matches`] then decides whether a candidate definition
+//! lives in the imported module, so resolution can follow imports instead of
+//! guessing by directory.
+
+use std::path::{Component, Path, PathBuf};
+
+use crate::types::Language;
+
+/// Imported name for glob and namespace imports (`use a::*`, `import pkg`).
+pub const GLOB: &str = "*";
+
+/// One name brought into a file's scope by an import.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportBinding {
+    /// Name usable in the importing file (the alias, if renamed)
+    pub local_name: String,
+    /// Name in the source module, or [`GLOB`]
+    pub imported_name: String,
+    /// Module as written: `crate::auth`, `.models`, `./b`, `package:app/x.dart`
+    pub module: String,
+    pub line: u32,
+}
+
+impl ImportBinding {
+    fn new(local: &str, imported: &str, module: &str, line: u32) -> Self {
+        Self {
+            local_name: local.to_string(),
+            imported_name: imported.to_string(),
+            module: module.to_string(),
+            line,
+        }
+    }
+}
+
+/// All import bindings in a parsed file.
+pub fn extract_imports(
+    language: &Language,
+    root: tree_sitter::Node,
+    content: &str,
+) -> Vec<ImportBinding> {
+    let kinds: &[&str] = match language {
+        Language::Rust => &["use_declaration"],
+        Language::Python => &["import_statement", "import_from_statement"],
+        Language::TypeScript | Language::JavaScript => &["import_statement"],
+        Language::Go | Language::Java => &["import_declaration"],
+        Language::Dart => &["import_or_export"],
+        _ => return vec![],
+    };
+
+    let mut bindings = Vec::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if kinds.contains(&node.kind()) {
+            let Ok(text) = node.utf8_text(content.as_bytes()) else {
+                continue;
+            };
+            let line = node.start_position().row as u32 + 1;
+            bindings.extend(parse_import(language, text, line));
+            continue;
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+    }
+    bindings.sort_by_key(|b| b.line);
+    bindings
+}
+
+/// Bindings from the text of a single import statement.
+fn parse_import(language: &Language, text: &str, line: u32) -> Vec<ImportBinding> {
+    match language {
+        Language::Rust => parse_rust_use(text, line),
+        Language::Python => parse_python_import(text, line),
+        Language::TypeScript | Language::JavaScript => parse_js_import(text, line),
+        Language::Go => parse_go_import(text, line),
+        Language::Java => parse_java_import(text, line),
+        Language::Dart => parse_dart_import(text, line),
+        _ => vec![],
+    }
+}
+
+/// `use a::b::{c, d as e, f::*};`
+fn parse_rust_use(text: &str, line: u32) -> Vec<ImportBinding> {
+    let Some((_, tree)) = text.split_once("use ") else {
+        return vec![];
+    };
+    let tree = tree.trim().trim_end_matches(';');
+    let tree = tree.split_whitespace().collect::<Vec<_>>().join(" ");
+    let tree = tree.replace(" as ", "@").replace(' ', "");
+
+    let mut paths = Vec::new();
+    expand_use_tree("", &tree, &mut paths);
+
+    paths
+        .into_iter()
+        .filter_map(|(path, alias)| {
+            let (module, name) = match path.rsplit_once("::") {
+                Some((module, name)) => (module.to_string(), name.to_string()),
+                None => (String::new(), path.clone()),
+            };
+            if name == GLOB {
+                return Some(ImportBinding::new(GLOB, GLOB, &module, line));
+            }
+            // `use a::b::{self}` imports the module `b` itself
+            let (module, name) = if name == "self" {
+                let (parent, last) = module.rsplit_once("::").unwrap_or(("", &module));
+                (parent.to_string(), last.to_string())
+            } else {
+                (module, name)
+            };
+            if name.is_empty() || alias.as_deref() == Some("_") {
+                return None;
+            }
+            let local = alias.unwrap_or_else(|| name.clone());
+            Some(ImportBinding::new(&local, &name, &module, line))
+        })
+        .collect()
+}
+
+/// Flatten a use tree into `(path, alias)` pairs. Aliases are marked with `@`.
+fn expand_use_tree(prefix: &str, tree: &str, out: &mut Vec<(String, Option<String>)>) {
+    let join = |base: &str| match (prefix.is_empty(), base.is_empty()) {
+        (true, _) => base.to_string(),
+        (false, true) => prefix.to_string(),
+        (false, false) => format!("{}::{}", prefix, base),
+    };
+
+    match (tree.find('{'), tree.rfind('}')) {
+        (Some(open), Some(close)) if open < close => {
+            let base = join(tree[..open].trim_end_matches("::"));
+            for part in split_top_level(&tree[open + 1..close]) {
+                expand_use_tree(&base, part, out);
+            }
+        }
+        _ => {
+            let (path, alias) = match tree.split_once('@') {
+                Some((path, alias)) => (path, Some(alias.to_string())),
+                None => (tree, None),
+            };
+            if !path.is_empty() {
+                out.push((join(path), alias));
+            }
+        }
+    }
+}
+
+/// Split on commas that are not nested inside braces or parentheses.
+fn split_top_level(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '{' | '(' => depth += 1,
+            '}' | ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(text[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(text[start..].trim());
+    parts.retain(|p| !p.is_empty());
+    parts
+}
+
+/// Split `name as alias` into its parts.
+fn split_alias(item: &str) -> (&str, Option<&str>) {
+    match item.split_once(" as ") {
+        Some((name, alias)) => (name.trim(), Some(alias.trim())),
+        None => (item.trim(), None),
+    }
+}
+
+/// `import a.b as c` and `from .x import (y, z as w)`
+fn parse_python_import(text: &str, line: u32) -> Vec<ImportBinding> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if let Some(rest) = text.strip_prefix("from ") {
+        let Some((module, names)) = rest.split_once(" import ") else {
+            return vec![];
+        };
+        let names = names.trim().trim_start_matches('(').trim_end_matches(')');
+        return split_top_level(names)
+            .into_iter()
+            .map(|item| {
+                let (name, alias) = split_alias(item);
+                if name == GLOB {
+                    ImportBinding::new(GLOB, GLOB, module.trim(), line)
+                } else {
+                    ImportBinding::new(alias.unwrap_or(name), name, module.trim(), line)
+                }
+            })
+            .collect();
+    }
+
+    let Some(rest) = text.strip_prefix("import ") else {
+        return vec![];
+    };
+    split_top_level(rest)
+        .into_iter()
+        .map(|item| {
+            let (module, alias) = split_alias(item);
+            let local = alias.unwrap_or_else(|| module.split('.').next().unwrap_or(module));
+            ImportBinding::new(local, GLOB, module, line)
+        })
+        .collect()
+}
+
+/// `import D, { a, b as c } from './x'` and `import * as ns from './x'`
+fn parse_js_import(text: &str, line: u32) -> Vec<ImportBinding> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let Some(module) = quoted(&text) else {
+        return vec![];
+    };
+    let Some(clause) = text
+        .strip_prefix("import ")
+        .and_then(|rest| rest.rsplit_once(" from "))
+        .map(|(clause, _)| clause.trim())
+    else {
+        // Side-effect import: `import './polyfill'`
+        return vec![];
+    };
+    let clause = clause.strip_prefix("type ").unwrap_or(clause);
+
+    let (default_part, named_part) = match (clause.find('{'), clause.rfind('}')) {
+        (Some(open), Some(close)) if open < close => {
+            (&clause[..open], Some(&clause[open + 1..close]))
+        }
+        _ => (clause, None),
+    };
+
+    let mut bindings = Vec::new();
+    for item in default_part
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+    {
+        if let Some(namespace) = item.strip_prefix("* as ") {
+            bindings.push(ImportBinding::new(namespace.trim(), GLOB, module, line));
+        } else {
+            bindings.push(ImportBinding::new(item, item, module, line));
+        }
+    }
+    for item in named_part.map(split_top_level).unwrap_or_default() {
+        let item = item.strip_prefix("type ").unwrap_or(item);
+        let (name, alias) = split_alias(item);
+        bindings.push(ImportBinding::new(
+            alias.unwrap_or(name),
+            name,
+            module,
+            line,
+        ));
+    }
+    bindings
+}
+
+/// `import "fmt"` and grouped `import ( alias "a/b" )`. Go imports whole
+/// packages, so every binding is a glob.
+fn parse_go_import(text: &str, line: u32) -> Vec<ImportBinding> {
+    text.lines()
+        .filter_map(|l| {
+            let l = l.trim().trim_start_matches("import").trim();
+            let path = quoted(l)?;
+            let alias = l[..l.find('"')?].trim().trim_start_matches('(').trim();
+            let local = match alias {
+                "_" => return None,
+                "" => path.rsplit('/').next().unwrap_or(path),
+                "." => GLOB,
+                alias => alias,
+            };
+            Some(ImportBinding::new(local, GLOB, path, line))
+        })
+        .collect()
+}
+
+/// `import a.b.C;`, `import a.b.*;` and `import static a.b.C.m;`
+fn parse_java_import(text: &str, line: u32) -> Vec<ImportBinding> {
+    let path = text
+        .trim()
+        .trim_start_matches("import")
+        .trim()
+        .trim_start_matches("static ")
+        .trim()
+        .trim_end_matches(';')
+        .trim();
+    match path.rsplit_once('.') {
+        Some((module, GLOB)) => vec![ImportBinding::new(GLOB, GLOB, module, line)],
+        Some((module, name)) => vec![ImportBinding::new(name, name, module, line)],
+        None => vec![],
+    }
+}
+
+/// `import 'uri' as p show A, B hide C;`. Without `show`, every public
+/// name of the library is visible.
+fn parse_dart_import(text: &str, line: u32) -> Vec<ImportBinding> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if !text.starts_with("import ") {
+        return vec![];
+    }
+    let Some(uri) = quoted(&text) else {
+        return vec![];
+    };
+
+    let Some((_, shown)) = text.split_once(" show ") else {
+        return vec![ImportBinding::new(GLOB, GLOB, uri, line)];
+    };
+    let shown = shown.split(" hide ").next().unwrap_or(shown);
+    shown
+        .trim_end_matches(';')
+        .split(',')
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .map(|name| ImportBinding::new(name, name, uri, line))
+        .collect()
+}
+
+/// Contents of the first single- or double-quoted string.
+fn quoted(text: &str) -> Option<&str> {
+    let start = text.find(['"', '\''])?;
+    let quote = text[start..].chars().next()?;
+    let rest = &text[start + 1..];
+    Some(&rest[..rest.find(quote)?])
+}
+
+/// Where an import points: an exact path resolved against the importing file,
+/// or a path suffix to look for anywhere in the project.
+#[derive(Debug, PartialEq)]
+enum ModuleTarget {
+    Anchored(PathBuf),
+    Suffix(PathBuf),
+}
+
+/// Whether `candidate_file` defines (or re-exports from within) `module`, as
+/// imported from `caller_file`. Package-manager and standard-library modules
+/// never match.
+pub fn module_matches(
+    language: &Language,
+    module: &str,
+    caller_file: &str,
+    candidate_file: &str,
+) -> bool {
+    let Some(target) = module_target(language, module, Path::new(caller_file)) else {
+        return false;
+    };
+    let candidate = module_file(Path::new(candidate_file));
+
+    match target {
+        ModuleTarget::Anchored(path) => candidate.starts_with(&path),
+        ModuleTarget::Suffix(suffix) => candidate.ancestors().any(|a| a.ends_with(&suffix)),
+    }
+}
+
+fn module_target(language: &Language, module: &str, caller: &Path) -> Option<ModuleTarget> {
+    let caller_dir = caller.parent().unwrap_or(Path::new(""));
+
+    match language {
+        Language::Rust => {
+            let segments: Vec<&str> = module.split("::").filter(|s| !s.is_empty()).collect();
+            let first = *segments.first()?;
+            match first {
+                "crate" => Some(ModuleTarget::Suffix(
+                    Path::new("src").join(segments[1..].iter().collect::<PathBuf>()),
+                )),
+                "self" | "super" => {
+                    let relative = segments
+                        .iter()
+                        .take_while(|s| **s == "self" || **s == "super")
+                        .count();
+                    let mut base = module_file(caller);
+                    for _ in segments[..relative].iter().filter(|s| **s == "super") {
+                        base.pop();
+                    }
+                    let rest: PathBuf = segments[relative..].iter().collect();
+                    Some(ModuleTarget::Anchored(base.join(rest)))
+                }
+                // Another workspace crate, laid out as `<name>/src/...`
+                name => Some(ModuleTarget::Suffix(
+                    Path::new(&name.replace('_', "-"))
+                        .join("src")
+                        .join(segments[1..].iter().collect::<PathBuf>()),
+                )),
+            }
+        }
+        Language::Python => {
+            let dots = module.chars().take_while(|c| *c == '.').count();
+            let path: PathBuf = module[dots..]
+                .split('.')
+                .filter(|s| !s.is_empty())
+                .collect();
+            if dots == 0 {
+                return Some(ModuleTarget::Suffix(path));
+            }
+            let mut base = caller_dir.to_path_buf();
+            for _ in 1..dots {
+                base.pop();
+            }
+            Some(ModuleTarget::Anchored(normalize(&base.join(path))))
+        }
+        Language::TypeScript | Language::JavaScript => {
+            if !module.starts_with('.') {
+                return None;
+            }
+            let path = normalize(&caller_dir.join(module));
+            Some(ModuleTarget::Anchored(module_file(&path)))
+        }
+        Language::Go => {
+            let package = module.rsplit('/').next()?;
+            (module.contains('/') || module.contains('.'))
+                .then(|| ModuleTarget::Suffix(PathBuf::from(package)))
+        }
+        Language::Java => Some(ModuleTarget::Suffix(module.split('.').collect())),
+        Language::Dart => {
+            if module.starts_with("dart:") {
+                return None;
+            }
+            if let Some(package) = module.strip_prefix("package:") {
+                let (_, path) = package.split_once('/')?;
+                return Some(ModuleTarget::Suffix(module_file(
+                    &Path::new("lib").join(path),
+                )));
+            }
+            let path = normalize(&caller_dir.join(module));
+            Some(ModuleTarget::Anchored(module_file(&path)))
+        }
+        _ => None,
+    }
+}
+
+/// Path without extension, with `mod`/`index`/`__init__` files standing for
+/// their directory.
+fn module_file(path: &Path) -> PathBuf {
+    let stem = path.with_extension("");
+    match stem.file_name().and_then(|n| n.to_str()) {
+        Some("mod" | "lib" | "main" | "index" | "__init__") => {
+            stem.parent().map(Path::to_path_buf).unwrap_or(stem)
+        }
+        _ => stem,
+    }
+}
+
+/// Resolve `.` and `..` components without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(bindings: &[ImportBinding]) -> Vec<(&str, &str, &str)> {
+        bindings
+            .iter()
+            .map(|b| {
+                (
+                    b.local_name.as_str(),
+                    b.imported_name.as_str(),
+                    b.module.as_str(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_import_statements() {
+        let rust = parse_rust_use(
+            "use crate::auth::{TokenValidator, jwt::{self, parse as parse_jwt}, *};",
+            1,
+        );
+        assert_eq!(
+            names(&rust),
+            vec![
+                ("TokenValidator", "TokenValidator", "crate::auth"),
+                ("jwt", "jwt", "crate::auth"),
+                ("parse_jwt", "parse", "crate::auth::jwt"),
+                ("*", "*", "crate::auth"),
+            ]
+        );
+
+        let python = parse_python_import("from .models import (\n  User,\n  Group as G\n)", 1);
+        assert_eq!(
+            names(&python),
+            vec![("User", "User", ".models"), ("G", "Group", ".models")]
+        );
+        assert_eq!(
+            names(&parse_python_import("import os.path as p", 1)),
+            vec![("p", "*", "os.path")]
+        );
+
+        let ts = parse_js_import("import Default, { a, type B as C } from './b';", 1);
+        assert_eq!(
+            names(&ts),
+            vec![
+                ("Default", "Default", "./b"),
+                ("a", "a", "./b"),
+                ("C", "B", "./b")
+            ]
+        );
+
+        let go = parse_go_import("import (\n\t\"fmt\"\n\tv \"example.com/app/auth\"\n)", 1);
+        assert_eq!(
+            names(&go),
+            vec![("fmt", "*", "fmt"), ("v", "*", "example.com/app/auth")]
+        );
+
+        assert_eq!(
+            names(&parse_java_import("import com.acme.auth.Validator;", 1)),
+            vec![("Validator", "Validator", "com.acme.auth")]
+        );
+        assert_eq!(
+            names(&parse_dart_import(
+                "import 'package:app/auth.dart' show Session, User;",
+                1
+            )),
+            vec![
+                ("Session", "Session", "package:app/auth.dart"),
+                ("User", "User", "package:app/auth.dart")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_module_matches() {
+        let rust = Language::Rust;
+        assert!(module_matches(
+            &rust,
+            "crate::auth",
+            "/p/src/main.rs",
+            "/p/src/auth/validator.rs"
+        ));
+        assert!(!module_matches(
+            &rust,
+            "crate::auth",
+            "/p/src/main.rs",
+            "/p/src/parser.rs"
+        ));
+        assert!(module_matches(
+            &rust,
+            "super::jwt",
+            "/p/src/auth/session.rs",
+            "/p/src/auth/jwt.rs"
+        ));
+        assert!(!module_matches(
+            &rust,
+            "std::io",
+            "/p/src/main.rs",
+            "/p/src/io.rs"
+        ));
+
+        let py = Language::Python;
+        assert!(module_matches(
+            &py,
+            "app.settings",
+            "/p/main.py",
+            "/p/app/settings.py"
+        ));
+        assert!(module_matches(
+            &py,
+            "..core",
+            "/p/app/api/views.py",
+            "/p/app/core/__init__.py"
+        ));
+
+        let ts = Language::TypeScript;
+        assert!(module_matches(&ts, "./b", "/p/src/a.ts", "/p/src/b.ts"));
+        assert!(module_matches(
+            &ts,
+            "../lib",
+            "/p/src/a.ts",
+            "/p/lib/index.ts"
+        ));
+        assert!(!module_matches(
+            &ts,
+            "react",
+            "/p/src/a.ts",
+            "/p/src/react.ts"
+        ));
+
+        assert!(module_matches(
+            &Language::Dart,
+            "package:app/auth.dart",
+            "/p/lib/main.dart",
+            "/p/lib/auth.dart"
+        ));
+        assert!(module_matches(
+            &Language::Java,
+            "com.acme.auth",
+            "/p/src/Main.java",
+            "/p/src/com/acme/auth/Validator.java"
+        ));
+    }
+}
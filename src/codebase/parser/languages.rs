@@ -18,6 +18,94 @@ pub trait LanguageSupport: Send + Sync {
             Some(sig.chars().take(500).collect())
         }
     }
+
+    /// Comment prefixes that mark documentation rather than ordinary comments.
+    fn doc_comment_prefixes(&self) -> &[&str] {
+        &["/**"]
+    }
+
+    /// Doc comment attached to a definition, markers stripped.
+    fn extract_doc(&self, definition: &tree_sitter::Node, content: &[u8]) -> Option<String> {
+        leading_doc_comment(definition, content, self.doc_comment_prefixes())
+    }
+}
+
+const MAX_DOC_CHARS: usize = 2000;
+
+/// The contiguous run of doc comments directly above a definition.
+/// Attributes and annotations between the comment and the item are skipped.
+fn leading_doc_comment(
+    definition: &tree_sitter::Node,
+    content: &[u8],
+    prefixes: &[&str],
+) -> Option<String> {
+    // Comments sit above the wrapper, not the inner declaration
+    let mut target = *definition;
+    while target.prev_named_sibling().is_none() {
+        match target.parent() {
+            Some(parent)
+                if matches!(
+                    parent.kind(),
+                    "export_statement"
+                        | "type_declaration"
+                        | "method_signature"
+                        | "declaration"
+                        | "decorated_definition"
+                ) =>
+            {
+                target = parent
+            }
+            _ => break,
+        }
+    }
+
+    let mut comments = Vec::new();
+    let mut next_row = target.start_position().row;
+    let mut sibling = target.prev_named_sibling();
+    while let Some(node) = sibling {
+        if node.end_position().row + 1 < next_row {
+            break;
+        }
+        let kind = node.kind();
+        let is_annotation = matches!(
+            kind,
+            "attribute_item" | "decorator" | "annotation" | "marker_annotation"
+        );
+        if !is_annotation {
+            if !kind.contains("comment") {
+                break;
+            }
+            let text = node.utf8_text(content).ok()?;
+            if !prefixes.iter().any(|p| text.trim_start().starts_with(p)) {
+                break;
+            }
+            comments.push(text.trim_end());
+        }
+        next_row = node.start_position().row;
+        sibling = node.prev_named_sibling();
+    }
+
+    comments.reverse();
+    clean_doc(&comments.join("\n"))
+}
+
+/// Strip comment markers (`///`, `/** */`, leading `*`) and surrounding blank lines.
+fn clean_doc(raw: &str) -> Option<String> {
+    let lines: Vec<&str> = raw
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            let line = ["/**", "///", "//!", "//", "/*"]
+                .iter()
+                .find_map(|marker| line.strip_prefix(marker))
+                .unwrap_or(line);
+            let line = line.strip_suffix("*/").unwrap_or(line).trim();
+            line.strip_prefix('*').unwrap_or(line).trim()
+        })
+        .collect();
+
+    let doc = lines.join("\n").trim().to_string();
+    (!doc.is_empty()).then(|| doc.chars().take(MAX_DOC_CHARS).collect())
 }
 
 fn extract_until_body_start(text: &str) -> String {
@@ -68,7 +156,6 @@ impl LanguageSupport for RustSupport {
         (call_expression function: (identifier) @call)
         (call_expression function: (field_expression field: (field_identifier) @method_call))
         (call_expression function: (scoped_identifier name: (identifier) @call))
-        (use_declaration argument: (scoped_identifier name: (identifier) @import))
         (impl_item trait: (type_identifier) @implements)
         "#
     }
@@ -88,12 +175,15 @@ impl LanguageSupport for RustSupport {
     fn map_relation_type(&self, kind: &str) -> CodeRelationType {
         match kind {
             "call" | "method_call" => CodeRelationType::Calls,
-            "import" => CodeRelationType::Imports,
             "implements" => CodeRelationType::Implements,
             "extends" => CodeRelationType::Extends,
             _ => CodeRelationType::Calls,
         }
     }
+
+    fn doc_comment_prefixes(&self) -> &[&str] {
+        &["///", "/**"]
+    }
 }
 
 pub struct PythonSupport;
@@ -113,8 +203,6 @@ impl LanguageSupport for PythonSupport {
         r#"
         (call function: (identifier) @call)
         (call function: (attribute attribute: (identifier) @method_call))
-        (import_statement name: (dotted_name (identifier) @import))
-        (import_from_statement name: (dotted_name (identifier) @import))
         (class_definition superclasses: (argument_list (identifier) @extends))
         "#
     }
@@ -130,12 +218,33 @@ impl LanguageSupport for PythonSupport {
     fn map_relation_type(&self, kind: &str) -> CodeRelationType {
         match kind {
             "call" | "method_call" => CodeRelationType::Calls,
-            "import" => CodeRelationType::Imports,
             "implements" => CodeRelationType::Implements,
             "extends" => CodeRelationType::Extends,
             _ => CodeRelationType::Calls,
         }
     }
+
+    /// Docstring: a string literal as the first statement of the body.
+    fn extract_doc(&self, definition: &tree_sitter::Node, content: &[u8]) -> Option<String> {
+        let body = definition.child_by_field_name("body")?;
+        let statement = body.named_child(0)?;
+        if statement.kind() != "expression_statement" {
+            return None;
+        }
+        let string = statement.named_child(0).filter(|n| n.kind() == "string")?;
+        let text = string.utf8_text(content).ok()?;
+        let text = text
+            .trim_start_matches(|c: char| c.is_ascii_alphabetic())
+            .trim_matches(|c| c == '"' || c == '\'');
+        let doc = text
+            .lines()
+            .map(str::trim)
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string();
+        (!doc.is_empty()).then(|| doc.chars().take(MAX_DOC_CHARS).collect())
+    }
 }
 
 pub struct TypeScriptSupport;
@@ -158,7 +267,6 @@ impl LanguageSupport for TypeScriptSupport {
         r#"
         (call_expression function: (identifier) @call)
         (call_expression function: (member_expression property: (property_identifier) @method_call))
-        (import_statement source: (string (string_fragment) @import))
         (class_heritage (extends_clause value: (identifier) @extends))
         (class_heritage (implements_clause (type) @implements))
         "#
@@ -177,7 +285,6 @@ impl LanguageSupport for TypeScriptSupport {
     fn map_relation_type(&self, kind: &str) -> CodeRelationType {
         match kind {
             "call" | "method_call" => CodeRelationType::Calls,
-            "import" => CodeRelationType::Imports,
             "implements" => CodeRelationType::Implements,
             "extends" => CodeRelationType::Extends,
             _ => CodeRelationType::Calls,
@@ -203,7 +310,6 @@ impl LanguageSupport for JavaScriptSupport {
         r#"
         (call_expression function: (identifier) @call)
         (call_expression function: (member_expression property: (property_identifier) @method_call))
-        (import_statement source: (string (string_fragment) @import))
         (class_heritage (identifier) @extends)
         "#
     }
@@ -220,7 +326,6 @@ impl LanguageSupport for JavaScriptSupport {
     fn map_relation_type(&self, kind: &str) -> CodeRelationType {
         match kind {
             "call" | "method_call" => CodeRelationType::Calls,
-            "import" => CodeRelationType::Imports,
             "implements" => CodeRelationType::Implements,
             "extends" => CodeRelationType::Extends,
             _ => CodeRelationType::Calls,
@@ -246,7 +351,6 @@ impl LanguageSupport for GoSupport {
         r#"
         (call_expression function: (identifier) @call)
         (call_expression function: (selector_expression field: (field_identifier) @method_call))
-        (import_spec path: (string_literal) @import)
         "#
     }
 
@@ -262,12 +366,15 @@ impl LanguageSupport for GoSupport {
     fn map_relation_type(&self, kind: &str) -> CodeRelationType {
         match kind {
             "call" | "method_call" => CodeRelationType::Calls,
-            "import" => CodeRelationType::Imports,
             "implements" => CodeRelationType::Implements,
             "extends" => CodeRelationType::Extends,
             _ => CodeRelationType::Calls,
         }
     }
+
+    fn doc_comment_prefixes(&self) -> &[&str] {
+        &["//"]
+    }
 }
 
 pub struct JavaSupport;
@@ -288,7 +395,6 @@ impl LanguageSupport for JavaSupport {
     fn get_reference_query(&self) -> &str {
         r#"
         (method_invocation name: (identifier) @call)
-        (import_declaration name: (scoped_identifier) @import)
         (class_declaration superclass: (superclass (type_identifier) @extends))
         (class_declaration interfaces: (super_interfaces (type_list (type_identifier) @implements)))
         "#
@@ -307,7 +413,6 @@ impl LanguageSupport for JavaSupport {
     fn map_relation_type(&self, kind: &str) -> CodeRelationType {
         match kind {
             "call" | "method_call" => CodeRelationType::Calls,
-            "import" => CodeRelationType::Imports,
             "implements" => CodeRelationType::Implements,
             "extends" => CodeRelationType::Extends,
             _ => CodeRelationType::Calls,
@@ -357,9 +462,6 @@ impl LanguageSupport for DartSupport {
           (cascade_selector (identifier) @method_call)
           (argument_part))
 
-        ; Imports
-        (import_or_export (library_import (import_specification (configurable_uri (uri (string_literal) @import)))))
-
         ; Extends: class Foo extends Bar
         (class_definition
           superclass: (superclass (type_identifier) @extends))
@@ -386,12 +488,15 @@ impl LanguageSupport for DartSupport {
     fn map_relation_type(&self, kind: &str) -> CodeRelationType {
         match kind {
             "call" | "method_call" => CodeRelationType::Calls,
-            "import" => CodeRelationType::Imports,
             "implements" => CodeRelationType::Implements,
             "extends" => CodeRelationType::Extends,
             _ => CodeRelationType::Calls,
         }
     }
+
+    fn doc_comment_prefixes(&self) -> &[&str] {
+        &["///", "/**"]
+    }
 }
 
 pub fn get_language_support(lang: Language) -> Option<Box<dyn LanguageSupport>> {
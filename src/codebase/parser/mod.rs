@@ -1,10 +1,14 @@
 pub mod extractor;
+pub mod imports;
 pub mod languages;
 
+use std::collections::HashMap;
 use std::path::Path;
 
 use crate::codebase::scanner::detect_language;
-use crate::types::symbol::{CodeReference, CodeSymbol};
+use crate::types::safe_thing;
+use crate::types::symbol::{CodeReference, CodeSymbol, SymbolType};
+use crate::types::Language;
 
 use extractor::Extractor;
 
@@ -17,14 +21,167 @@ impl CodeParser {
         project_id: &str,
     ) -> (Vec<CodeSymbol>, Vec<CodeReference>) {
         let language = detect_language(path);
-        let Some(mut extractor) = Extractor::new(language) else {
-            return (vec![], vec![]);
+        let (mut symbols, references) = if language.is_document() {
+            crate::codebase::docs::parse_document(path, content, project_id)
+        } else if language.is_structured() {
+            crate::codebase::structured::parse_structured(path, content, project_id)
+        } else {
+            let Some(mut extractor) = Extractor::new(language.clone()) else {
+                return (vec![], vec![]);
+            };
+            extractor.parse(content, path.to_string_lossy().as_ref(), project_id)
         };
 
-        extractor.parse(content, path.to_string_lossy().as_ref(), project_id)
+        qualify_symbols(&language, path, content, &mut symbols);
+        assign_ids(&mut symbols);
+        (symbols, references)
+    }
+}
+
+/// Give each symbol a record ID from its qualified name and kind. Same-named
+/// symbols of one kind in a file (overloads, repeated `impl` blocks) are
+/// numbered in source order, so IDs survive edits that only shift lines.
+fn assign_ids(symbols: &mut [CodeSymbol]) {
+    let mut seen: HashMap<(String, SymbolType), u32> = HashMap::new();
+    for symbol in symbols.iter_mut() {
+        let qualified = symbol
+            .qualified_name
+            .clone()
+            .unwrap_or_else(|| symbol.name.clone());
+        let overload = seen
+            .entry((qualified.clone(), symbol.symbol_type))
+            .or_insert(0);
+        symbol.id = Some(safe_thing::symbol_thing(
+            &symbol.project_id,
+            &symbol.file_path,
+            &qualified,
+            symbol.symbol_type,
+            *overload,
+        ));
+        *overload += 1;
+    }
+}
+
+/// Index of the innermost other symbol whose line range strictly encloses
+/// `symbols[index]`.
+pub fn parent_index(symbols: &[CodeSymbol], index: usize) -> Option<usize> {
+    let child = &symbols[index];
+    symbols
+        .iter()
+        .enumerate()
+        .filter(|(i, s)| {
+            *i != index
+                && s.start_line <= child.start_line
+                && s.end_line >= child.end_line
+                && (s.start_line, s.end_line) != (child.start_line, child.end_line)
+        })
+        .min_by_key(|(_, s)| s.end_line - s.start_line)
+        .map(|(i, _)| i)
+}
+
+/// Fill in `qualified_name` and `parent` from the module path and lexical
+/// nesting. Go methods are qualified by their receiver type.
+fn qualify_symbols(language: &Language, path: &Path, content: &str, symbols: &mut [CodeSymbol]) {
+    let sep = match language {
+        Language::Rust => "::",
+        _ => ".",
+    };
+    let module = module_path(language, path, content);
+
+    // Outermost first, so a parent is always qualified before its members
+    let mut order: Vec<usize> = (0..symbols.len()).collect();
+    order.sort_by_key(|&i| {
+        let s = &symbols[i];
+        (std::cmp::Reverse(s.end_line - s.start_line), s.start_line)
+    });
+
+    for i in order {
+        let parent = parent_index(symbols, i)
+            .and_then(|p| symbols[p].qualified_name.clone())
+            .or_else(|| go_receiver(language, &symbols[i]).map(|recv| join(&module, sep, &recv)));
+        let prefix = parent.clone().unwrap_or_else(|| module.clone());
+        symbols[i].qualified_name = Some(join(&prefix, sep, &symbols[i].name));
+        symbols[i].parent = parent;
+    }
+}
+
+fn join(prefix: &str, sep: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}{}{}", prefix, sep, name)
+    }
+}
+
+/// Module prefix for a file: the crate path for Rust, the declared package
+/// for Go and Java, and the path below `src/`/`lib/` (or the file stem) for
+/// everything else.
+fn module_path(language: &Language, path: &Path, content: &str) -> String {
+    match language {
+        Language::Go => return package_clause(content, "package ").unwrap_or_default(),
+        Language::Java => return package_clause(content, "package ").unwrap_or_default(),
+        _ => {}
+    }
+
+    let components: Vec<String> = path
+        .with_extension("")
+        .components()
+        .filter_map(|c| c.as_os_str().to_str().map(str::to_string))
+        .collect();
+    let root = components
+        .iter()
+        .rposition(|c| c == "src" || (c == "lib" && *language != Language::Rust));
+    let mut segments: Vec<String> = match root {
+        Some(i) => components[i + 1..].to_vec(),
+        None => components.last().cloned().into_iter().collect(),
+    };
+
+    match language {
+        Language::Rust => {
+            if matches!(
+                segments.last().map(String::as_str),
+                Some("mod" | "lib" | "main")
+            ) {
+                segments.pop();
+            }
+            if root.is_some() {
+                segments.insert(0, "crate".to_string());
+            }
+            segments.join("::")
+        }
+        _ => {
+            if matches!(
+                segments.last().map(String::as_str),
+                Some("index" | "__init__")
+            ) {
+                segments.pop();
+            }
+            segments.join(".")
+        }
     }
 }
 
+/// First `package x.y;` style declaration in the file.
+fn package_clause(content: &str, keyword: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let rest = line.trim().strip_prefix(keyword)?;
+        let name = rest.trim().trim_end_matches(';').trim();
+        (!name.is_empty()).then(|| name.to_string())
+    })
+}
+
+/// Receiver type of a Go method, from a signature like `func (v *Validator) Check()`.
+fn go_receiver(language: &Language, symbol: &CodeSymbol) -> Option<String> {
+    if *language != Language::Go {
+        return None;
+    }
+    let receiver = symbol.signature.as_deref()?.strip_prefix("func (")?;
+    let receiver = &receiver[..receiver.find(')')?];
+    let ty = receiver.split_whitespace().last()?.trim_start_matches('*');
+    let ty = ty.split('[').next().unwrap_or(ty);
+    (!ty.is_empty()).then(|| ty.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -39,6 +196,125 @@ mod tests {
         assert!(!symbols.is_empty());
     }
 
+    #[test]
+    fn test_doc_comment_extraction() {
+        let doc_of = |file: &str, content: &str, name: &str| {
+            let (symbols, _) = CodeParser::parse_file(&PathBuf::from(file), content, "test");
+            symbols
+                .into_iter()
+                .find(|s| s.name == name)
+                .and_then(|s| s.doc)
+        };
+
+        let rust = "// not docs\n\n/// Validates tokens.\n/// Rejects expired ones.\n#[inline]\nfn check() {}\n\nfn bare() {}\n";
+        assert_eq!(
+            doc_of("a.rs", rust, "check").as_deref(),
+            Some("Validates tokens.\nRejects expired ones.")
+        );
+        assert_eq!(doc_of("a.rs", rust, "bare"), None);
+
+        let python = "def load(path):\n    \"\"\"Load a config file.\n\n    Raises on missing keys.\n    \"\"\"\n    return path\n";
+        assert_eq!(
+            doc_of("a.py", python, "load").as_deref(),
+            Some("Load a config file.\n\nRaises on missing keys.")
+        );
+
+        let ts = "/**\n * Parse a JWT.\n * @param token raw token\n */\nexport function parse(token: string) {}\n";
+        assert_eq!(
+            doc_of("a.ts", ts, "parse").as_deref(),
+            Some("Parse a JWT.\n@param token raw token")
+        );
+
+        let go = "package main\n\n// Serve starts the HTTP server.\nfunc Serve() {}\n";
+        assert_eq!(
+            doc_of("a.go", go, "Serve").as_deref(),
+            Some("Serve starts the HTTP server.")
+        );
+    }
+
+    #[test]
+    fn test_qualified_names_and_parents() {
+        let qualified = |file: &str, content: &str| {
+            let (symbols, _) = CodeParser::parse_file(&PathBuf::from(file), content, "test");
+            symbols
+                .into_iter()
+                .map(|s| (s.qualified_name.unwrap_or_default(), s.parent))
+                .collect::<Vec<_>>()
+        };
+
+        let rust = "struct TokenValidator;
+
+impl TokenValidator {
+    fn check(&self) -> bool {
+        true
+    }
+}
+";
+        let symbols = qualified("/repo/src/auth/mod.rs", rust);
+        assert!(symbols.contains(&(
+            "crate::auth::TokenValidator::check".to_string(),
+            Some("crate::auth::TokenValidator".to_string())
+        )));
+
+        let python = "class Config:
+    def load(self):
+        pass
+";
+        let symbols = qualified("/repo/src/app/settings.py", python);
+        assert!(symbols.contains(&(
+            "app.settings.Config.load".to_string(),
+            Some("app.settings.Config".to_string())
+        )));
+        assert!(symbols.contains(&("app.settings.Config".to_string(), None)));
+
+        let go = "package auth
+
+type Validator struct{}
+
+func (v *Validator) Check() bool { return true }
+";
+        let symbols = qualified("auth/validator.go", go);
+        assert!(symbols.contains(&(
+            "auth.Validator.Check".to_string(),
+            Some("auth.Validator".to_string())
+        )));
+    }
+
+    #[test]
+    fn test_references_attributed_to_innermost_symbol() {
+        let content = "impl Server {
+    fn start(&self) {
+        listen();
+    }
+}
+";
+        let (symbols, refs) = CodeParser::parse_file(&PathBuf::from("server.rs"), content, "test");
+        let call = refs.iter().find(|r| r.to_symbol == "listen").unwrap();
+        assert_eq!(call.from_symbol, "start");
+        assert_eq!(call.from_symbol_line, 2);
+
+        let contains = crate::codebase::relations::containment_relations("test", &symbols);
+        assert_eq!(contains.len(), 1);
+        assert_eq!(contains[0].relation_type, CodeRelationType::Contains);
+        assert_eq!(contains[0].line_number, 2);
+    }
+
+    #[test]
+    fn test_sql_in_string_references_table() {
+        let content = r#"
+fn load_user(id: i64) {
+    let sql = "SELECT id, email FROM users WHERE id = ?";
+    query(sql, id);
+}
+"#;
+        let path = PathBuf::from("repo.rs");
+        let (_, refs) = CodeParser::parse_file(&path, content, "test");
+
+        assert!(refs.iter().any(|r| r.from_symbol == "load_user"
+            && r.to_symbol == "users"
+            && r.relation_type == CodeRelationType::References));
+    }
+
     #[test]
     fn test_rust_call_extraction() {
         let content = r#"
@@ -0,0 +1,130 @@
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+/// Path of `path` below the project `root`, `/`-separated. This is the form
+/// chunks, symbols and file hashes are stored under, so the same checkout
+/// indexes identically wherever it is mounted.
+pub fn relative_path(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    relative
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Location on the server of a stored project-relative path.
+pub fn server_path(root: &Path, relative: &str) -> PathBuf {
+    root.join(relative)
+}
+
+/// Prefix mapping between paths as the server sees them (e.g. inside a
+/// container) and as the client sees them on the host.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PathMap {
+    /// (server prefix, host prefix)
+    prefixes: Vec<(PathBuf, PathBuf)>,
+}
+
+impl PathMap {
+    /// Host path for a server path. Unmapped paths are returned unchanged.
+    pub fn to_host(&self, path: &Path) -> PathBuf {
+        self.translate(path, |(server, host)| (server, host))
+    }
+
+    /// Server path for a host path. Unmapped paths are returned unchanged.
+    pub fn to_server(&self, path: &Path) -> PathBuf {
+        self.translate(path, |(server, host)| (host, server))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prefixes.is_empty()
+    }
+
+    /// Rewrite the longest matching prefix.
+    fn translate<'a>(
+        &'a self,
+        path: &Path,
+        direction: impl Fn(&'a (PathBuf, PathBuf)) -> (&'a PathBuf, &'a PathBuf),
+    ) -> PathBuf {
+        self.prefixes
+            .iter()
+            .map(direction)
+            .filter_map(|(from, to)| Some((from, to, path.strip_prefix(from).ok()?)))
+            .max_by_key(|(from, _, _)| from.components().count())
+            .map(|(_, to, rest)| to.join(rest))
+            .unwrap_or_else(|| path.to_path_buf())
+    }
+}
+
+impl FromStr for PathMap {
+    type Err = String;
+
+    /// Comma-separated `server=host` pairs, e.g. `/project=/Users/me/repo`.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let prefixes = s
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| match pair.split_once('=') {
+                Some((server, host)) if !server.is_empty() && !host.is_empty() => {
+                    Ok((PathBuf::from(server), PathBuf::from(host)))
+                }
+                _ => Err(format!(
+                    "Invalid path mapping '{}'. Expected server_path=host_path",
+                    pair
+                )),
+            })
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(Self { prefixes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_path() {
+        let root = Path::new("/project");
+        assert_eq!(
+            relative_path(root, Path::new("/project/src/x.rs")),
+            "src/x.rs"
+        );
+        assert_eq!(relative_path(root, Path::new("/project")), "");
+        assert_eq!(
+            server_path(root, "src/x.rs"),
+            PathBuf::from("/project/src/x.rs")
+        );
+    }
+
+    #[test]
+    fn test_path_map() {
+        let map: PathMap = "/project=/Users/me/repo, /project/vendor=/opt/vendor"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            map.to_host(Path::new("/project/src/x.rs")),
+            PathBuf::from("/Users/me/repo/src/x.rs")
+        );
+        assert_eq!(
+            map.to_host(Path::new("/project/vendor/lib.rs")),
+            PathBuf::from("/opt/vendor/lib.rs")
+        );
+        assert_eq!(
+            map.to_server(Path::new("/Users/me/repo/src/x.rs")),
+            PathBuf::from("/project/src/x.rs")
+        );
+        // Prefixes match whole components only
+        assert_eq!(
+            map.to_host(Path::new("/projects/x.rs")),
+            PathBuf::from("/projects/x.rs")
+        );
+
+        assert!("".parse::<PathMap>().unwrap().is_empty());
+        assert!("/project".parse::<PathMap>().is_err());
+    }
+}
@@ -1,9 +1,19 @@
 //! Shared logic for creating symbol relations.
 
-use crate::codebase::symbol_index::{ResolutionContext, SymbolIndex};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::codebase::parser::imports::GLOB;
+use crate::codebase::parser::parent_index;
+use crate::codebase::scanner::detect_language;
+use crate::codebase::symbol_index::{ImportScope, Resolution, ResolutionContext, SymbolIndex};
 use crate::storage::StorageBackend;
 use crate::types::safe_thing;
-use crate::types::symbol::{CodeReference, SymbolRef, SymbolRelation};
+use crate::types::symbol::{
+    CodeReference, CodeRelationType, CodeSymbol, ReferenceOccurrence, RelationSource, SymbolRef,
+    SymbolRelation, SymbolType, UnresolvedReference,
+};
+use crate::types::{record_id_string, Datetime, Language};
 
 /// Statistics from relation creation.
 #[derive(Debug, Default)]
@@ -14,6 +24,11 @@ pub struct RelationStats {
 }
 
 /// Create symbol relations from references using the symbol index for resolution.
+///
+/// Names are resolved through each file's imports (see [`SymbolIndex::lookup`]).
+/// References that cannot be resolved are stored as unresolved rather than
+/// linked to a guessed target. Every reference is also stored as an
+/// occurrence at its position.
 pub async fn create_symbol_relations(
     storage: &dyn StorageBackend,
     project_id: &str,
@@ -21,67 +36,109 @@ pub async fn create_symbol_relations(
     symbol_index: &SymbolIndex,
 ) -> RelationStats {
     let mut stats = RelationStats::default();
+    let mut relations = Vec::new();
+    let mut unresolved = Vec::new();
+    let mut occurrences = Vec::new();
+    let mut seen_unresolved: HashSet<(&str, &str, &str)> = HashSet::new();
+    let mut seen_occurrences: HashSet<(&str, u32, u32, &str)> = HashSet::new();
 
+    let mut by_file: HashMap<&str, Vec<&CodeReference>> = HashMap::new();
     for reference in references {
-        // 1. Build from_symbol Thing using the stored definition line
-        let from_thing = safe_thing::symbol_thing(
-            project_id,
-            &reference.file_path,
-            &reference.from_symbol,
-            reference.from_symbol_line,
-        );
+        by_file
+            .entry(reference.file_path.as_str())
+            .or_default()
+            .push(reference);
+    }
+    let scopes: HashMap<&str, ImportScope> = by_file
+        .iter()
+        .map(|(file, refs)| (*file, ImportScope::from_references(refs.iter().copied())))
+        .collect();
 
-        // 2. Resolve to_symbol with priority (same file > same dir > any)
-        let ctx = ResolutionContext::new(reference.file_path.clone());
+    for reference in references {
+        // Glob imports only widen the file's scope; they name no single symbol
+        if reference.relation_type == CodeRelationType::Imports && reference.to_symbol == GLOB {
+            continue;
+        }
 
-        let to_thing = if let Some(resolved) = symbol_index.resolve(&reference.to_symbol, &ctx) {
-            resolved.to_thing(project_id)
-        } else {
-            // Fallback: DB lookup with file context preference
-            match storage
-                .find_symbol_by_name_with_context(
-                    project_id,
-                    &reference.to_symbol,
-                    Some(&reference.file_path),
-                )
-                .await
-            {
-                Ok(Some(sym)) => SymbolRef::from_symbol(&sym).to_thing(project_id),
-                _ => {
-                    stats.unresolved += 1;
-                    tracing::debug!(
-                        from = %reference.from_symbol,
-                        to = %reference.to_symbol,
-                        file = %reference.file_path,
-                        "Skipping external symbol (not in project)"
-                    );
-                    continue;
+        // 1. Find the enclosing symbol by its definition line; top-level code
+        //    hangs off the file itself
+        let from_thing = symbol_index
+            .locate(
+                &reference.file_path,
+                &reference.from_symbol,
+                reference.from_symbol_line,
+            )
+            .map(SymbolRef::to_thing)
+            .unwrap_or_else(|| safe_thing::file_scope_thing(project_id, &reference.file_path));
+
+        // 2. Resolve to_symbol through the file's imports. An import is looked
+        //    up by its local name so it goes through its own binding.
+        let ctx = ResolutionContext::new(reference.file_path.clone())
+            .with_imports(scopes[reference.file_path.as_str()].clone());
+        let name = match reference.relation_type {
+            CodeRelationType::Imports => &reference.name,
+            _ => &reference.to_symbol,
+        };
+
+        let resolution = symbol_index.lookup(name, &ctx);
+        if seen_occurrences.insert((
+            &reference.file_path,
+            reference.line,
+            reference.column,
+            &reference.name,
+        )) {
+            occurrences.push(occurrence(project_id, reference, &resolution));
+        }
+
+        let to_thing = match resolution {
+            Resolution::Resolved(resolved) => resolved.to_thing(),
+            resolution => {
+                stats.unresolved += 1;
+                let module = match &resolution {
+                    Resolution::External(module) => Some(module.clone()),
+                    _ => reference.module.clone(),
+                };
+                if seen_unresolved.insert((&reference.file_path, &reference.from_symbol, name)) {
+                    unresolved.push(UnresolvedReference {
+                        id: None,
+                        project_id: project_id.to_string(),
+                        file_path: reference.file_path.clone(),
+                        from_symbol: reference.from_symbol.clone(),
+                        name: reference.to_symbol.clone(),
+                        relation_type: reference.relation_type,
+                        line: reference.line,
+                        module,
+                        reason: resolution.reason().to_string(),
+                        created_at: Datetime::default(),
+                    });
                 }
+                continue;
             }
         };
 
-        // 3. Create the relation
-        let relation = SymbolRelation::new(
+        relations.push(SymbolRelation::new(
             from_thing,
             to_thing,
             reference.relation_type,
             reference.file_path.clone(),
             reference.line,
             project_id.to_string(),
-        );
+        ));
+    }
 
-        match storage.create_symbol_relation(relation).await {
-            Ok(_) => stats.created += 1,
-            Err(e) => {
-                stats.failed += 1;
-                tracing::warn!(
-                    from = %reference.from_symbol,
-                    to = %reference.to_symbol,
-                    error = %e,
-                    "Failed to create symbol relation"
-                );
-            }
-        }
+    // 3. Create the relations
+    let stored = store_relations(storage, relations).await;
+    stats.created = stored.created;
+    stats.failed = stored.failed;
+
+    if let Err(e) = storage.create_unresolved_references_batch(unresolved).await {
+        tracing::warn!(error = %e, "Failed to record unresolved references");
+    }
+    if let Err(e) = storage
+        .create_reference_occurrences_batch(occurrences)
+        .await
+    {
+        tracing::warn!(error = %e, "Failed to record reference occurrences");
     }
 
     if stats.created > 0 || stats.failed > 0 || stats.unresolved > 0 {
@@ -95,3 +152,340 @@ pub async fn create_symbol_relations(
 
     stats
 }
+
+fn occurrence(
+    project_id: &str,
+    reference: &CodeReference,
+    resolution: &Resolution,
+) -> ReferenceOccurrence {
+    let (to_symbol, reason) = match resolution {
+        Resolution::Resolved(resolved) => (Some(record_id_string(&resolved.to_thing())), None),
+        other => (None, Some(other.reason().to_string())),
+    };
+    let module = match resolution {
+        Resolution::External(module) => Some(module.clone()),
+        _ => reference.module.clone(),
+    };
+    ReferenceOccurrence {
+        id: None,
+        project_id: project_id.to_string(),
+        file_path: reference.file_path.clone(),
+        name: reference.name.clone(),
+        relation_type: reference.relation_type,
+        line: reference.line,
+        column: reference.column,
+        from_symbol: reference.from_symbol.clone(),
+        to_symbol,
+        reason,
+        module,
+        source: RelationSource::TreeSitter,
+    }
+}
+
+/// `Contains` edges from each symbol's innermost enclosing symbol (class,
+/// impl, module) to it. Built from exact definition lines, so members that
+/// share a name are never confused.
+pub fn containment_relations(project_id: &str, symbols: &[CodeSymbol]) -> Vec<SymbolRelation> {
+    (0..symbols.len())
+        .filter_map(|i| {
+            let parent = &symbols[parent_index(symbols, i)?];
+            let child = &symbols[i];
+            Some(SymbolRelation::new(
+                SymbolRef::from_symbol(parent).to_thing(),
+                SymbolRef::from_symbol(child).to_thing(),
+                CodeRelationType::Contains,
+                child.file_path.clone(),
+                child.start_line,
+                project_id.to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// Store prebuilt relations such as [`containment_relations`] in batches.
+pub async fn store_relations(
+    storage: &dyn StorageBackend,
+    relations: Vec<SymbolRelation>,
+) -> RelationStats {
+    let mut stats = RelationStats::default();
+    let count = relations.len() as u32;
+    match storage.create_symbol_relations_batch(relations).await {
+        Ok(created) => stats.created = created as u32,
+        Err(e) => {
+            stats.failed = count;
+            tracing::warn!(error = %e, count, "Failed to create symbol relations");
+        }
+    }
+    stats
+}
+
+/// Link code types to schema definitions of the same name: a Rust `User`
+/// struct to a proto `User` message, or to a SQL `users` table.
+///
+/// `candidates` may hold symbols from either side; each is matched against
+/// the whole index. Edges point from the code type to the schema symbol.
+pub async fn create_schema_links(
+    storage: &dyn StorageBackend,
+    project_id: &str,
+    candidates: &[SymbolRef],
+    symbol_index: &SymbolIndex,
+) -> RelationStats {
+    let mut relations = Vec::new();
+    let mut seen: HashSet<(String, u32, String, u32)> = HashSet::new();
+
+    for candidate in candidates {
+        let pairs: Vec<(&SymbolRef, &SymbolRef)> = if is_code_type(candidate) {
+            schema_names(&candidate.name)
+                .iter()
+                .filter_map(|n| symbol_index.get_all(n))
+                .flatten()
+                .filter(|s| is_schema_type(s) && schema_matches(&candidate.name, s))
+                .map(|s| (candidate, s))
+                .collect()
+        } else if is_schema_type(candidate) {
+            code_names(candidate)
+                .iter()
+                .filter_map(|n| symbol_index.get_all(n))
+                .flatten()
+                .filter(|s| is_code_type(s))
+                .map(|s| (s, candidate))
+                .collect()
+        } else {
+            continue;
+        };
+
+        for (code, schema) in pairs {
+            let key = (
+                code.file_path.clone(),
+                code.line,
+                schema.file_path.clone(),
+                schema.line,
+            );
+            if !seen.insert(key) {
+                continue;
+            }
+
+            relations.push(SymbolRelation::new(
+                code.to_thing(),
+                schema.to_thing(),
+                CodeRelationType::References,
+                code.file_path.clone(),
+                code.line,
+                project_id.to_string(),
+            ));
+        }
+    }
+
+    let stats = store_relations(storage, relations).await;
+    if stats.created > 0 {
+        tracing::debug!(created = stats.created, "Schema links created");
+    }
+
+    stats
+}
+
+fn language_of(symbol: &SymbolRef) -> Language {
+    detect_language(Path::new(&symbol.file_path))
+}
+
+fn is_code_type(symbol: &SymbolRef) -> bool {
+    let language = language_of(symbol);
+    !language.is_schema()
+        && !language.is_document()
+        && matches!(
+            symbol.symbol_type,
+            SymbolType::Struct | SymbolType::Class | SymbolType::Interface | SymbolType::Enum
+        )
+}
+
+fn is_schema_type(symbol: &SymbolRef) -> bool {
+    language_of(symbol).is_schema()
+        && matches!(
+            symbol.symbol_type,
+            SymbolType::Table
+                | SymbolType::Struct
+                | SymbolType::Class
+                | SymbolType::Interface
+                | SymbolType::Enum
+        )
+}
+
+/// Whether a code type name corresponds to a schema symbol.
+fn schema_matches(code_name: &str, schema: &SymbolRef) -> bool {
+    if schema.symbol_type == SymbolType::Table {
+        let table = schema.name.to_lowercase();
+        let snake = to_snake_case(code_name);
+        snake == table || snake == singular(&table)
+    } else {
+        code_name == schema.name
+    }
+}
+
+/// Schema names a code type may be defined under: `UserAccount` →
+/// `UserAccount`, `user_account`, `user_accounts`.
+fn schema_names(code_name: &str) -> Vec<String> {
+    let snake = to_snake_case(code_name);
+    let mut names = vec![code_name.to_string(), plural(&snake)];
+    if snake != code_name {
+        names.push(snake);
+    }
+    names
+}
+
+/// Code type names matching a schema symbol: `user_accounts` → `UserAccount`, `UserAccounts`.
+fn code_names(schema: &SymbolRef) -> Vec<String> {
+    if schema.symbol_type != SymbolType::Table {
+        return vec![schema.name.clone()];
+    }
+    let table = schema.name.to_lowercase();
+    let mut names = vec![to_pascal_case(&singular(&table)), to_pascal_case(&table)];
+    names.dedup();
+    names
+}
+
+fn to_snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn to_pascal_case(name: &str) -> String {
+    name.split('_')
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            let mut chars = p.chars();
+            chars
+                .next()
+                .map(|c| c.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn plural(name: &str) -> String {
+    if let Some(stem) = name.strip_suffix('y') {
+        format!("{}ies", stem)
+    } else if name.ends_with('s') || name.ends_with('x') || name.ends_with("ch") {
+        format!("{}es", name)
+    } else {
+        format!("{}s", name)
+    }
+}
+
+fn singular(name: &str) -> String {
+    if let Some(stem) = name.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else if let Some(stem) = name
+        .strip_suffix("ses")
+        .or_else(|| name.strip_suffix("xes"))
+        .or_else(|| name.strip_suffix("ches"))
+    {
+        format!("{}{}", stem, &name[stem.len()..name.len() - 2])
+    } else {
+        name.strip_suffix('s').unwrap_or(name).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sym(name: &str, file: &str, symbol_type: SymbolType) -> SymbolRef {
+        SymbolRef::from_symbol(&CodeSymbol::new(
+            name.to_string(),
+            symbol_type,
+            file.to_string(),
+            1,
+            1,
+            "p".to_string(),
+        ))
+    }
+
+    #[test]
+    fn test_schema_name_matching() {
+        let table = sym("user_accounts", "db/schema.sql", SymbolType::Table);
+        assert!(schema_matches("UserAccount", &table));
+        assert!(!schema_matches("Account", &table));
+        assert_eq!(code_names(&table), vec!["UserAccount", "UserAccounts"]);
+        assert!(schema_names("Category").contains(&"categories".to_string()));
+        assert_eq!(singular("addresses"), "address");
+
+        let message = sym("User", "api/user.proto", SymbolType::Struct);
+        assert!(is_schema_type(&message));
+        assert!(schema_matches("User", &message));
+        assert!(is_code_type(&sym(
+            "User",
+            "src/user.rs",
+            SymbolType::Struct
+        )));
+        assert!(!is_code_type(&message));
+    }
+
+    #[tokio::test]
+    async fn test_calls_follow_imports() {
+        use crate::codebase::CodeParser;
+        use crate::test_utils::TestContext;
+        use std::path::PathBuf;
+
+        let ctx = TestContext::new().await;
+        let files = [
+            ("/p/src/config.rs", "pub fn parse() {}\n"),
+            ("/p/src/jwt.rs", "pub fn parse() {}\n"),
+            (
+                "/p/src/main.rs",
+                "use crate::jwt::parse;\n\nfn main() {\n    parse();\n    println_external();\n}\n",
+            ),
+        ];
+
+        let mut index = SymbolIndex::new();
+        let mut references = Vec::new();
+        for (path, content) in files {
+            let (symbols, refs) = CodeParser::parse_file(&PathBuf::from(path), content, "p");
+            index.add_batch(&symbols);
+            ctx.state
+                .storage
+                .create_code_symbols_batch(symbols)
+                .await
+                .unwrap();
+            references.extend(refs);
+        }
+
+        let stats =
+            create_symbol_relations(ctx.state.storage.as_ref(), "p", &references, &index).await;
+        assert_eq!(stats.unresolved, 1);
+
+        let main = index
+            .locate("/p/src/main.rs", "main", 3)
+            .unwrap()
+            .to_thing();
+        let main_id = format!(
+            "code_symbols:{}",
+            crate::types::record_key_to_string(&main.key)
+        );
+        let callees = ctx
+            .state
+            .storage
+            .get_symbol_callees(&main_id)
+            .await
+            .unwrap();
+        assert_eq!(callees.len(), 1);
+        assert_eq!(callees[0].file_path, "/p/src/jwt.rs");
+
+        let unresolved = ctx
+            .state
+            .storage
+            .count_unresolved_references("p")
+            .await
+            .unwrap();
+        assert_eq!(unresolved, vec![("not_found".to_string(), 1)]);
+    }
+}
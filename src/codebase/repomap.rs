@@ -0,0 +1,348 @@
+//! Repository map: the most important files of a project and their key
+//! signatures, ranked by PageRank over the symbol graph and cut to a token
+//! budget.
+//!
+//! Rank flows along use edges (caller to callee, importer to imported), so
+//! code that much of the project depends on rises to the top. With focus
+//! paths the walk restarts from their symbols instead of uniformly, ranking
+//! what the focused code depends on.
+
+use petgraph::graph::{DiGraph, NodeIndex};
+use std::collections::HashMap;
+
+use crate::graph::{detect_communities, personalized_page_rank, PPR_TOLERANCE};
+use crate::types::{record_id_string, CodeRelationType, CodeSymbol, SymbolRelation};
+
+/// Classic PageRank damping: a global ranking, not a local neighbourhood.
+pub const REPO_MAP_DAMPING: f32 = 0.85;
+const REPO_MAP_MAX_ITER: usize = 50;
+/// Signatures are cut to this many characters in the outline.
+const MAX_SIGNATURE_CHARS: usize = 160;
+
+#[derive(Debug, Clone)]
+pub struct RankedFile {
+    pub file_path: String,
+    pub rank: f32,
+    /// Indexes into the ranked symbols with their rank, highest first
+    pub symbols: Vec<(usize, f32)>,
+}
+
+/// Files that reference each other more than the rest of the project.
+#[derive(Debug, Clone)]
+pub struct FileCommunity {
+    /// Indexes into [`RepoMap::files`], highest rank first
+    pub files: Vec<usize>,
+    pub rank: f32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RepoMap {
+    /// Highest rank first
+    pub files: Vec<RankedFile>,
+    /// Communities of two or more files, highest rank first
+    pub communities: Vec<FileCommunity>,
+    /// Whether focus paths matched any symbol
+    pub focused: bool,
+}
+
+/// Rough token count of text, at four characters per token.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Rank the files and symbols of a project. `focus` holds file paths or
+/// directory prefixes, relative to the project root.
+pub fn rank(symbols: &[CodeSymbol], relations: &[SymbolRelation], focus: &[String]) -> RepoMap {
+    let mut graph: DiGraph<String, f32> = DiGraph::new();
+    let mut nodes: HashMap<String, NodeIndex> = HashMap::new();
+    for symbol in symbols {
+        let id = format!("code_symbols:{}", symbol.unique_key());
+        nodes
+            .entry(id.clone())
+            .or_insert_with(|| graph.add_node(id));
+    }
+    for relation in relations {
+        if relation.relation_type == CodeRelationType::Contains {
+            continue;
+        }
+        let from = nodes.get(&record_id_string(&relation.from_symbol));
+        let to = nodes.get(&record_id_string(&relation.to_symbol));
+        if let (Some(&from), Some(&to)) = (from, to) {
+            if from != to {
+                graph.add_edge(from, to, 1.0);
+            }
+        }
+    }
+
+    let seeds: Vec<NodeIndex> = symbols
+        .iter()
+        .filter(|s| focus.iter().any(|f| in_path(&s.file_path, f)))
+        .map(|s| nodes[&format!("code_symbols:{}", s.unique_key())])
+        .collect();
+    let scores = personalized_page_rank(
+        &graph,
+        &seeds,
+        REPO_MAP_DAMPING,
+        PPR_TOLERANCE,
+        REPO_MAP_MAX_ITER,
+    );
+
+    let mut by_file: HashMap<&str, Vec<(usize, f32)>> = HashMap::new();
+    for (i, symbol) in symbols.iter().enumerate() {
+        let node = nodes[&format!("code_symbols:{}", symbol.unique_key())];
+        let score = scores.get(&node).copied().unwrap_or(0.0);
+        by_file
+            .entry(&symbol.file_path)
+            .or_default()
+            .push((i, score));
+    }
+    let mut files: Vec<RankedFile> = by_file
+        .into_iter()
+        .map(|(file_path, mut ranked)| {
+            ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+            RankedFile {
+                file_path: file_path.to_string(),
+                rank: ranked.iter().map(|(_, score)| score).sum(),
+                symbols: ranked,
+            }
+        })
+        .collect();
+    files.sort_by(|a, b| {
+        b.rank
+            .total_cmp(&a.rank)
+            .then(a.file_path.cmp(&b.file_path))
+    });
+
+    let communities = file_communities(&files, symbols, relations);
+    RepoMap {
+        files,
+        communities,
+        focused: !seeds.is_empty(),
+    }
+}
+
+fn in_path(file_path: &str, focus: &str) -> bool {
+    let focus = focus.trim_end_matches('/');
+    file_path == focus
+        || file_path
+            .strip_prefix(focus)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Communities of the file graph, edges weighted by the symbol relations
+/// between two files.
+fn file_communities(
+    files: &[RankedFile],
+    symbols: &[CodeSymbol],
+    relations: &[SymbolRelation],
+) -> Vec<FileCommunity> {
+    let mut graph: DiGraph<String, f32> = DiGraph::new();
+    let file_nodes: HashMap<&str, NodeIndex> = files
+        .iter()
+        .map(|f| (f.file_path.as_str(), graph.add_node(f.file_path.clone())))
+        .collect();
+    let symbol_files: HashMap<String, &str> = symbols
+        .iter()
+        .map(|s| {
+            (
+                format!("code_symbols:{}", s.unique_key()),
+                s.file_path.as_str(),
+            )
+        })
+        .collect();
+    let mut weights: HashMap<(NodeIndex, NodeIndex), f32> = HashMap::new();
+    for relation in relations {
+        let from = symbol_files.get(&record_id_string(&relation.from_symbol));
+        let to = symbol_files.get(&record_id_string(&relation.to_symbol));
+        if let (Some(from), Some(to)) = (from, to) {
+            if from != to {
+                *weights
+                    .entry((file_nodes[from], file_nodes[to]))
+                    .or_default() += 1.0;
+            }
+        }
+    }
+    let mut edges: Vec<_> = weights.into_iter().collect();
+    edges.sort_by_key(|((from, to), _)| (*from, *to));
+    for ((from, to), weight) in edges {
+        graph.add_edge(from, to, weight);
+    }
+
+    // Node indexes follow `files`, which is already in rank order
+    let mut communities: Vec<FileCommunity> = detect_communities(&graph)
+        .into_iter()
+        .filter(|members| members.len() > 1)
+        .map(|members| {
+            let mut files_in: Vec<usize> = members.iter().map(|n| n.index()).collect();
+            files_in.sort_unstable();
+            FileCommunity {
+                rank: files_in.iter().map(|&i| files[i].rank).sum(),
+                files: files_in,
+            }
+        })
+        .collect();
+    communities.sort_by(|a, b| b.rank.total_cmp(&a.rank).then(a.files.cmp(&b.files)));
+    communities
+}
+
+/// One outline line for a symbol: its first signature line (or kind and
+/// name), indented under its parent.
+fn outline_line(symbol: &CodeSymbol) -> String {
+    let signature = symbol
+        .signature
+        .as_deref()
+        .and_then(|s| s.lines().next())
+        .map(|s| s.trim().trim_end_matches('{').trim_end().to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| format!("{} {}", symbol.symbol_type, symbol.name));
+    let signature: String = signature.chars().take(MAX_SIGNATURE_CHARS).collect();
+    let indent = if symbol.parent.is_some() {
+        "    "
+    } else {
+        "  "
+    };
+    format!("{}{}: {}\n", indent, symbol.start_line, signature)
+}
+
+/// The outline of the highest-ranked symbols that fits in `max_tokens`,
+/// grouped by file in rank order and by line within a file. Returns the
+/// text, the symbols shown per file (indexes into `map.files` and the
+/// symbols) and whether anything was left out.
+pub fn render(
+    map: &RepoMap,
+    symbols: &[CodeSymbol],
+    max_tokens: usize,
+) -> (String, Vec<(usize, Vec<usize>)>, bool) {
+    let mut ranked: Vec<(usize, usize, f32)> = map
+        .files
+        .iter()
+        .enumerate()
+        .flat_map(|(f, file)| file.symbols.iter().map(move |&(s, score)| (f, s, score)))
+        .collect();
+    ranked.sort_by(|a, b| b.2.total_cmp(&a.2).then((a.0, a.1).cmp(&(b.0, b.1))));
+
+    let mut used = 0;
+    let mut shown: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut truncated = false;
+    for (f, s, _) in ranked {
+        let mut cost = estimate_tokens(&outline_line(&symbols[s]));
+        if !shown.contains_key(&f) {
+            cost += estimate_tokens(&map.files[f].file_path) + 1;
+        }
+        if used + cost > max_tokens {
+            truncated = true;
+            break;
+        }
+        used += cost;
+        shown.entry(f).or_default().push(s);
+    }
+
+    let mut shown: Vec<(usize, Vec<usize>)> = shown.into_iter().collect();
+    shown.sort_by_key(|(f, _)| *f);
+    let mut text = String::new();
+    for (f, file_symbols) in &mut shown {
+        file_symbols.sort_by_key(|&s| (symbols[s].start_line, s));
+        text.push_str(&map.files[*f].file_path);
+        text.push('\n');
+        for &s in file_symbols.iter() {
+            text.push_str(&outline_line(&symbols[s]));
+        }
+    }
+    (text, shown, truncated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{edge, symbol};
+    use crate::types::SymbolType;
+
+    fn function(key: &str, path: &str, line: u32) -> CodeSymbol {
+        let mut s = symbol(key, key, SymbolType::Function, (line, line + 2));
+        s.file_path = path.to_string();
+        s.signature = Some(format!("fn {}() {{", key));
+        s
+    }
+
+    fn calls(from: &str, to: &str) -> SymbolRelation {
+        edge(from, to, CodeRelationType::Calls)
+    }
+
+    // Two groups of files: a storage layer everything uses, and a
+    // separate cli/ui pair
+    fn project() -> (Vec<CodeSymbol>, Vec<SymbolRelation>) {
+        let symbols = vec![
+            function("save", "src/store.rs", 1),
+            function("load", "src/store.rs", 10),
+            function("handle", "src/api.rs", 1),
+            function("route", "src/api.rs", 10),
+            function("serve", "src/api.rs", 20),
+            function("run", "src/cli.rs", 1),
+            function("draw", "src/ui.rs", 1),
+            function("paint", "src/ui.rs", 10),
+        ];
+        let relations = vec![
+            calls("handle", "save"),
+            calls("handle", "load"),
+            calls("route", "load"),
+            calls("route", "handle"),
+            calls("serve", "load"),
+            calls("serve", "save"),
+            calls("run", "draw"),
+            calls("run", "paint"),
+            calls("draw", "paint"),
+        ];
+        (symbols, relations)
+    }
+
+    #[test]
+    fn test_rank() {
+        let (symbols, relations) = project();
+        let map = rank(&symbols, &relations, &[]);
+        assert!(!map.focused);
+        assert_eq!(map.files[0].file_path, "src/store.rs");
+        let top = map.files[0].symbols[0].0;
+        assert_eq!(symbols[top].name, "load");
+
+        let files = |c: &FileCommunity| -> Vec<&str> {
+            let mut paths: Vec<&str> = c
+                .files
+                .iter()
+                .map(|&f| map.files[f].file_path.as_str())
+                .collect();
+            paths.sort();
+            paths
+        };
+        assert_eq!(map.communities.len(), 2);
+        assert_eq!(
+            files(&map.communities[0]),
+            vec!["src/api.rs", "src/store.rs"]
+        );
+        assert_eq!(files(&map.communities[1]), vec!["src/cli.rs", "src/ui.rs"]);
+
+        // Focusing on the cli ranks what it uses first
+        let map = rank(&symbols, &relations, &["src/cli.rs".to_string()]);
+        assert!(map.focused);
+        assert_eq!(map.files[0].file_path, "src/ui.rs");
+        let map = rank(&symbols, &relations, &["src/".to_string()]);
+        assert!(map.focused);
+        assert!(!rank(&symbols, &relations, &["src/c".to_string()]).focused);
+    }
+
+    #[test]
+    fn test_render() {
+        let (symbols, relations) = project();
+        let map = rank(&symbols, &relations, &[]);
+        let (text, shown, truncated) = render(&map, &symbols, 1000);
+        assert!(!truncated);
+        assert_eq!(shown.iter().map(|(_, s)| s.len()).sum::<usize>(), 8);
+        assert!(text.starts_with("src/store.rs\n  1: fn save()\n  10: fn load()\n"));
+
+        let (text, shown, truncated) = render(&map, &symbols, 12);
+        assert!(truncated);
+        assert!(estimate_tokens(&text) <= 12);
+        assert_eq!(shown.len(), 1);
+        // paint collects all of the ui chain's rank
+        assert_eq!(text, "src/ui.rs\n  10: fn paint()\n");
+    }
+}
@@ -1,10 +1,20 @@
 use std::path::{Path, PathBuf};
 
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use ignore::{overrides::OverrideBuilder, WalkBuilder};
 
 use crate::types::Language;
 
 pub fn scan_directory(root: &Path) -> crate::Result<Vec<PathBuf>> {
+    scan_directory_until(root, || true)
+}
+
+/// [`scan_directory`] that stops early once `keep_going` returns false, so a
+/// cancelled job doesn't finish walking a huge tree first.
+pub fn scan_directory_until(
+    root: &Path,
+    keep_going: impl Fn() -> bool,
+) -> crate::Result<Vec<PathBuf>> {
     let mut overrides = OverrideBuilder::new(root);
     let _ = overrides.add("!**/*.g.dart");
     let _ = overrides.add("!**/*.freezed.dart");
@@ -32,8 +42,11 @@ pub fn scan_directory(root: &Path) -> crate::Result<Vec<PathBuf>> {
 
     let mut files = Vec::new();
     for entry in walker.filter_map(|e| e.ok()) {
+        if !keep_going() {
+            break;
+        }
         let path = entry.path();
-        if path.is_file() && !is_ignored_file(path) && is_code_file(path) {
+        if path.is_file() && !is_ignored_file(path) && is_indexable_file(path) {
             files.push(path.to_path_buf());
         }
     }
@@ -41,6 +54,103 @@ pub fn scan_directory(root: &Path) -> crate::Result<Vec<PathBuf>> {
     Ok(files)
 }
 
+/// Per-directory ignore files honoured by the scanner and watcher.
+pub const IGNORE_FILES: [&str; 2] = [".gitignore", ".memoryignore"];
+
+pub fn is_ignore_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| IGNORE_FILES.contains(&n))
+}
+
+/// The scanner's ignore rules, for checking single paths as the watcher sees
+/// them: `.gitignore` and `.memoryignore` at any depth, hidden paths and
+/// generated or build output.
+pub struct IgnoreRules {
+    root: PathBuf,
+    /// One matcher per directory holding ignore files, deepest first
+    matchers: Vec<(PathBuf, Gitignore)>,
+}
+
+impl IgnoreRules {
+    /// Collect every ignore file under `root`. Reload after one changes.
+    pub fn load(root: &Path) -> Self {
+        let walker = WalkBuilder::new(root)
+            .hidden(false)
+            .git_ignore(true)
+            .add_custom_ignore_filename(".memoryignore")
+            .filter_entry(|e| e.file_name() != ".git")
+            .build();
+
+        let mut dirs: Vec<PathBuf> = walker
+            .filter_map(|e| e.ok())
+            .filter(|e| is_ignore_file(e.path()))
+            .filter_map(|e| e.path().parent().map(Path::to_path_buf))
+            .collect();
+        dirs.sort();
+        dirs.dedup();
+
+        let mut matchers: Vec<(PathBuf, Gitignore)> = dirs
+            .into_iter()
+            .filter_map(|dir| {
+                let mut builder = GitignoreBuilder::new(&dir);
+                for name in IGNORE_FILES {
+                    let file = dir.join(name);
+                    if file.is_file() {
+                        if let Some(e) = builder.add(&file) {
+                            tracing::warn!(path = ?file, error = %e, "Invalid ignore file");
+                        }
+                    }
+                }
+                builder.build().ok().map(|gi| (dir, gi))
+            })
+            .collect();
+        matchers.sort_by_key(|(dir, _)| std::cmp::Reverse(dir.components().count()));
+
+        Self {
+            root: root.to_path_buf(),
+            matchers,
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Whether the scanner would skip `path`. Paths outside the root are
+    /// always ignored.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return true;
+        };
+        if relative.components().any(|c| {
+            c.as_os_str()
+                .to_str()
+                .is_some_and(|c| c.starts_with('.') && c != "." && c != "..")
+        }) {
+            return true;
+        }
+        if is_ignored_file(path) {
+            return true;
+        }
+
+        // The deepest ignore file with an opinion decides, as in git
+        for (dir, gitignore) in &self.matchers {
+            if !path.starts_with(dir) {
+                continue;
+            }
+            let matched = gitignore.matched_path_or_any_parents(path, is_dir);
+            if matched.is_ignore() {
+                return true;
+            }
+            if matched.is_whitelist() {
+                return false;
+            }
+        }
+        false
+    }
+}
+
 pub fn is_ignored_file(path: &Path) -> bool {
     let path_str = path.to_string_lossy().to_lowercase();
     if path_str.contains("/node_modules/")
@@ -119,6 +229,35 @@ pub fn is_code_file(path: &Path) -> bool {
     )
 }
 
+/// Documentation files indexed alongside code (chunked by heading).
+pub fn is_doc_file(path: &Path) -> bool {
+    let Some(ext) = path.extension().and_then(|e| e.to_str()) else {
+        return false;
+    };
+
+    matches!(
+        ext.to_lowercase().as_str(),
+        "md" | "markdown" | "rst" | "txt"
+    )
+}
+
+/// Config and schema files indexed by key, table or definition.
+pub fn is_structured_file(path: &Path) -> bool {
+    let Some(ext) = path.extension().and_then(|e| e.to_str()) else {
+        return false;
+    };
+
+    matches!(
+        ext.to_lowercase().as_str(),
+        "yaml" | "yml" | "toml" | "json" | "sql" | "proto" | "graphql" | "gql"
+    )
+}
+
+/// Whether the scanner and watcher should pick up this file at all.
+pub fn is_indexable_file(path: &Path) -> bool {
+    is_code_file(path) || is_doc_file(path) || is_structured_file(path)
+}
+
 pub fn detect_language(path: &Path) -> Language {
     let Some(ext) = path.extension().and_then(|e| e.to_str()) else {
         return Language::Unknown;
@@ -132,6 +271,53 @@ pub fn detect_language(path: &Path) -> Language {
         "go" => Language::Go,
         "java" => Language::Java,
         "dart" => Language::Dart,
+        "md" | "markdown" => Language::Markdown,
+        "rst" => Language::ReStructuredText,
+        "txt" => Language::Text,
+        "yaml" | "yml" => Language::Yaml,
+        "toml" => Language::Toml,
+        "json" => Language::Json,
+        "sql" => Language::Sql,
+        "proto" => Language::Protobuf,
+        "graphql" | "gql" => Language::GraphQL,
         _ => Language::Unknown,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_ignore_rules_match_scanner() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src/gen")).unwrap();
+        fs::create_dir_all(root.join(".cache")).unwrap();
+        fs::write(root.join(".gitignore"), "*.log\nsecrets/\n").unwrap();
+        fs::write(root.join("src/.memoryignore"), "gen/\n").unwrap();
+        for file in [
+            "src/main.rs",
+            "src/gen/api.rs",
+            ".cache/x.rs",
+            "notes.md",
+            "run.log",
+        ] {
+            fs::write(root.join(file), "x").unwrap();
+        }
+
+        let rules = IgnoreRules::load(root);
+        assert!(!rules.is_ignored(&root.join("src/main.rs"), false));
+        assert!(!rules.is_ignored(&root.join("notes.md"), false));
+        assert!(rules.is_ignored(&root.join("src/gen/api.rs"), false));
+        assert!(rules.is_ignored(&root.join("src/gen"), true));
+        assert!(rules.is_ignored(&root.join(".cache/x.rs"), false));
+        assert!(rules.is_ignored(&root.join("run.log"), false));
+        assert!(rules.is_ignored(&root.join("secrets/key.rs"), false));
+        assert!(rules.is_ignored(Path::new("/elsewhere/a.rs"), false));
+
+        let scanned = scan_directory(root).unwrap();
+        assert!(scanned.iter().all(|p| !rules.is_ignored(p, false)));
+    }
+}
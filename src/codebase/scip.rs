@@ -0,0 +1,876 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use prost::Message;
+
+use super::indexer::{store_symbols, symbol_embedding_texts};
+use crate::config::AppState;
+use crate::storage::StorageBackend;
+use crate::types::{
+    AppError, CodeRelationType, CodeSymbol, Datetime, ReferenceOccurrence, RelationSource,
+    SymbolRelation, SymbolType, Thing, UnresolvedReference,
+};
+use crate::Result;
+
+/// The subset of the SCIP protobuf schema (github.com/sourcegraph/scip,
+/// `scip.proto`) the importer reads. Unknown fields are skipped on decode.
+pub mod proto {
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Index {
+        #[prost(message, optional, tag = "1")]
+        pub metadata: Option<Metadata>,
+        #[prost(message, repeated, tag = "2")]
+        pub documents: Vec<Document>,
+        #[prost(message, repeated, tag = "3")]
+        pub external_symbols: Vec<SymbolInformation>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Metadata {
+        #[prost(int32, tag = "1")]
+        pub version: i32,
+        #[prost(message, optional, tag = "2")]
+        pub tool_info: Option<ToolInfo>,
+        /// URI of the directory document paths are relative to
+        #[prost(string, tag = "3")]
+        pub project_root: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ToolInfo {
+        #[prost(string, tag = "1")]
+        pub name: String,
+        #[prost(string, tag = "2")]
+        pub version: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Document {
+        #[prost(string, tag = "1")]
+        pub relative_path: String,
+        #[prost(message, repeated, tag = "2")]
+        pub occurrences: Vec<Occurrence>,
+        #[prost(message, repeated, tag = "3")]
+        pub symbols: Vec<SymbolInformation>,
+        #[prost(string, tag = "4")]
+        pub language: String,
+        #[prost(string, tag = "5")]
+        pub text: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SymbolInformation {
+        #[prost(string, tag = "1")]
+        pub symbol: String,
+        #[prost(string, repeated, tag = "3")]
+        pub documentation: Vec<String>,
+        #[prost(message, repeated, tag = "4")]
+        pub relationships: Vec<Relationship>,
+        #[prost(int32, tag = "5")]
+        pub kind: i32,
+        #[prost(string, tag = "6")]
+        pub display_name: String,
+        #[prost(message, optional, tag = "7")]
+        pub signature_documentation: Option<Document>,
+        #[prost(string, tag = "8")]
+        pub enclosing_symbol: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Relationship {
+        #[prost(string, tag = "1")]
+        pub symbol: String,
+        #[prost(bool, tag = "2")]
+        pub is_reference: bool,
+        #[prost(bool, tag = "3")]
+        pub is_implementation: bool,
+        #[prost(bool, tag = "4")]
+        pub is_type_definition: bool,
+        #[prost(bool, tag = "5")]
+        pub is_definition: bool,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Occurrence {
+        /// `[start_line, start_char, end_line, end_char]`, or three elements
+        /// when start and end are on the same line; 0-based
+        #[prost(int32, repeated, tag = "1")]
+        pub range: Vec<i32>,
+        #[prost(string, tag = "2")]
+        pub symbol: String,
+        /// Bitset of `SymbolRole`s
+        #[prost(int32, tag = "3")]
+        pub symbol_roles: i32,
+        /// Range of the whole definition (body included), same encoding
+        #[prost(int32, repeated, tag = "7")]
+        pub enclosing_range: Vec<i32>,
+    }
+
+    /// `SymbolRole` bits
+    pub const ROLE_DEFINITION: i32 = 0x1;
+    pub const ROLE_IMPORT: i32 = 0x2;
+}
+
+/// What an import changed.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct ScipImportStats {
+    pub tool: Option<String>,
+    pub files: usize,
+    pub symbols: usize,
+    /// Existing tree-sitter symbols now backed by a SCIP definition
+    pub matched_symbols: usize,
+    pub relations: usize,
+    pub unresolved: usize,
+}
+
+/// Symbols defined in a document, with their SCIP symbol
+type Definitions = Vec<(String, CodeSymbol)>;
+
+/// Read an `index.scip` file.
+pub fn read_index(path: &Path) -> Result<proto::Index> {
+    let bytes = std::fs::read(path)?;
+    proto::Index::decode(bytes.as_slice())
+        .map_err(|e| AppError::Indexing(format!("invalid SCIP index {}: {}", path.display(), e)))
+}
+
+/// Replace the code graph of the files covered by a SCIP index with its
+/// compiler-accurate symbols and references. Tree-sitter symbols at the same
+/// definition keep their record (and inbound edges from other files); edges
+/// out of covered files are rebuilt from SCIP and marked `source = scip`.
+/// Editing a covered file later reindexes it with tree-sitter.
+pub async fn import_scip(
+    state: Arc<AppState>,
+    project_id: &str,
+    scip_path: &Path,
+) -> Result<ScipImportStats> {
+    let Some(root) = state
+        .storage
+        .get_index_status(project_id)
+        .await?
+        .and_then(|status| status.root)
+    else {
+        return Err(AppError::Indexing(format!(
+            "{} is not indexed; run index_project first",
+            project_id
+        )));
+    };
+    let index = {
+        let scip_path = scip_path.to_path_buf();
+        tokio::task::spawn_blocking(move || read_index(&scip_path))
+            .await
+            .map_err(|e| AppError::Indexing(e.to_string()))??
+    };
+    let prefix = document_prefix(&state, &index, Path::new(&root));
+
+    let mut stats = ScipImportStats {
+        tool: index
+            .metadata
+            .as_ref()
+            .and_then(|m| m.tool_info.as_ref())
+            .map(|t| format!("{} {}", t.name, t.version).trim().to_string()),
+        ..Default::default()
+    };
+    let info: HashMap<&str, &proto::SymbolInformation> = index
+        .documents
+        .iter()
+        .flat_map(|doc| doc.symbols.iter())
+        .chain(index.external_symbols.iter())
+        .map(|info| (info.symbol.as_str(), info))
+        .collect();
+
+    // Pass 1: definitions. Every file's symbols are stored before any edge,
+    // since references cross files.
+    let mut defined: HashMap<String, CodeSymbol> = HashMap::new();
+    let mut files: Vec<(String, &proto::Document, Definitions)> = Vec::new();
+    for doc in &index.documents {
+        let path = join_relative(&prefix, &doc.relative_path);
+        let old = state.storage.get_symbols_by_path(project_id, &path).await?;
+        let mut symbols = definitions(doc, &info, &path, project_id);
+        stats.matched_symbols += adopt_existing(&mut symbols, &old);
+
+        let _permit = state.db_semaphore.acquire().await;
+        state
+            .storage
+            .delete_outgoing_relations_by_path(project_id, &path)
+            .await?;
+        let keep: HashSet<String> = symbols.iter().map(|(_, s)| s.unique_key()).collect();
+        for stale in old.iter().filter(|s| !keep.contains(&s.unique_key())) {
+            let replacement = symbols
+                .iter()
+                .find(|(_, s)| s.name == stale.name)
+                .map(|(_, s)| format!("code_symbols:{}", s.unique_key()));
+            state
+                .storage
+                .remove_symbol(
+                    &format!("code_symbols:{}", stale.unique_key()),
+                    replacement.as_deref(),
+                )
+                .await?;
+        }
+
+        // Adopted symbols keep their embedding; new ones are queued
+        let plain: Vec<CodeSymbol> = symbols.iter().map(|(_, s)| s.clone()).collect();
+        let texts = symbol_embedding_texts(&state.config.embedding_text, &plain);
+        let batch = plain
+            .into_iter()
+            .zip(texts)
+            .map(|(s, text)| {
+                let text = if s.embedding.is_some() { None } else { text };
+                (s, text)
+            })
+            .collect();
+        store_symbols(&state, batch).await?;
+
+        stats.files += 1;
+        stats.symbols += symbols.len();
+        for (scip_symbol, symbol) in &symbols {
+            defined.insert(scip_symbol.clone(), symbol.clone());
+        }
+        files.push((path, doc, symbols));
+    }
+
+    // Pass 2: edges from each file's references and relationships
+    let packages: HashSet<String> = defined.keys().map(|s| package(s)).collect();
+    let scope = Scope {
+        defined: &defined,
+        packages: &packages,
+        info: &info,
+    };
+    let mut out = References::default();
+    for (path, doc, symbols) in &files {
+        references(doc, symbols, &scope, path, project_id, &mut out);
+    }
+    stats.relations = state
+        .storage
+        .create_symbol_relations_batch(out.relations)
+        .await?;
+    stats.unresolved = state
+        .storage
+        .create_unresolved_references_batch(out.unresolved)
+        .await?;
+    state
+        .storage
+        .create_reference_occurrences_batch(out.occurrences)
+        .await?;
+
+    // The in-memory symbol index is rebuilt from storage on next use
+    state.symbol_indexes.remove(project_id).await;
+    tracing::info!(project = %project_id, ?stats, "Imported SCIP index");
+    Ok(stats)
+}
+
+/// Project-relative directory of the index's document paths: the SCIP
+/// project root's place under the indexed root, or the root itself when the
+/// index was produced elsewhere (e.g. in CI).
+fn document_prefix(state: &AppState, index: &proto::Index, root: &Path) -> String {
+    let scip_root = index
+        .metadata
+        .as_ref()
+        .map(|m| m.project_root.trim_start_matches("file://"))
+        .filter(|r| !r.is_empty())
+        .map(|r| state.config.path_map.to_server(Path::new(r)));
+    match scip_root.and_then(|r| r.canonicalize().ok()) {
+        Some(scip_root) if scip_root.starts_with(root) => super::relative_path(root, &scip_root),
+        _ => String::new(),
+    }
+}
+
+fn join_relative(prefix: &str, path: &str) -> String {
+    if prefix.is_empty() {
+        path.to_string()
+    } else {
+        super::relative_path(Path::new(""), &PathBuf::from(prefix).join(path))
+    }
+}
+
+/// A parsed SCIP symbol descriptor: `name/` namespace, `name#` type,
+/// `name.` term, `name().` method, `name!` macro.
+#[derive(Debug, Clone, PartialEq)]
+enum Descriptor {
+    Namespace(String),
+    Type(String),
+    Term(String),
+    Method(String),
+    Macro(String),
+    /// Type parameters, parameters and meta descriptors
+    Other,
+}
+
+impl Descriptor {
+    fn name(&self) -> Option<&str> {
+        match self {
+            Descriptor::Namespace(n)
+            | Descriptor::Type(n)
+            | Descriptor::Term(n)
+            | Descriptor::Method(n)
+            | Descriptor::Macro(n) => Some(n),
+            Descriptor::Other => None,
+        }
+    }
+}
+
+/// Descriptors of a global symbol (`<scheme> <manager> <package> <version>
+/// <descriptors>`); empty for `local` symbols.
+fn descriptors(symbol: &str) -> Vec<Descriptor> {
+    if symbol.starts_with("local ") {
+        return Vec::new();
+    }
+    let Some(text) = symbol_parts(symbol).get(4).cloned() else {
+        return Vec::new();
+    };
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            // `[T]` type parameter, `(x)` parameter
+            '[' | '(' => {
+                let close = if chars[i] == '[' { ']' } else { ')' };
+                while i < chars.len() && chars[i] != close {
+                    i += 1;
+                }
+                i += 1;
+                out.push(Descriptor::Other);
+                continue;
+            }
+            _ => {}
+        }
+        let mut name = String::new();
+        if chars[i] == '`' {
+            i += 1;
+            while i < chars.len() {
+                if chars[i] == '`' && chars.get(i + 1) == Some(&'`') {
+                    name.push('`');
+                    i += 2;
+                } else if chars[i] == '`' {
+                    i += 1;
+                    break;
+                } else {
+                    name.push(chars[i]);
+                    i += 1;
+                }
+            }
+        } else {
+            while i < chars.len() && !"/#.:!([".contains(chars[i]) {
+                name.push(chars[i]);
+                i += 1;
+            }
+        }
+        let Some(&suffix) = chars.get(i) else {
+            break;
+        };
+        i += 1;
+        out.push(match suffix {
+            '/' => Descriptor::Namespace(name),
+            '#' => Descriptor::Type(name),
+            '.' => Descriptor::Term(name),
+            '!' => Descriptor::Macro(name),
+            '(' => {
+                // `name(disambiguator).`
+                while i < chars.len() && chars[i] != ')' {
+                    i += 1;
+                }
+                i += 2;
+                Descriptor::Method(name)
+            }
+            _ => Descriptor::Other,
+        });
+    }
+    out
+}
+
+/// Space-separated parts of a symbol; a doubled space escapes a space.
+fn symbol_parts(symbol: &str) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = symbol.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == ' ' && parts.len() < 5 {
+            if chars.peek() == Some(&' ') {
+                chars.next();
+                parts.last_mut().unwrap().push(' ');
+            } else {
+                parts.push(String::new());
+            }
+        } else {
+            parts.last_mut().unwrap().push(c);
+        }
+    }
+    parts
+}
+
+/// `<manager> <package> <version>` of a global symbol.
+fn package(symbol: &str) -> String {
+    symbol_parts(symbol).get(1..4).unwrap_or_default().join(" ")
+}
+
+/// Symbol type for a SCIP `SymbolInformation.Kind`, falling back to the
+/// descriptor suffix when the indexer left the kind unspecified. `None` for
+/// kinds the code graph doesn't model (locals, parameters, constants).
+fn symbol_type(kind: i32, descriptors: &[Descriptor]) -> Option<SymbolType> {
+    match kind {
+        17 => Some(SymbolType::Function),
+        9 | 18 | 26 | 45 | 66 | 67 | 68 | 69 | 70 | 71 | 76 | 80 => Some(SymbolType::Method),
+        7 | 33 | 75 => Some(SymbolType::Class),
+        49 => Some(SymbolType::Struct),
+        11 => Some(SymbolType::Enum),
+        21 | 42 => Some(SymbolType::Interface),
+        53 | 56 => Some(SymbolType::Trait),
+        29 | 30 | 35 | 36 => Some(SymbolType::Module),
+        15 | 41 | 79 | 81 => Some(SymbolType::Field),
+        0 => match descriptors.iter().rev().find(|d| d.name().is_some())? {
+            Descriptor::Method(_) => Some(
+                if descriptors.iter().any(|d| matches!(d, Descriptor::Type(_))) {
+                    SymbolType::Method
+                } else {
+                    SymbolType::Function
+                },
+            ),
+            Descriptor::Type(_) => Some(SymbolType::Class),
+            Descriptor::Namespace(_) => Some(SymbolType::Module),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// 0-based `(start_line, end_line)` of a SCIP range.
+fn line_span(range: &[i32]) -> Option<(u32, u32)> {
+    match range {
+        [start, _, end, _] => Some((*start as u32, *end as u32)),
+        [line, _, _] => Some((*line as u32, *line as u32)),
+        _ => None,
+    }
+}
+
+fn is_rust(doc: &proto::Document, path: &str) -> bool {
+    doc.language.eq_ignore_ascii_case("rust") || path.ends_with(".rs")
+}
+
+/// Symbols defined in a document, keyed by SCIP symbol.
+fn definitions(
+    doc: &proto::Document,
+    info: &HashMap<&str, &proto::SymbolInformation>,
+    path: &str,
+    project_id: &str,
+) -> Definitions {
+    let separator = if is_rust(doc, path) { "::" } else { "." };
+    let mut seen = HashSet::new();
+    let mut symbols = Vec::new();
+    for occurrence in &doc.occurrences {
+        if occurrence.symbol_roles & proto::ROLE_DEFINITION == 0
+            || !seen.insert(occurrence.symbol.as_str())
+        {
+            continue;
+        }
+        let descriptors = descriptors(&occurrence.symbol);
+        let symbol_info = info.get(occurrence.symbol.as_str());
+        let Some(symbol_type) = symbol_type(symbol_info.map_or(0, |i| i.kind), &descriptors) else {
+            continue;
+        };
+        let Some((line, _)) = line_span(&occurrence.range) else {
+            continue;
+        };
+        let (start, end) = line_span(&occurrence.enclosing_range).unwrap_or((line, line));
+        let names: Vec<&str> = descriptors.iter().filter_map(Descriptor::name).collect();
+        let Some(name) = symbol_info
+            .map(|i| i.display_name.as_str())
+            .filter(|n| !n.is_empty())
+            .or(names.last().copied())
+        else {
+            continue;
+        };
+
+        let mut symbol = CodeSymbol::new(
+            name.to_string(),
+            symbol_type,
+            path.to_string(),
+            start + 1,
+            end + 1,
+            project_id.to_string(),
+        );
+        symbol.qualified_name = Some(names.join(separator));
+        if names.len() > 1 {
+            symbol.parent = Some(names[..names.len() - 1].join(separator));
+        }
+        if let Some(symbol_info) = symbol_info {
+            if let Some(signature) = symbol_info
+                .signature_documentation
+                .as_ref()
+                .map(|d| d.text.trim())
+                .filter(|s| !s.is_empty())
+            {
+                symbol.signature = Some(signature.to_string());
+            }
+            // Indexers often repeat the signature as a fenced first entry
+            let doc_text = symbol_info
+                .documentation
+                .iter()
+                .map(|d| d.trim())
+                .filter(|d| !d.is_empty() && !d.starts_with("```"))
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            if !doc_text.is_empty() {
+                symbol.doc = Some(doc_text);
+            }
+        }
+        symbols.push((occurrence.symbol.clone(), symbol));
+    }
+    symbols
+}
+
+/// Give SCIP definitions that match a stored tree-sitter symbol (same name,
+/// defined within its lines) that symbol's record, span and embedding, so
+/// edges into it survive. Returns the number matched.
+fn adopt_existing(symbols: &mut [(String, CodeSymbol)], old: &[CodeSymbol]) -> usize {
+    let mut taken = HashSet::new();
+    let mut matched = 0;
+    for (_, symbol) in symbols.iter_mut() {
+        let Some(existing) = old
+            .iter()
+            .filter(|o| {
+                o.name == symbol.name
+                    && o.start_line <= symbol.start_line
+                    && symbol.start_line <= o.end_line
+                    && !taken.contains(&o.unique_key())
+            })
+            .min_by_key(|o| o.end_line - o.start_line)
+        else {
+            continue;
+        };
+        taken.insert(existing.unique_key());
+        symbol.id = Some(Thing::new("code_symbols", existing.unique_key().as_str()));
+        // Without an enclosing range SCIP only knows the name's line
+        if symbol.start_line == symbol.end_line {
+            symbol.start_line = existing.start_line;
+            symbol.end_line = existing.end_line;
+        }
+        symbol.embedding = existing.embedding.clone();
+        if symbol.signature.is_none() {
+            symbol.signature = existing.signature.clone();
+        }
+        if symbol.doc.is_none() {
+            symbol.doc = existing.doc.clone();
+        }
+        matched += 1;
+    }
+    matched
+}
+
+/// What a document's references produce.
+#[derive(Default)]
+struct References {
+    relations: Vec<SymbolRelation>,
+    unresolved: Vec<UnresolvedReference>,
+    occurrences: Vec<ReferenceOccurrence>,
+}
+
+/// Everything the index defines, for resolving references across documents.
+struct Scope<'a> {
+    defined: &'a HashMap<String, CodeSymbol>,
+    /// `<manager> <package> <version>` of the indexed packages
+    packages: &'a HashSet<String>,
+    info: &'a HashMap<&'a str, &'a proto::SymbolInformation>,
+}
+
+/// Edges out of a document's definitions, plus references to symbols of
+/// other packages (libraries). Unmodelled symbols of indexed packages, such
+/// as modules named by a path, are dropped.
+fn references(
+    doc: &proto::Document,
+    symbols: &[(String, CodeSymbol)],
+    scope: &Scope,
+    path: &str,
+    project_id: &str,
+    out: &mut References,
+) {
+    let Scope {
+        defined,
+        packages,
+        info,
+    } = scope;
+    let mut seen = HashSet::new();
+    let relations = &mut out.relations;
+    let mut edge = |from: &CodeSymbol, to: &CodeSymbol, relation_type, line| {
+        let (from, to) = (from.unique_key(), to.unique_key());
+        if from != to && seen.insert((from.clone(), to.clone(), relation_type)) {
+            relations.push(
+                SymbolRelation::new(
+                    Thing::new("code_symbols", from.as_str()),
+                    Thing::new("code_symbols", to.as_str()),
+                    relation_type,
+                    path.to_string(),
+                    line,
+                    project_id.to_string(),
+                )
+                .with_source(RelationSource::Scip),
+            );
+        }
+    };
+
+    for occurrence in &doc.occurrences {
+        if occurrence.symbol_roles & proto::ROLE_DEFINITION != 0
+            || occurrence.symbol.starts_with("local ")
+        {
+            continue;
+        }
+        let Some((line, _)) = line_span(&occurrence.range) else {
+            continue;
+        };
+        let line = line + 1;
+        let column = occurrence.range.get(1).copied().unwrap_or(0) as u32;
+        // Innermost definition around the reference; top-level uses get no edge
+        let from = symbols
+            .iter()
+            .filter(|(_, s)| s.start_line <= line && line <= s.end_line)
+            .min_by_key(|(_, s)| s.end_line - s.start_line)
+            .map(|(_, s)| s);
+        let from_symbol = from.map_or("global", |s| s.name.as_str()).to_string();
+        let target = defined.get(&occurrence.symbol);
+        let external = target.is_none() && !packages.contains(&package(&occurrence.symbol));
+        if target.is_none() && !external {
+            continue;
+        }
+        let Some(name) = target.map(|t| t.name.clone()).or_else(|| {
+            descriptors(&occurrence.symbol)
+                .iter()
+                .rev()
+                .find_map(|d| d.name().map(str::to_string))
+        }) else {
+            continue;
+        };
+        let relation_type = if occurrence.symbol_roles & proto::ROLE_IMPORT != 0 {
+            CodeRelationType::Imports
+        } else {
+            let target_type = target.map(|t| t.symbol_type).or_else(|| {
+                let kind = info.get(occurrence.symbol.as_str()).map_or(0, |i| i.kind);
+                symbol_type(kind, &descriptors(&occurrence.symbol))
+            });
+            match target_type {
+                Some(SymbolType::Function | SymbolType::Method) => CodeRelationType::Calls,
+                _ => CodeRelationType::References,
+            }
+        };
+        let module = if external {
+            symbol_parts(&occurrence.symbol)
+                .get(2)
+                .filter(|p| !p.is_empty() && *p != ".")
+                .cloned()
+        } else {
+            None
+        };
+
+        out.occurrences.push(ReferenceOccurrence {
+            id: None,
+            project_id: project_id.to_string(),
+            file_path: path.to_string(),
+            name: name.clone(),
+            relation_type,
+            line,
+            column,
+            from_symbol: from_symbol.clone(),
+            to_symbol: target.map(|t| format!("code_symbols:{}", t.unique_key())),
+            reason: external.then(|| "external".to_string()),
+            module: module.clone(),
+            source: RelationSource::Scip,
+        });
+        match (from, target) {
+            (Some(from), Some(to)) => edge(from, to, relation_type, line),
+            (_, None) => out.unresolved.push(UnresolvedReference {
+                id: None,
+                project_id: project_id.to_string(),
+                file_path: path.to_string(),
+                from_symbol,
+                name,
+                relation_type,
+                line,
+                module,
+                reason: "external".to_string(),
+                created_at: Datetime::default(),
+            }),
+            (None, Some(_)) => {}
+        }
+    }
+
+    // `impl Trait for Type`, `class A extends B`
+    for (scip_symbol, symbol) in symbols {
+        let Some(symbol_info) = info.get(scip_symbol.as_str()) else {
+            continue;
+        };
+        for relationship in symbol_info
+            .relationships
+            .iter()
+            .filter(|r| r.is_implementation)
+        {
+            if let Some(to) = defined.get(&relationship.symbol) {
+                let relation_type = match to.symbol_type {
+                    SymbolType::Interface | SymbolType::Trait => CodeRelationType::Implements,
+                    _ => CodeRelationType::Extends,
+                };
+                edge(symbol, to, relation_type, symbol.start_line);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_descriptors() {
+        assert_eq!(
+            descriptors("rust-analyzer cargo demo 0.1.0 auth/Session#validate()."),
+            vec![
+                Descriptor::Namespace("auth".into()),
+                Descriptor::Type("Session".into()),
+                Descriptor::Method("validate".into()),
+            ]
+        );
+        assert_eq!(
+            descriptors("scip-typescript npm pkg 1.0.0 src/`a b.ts`/Foo#bar(+1).[T]"),
+            vec![
+                Descriptor::Namespace("src".into()),
+                Descriptor::Namespace("a b.ts".into()),
+                Descriptor::Type("Foo".into()),
+                Descriptor::Method("bar".into()),
+                Descriptor::Other,
+            ]
+        );
+        assert!(descriptors("local 12").is_empty());
+        assert_eq!(
+            symbol_parts("scip-java maven . . com/Foo#")[4],
+            "com/Foo#".to_string()
+        );
+
+        let method = descriptors("x y z 1 Foo#bar().");
+        assert_eq!(symbol_type(0, &method), Some(SymbolType::Method));
+        assert_eq!(
+            symbol_type(0, &descriptors("x y z 1 bar().")),
+            Some(SymbolType::Function)
+        );
+        assert_eq!(symbol_type(53, &method), Some(SymbolType::Trait));
+        assert_eq!(symbol_type(61, &method), None);
+    }
+
+    #[tokio::test]
+    async fn test_import_scip_fixture() {
+        let ctx = crate::test_utils::TestContext::new().await;
+        let root = ctx._temp_dir.path().join("demo");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("auth.rs"), AUTH_RS).unwrap();
+        std::fs::write(root.join("main.rs"), MAIN_RS).unwrap();
+        crate::codebase::index_project(ctx.state.clone(), &root)
+            .await
+            .unwrap();
+
+        let storage = &ctx.state.storage;
+        let tree_sitter_login = storage
+            .get_symbols_by_path("demo", "auth.rs")
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|s| s.name == "login")
+            .unwrap();
+
+        let fixture =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("src/codebase/testdata/index.scip");
+        let stats = import_scip(ctx.state.clone(), "demo", &fixture)
+            .await
+            .unwrap();
+        assert_eq!(stats.tool.as_deref(), Some("rust-analyzer 0.3.0"));
+        assert_eq!(stats.files, 2);
+        assert_eq!(stats.unresolved, 2);
+
+        let auth = storage
+            .get_symbols_by_path("demo", "auth.rs")
+            .await
+            .unwrap();
+        let login = auth.iter().find(|s| s.name == "login").unwrap();
+        // The tree-sitter record was adopted, not duplicated
+        assert_eq!(login.unique_key(), tree_sitter_login.unique_key());
+        assert_eq!(auth.iter().filter(|s| s.name == "login").count(), 1);
+        assert_eq!(
+            login.signature.as_deref(),
+            Some("pub fn login(user: &str) -> Session")
+        );
+        assert_eq!(login.doc.as_deref(), Some("Start a session for `user`."));
+
+        let uses = storage
+            .get_symbol_occurrences(&format!("code_symbols:{}", login.unique_key()), 10)
+            .await
+            .unwrap();
+        assert_eq!(uses.len(), 1);
+        assert_eq!(
+            (uses[0].file_path.as_str(), uses[0].line, uses[0].column),
+            ("main.rs", 4, 18)
+        );
+        assert_eq!(uses[0].source, RelationSource::Scip);
+
+        let edges = |name: &str, symbols: &[CodeSymbol]| {
+            let id = format!(
+                "code_symbols:{}",
+                symbols
+                    .iter()
+                    .find(|s| s.name == name)
+                    .unwrap()
+                    .unique_key()
+            );
+            async move {
+                let (_, relations) = storage
+                    .get_related_symbols(&id, 1, crate::types::Direction::Outgoing)
+                    .await
+                    .unwrap();
+                relations
+            }
+        };
+        let main = storage
+            .get_symbols_by_path("demo", "main.rs")
+            .await
+            .unwrap();
+        let helper = edges("helper", &main).await;
+        assert!(helper.iter().all(|r| r.source == RelationSource::Scip));
+        let types: HashSet<_> = helper.iter().map(|r| r.relation_type).collect();
+        assert_eq!(
+            types,
+            HashSet::from([CodeRelationType::Calls, CodeRelationType::References])
+        );
+
+        let session = edges("Session", &auth).await;
+        assert_eq!(session.len(), 1);
+        assert_eq!(session[0].relation_type, CodeRelationType::Implements);
+    }
+
+    const AUTH_RS: &str = "pub struct Session {
+    pub user: String,
+}
+
+impl Session {
+    pub fn validate(&self) -> bool {
+        !self.user.is_empty()
+    }
+}
+
+pub fn login(user: &str) -> Session {
+    let session = Session { user: user.to_string() };
+    session.validate();
+    session
+}
+
+pub trait Check {}
+
+impl Check for Session {}
+";
+
+    const MAIN_RS: &str = "mod auth;
+
+fn main() {
+    let s = auth::login(\"ada\");
+    helper(&s);
+}
+
+fn helper(s: &auth::Session) {
+    s.validate();
+}
+";
+}
@@ -0,0 +1,189 @@
+use std::path::Path;
+
+use crate::types::SkipReason;
+
+/// Files averaging longer lines than this are taken to be minified bundles.
+const MINIFIED_AVG_LINE: usize = 300;
+/// Below this size a file is never considered minified.
+const MINIFIED_MIN_SIZE: usize = 4096;
+/// Header lines searched for a generated-code marker.
+const GENERATED_HEADER_LINES: usize = 30;
+
+/// Contents of a candidate file, or why it is left out of the index.
+#[derive(Debug, PartialEq)]
+pub enum Source {
+    Text(String),
+    Skipped(SkipReason),
+}
+
+/// Read a file for indexing, transcoding it to UTF-8 and rejecting files
+/// over `max_size` bytes (0 = no limit), binaries and generated code.
+pub fn read_source(path: &Path, max_size: u64) -> std::io::Result<Source> {
+    if max_size > 0 && std::fs::metadata(path)?.len() > max_size {
+        return Ok(Source::Skipped(SkipReason::TooLarge));
+    }
+    let bytes = std::fs::read(path)?;
+    Ok(classify(&bytes))
+}
+
+/// Decode file contents and apply the content checks.
+pub fn classify(bytes: &[u8]) -> Source {
+    let Some(text) = decode(bytes) else {
+        return Source::Skipped(SkipReason::Binary);
+    };
+    if is_generated(&text) {
+        return Source::Skipped(SkipReason::Generated);
+    }
+    if is_minified(&text) {
+        return Source::Skipped(SkipReason::Minified);
+    }
+    Source::Text(text)
+}
+
+/// Decode source bytes: UTF-8 (with or without BOM), UTF-16 (BOM, or the
+/// zero-byte pattern of ASCII text), else Windows-1252. `None` for binaries.
+pub fn decode(bytes: &[u8]) -> Option<String> {
+    if let Some(rest) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        return Some(String::from_utf8_lossy(rest).into_owned());
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        return Some(decode_utf16(rest, u16::from_le_bytes));
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        return Some(decode_utf16(rest, u16::from_be_bytes));
+    }
+    if bytes.contains(&0) {
+        return match utf16_without_bom(bytes) {
+            Some(true) => Some(decode_utf16(bytes, u16::from_le_bytes)),
+            Some(false) => Some(decode_utf16(bytes, u16::from_be_bytes)),
+            None => None,
+        };
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Some(text.to_string()),
+        Err(_) => Some(bytes.iter().map(|&b| windows_1252(b)).collect()),
+    }
+}
+
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> String {
+    let units = bytes.chunks_exact(2).map(|pair| unit([pair[0], pair[1]]));
+    char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+/// `Some(true)` for little-endian, `Some(false)` for big-endian UTF-16 text
+/// without a BOM: mostly-ASCII content leaves every other byte zero.
+fn utf16_without_bom(bytes: &[u8]) -> Option<bool> {
+    if bytes.len() < 2 || !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    let pairs = bytes.len() / 2;
+    let zero_high = bytes.chunks_exact(2).filter(|p| p[1] == 0).count();
+    let zero_low = bytes.chunks_exact(2).filter(|p| p[0] == 0).count();
+    if zero_high * 10 >= pairs * 9 && zero_low == 0 {
+        Some(true)
+    } else if zero_low * 10 >= pairs * 9 && zero_high == 0 {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+fn windows_1252(byte: u8) -> char {
+    const HIGH: [char; 32] = [
+        '\u{20AC}', '\u{81}', '\u{201A}', '\u{192}', '\u{201E}', '\u{2026}', '\u{2020}',
+        '\u{2021}', '\u{2C6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8D}', '\u{17D}',
+        '\u{8F}', '\u{90}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}',
+        '\u{2014}', '\u{2DC}', '\u{2122}', '\u{161}', '\u{203A}', '\u{153}', '\u{9D}', '\u{17E}',
+        '\u{178}',
+    ];
+    match byte {
+        0x80..=0x9F => HIGH[(byte - 0x80) as usize],
+        _ => byte as char,
+    }
+}
+
+/// A generator's marker in a header comment: Go's `Code generated ... DO NOT
+/// EDIT.`, the `@generated` tag, or .NET's `<auto-generated>`.
+pub fn is_generated(text: &str) -> bool {
+    text.lines()
+        .take(GENERATED_HEADER_LINES)
+        .map(str::trim_start)
+        .filter(|line| {
+            ["//", "#", "/*", "*", "--", "<!--", ";"]
+                .iter()
+                .any(|prefix| line.starts_with(prefix))
+        })
+        .any(|line| {
+            (line.contains("Code generated") && line.contains("DO NOT EDIT"))
+                || line.contains("@generated")
+                || line.contains("<auto-generated")
+        })
+}
+
+/// Very long average line length, as in minified JavaScript and CSS.
+pub fn is_minified(text: &str) -> bool {
+    if text.len() < MINIFIED_MIN_SIZE {
+        return false;
+    }
+    let lines = text.lines().count().max(1);
+    text.len() / lines > MINIFIED_AVG_LINE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_encodings() {
+        assert_eq!(decode(b"fn main() {}").unwrap(), "fn main() {}");
+        assert_eq!(decode(b"\xEF\xBB\xBFfn a() {}").unwrap(), "fn a() {}");
+
+        let utf16le: Vec<u8> = [0xFF, 0xFE]
+            .into_iter()
+            .chain("def é(): pass".encode_utf16().flat_map(u16::to_le_bytes))
+            .collect();
+        assert_eq!(decode(&utf16le).unwrap(), "def é(): pass");
+        let utf16be: Vec<u8> = "class A {}"
+            .encode_utf16()
+            .flat_map(u16::to_be_bytes)
+            .collect();
+        assert_eq!(decode(&utf16be).unwrap(), "class A {}");
+
+        // Latin-1 / Windows-1252
+        assert_eq!(
+            decode(b"// caf\xE9 \x93quoted\x94 \x80").unwrap(),
+            "// café \u{201C}quoted\u{201D} €"
+        );
+
+        assert!(decode(b"\x7FELF\x02\x01\x00\x00\x00\xFF\x10").is_none());
+    }
+
+    #[test]
+    fn test_generated_and_minified() {
+        assert!(is_generated(
+            "// Code generated by protoc-gen-go. DO NOT EDIT.\npackage pb\n"
+        ));
+        assert!(is_generated(
+            "/**\n * @generated\n */\nexport const x = 1;\n"
+        ));
+        assert!(is_generated(
+            "// <auto-generated>\n//   by a tool\n// </auto-generated>\n"
+        ));
+        // Markers outside comments don't count
+        assert!(!is_generated(
+            "let marker = \"Code generated ... DO NOT EDIT\";\n"
+        ));
+
+        let bundle = format!("!function(){{{}}}();\n", "var a=1;".repeat(1000));
+        assert!(is_minified(&bundle));
+        assert_eq!(
+            classify(bundle.as_bytes()),
+            Source::Skipped(SkipReason::Minified)
+        );
+        let normal = "fn f() {\n    let x = 1;\n}\n".repeat(500);
+        assert!(!is_minified(&normal));
+        assert!(matches!(classify(normal.as_bytes()), Source::Text(_)));
+    }
+}
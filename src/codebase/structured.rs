@@ -0,0 +1,1340 @@
+//! Structure-aware outlines and symbols for config and schema files.
+//!
+//! YAML, TOML and JSON are split by top-level key or table. SQL is split by
+//! statement, Protobuf and GraphQL by top-level definition. The schema
+//! formats also yield symbols: SQL tables and columns, proto messages,
+//! enums, services and rpcs, GraphQL types and operations.
+
+use std::path::Path;
+
+use crate::types::symbol::{CodeReference, CodeRelationType, CodeSymbol, SymbolType};
+use crate::types::{ChunkType, Language};
+
+use super::scanner::detect_language;
+
+/// A chunkable region of a structured file (1-based, inclusive lines).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructuredSection {
+    pub name: Option<String>,
+    pub start_line: u32,
+    pub end_line: u32,
+    pub chunk_type: ChunkType,
+}
+
+/// Split a config or schema file into named sections.
+/// Returns an empty list when no structure is recognised.
+pub fn outline(content: &str, language: &Language) -> Vec<StructuredSection> {
+    let lines: Vec<&str> = content.lines().collect();
+    match language {
+        Language::Yaml => merge_single_lines(yaml_outline(&lines)),
+        Language::Toml => toml_outline(&lines),
+        Language::Json => merge_single_lines(json_outline(content, &lines)),
+        Language::Sql => sql_outline(content, &lines),
+        Language::Protobuf | Language::GraphQL => {
+            merge_single_lines(definition_outline(&lines, language))
+        }
+        _ => vec![],
+    }
+}
+
+/// Extract schema symbols and their reference edges.
+/// Config formats (YAML, TOML, JSON) only produce chunks, never symbols.
+pub fn parse_structured(
+    path: &Path,
+    content: &str,
+    project_id: &str,
+) -> (Vec<CodeSymbol>, Vec<CodeReference>) {
+    let language = detect_language(path);
+    let file_path = path.to_string_lossy().to_string();
+    let mut out = SymbolSink::new(file_path, project_id);
+
+    match language {
+        Language::Sql => parse_sql(content, &mut out),
+        Language::Protobuf | Language::GraphQL => {
+            let lines: Vec<&str> = content.lines().collect();
+            parse_definitions(&lines, &language, &mut out);
+        }
+        _ => {}
+    }
+
+    (out.symbols, out.references)
+}
+
+/// Table names referenced by a SQL statement (`FROM`, `JOIN`, `INTO`,
+/// `UPDATE`, `TABLE`, `REFERENCES`), schema prefixes removed.
+pub fn sql_table_references(sql: &str) -> Vec<String> {
+    let words = sql_words(sql);
+    let mut tables: Vec<String> = Vec::new();
+
+    for (i, (_, word)) in words.iter().enumerate() {
+        let keyword = word.to_ascii_uppercase();
+        if !matches!(
+            keyword.as_str(),
+            "FROM" | "JOIN" | "INTO" | "UPDATE" | "TABLE" | "REFERENCES"
+        ) {
+            continue;
+        }
+
+        let mut j = i + 1;
+        while let Some((_, next)) = words.get(j) {
+            let upper = next.to_ascii_uppercase();
+            if matches!(upper.as_str(), "IF" | "NOT" | "EXISTS" | "ONLY" | "LATERAL") {
+                j += 1;
+            } else {
+                break;
+            }
+        }
+
+        if let Some((_, name)) = words.get(j) {
+            let ident = sql_ident(name);
+            if is_identifier(&ident) && !is_sql_keyword(&ident) && !tables.contains(&ident) {
+                tables.push(ident);
+            }
+        }
+    }
+
+    tables
+}
+
+/// Heuristic check that a string literal holds a SQL statement.
+pub fn looks_like_sql(text: &str) -> bool {
+    let words = sql_words(text);
+    let Some((_, first)) = words.first() else {
+        return false;
+    };
+    let upper = first.to_ascii_uppercase();
+    let is_statement = matches!(
+        upper.as_str(),
+        "SELECT" | "INSERT" | "UPDATE" | "DELETE" | "WITH" | "CREATE" | "ALTER" | "DROP"
+    );
+    // Lower-case keywords alone read like prose; require some SQL punctuation too
+    let sql_shaped = *first == upper || text.contains(['*', '=', '?', ';', '$']);
+    is_statement
+        && sql_shaped
+        && words.iter().any(|(_, w)| {
+            matches!(
+                w.to_ascii_uppercase().as_str(),
+                "FROM" | "INTO" | "JOIN" | "TABLE" | "SET"
+            )
+        })
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Shared helpers
+// ─────────────────────────────────────────────────────────────────────────────
+
+struct SymbolSink {
+    file_path: String,
+    project_id: String,
+    symbols: Vec<CodeSymbol>,
+    references: Vec<CodeReference>,
+}
+
+impl SymbolSink {
+    fn new(file_path: String, project_id: &str) -> Self {
+        Self {
+            file_path,
+            project_id: project_id.to_string(),
+            symbols: Vec::new(),
+            references: Vec::new(),
+        }
+    }
+
+    fn symbol(
+        &mut self,
+        name: &str,
+        symbol_type: SymbolType,
+        start_line: u32,
+        end_line: u32,
+        signature: String,
+    ) {
+        self.symbols.push(
+            CodeSymbol::new(
+                name.to_string(),
+                symbol_type,
+                self.file_path.clone(),
+                start_line,
+                end_line,
+                self.project_id.clone(),
+            )
+            .with_signature(signature),
+        );
+    }
+
+    fn reference(
+        &mut self,
+        from: (&str, u32),
+        to: &str,
+        relation_type: CodeRelationType,
+        line: u32,
+        column: u32,
+    ) {
+        self.references.push(
+            CodeReference::builder()
+                .name(to.to_string())
+                .from_symbol(from.0.to_string())
+                .from_symbol_line(from.1)
+                .to_symbol(to.to_string())
+                .relation_type(relation_type)
+                .file_path(self.file_path.clone())
+                .line(line)
+                .column(column)
+                .build(),
+        );
+    }
+}
+
+/// Byte offset → 0-based line lookup.
+struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(content: &str) -> Self {
+        let mut starts = vec![0];
+        starts.extend(content.match_indices('\n').map(|(i, _)| i + 1));
+        Self { starts }
+    }
+
+    fn line_of(&self, offset: usize) -> usize {
+        self.starts
+            .partition_point(|&s| s <= offset)
+            .saturating_sub(1)
+    }
+
+    fn column_of(&self, offset: usize) -> usize {
+        offset - self.starts[self.line_of(offset)]
+    }
+}
+
+/// Build a section from a 0-based `[start, end)` line range, dropping
+/// trailing blank lines. Returns `None` for an all-blank range.
+fn section(
+    lines: &[&str],
+    name: Option<String>,
+    start: usize,
+    end: usize,
+    chunk_type: ChunkType,
+) -> Option<StructuredSection> {
+    let mut end = end.min(lines.len());
+    while end > start && lines[end - 1].trim().is_empty() {
+        end -= 1;
+    }
+    if end <= start {
+        return None;
+    }
+    Some(StructuredSection {
+        name,
+        start_line: start as u32 + 1,
+        end_line: end as u32,
+        chunk_type,
+    })
+}
+
+/// Fold one-line sections (`kind: Deployment`, `syntax = "proto3";`) into
+/// the section that follows them, as long as both share the same label prefix.
+fn merge_single_lines(sections: Vec<StructuredSection>) -> Vec<StructuredSection> {
+    fn label(name: &Option<String>) -> Option<&str> {
+        name.as_deref()
+            .and_then(|n| n.rsplit_once(" > "))
+            .map(|(l, _)| l)
+    }
+
+    let mut out: Vec<StructuredSection> = Vec::with_capacity(sections.len());
+    let mut pending: Option<StructuredSection> = None;
+
+    for mut section in sections {
+        let single_line = section.start_line == section.end_line;
+        if let Some(carried) = pending.take() {
+            if label(&carried.name) == label(&section.name) {
+                section.start_line = carried.start_line;
+            } else {
+                out.push(carried);
+            }
+        }
+        if single_line {
+            pending = Some(section);
+        } else {
+            out.push(section);
+        }
+    }
+    out.extend(pending);
+
+    out
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// YAML / TOML / JSON
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn yaml_outline(lines: &[&str]) -> Vec<StructuredSection> {
+    // Multi-document streams (Kubernetes manifests) are split on `---` first
+    let mut docs: Vec<(usize, usize)> = Vec::new();
+    let mut start = 0;
+    for (idx, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_end();
+        if trimmed == "---" || trimmed.starts_with("--- ") || trimmed == "..." {
+            if idx > start {
+                docs.push((start, idx));
+            }
+            start = idx + 1;
+        }
+    }
+    if lines.len() > start {
+        docs.push((start, lines.len()));
+    }
+    let multi = docs.len() > 1;
+
+    let mut sections = Vec::new();
+    for (doc_idx, &(doc_start, doc_end)) in docs.iter().enumerate() {
+        let doc = &lines[doc_start..doc_end];
+        let label =
+            yaml_document_label(doc).or_else(|| multi.then(|| format!("document {}", doc_idx + 1)));
+        let keys: Vec<(usize, String)> = (doc_start..doc_end)
+            .filter_map(|i| yaml_top_level_key(lines[i]).map(|k| (i, k)))
+            .collect();
+
+        if keys.is_empty() {
+            sections.extend(section(
+                lines,
+                label,
+                doc_start,
+                doc_end,
+                ChunkType::Section,
+            ));
+            continue;
+        }
+
+        for (k, (line_idx, key)) in keys.iter().enumerate() {
+            let end = keys.get(k + 1).map(|(n, _)| *n).unwrap_or(doc_end);
+            let name = match &label {
+                Some(l) => format!("{} > {}", l, key),
+                None => key.clone(),
+            };
+            sections.extend(section(
+                lines,
+                Some(name),
+                *line_idx,
+                end,
+                ChunkType::Section,
+            ));
+        }
+    }
+
+    sections
+}
+
+fn yaml_top_level_key(line: &str) -> Option<String> {
+    let first = line.chars().next()?;
+    if first.is_whitespace() || matches!(first, '#' | '-' | '{' | '[' | '}' | ']') {
+        return None;
+    }
+
+    let (key, rest) = if first == '"' || first == '\'' {
+        let close = line[1..].find(first)? + 1;
+        (&line[1..close], &line[close + 1..])
+    } else {
+        let colon = line.find(':')?;
+        (&line[..colon], &line[colon..])
+    };
+    let after = rest.strip_prefix(':')?;
+    if !after.is_empty() && !after.starts_with([' ', '\t']) {
+        return None;
+    }
+
+    let key = key.trim();
+    (!key.is_empty()).then(|| key.to_string())
+}
+
+/// `Deployment/web` for Kubernetes-style documents with `kind` and `metadata.name`.
+fn yaml_document_label(doc: &[&str]) -> Option<String> {
+    let scalar = |line: &str, key: &str| {
+        line.strip_prefix(key)
+            .map(|v| v.trim().trim_matches(['"', '\'']).to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    let kind = doc.iter().find_map(|l| scalar(l, "kind:"))?;
+    let name = doc
+        .iter()
+        .position(|l| l.trim_end() == "metadata:")
+        .and_then(|meta| {
+            doc[meta + 1..]
+                .iter()
+                .take_while(|l| l.is_empty() || l.starts_with([' ', '\t']))
+                .find_map(|l| scalar(l.trim_start(), "name:"))
+        });
+
+    Some(match name {
+        Some(name) => format!("{}/{}", kind, name),
+        None => kind,
+    })
+}
+
+fn toml_outline(lines: &[&str]) -> Vec<StructuredSection> {
+    let headers: Vec<(usize, String)> = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(i, l)| toml_header(l).map(|h| (i, h)))
+        .collect();
+
+    let mut sections = Vec::new();
+    let first = headers.first().map(|(i, _)| *i).unwrap_or(lines.len());
+    sections.extend(section(lines, None, 0, first, ChunkType::Section));
+
+    for (k, (idx, name)) in headers.iter().enumerate() {
+        let end = headers.get(k + 1).map(|(n, _)| *n).unwrap_or(lines.len());
+        sections.extend(section(
+            lines,
+            Some(name.clone()),
+            *idx,
+            end,
+            ChunkType::Section,
+        ));
+    }
+
+    sections
+}
+
+fn toml_header(line: &str) -> Option<String> {
+    let trimmed = line.trim();
+    let inner = trimmed.strip_prefix('[')?;
+    let inner = inner.strip_prefix('[').unwrap_or(inner);
+    let close = inner.find(']')?;
+    let name = inner[..close].trim();
+
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '"' | '\'' | ' '));
+    valid.then(|| name.to_string())
+}
+
+fn json_outline(content: &str, lines: &[&str]) -> Vec<StructuredSection> {
+    let index = LineIndex::new(content);
+    let bytes = content.as_bytes();
+
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escape = false;
+    let mut string_start = 0;
+    let mut top_is_object = None;
+    let mut close_line = lines.len();
+    let mut keys: Vec<(usize, String)> = Vec::new();
+
+    for (i, &b) in bytes.iter().enumerate() {
+        if in_string {
+            if escape {
+                escape = false;
+            } else if b == b'\\' {
+                escape = true;
+            } else if b == b'"' {
+                in_string = false;
+                let is_key = depth == 1
+                    && top_is_object == Some(true)
+                    && content[i + 1..].trim_start().starts_with(':');
+                if is_key {
+                    let line = index.line_of(string_start);
+                    if keys.last().is_none_or(|(l, _)| *l != line) {
+                        keys.push((line, content[string_start + 1..i].to_string()));
+                    }
+                }
+            }
+            continue;
+        }
+
+        match b {
+            b'"' => {
+                in_string = true;
+                string_start = i;
+            }
+            b'{' | b'[' => {
+                if depth == 0 && top_is_object.is_none() {
+                    top_is_object = Some(b == b'{');
+                }
+                depth += 1;
+            }
+            b'}' | b']' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    close_line = index.line_of(i);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut sections = Vec::new();
+    for (k, (line, key)) in keys.iter().enumerate() {
+        let end = keys
+            .get(k + 1)
+            .map(|(n, _)| *n)
+            .unwrap_or(close_line)
+            .max(line + 1);
+        sections.extend(section(
+            lines,
+            Some(key.clone()),
+            *line,
+            end,
+            ChunkType::Section,
+        ));
+    }
+
+    sections
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// SQL
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// `CREATE TABLE users` style header of a DDL statement.
+struct SqlObject {
+    verb: String,
+    kind: String,
+    name: String,
+    /// Byte offset of the object name within the statement.
+    name_offset: usize,
+}
+
+impl SqlObject {
+    fn display(&self) -> String {
+        format!("{} {} {}", self.verb, self.kind, self.name)
+    }
+}
+
+fn sql_outline(content: &str, lines: &[&str]) -> Vec<StructuredSection> {
+    let index = LineIndex::new(content);
+    let mut sections: Vec<StructuredSection> = Vec::new();
+    let mut previous_unnamed = false;
+
+    for (start, end) in sql_statements(content) {
+        let object = sql_object(&content[start..end]);
+        let start_line = index.line_of(start);
+        let end_line = index.line_of(end.saturating_sub(1)) + 1;
+
+        match object {
+            Some(obj) => {
+                let chunk_type = match obj.kind.as_str() {
+                    "TABLE" | "VIEW" | "TYPE" => ChunkType::Class,
+                    "FUNCTION" | "PROCEDURE" => ChunkType::Function,
+                    _ => ChunkType::Other,
+                };
+                sections.extend(section(
+                    lines,
+                    Some(obj.display()),
+                    start_line,
+                    end_line,
+                    chunk_type,
+                ));
+                previous_unnamed = false;
+            }
+            None => {
+                // Consecutive DML statements (seed data, queries) share one section
+                match sections.last_mut() {
+                    Some(last) if previous_unnamed => last.end_line = end_line as u32,
+                    _ => sections.extend(section(
+                        lines,
+                        None,
+                        start_line,
+                        end_line,
+                        ChunkType::Other,
+                    )),
+                }
+                previous_unnamed = true;
+            }
+        }
+    }
+
+    sections
+}
+
+fn parse_sql(content: &str, out: &mut SymbolSink) {
+    let index = LineIndex::new(content);
+
+    for (start, end) in sql_statements(content) {
+        let statement = &content[start..end];
+        let Some(obj) = sql_object(statement) else {
+            continue;
+        };
+        if obj.verb != "CREATE" {
+            continue;
+        }
+
+        let start_line = index.line_of(start) as u32 + 1;
+        let end_line = index.line_of(end.saturating_sub(1)) as u32 + 1;
+        let name_pos = start + obj.name_offset;
+        let from = (obj.name.as_str(), start_line);
+
+        match obj.kind.as_str() {
+            "TABLE" => {
+                out.symbol(
+                    &obj.name,
+                    SymbolType::Table,
+                    start_line,
+                    end_line,
+                    obj.display(),
+                );
+                for (item_offset, item) in sql_table_items(statement, obj.name_offset) {
+                    let item_pos = start + item_offset;
+                    let line = index.line_of(item_pos) as u32 + 1;
+                    let column = index.column_of(item_pos) as u32;
+
+                    let first = sql_words(item)
+                        .first()
+                        .map(|(_, w)| w.to_ascii_uppercase())
+                        .unwrap_or_default();
+                    let is_constraint = matches!(
+                        first.as_str(),
+                        "PRIMARY"
+                            | "FOREIGN"
+                            | "CONSTRAINT"
+                            | "UNIQUE"
+                            | "CHECK"
+                            | "INDEX"
+                            | "KEY"
+                            | "EXCLUDE"
+                            | "LIKE"
+                            | "FULLTEXT"
+                    );
+                    if !is_constraint {
+                        if let Some((_, column_name)) = sql_words(item).first() {
+                            let column_name = sql_ident(column_name);
+                            let signature: String = item
+                                .split_whitespace()
+                                .collect::<Vec<_>>()
+                                .join(" ")
+                                .chars()
+                                .take(200)
+                                .collect();
+                            out.symbol(&column_name, SymbolType::Field, line, line, signature);
+                        }
+                    }
+
+                    // Foreign keys (column-level or table constraint)
+                    let words = sql_words(item);
+                    for (i, (_, w)) in words.iter().enumerate() {
+                        if w.eq_ignore_ascii_case("REFERENCES") {
+                            if let Some((_, target)) = words.get(i + 1) {
+                                let target = sql_ident(target);
+                                out.reference(
+                                    from,
+                                    &target,
+                                    CodeRelationType::References,
+                                    line,
+                                    column,
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+            "VIEW" | "FUNCTION" | "PROCEDURE" | "TYPE" => {
+                let symbol_type = match obj.kind.as_str() {
+                    "VIEW" => SymbolType::Table,
+                    "FUNCTION" | "PROCEDURE" => SymbolType::Function,
+                    _ if statement.to_ascii_uppercase().contains("AS ENUM") => SymbolType::Enum,
+                    _ => SymbolType::Struct,
+                };
+                out.symbol(&obj.name, symbol_type, start_line, end_line, obj.display());
+
+                for table in sql_table_references(&statement[obj.name_offset..]) {
+                    if table != obj.name {
+                        out.reference(
+                            from,
+                            &table,
+                            CodeRelationType::References,
+                            index.line_of(name_pos) as u32 + 1,
+                            index.column_of(name_pos) as u32,
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Split SQL into statements, respecting quotes, comments and dollar quoting.
+/// Returns byte ranges starting at the first significant character.
+fn sql_statements(content: &str) -> Vec<(usize, usize)> {
+    let b = content.as_bytes();
+    let mut statements = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut i = 0;
+
+    while i < b.len() {
+        let c = b[i];
+        if c == b'-' && b.get(i + 1) == Some(&b'-') {
+            while i < b.len() && b[i] != b'\n' {
+                i += 1;
+            }
+            continue;
+        }
+        if c == b'/' && b.get(i + 1) == Some(&b'*') {
+            i += 2;
+            while i + 1 < b.len() && !(b[i] == b'*' && b[i + 1] == b'/') {
+                i += 1;
+            }
+            i += 2;
+            continue;
+        }
+        if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        start.get_or_insert(i);
+        match c {
+            b'\'' | b'"' | b'`' => {
+                i += 1;
+                while i < b.len() && b[i] != c {
+                    i += 1;
+                }
+            }
+            b'$' => {
+                if let Some(tag_len) = dollar_tag(&b[i..]) {
+                    let tag = &content[i..i + tag_len];
+                    if let Some(pos) = content[i + tag_len..].find(tag) {
+                        i += tag_len + pos + tag_len;
+                        continue;
+                    }
+                }
+            }
+            b';' => {
+                if let Some(s) = start.take() {
+                    statements.push((s, i + 1));
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    if let Some(s) = start {
+        statements.push((s, b.len()));
+    }
+
+    statements
+}
+
+/// Length of a `$tag$` dollar-quote opener at the start of `b`.
+fn dollar_tag(b: &[u8]) -> Option<usize> {
+    let mut j = 1;
+    while j < b.len() && (b[j].is_ascii_alphanumeric() || b[j] == b'_') {
+        j += 1;
+    }
+    (j < b.len() && b[j] == b'$').then_some(j + 1)
+}
+
+fn sql_object(statement: &str) -> Option<SqlObject> {
+    let words = sql_words(statement);
+    let mut it = words.iter();
+
+    let (_, verb) = it.next()?;
+    let verb = verb.to_ascii_uppercase();
+    if !matches!(verb.as_str(), "CREATE" | "ALTER" | "DROP") {
+        return None;
+    }
+
+    let mut kind = None;
+    for (_, word) in it.by_ref() {
+        let upper = word.to_ascii_uppercase();
+        match upper.as_str() {
+            "OR" | "REPLACE" | "TEMP" | "TEMPORARY" | "UNIQUE" | "MATERIALIZED" | "UNLOGGED"
+            | "GLOBAL" | "LOCAL" | "VIRTUAL" | "RECURSIVE" => continue,
+            "TABLE" | "VIEW" | "INDEX" | "FUNCTION" | "PROCEDURE" | "TYPE" | "TRIGGER"
+            | "SEQUENCE" | "SCHEMA" => {
+                kind = Some(upper);
+                break;
+            }
+            _ => return None,
+        }
+    }
+    let kind = kind?;
+
+    for (offset, word) in it {
+        let upper = word.to_ascii_uppercase();
+        if matches!(upper.as_str(), "IF" | "NOT" | "EXISTS" | "CONCURRENTLY") {
+            continue;
+        }
+        let name = sql_ident(word);
+        if name.is_empty() {
+            return None;
+        }
+        return Some(SqlObject {
+            verb,
+            kind,
+            name,
+            name_offset: *offset,
+        });
+    }
+
+    None
+}
+
+/// Top-level comma-separated items of the `( ... )` column list following
+/// the table name, as `(offset within statement, text)` pairs.
+fn sql_table_items(statement: &str, name_offset: usize) -> Vec<(usize, &str)> {
+    let Some(open) = statement[name_offset..].find('(').map(|p| p + name_offset) else {
+        return vec![];
+    };
+
+    let bytes = statement.as_bytes();
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut item_start = open + 1;
+    let mut quote: Option<u8> = None;
+
+    for (i, &b) in bytes.iter().enumerate().skip(open + 1) {
+        if let Some(q) = quote {
+            if b == q {
+                quote = None;
+            }
+            continue;
+        }
+        match b {
+            b'\'' | b'"' | b'`' => quote = Some(b),
+            b'(' => depth += 1,
+            b')' if depth == 0 => {
+                items.push((item_start, &statement[item_start..i]));
+                break;
+            }
+            b')' => depth -= 1,
+            b',' if depth == 0 => {
+                items.push((item_start, &statement[item_start..i]));
+                item_start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    items
+        .into_iter()
+        .filter_map(|(offset, text)| {
+            let trimmed = text.trim_start();
+            let skipped = text.len() - trimmed.len();
+            let trimmed = trimmed.trim_end();
+            (!trimmed.is_empty()).then_some((offset + skipped, trimmed))
+        })
+        .collect()
+}
+
+/// Word tokens with their byte offsets. Quoted identifiers and dotted
+/// names (`"public"."users"`) stay in one token.
+fn sql_words(text: &str) -> Vec<(usize, &str)> {
+    let is_word = |c: char| c.is_alphanumeric() || matches!(c, '_' | '.' | '"' | '`' | '$');
+    let mut words = Vec::new();
+    let mut start: Option<usize> = None;
+
+    for (i, c) in text.char_indices() {
+        match (is_word(c), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                words.push((s, &text[s..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        words.push((s, &text[s..]));
+    }
+
+    words
+}
+
+/// Strip quoting and schema qualification: `"public"."Users"` → `Users`.
+fn sql_ident(word: &str) -> String {
+    word.rsplit('.')
+        .next()
+        .unwrap_or(word)
+        .trim_matches(['"', '`', '[', ']'])
+        .to_string()
+}
+
+fn is_sql_keyword(word: &str) -> bool {
+    matches!(
+        word.to_ascii_uppercase().as_str(),
+        "SELECT"
+            | "WHERE"
+            | "SET"
+            | "VALUES"
+            | "AS"
+            | "ON"
+            | "USING"
+            | "WITH"
+            | "TABLE"
+            | "DEFAULT"
+    )
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Protobuf / GraphQL
+// ─────────────────────────────────────────────────────────────────────────────
+
+struct Definition {
+    keyword: String,
+    name: String,
+    header: String,
+    /// 0-based first and last line.
+    start: usize,
+    end: usize,
+    depth: usize,
+}
+
+fn strip_line_comment<'a>(line: &'a str, language: &Language) -> &'a str {
+    let marker = if *language == Language::GraphQL {
+        "#"
+    } else {
+        "//"
+    };
+    line.find(marker).map(|i| &line[..i]).unwrap_or(line)
+}
+
+fn definition_header(trimmed: &str, language: &Language) -> Option<(String, String)> {
+    let keywords: &[&str] = match language {
+        Language::Protobuf => &["message", "enum", "service", "rpc"],
+        _ => &[
+            "type",
+            "interface",
+            "input",
+            "enum",
+            "union",
+            "scalar",
+            "query",
+            "mutation",
+            "subscription",
+            "fragment",
+        ],
+    };
+
+    let rest = trimmed.strip_prefix("extend ").unwrap_or(trimmed);
+    let keyword = keywords.iter().find(|k| {
+        rest.strip_prefix(**k)
+            .is_some_and(|r| r.starts_with([' ', '\t']))
+    })?;
+    let name: String = rest[keyword.len()..]
+        .trim_start()
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .collect();
+
+    (!name.is_empty()).then(|| (keyword.to_string(), name))
+}
+
+fn definitions(lines: &[&str], language: &Language) -> Vec<Definition> {
+    let mut defs: Vec<Definition> = Vec::new();
+    // (definition index, depth at which it was opened)
+    let mut open: Vec<(usize, usize)> = Vec::new();
+    let mut pending: Option<usize> = None;
+    let mut depth = 0usize;
+
+    for (idx, raw) in lines.iter().enumerate() {
+        let line = strip_line_comment(raw, language);
+        let trimmed = line.trim();
+
+        if let Some((keyword, name)) = definition_header(trimmed, language) {
+            let header = trimmed.split('{').next().unwrap_or(trimmed).trim();
+            defs.push(Definition {
+                keyword: keyword.clone(),
+                name,
+                header: header.trim_end_matches(';').trim().to_string(),
+                start: idx,
+                end: idx,
+                depth,
+            });
+            let expects_body = !matches!(keyword.as_str(), "rpc" | "scalar" | "union");
+            if !line.contains('{') && expects_body {
+                pending = Some(defs.len() - 1);
+            } else if line.contains('{') {
+                open.push((defs.len() - 1, depth));
+            }
+        }
+
+        for ch in line.chars() {
+            match ch {
+                '{' => {
+                    if let Some(p) = pending.take() {
+                        open.push((p, depth));
+                    }
+                    depth += 1;
+                }
+                '}' => {
+                    depth = depth.saturating_sub(1);
+                    while let Some(&(def_idx, open_depth)) = open.last() {
+                        if depth > open_depth {
+                            break;
+                        }
+                        defs[def_idx].end = idx;
+                        open.pop();
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Unterminated blocks run to the end of the file
+    for (def_idx, _) in open {
+        defs[def_idx].end = lines.len().saturating_sub(1);
+    }
+
+    defs
+}
+
+fn definition_outline(lines: &[&str], language: &Language) -> Vec<StructuredSection> {
+    let comment = if *language == Language::GraphQL {
+        "#"
+    } else {
+        "//"
+    };
+
+    let mut sections = Vec::new();
+    let mut cursor = 0;
+    for def in definitions(lines, language)
+        .into_iter()
+        .filter(|d| d.depth == 0)
+    {
+        // Leading comments document the definition below them
+        let mut start = def.start;
+        while start > cursor && lines[start - 1].trim_start().starts_with(comment) {
+            start -= 1;
+        }
+        if start > cursor {
+            sections.extend(section(lines, None, cursor, start, ChunkType::Other));
+        }
+
+        let chunk_type = match def.keyword.as_str() {
+            "rpc" | "query" | "mutation" | "subscription" | "fragment" => ChunkType::Function,
+            _ => ChunkType::Class,
+        };
+        sections.extend(section(
+            lines,
+            Some(format!("{} {}", def.keyword, def.name)),
+            start,
+            def.end + 1,
+            chunk_type,
+        ));
+        cursor = def.end + 1;
+    }
+    if cursor > 0 {
+        sections.extend(section(lines, None, cursor, lines.len(), ChunkType::Other));
+    }
+
+    sections
+}
+
+fn parse_definitions(lines: &[&str], language: &Language, out: &mut SymbolSink) {
+    let defs = definitions(lines, language);
+
+    for def in &defs {
+        let symbol_type = match (language, def.keyword.as_str()) {
+            (Language::Protobuf, "message") => SymbolType::Struct,
+            (Language::Protobuf, "service") => SymbolType::Interface,
+            (Language::Protobuf, "rpc") => SymbolType::Method,
+            (_, "enum") => SymbolType::Enum,
+            (_, "interface") => SymbolType::Interface,
+            (_, "input") | (_, "scalar") => SymbolType::Struct,
+            (_, "type") | (_, "union") => SymbolType::Class,
+            _ => SymbolType::Function,
+        };
+        let start_line = def.start as u32 + 1;
+        out.symbol(
+            &def.name,
+            symbol_type,
+            start_line,
+            def.end as u32 + 1,
+            def.header.clone(),
+        );
+
+        let from = (def.name.as_str(), start_line);
+        let header_refs = match (language, def.keyword.as_str()) {
+            (Language::Protobuf, "rpc") => rpc_types(&def.header),
+            (Language::GraphQL, "type") | (Language::GraphQL, "interface") => {
+                for iface in implemented_interfaces(&def.header) {
+                    out.reference(from, &iface, CodeRelationType::Implements, start_line, 0);
+                }
+                vec![]
+            }
+            (Language::GraphQL, "union") => def
+                .header
+                .split_once('=')
+                .map(|(_, members)| {
+                    members
+                        .split('|')
+                        .map(|m| m.trim().to_string())
+                        .filter(|m| is_identifier(m))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            (Language::GraphQL, "fragment") => def
+                .header
+                .split_once(" on ")
+                .map(|(_, ty)| vec![ty.trim().to_string()])
+                .unwrap_or_default(),
+            _ => vec![],
+        };
+        for target in header_refs {
+            out.reference(from, &target, CodeRelationType::References, start_line, 0);
+        }
+    }
+
+    // Field types inside messages / object types reference other definitions
+    for (idx, raw) in lines.iter().enumerate() {
+        let trimmed = strip_line_comment(raw, language).trim();
+        let Some(owner) = defs
+            .iter()
+            .filter(|d| d.start < idx && idx <= d.end)
+            .max_by_key(|d| d.start)
+        else {
+            continue;
+        };
+
+        let field_type = match (language, owner.keyword.as_str()) {
+            (Language::Protobuf, "message") => proto_field_type(trimmed),
+            (Language::GraphQL, "type" | "interface" | "input") => graphql_field_type(trimmed),
+            _ => None,
+        };
+        if let Some(target) = field_type {
+            let column = raw.len() - raw.trim_start().len();
+            out.reference(
+                (&owner.name, owner.start as u32 + 1),
+                &target,
+                CodeRelationType::References,
+                idx as u32 + 1,
+                column as u32,
+            );
+        }
+    }
+}
+
+/// `rpc Get (stream GetRequest) returns (pkg.GetResponse)` → request and response types.
+fn rpc_types(header: &str) -> Vec<String> {
+    let mut types = Vec::new();
+    let mut rest = header;
+    while let Some(open) = rest.find('(') {
+        let Some(close) = rest[open..].find(')').map(|c| c + open) else {
+            break;
+        };
+        let inner = rest[open + 1..close].trim();
+        let inner = inner.strip_prefix("stream ").unwrap_or(inner).trim();
+        let name = inner.rsplit('.').next().unwrap_or(inner);
+        if is_identifier(name) {
+            types.push(name.to_string());
+        }
+        rest = &rest[close + 1..];
+    }
+    types
+}
+
+fn implemented_interfaces(header: &str) -> Vec<String> {
+    let Some((_, list)) = header.split_once(" implements ") else {
+        return vec![];
+    };
+    list.split(['&', ','])
+        .map(|s| s.split_whitespace().next().unwrap_or("").to_string())
+        .filter(|s| is_identifier(s))
+        .collect()
+}
+
+fn proto_field_type(line: &str) -> Option<String> {
+    if !line.contains('=') || !line.ends_with(';') {
+        return None;
+    }
+    let mut tokens = line.split_whitespace();
+    let mut ty = tokens.next()?;
+    if matches!(
+        ty,
+        "option" | "reserved" | "extensions" | "message" | "enum" | "oneof"
+    ) {
+        return None;
+    }
+    if matches!(ty, "repeated" | "optional" | "required") {
+        ty = tokens.next()?;
+    }
+
+    let ty = if let Some(map) = line.find("map<") {
+        let inner = &line[map + 4..];
+        let close = inner.find('>')?;
+        inner[..close].split(',').nth(1)?.trim()
+    } else {
+        ty
+    };
+
+    const SCALARS: &[&str] = &[
+        "double", "float", "int32", "int64", "uint32", "uint64", "sint32", "sint64", "fixed32",
+        "fixed64", "sfixed32", "sfixed64", "bool", "string", "bytes",
+    ];
+    let name = ty.rsplit('.').next().unwrap_or(ty);
+    (!SCALARS.contains(&name) && is_identifier(name)).then(|| name.to_string())
+}
+
+fn graphql_field_type(line: &str) -> Option<String> {
+    // `user(id: ID!): User!` — the type follows the last top-level colon
+    let after_args = match line.rfind(')') {
+        Some(close) => &line[close + 1..],
+        None => line,
+    };
+    let (_, ty) = after_args.split_once(':')?;
+    let ty = ty.split('@').next().unwrap_or(ty);
+    let name: String = ty
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '_')
+        .collect();
+
+    const SCALARS: &[&str] = &["Int", "Float", "String", "Boolean", "ID"];
+    (!SCALARS.contains(&name.as_str()) && is_identifier(&name)).then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn names(sections: &[StructuredSection]) -> Vec<String> {
+        sections
+            .iter()
+            .map(|s| s.name.clone().unwrap_or_default())
+            .collect()
+    }
+
+    #[test]
+    fn test_yaml_multi_document_labels() {
+        let content = "apiVersion: apps/v1\nkind: Deployment\nmetadata:\n  name: web\nspec:\n  replicas: 2\n---\nkind: Service\nmetadata:\n  name: web-svc\n";
+        let sections = outline(content, &Language::Yaml);
+
+        assert_eq!(
+            names(&sections),
+            vec![
+                "Deployment/web > metadata",
+                "Deployment/web > spec",
+                "Service/web-svc > metadata"
+            ]
+        );
+        // apiVersion/kind fold into the following key
+        assert_eq!(sections[0].start_line, 1);
+    }
+
+    #[test]
+    fn test_toml_and_json_sections() {
+        let toml = "name = \"x\"\n\n[dependencies]\nserde = \"1\"\n\n[[bin]]\nname = \"cli\"\n";
+        assert_eq!(
+            names(&outline(toml, &Language::Toml)),
+            vec!["", "dependencies", "bin"]
+        );
+
+        let json = "{\n  \"name\": \"app\",\n  \"scripts\": {\n    \"build\": \"tsc\"\n  },\n  \"deps\": {}\n}\n";
+        let sections = outline(json, &Language::Json);
+        assert_eq!(names(&sections), vec!["scripts", "deps"]);
+        assert_eq!((sections[0].start_line, sections[0].end_line), (2, 5));
+    }
+
+    #[test]
+    fn test_sql_tables_columns_and_foreign_keys() {
+        let content = "-- users\nCREATE TABLE IF NOT EXISTS users (\n  id BIGINT PRIMARY KEY,\n  email TEXT NOT NULL\n);\n\nCREATE TABLE orders (\n  id BIGINT,\n  user_id BIGINT REFERENCES users(id),\n  PRIMARY KEY (id)\n);\nINSERT INTO orders VALUES (1, 1);\n";
+        let (symbols, refs) = parse_structured(&PathBuf::from("schema.sql"), content, "p");
+
+        let tables: Vec<_> = symbols
+            .iter()
+            .filter(|s| s.symbol_type == SymbolType::Table)
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(tables, vec!["users", "orders"]);
+
+        let columns: Vec<_> = symbols
+            .iter()
+            .filter(|s| s.symbol_type == SymbolType::Field)
+            .map(|s| (s.name.as_str(), s.start_line))
+            .collect();
+        assert_eq!(
+            columns,
+            vec![("id", 3), ("email", 4), ("id", 8), ("user_id", 9)]
+        );
+
+        assert!(refs
+            .iter()
+            .any(|r| r.from_symbol == "orders" && r.to_symbol == "users"));
+
+        let sections = outline(content, &Language::Sql);
+        assert_eq!(
+            names(&sections),
+            vec!["CREATE TABLE users", "CREATE TABLE orders", ""]
+        );
+    }
+
+    #[test]
+    fn test_proto_messages_services_and_references() {
+        let content = "syntax = \"proto3\";\n\n// A user\nmessage User {\n  string id = 1;\n  Address address = 2;\n  map<string, Tag> tags = 3;\n}\n\nservice Users {\n  rpc Get (GetUserRequest) returns (User);\n}\n";
+        let (symbols, refs) = parse_structured(&PathBuf::from("api.proto"), content, "p");
+
+        let kinds: Vec<_> = symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.symbol_type))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("User", SymbolType::Struct),
+                ("Users", SymbolType::Interface),
+                ("Get", SymbolType::Method)
+            ]
+        );
+        assert_eq!((symbols[0].start_line, symbols[0].end_line), (4, 8));
+
+        let edges: Vec<_> = refs
+            .iter()
+            .map(|r| (r.from_symbol.as_str(), r.to_symbol.as_str()))
+            .collect();
+        assert!(edges.contains(&("User", "Address")));
+        assert!(edges.contains(&("User", "Tag")));
+        assert!(edges.contains(&("Get", "GetUserRequest")));
+        assert!(edges.contains(&("Get", "User")));
+
+        let sections = outline(content, &Language::Protobuf);
+        // The one-line `syntax` preamble folds into the first message
+        assert_eq!(names(&sections), vec!["message User", "service Users"]);
+        assert_eq!(sections[0].start_line, 1);
+    }
+
+    #[test]
+    fn test_graphql_types_and_implements() {
+        let content = "type User implements Node & Entity {\n  id: ID!\n  posts(first: Int): [Post!]!\n}\n\nquery GetUser($id: ID!) {\n  user(id: $id) { id }\n}\n";
+        let (symbols, refs) = parse_structured(&PathBuf::from("schema.graphql"), content, "p");
+
+        assert_eq!(symbols[0].name, "User");
+        assert_eq!(symbols[0].symbol_type, SymbolType::Class);
+        assert_eq!(symbols[1].name, "GetUser");
+        assert_eq!(symbols[1].symbol_type, SymbolType::Function);
+
+        let implements: Vec<_> = refs
+            .iter()
+            .filter(|r| r.relation_type == CodeRelationType::Implements)
+            .map(|r| r.to_symbol.as_str())
+            .collect();
+        assert_eq!(implements, vec!["Node", "Entity"]);
+        assert!(refs.iter().any(|r| r.to_symbol == "Post"));
+    }
+
+    #[test]
+    fn test_sql_table_references_in_queries() {
+        assert!(looks_like_sql(
+            "SELECT u.id FROM public.users u JOIN orders o ON o.user_id = u.id"
+        ));
+        assert!(!looks_like_sql("select an option from the menu"));
+        assert_eq!(
+            sql_table_references("SELECT * FROM public.users u JOIN \"orders\" o ON 1=1"),
+            vec!["users", "orders"]
+        );
+        assert_eq!(
+            sql_table_references("UPDATE accounts SET x = 1 WHERE id IN (SELECT id FROM t)"),
+            vec!["accounts", "t"]
+        );
+    }
+}
@@ -1,25 +1,106 @@
 //! In-memory symbol index for fast cross-file resolution.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
 
-use crate::types::symbol::{CodeSymbol, SymbolRef};
+use tokio::sync::RwLock;
 
-/// Context for symbol resolution with priority scoring.
+use crate::codebase::parser::imports::{module_matches, GLOB};
+use crate::codebase::scanner::detect_language;
+use crate::storage::StorageBackend;
+use crate::types::symbol::{CodeReference, CodeRelationType, CodeSymbol, SymbolRef};
+use crate::types::Language;
+use crate::Result;
+
+/// Names a file brings into scope through its imports.
+#[derive(Debug, Clone, Default)]
+pub struct ImportScope {
+    /// Local name -> (module, name in that module)
+    items: HashMap<String, Vec<(String, String)>>,
+    /// Modules whose names are all visible (`use a::*`, Go packages, Dart libraries)
+    globs: Vec<String>,
+}
+
+impl ImportScope {
+    /// Build from a file's `Imports` references.
+    pub fn from_references<'a>(references: impl IntoIterator<Item = &'a CodeReference>) -> Self {
+        let mut scope = Self::default();
+        for reference in references {
+            if reference.relation_type != CodeRelationType::Imports {
+                continue;
+            }
+            let Some(module) = &reference.module else {
+                continue;
+            };
+            if reference.to_symbol == GLOB {
+                scope.globs.push(module.clone());
+            } else {
+                scope
+                    .items
+                    .entry(reference.name.clone())
+                    .or_default()
+                    .push((module.clone(), reference.to_symbol.clone()));
+            }
+        }
+        scope
+    }
+}
+
+/// Context for symbol resolution: the calling file and what it imports.
 #[derive(Debug, Clone)]
 pub struct ResolutionContext {
     pub caller_file: String,
+    pub language: Language,
+    pub imports: ImportScope,
 }
 
 impl ResolutionContext {
     pub fn new(caller_file: String) -> Self {
-        Self { caller_file }
+        let language = detect_language(Path::new(&caller_file));
+        Self {
+            caller_file,
+            language,
+            imports: ImportScope::default(),
+        }
+    }
+
+    pub fn with_imports(mut self, imports: ImportScope) -> Self {
+        self.imports = imports;
+        self
     }
 }
 
-/// In-memory index for fast symbol lookup with priority-based resolution.
+/// Outcome of resolving a referenced name.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Resolution {
+    Resolved(SymbolRef),
+    /// Imported from a module with no matching definition in the project
+    External(String),
+    /// Several candidates and nothing in scope to choose between them
+    Ambiguous,
+    /// No definition anywhere in the project
+    NotFound,
+}
+
+impl Resolution {
+    /// Short label stored with unresolved references.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Resolution::Resolved(_) => "resolved",
+            Resolution::External(_) => "external",
+            Resolution::Ambiguous => "ambiguous",
+            Resolution::NotFound => "not_found",
+        }
+    }
+}
+
+/// In-memory index for fast symbol lookup with import-aware resolution.
 #[derive(Debug, Default)]
 pub struct SymbolIndex {
     by_name: HashMap<String, Vec<SymbolRef>>,
+    /// Names defined in each file, for removing a file's symbols
+    by_file: HashMap<String, HashSet<String>>,
 }
 
 impl SymbolIndex {
@@ -27,13 +108,36 @@ impl SymbolIndex {
         Self::default()
     }
 
-    /// Add a symbol to the index.
+    /// Add a symbol to the index, replacing any entry with the same ID.
     pub fn add(&mut self, symbol: &CodeSymbol) {
         let sym_ref = SymbolRef::from_symbol(symbol);
-        self.by_name
-            .entry(symbol.name.clone())
+        self.by_file
+            .entry(symbol.file_path.clone())
             .or_default()
-            .push(sym_ref);
+            .insert(symbol.name.clone());
+        let entries = self.by_name.entry(symbol.name.clone()).or_default();
+        match entries.iter_mut().find(|s| s.key == sym_ref.key) {
+            Some(existing) => *existing = sym_ref,
+            None => entries.push(sym_ref),
+        }
+    }
+
+    /// Drop every symbol defined in `file_path`.
+    pub fn remove_file(&mut self, file_path: &str) {
+        for name in self.by_file.remove(file_path).unwrap_or_default() {
+            if let Some(entries) = self.by_name.get_mut(&name) {
+                entries.retain(|s| s.file_path != file_path);
+                if entries.is_empty() {
+                    self.by_name.remove(&name);
+                }
+            }
+        }
+    }
+
+    /// Replace a file's symbols with its freshly parsed ones.
+    pub fn replace_file(&mut self, file_path: &str, symbols: &[CodeSymbol]) {
+        self.remove_file(file_path);
+        self.add_batch(symbols);
     }
 
     /// Add multiple symbols to the index.
@@ -43,16 +147,75 @@ impl SymbolIndex {
         }
     }
 
-    /// Resolve a symbol name with priority scoring.
-    /// Priority: same file (100) > same directory (50) > any (0)
+    /// Resolve a name, or `None` if it cannot be resolved without guessing.
     pub fn resolve(&self, name: &str, ctx: &ResolutionContext) -> Option<SymbolRef> {
-        let candidates = self.by_name.get(name)?;
+        match self.lookup(name, ctx) {
+            Resolution::Resolved(symbol) => Some(symbol),
+            _ => None,
+        }
+    }
+
+    /// Resolve a name as seen from the caller's file. In order: a definition
+    /// in the same file, an explicitly imported name, a name from a glob or
+    /// package import, the same package (Go, Java), and finally the only
+    /// definition of that name in a compatible language. Anything else is
+    /// left unresolved rather than guessed.
+    pub fn lookup(&self, name: &str, ctx: &ResolutionContext) -> Resolution {
+        let candidates: &[SymbolRef] = self.by_name.get(name).map_or(&[], Vec::as_slice);
+
+        if let Some(local) = candidates.iter().find(|s| s.file_path == ctx.caller_file) {
+            return Resolution::Resolved(local.clone());
+        }
+
+        if let Some(bindings) = ctx.imports.items.get(name) {
+            for (module, imported) in bindings {
+                let found = self.by_name.get(imported).and_then(|c| {
+                    c.iter().find(|s| {
+                        module_matches(&ctx.language, module, &ctx.caller_file, &s.file_path)
+                    })
+                });
+                if let Some(symbol) = found {
+                    return Resolution::Resolved(symbol.clone());
+                }
+            }
+            return Resolution::External(bindings[0].0.clone());
+        }
+
+        if let Some(symbol) = candidates.iter().find(|s| {
+            ctx.imports
+                .globs
+                .iter()
+                .any(|m| module_matches(&ctx.language, m, &ctx.caller_file, &s.file_path))
+        }) {
+            return Resolution::Resolved(symbol.clone());
+        }
+
+        if matches!(ctx.language, Language::Go | Language::Java) {
+            if let Some(symbol) = candidates
+                .iter()
+                .find(|s| same_directory(&s.file_path, &ctx.caller_file))
+            {
+                return Resolution::Resolved(symbol.clone());
+            }
+        }
+
+        let compatible: Vec<&SymbolRef> = candidates
+            .iter()
+            .filter(|s| compatible_language(&ctx.language, &s.file_path))
+            .collect();
+        match compatible.as_slice() {
+            [only] => Resolution::Resolved((*only).clone()),
+            [] => Resolution::NotFound,
+            _ => Resolution::Ambiguous,
+        }
+    }
 
-        candidates
+    /// The symbol named `name` defined at `line` of `file_path`.
+    pub fn locate(&self, file_path: &str, name: &str, line: u32) -> Option<&SymbolRef> {
+        self.by_name
+            .get(name)?
             .iter()
-            .map(|s| (self.score(s, ctx), s))
-            .max_by_key(|(score, _)| *score)
-            .map(|(_, s)| s.clone())
+            .find(|s| s.line == line && s.file_path == file_path)
     }
 
     /// Get all symbols with a given name (for debugging).
@@ -60,6 +223,11 @@ impl SymbolIndex {
         self.by_name.get(name)
     }
 
+    /// Iterate over every indexed symbol.
+    pub fn iter(&self) -> impl Iterator<Item = &SymbolRef> {
+        self.by_name.values().flatten()
+    }
+
     /// Total number of unique names in the index.
     pub fn len(&self) -> usize {
         self.by_name.len()
@@ -69,23 +237,65 @@ impl SymbolIndex {
     pub fn is_empty(&self) -> bool {
         self.by_name.is_empty()
     }
+}
+
+/// Long-lived symbol indexes, one per project, kept in step with stored
+/// symbols as files are indexed, changed or removed, so an edit does not
+/// reload the project's whole symbol table.
+#[derive(Default)]
+pub struct SymbolIndexRegistry {
+    projects: RwLock<HashMap<String, Arc<RwLock<SymbolIndex>>>>,
+}
 
-    fn score(&self, symbol: &SymbolRef, ctx: &ResolutionContext) -> i32 {
-        let mut score = 0;
+impl SymbolIndexRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-        // Same file gets highest priority
-        if symbol.file_path == ctx.caller_file {
-            score += 100;
+    /// The project's index, loaded from storage on first use.
+    pub async fn get_or_load(
+        &self,
+        storage: &dyn StorageBackend,
+        project_id: &str,
+    ) -> Result<Arc<RwLock<SymbolIndex>>> {
+        if let Some(index) = self.get(project_id).await {
+            return Ok(index);
         }
-        // Same directory gets medium priority
-        else if same_directory(&symbol.file_path, &ctx.caller_file) {
-            score += 50;
+
+        let mut projects = self.projects.write().await;
+        if let Some(index) = projects.get(project_id) {
+            return Ok(index.clone());
         }
+        let mut index = SymbolIndex::new();
+        index.add_batch(&storage.get_project_symbols(project_id).await?);
+        tracing::debug!(project = %project_id, names = index.len(), "Symbol index loaded");
+        let index = Arc::new(RwLock::new(index));
+        projects.insert(project_id.to_string(), index.clone());
+        Ok(index)
+    }
+
+    /// The project's index if it has been loaded.
+    pub async fn get(&self, project_id: &str) -> Option<Arc<RwLock<SymbolIndex>>> {
+        self.projects.read().await.get(project_id).cloned()
+    }
+
+    pub async fn remove(&self, project_id: &str) {
+        self.projects.write().await.remove(project_id);
+    }
 
-        score
+    pub async fn clear(&self) {
+        self.projects.write().await.clear();
     }
 }
 
+/// Code can reference definitions in its own language (TS and JS mix freely)
+/// and in schema files such as SQL tables.
+fn compatible_language(caller: &Language, candidate_file: &str) -> bool {
+    let candidate = detect_language(Path::new(candidate_file));
+    let is_js = |l: &Language| matches!(l, Language::TypeScript | Language::JavaScript);
+    candidate == *caller || (is_js(caller) && is_js(&candidate)) || candidate.is_structured()
+}
+
 /// Check if two file paths are in the same directory.
 fn same_directory(path1: &str, path2: &str) -> bool {
     let parent1 = std::path::Path::new(path1).parent();
@@ -123,15 +333,74 @@ mod tests {
     }
 
     #[test]
-    fn test_resolve_same_directory_priority() {
+    fn test_resolve_same_package() {
         let mut index = SymbolIndex::new();
-        index.add(&make_symbol("bar", "/src/utils/a.rs", 10));
-        index.add(&make_symbol("bar", "/other/b.rs", 20));
+        index.add(&make_symbol("bar", "/src/utils/a.go", 10));
+        index.add(&make_symbol("bar", "/other/b.go", 20));
 
-        let ctx = ResolutionContext::new("/src/utils/caller.rs".to_string());
+        let ctx = ResolutionContext::new("/src/utils/caller.go".to_string());
         let resolved = index.resolve("bar", &ctx).unwrap();
 
-        assert_eq!(resolved.file_path, "/src/utils/a.rs");
+        assert_eq!(resolved.file_path, "/src/utils/a.go");
+    }
+
+    #[test]
+    fn test_resolve_through_imports() {
+        let mut index = SymbolIndex::new();
+        index.add(&make_symbol("parse", "/p/src/config.rs", 10));
+        index.add(&make_symbol("parse", "/p/src/jwt.rs", 20));
+
+        let import = |name: &str, module: &str| {
+            CodeReference::builder()
+                .name(name)
+                .from_symbol("global")
+                .from_symbol_line(0)
+                .to_symbol(name)
+                .relation_type(CodeRelationType::Imports)
+                .file_path("/p/src/main.rs")
+                .line(1)
+                .column(0)
+                .module(module)
+                .build()
+        };
+
+        // Without imports the same-directory guess is no longer made
+        let bare = ResolutionContext::new("/p/src/main.rs".to_string());
+        assert_eq!(index.lookup("parse", &bare), Resolution::Ambiguous);
+
+        let imports = [import("parse", "crate::jwt")];
+        let ctx = bare
+            .clone()
+            .with_imports(ImportScope::from_references(&imports));
+        assert_eq!(
+            index.resolve("parse", &ctx).unwrap().file_path,
+            "/p/src/jwt.rs"
+        );
+
+        let imports = [import("parse", "serde_json")];
+        let ctx = bare.with_imports(ImportScope::from_references(&imports));
+        assert_eq!(
+            index.lookup("parse", &ctx),
+            Resolution::External("serde_json".to_string())
+        );
+    }
+
+    #[test]
+    fn test_replace_and_remove_file() {
+        let mut index = SymbolIndex::new();
+        index.add(&make_symbol("foo", "/src/a.rs", 10));
+        index.add(&make_symbol("foo", "/src/b.rs", 20));
+        index.add(&make_symbol("bar", "/src/a.rs", 30));
+
+        index.replace_file("/src/a.rs", &[make_symbol("baz", "/src/a.rs", 5)]);
+        assert!(index.get_all("bar").is_none());
+        assert_eq!(index.get_all("foo").unwrap().len(), 1);
+        assert_eq!(index.get_all("foo").unwrap()[0].file_path, "/src/b.rs");
+        assert!(index.locate("/src/a.rs", "baz", 5).is_some());
+
+        index.remove_file("/src/b.rs");
+        assert!(index.get_all("foo").is_none());
+        assert_eq!(index.len(), 1);
     }
 
     #[test]
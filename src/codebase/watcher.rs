@@ -1,18 +1,299 @@
-use std::collections::HashSet;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
-use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use ignore::WalkBuilder;
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Config, Event, EventKind, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
 use tokio::sync::mpsc;
 use tracing::{info, warn};
 
-use super::scanner::{is_code_file, is_ignored_file};
+use super::scanner::{is_ignore_file, is_indexable_file, IgnoreRules};
 use crate::Result;
 
+/// How file changes are detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WatchMode {
+    /// Native events, unless the project sits on a mount known not to
+    /// deliver them (Docker Desktop bind mounts, network filesystems)
+    #[default]
+    Auto,
+    Native,
+    /// Periodically rescan file metadata
+    Poll,
+}
+
+impl FromStr for WatchMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "native" => Ok(Self::Native),
+            "poll" => Ok(Self::Poll),
+            other => Err(format!(
+                "Unknown watch mode '{}'. Expected auto, native or poll",
+                other
+            )),
+        }
+    }
+}
+
+/// File changes collected over one debounce window.
+#[derive(Debug, Default, PartialEq)]
+pub struct ChangeBatch {
+    /// Files created, modified or removed, and removed directories
+    pub paths: Vec<PathBuf>,
+    /// Files or directories moved within the project, as (from, to)
+    pub renames: Vec<(PathBuf, PathBuf)>,
+    /// An ignore file changed, so the whole project needs rescanning
+    pub rescan: bool,
+    /// HEAD or a branch moved (checkout, commit, pull, reset)
+    pub head_changed: bool,
+}
+
+/// A filtered filesystem event, before debouncing.
+#[derive(Debug)]
+enum Change {
+    Path(PathBuf),
+    /// One half of a rename; halves with the same tracker are paired
+    RenameFrom(usize, PathBuf),
+    RenameTo(usize, PathBuf),
+    Rename(PathBuf, PathBuf),
+    IgnoreFile,
+    GitHead,
+}
+
+type SharedRules = Arc<RwLock<Vec<IgnoreRules>>>;
+
+fn is_ignored(rules: &SharedRules, path: &Path, is_dir: bool) -> bool {
+    let rules = rules.read().unwrap();
+    match rules.iter().find(|r| path.starts_with(r.root())) {
+        Some(r) => r.is_ignored(path, is_dir),
+        None => true,
+    }
+}
+
+/// Indexable files under a directory that appeared in one piece (created,
+/// or moved in from outside the project).
+fn expand_directory(rules: &SharedRules, dir: &Path) -> Vec<PathBuf> {
+    WalkBuilder::new(dir)
+        .standard_filters(false)
+        .build()
+        .filter_map(|e| e.ok())
+        .map(|e| e.into_path())
+        .filter(|p| p.is_file() && is_indexable_file(p) && !is_ignored(rules, p, false))
+        .collect()
+}
+
+/// Map a notify event to the changes the indexer cares about.
+fn classify(event: Event, rules: &SharedRules) -> Vec<Change> {
+    if event.paths.iter().any(|p| is_ignore_file(p)) {
+        return vec![Change::IgnoreFile];
+    }
+    if event.paths.iter().any(|p| is_git_head(p)) {
+        return vec![Change::GitHead];
+    }
+
+    let tracker = event.attrs.tracker();
+    let mut paths = event.paths;
+    match event.kind {
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if paths.len() == 2 => {
+            let to = paths.pop().unwrap();
+            let from = paths.pop().unwrap();
+            let from_kept = !is_ignored(rules, &from, to.is_dir());
+            let to_kept = !is_ignored(rules, &to, to.is_dir());
+            match (from_kept, to_kept) {
+                (true, true) => vec![Change::Rename(from, to)],
+                (true, false) => vec![Change::Path(from)],
+                (false, true) => added(rules, to),
+                (false, false) => Vec::new(),
+            }
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => paths
+            .into_iter()
+            .filter(|p| !is_ignored(rules, p, false))
+            .map(|p| match tracker {
+                Some(t) => Change::RenameFrom(t, p),
+                None => Change::Path(p),
+            })
+            .collect(),
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => paths
+            .into_iter()
+            .filter(|p| !is_ignored(rules, p, p.is_dir()))
+            .flat_map(|p| match tracker {
+                Some(t) => vec![Change::RenameTo(t, p)],
+                None => added(rules, p),
+            })
+            .collect(),
+        kind if kind.is_create() || kind.is_modify() || kind.is_remove() => paths
+            .into_iter()
+            .filter(|p| !is_ignored(rules, p, p.is_dir()))
+            .flat_map(|p| {
+                if p.is_dir() {
+                    // Directory metadata changes carry nothing to index
+                    if kind.is_create() {
+                        added(rules, p)
+                    } else {
+                        Vec::new()
+                    }
+                } else if !p.exists() || is_indexable_file(&p) {
+                    // Removed paths may be directories; the indexer checks
+                    vec![Change::Path(p)]
+                } else {
+                    Vec::new()
+                }
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// `.git/HEAD` or a branch ref under `.git/refs/heads`.
+fn is_git_head(path: &Path) -> bool {
+    let mut components = path.components().map(|c| c.as_os_str());
+    if !components.any(|c| c == ".git") {
+        return false;
+    }
+    let rest: Vec<_> = components.collect();
+    matches!(rest.as_slice(), [head] if *head == "HEAD")
+        || matches!(rest.as_slice(), [refs, heads, ..] if *refs == "refs" && *heads == "heads")
+}
+
+fn added(rules: &SharedRules, path: PathBuf) -> Vec<Change> {
+    if path.is_dir() {
+        expand_directory(rules, &path)
+            .into_iter()
+            .map(Change::Path)
+            .collect()
+    } else if is_indexable_file(&path) {
+        vec![Change::Path(path)]
+    } else {
+        Vec::new()
+    }
+}
+
+/// Changes accumulated during the debounce window.
+#[derive(Default)]
+struct PendingChanges {
+    paths: HashSet<PathBuf>,
+    renames: Vec<(PathBuf, PathBuf)>,
+    /// Rename sources still waiting for their destination
+    rename_from: HashMap<usize, PathBuf>,
+    rename_to: HashMap<usize, PathBuf>,
+    rescan: bool,
+    head_changed: bool,
+}
+
+impl PendingChanges {
+    fn push(&mut self, change: Change) {
+        match change {
+            Change::Path(path) => {
+                self.paths.insert(path);
+            }
+            Change::RenameFrom(tracker, from) => match self.rename_to.remove(&tracker) {
+                Some(to) => self.add_rename(from, to),
+                None => {
+                    self.rename_from.insert(tracker, from);
+                }
+            },
+            Change::RenameTo(tracker, to) => match self.rename_from.remove(&tracker) {
+                Some(from) => self.add_rename(from, to),
+                None => {
+                    self.rename_to.insert(tracker, to);
+                }
+            },
+            Change::Rename(from, to) => self.add_rename(from, to),
+            Change::IgnoreFile => self.rescan = true,
+            Change::GitHead => self.head_changed = true,
+        }
+    }
+
+    fn add_rename(&mut self, from: PathBuf, to: PathBuf) {
+        let rename = (from, to);
+        if !self.renames.contains(&rename) {
+            self.renames.push(rename);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+            && self.renames.is_empty()
+            && self.rename_from.is_empty()
+            && self.rename_to.is_empty()
+            && !self.rescan
+            && !self.head_changed
+    }
+
+    /// Drain into a batch. Unpaired rename halves become a removal or an
+    /// addition.
+    fn take(&mut self, rules: &SharedRules) -> ChangeBatch {
+        let mut paths = std::mem::take(&mut self.paths);
+        paths.extend(self.rename_from.drain().map(|(_, from)| from));
+        for (_, to) in self.rename_to.drain() {
+            for change in added(rules, to) {
+                if let Change::Path(path) = change {
+                    paths.insert(path);
+                }
+            }
+        }
+
+        let renames = std::mem::take(&mut self.renames);
+        let mut paths: Vec<PathBuf> = paths
+            .into_iter()
+            .filter(|p| !renames.iter().any(|(from, to)| p == from || p == to))
+            .collect();
+        paths.sort();
+
+        ChangeBatch {
+            paths,
+            renames,
+            rescan: std::mem::take(&mut self.rescan),
+            head_changed: std::mem::take(&mut self.head_changed),
+        }
+    }
+}
+
+/// Whether native events are known not to arrive for `path`: its mount is a
+/// Docker Desktop bind mount (macOS/Windows hosts) or a network filesystem.
+fn native_events_unreliable(path: &Path) -> bool {
+    const POLL_FS: [&str; 9] = [
+        "fakeowner",
+        "grpcfuse",
+        "osxfs",
+        "virtiofs",
+        "9p",
+        "nfs",
+        "nfs4",
+        "cifs",
+        "smb3",
+    ];
+
+    let Ok(mounts) = std::fs::read_to_string("/proc/self/mounts") else {
+        return false;
+    };
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mount_point = fields.nth(1)?;
+            let fs_type = fields.next()?;
+            Some((PathBuf::from(mount_point), fs_type))
+        })
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.components().count())
+        .is_some_and(|(_, fs_type)| POLL_FS.contains(&fs_type))
+}
+
 pub struct FileWatcher {
     paths: Vec<PathBuf>,
-    watcher: Option<RecommendedWatcher>,
+    mode: WatchMode,
+    watcher: Option<Box<dyn Watcher + Send>>,
     debounce_duration: Duration,
+    poll_interval: Duration,
     cancel_tx: Option<mpsc::Sender<()>>,
 }
 
@@ -20,32 +301,51 @@ impl FileWatcher {
     pub fn new(paths: Vec<PathBuf>) -> Self {
         Self {
             paths,
+            mode: WatchMode::Auto,
             watcher: None,
             debounce_duration: Duration::from_secs(2),
+            poll_interval: Duration::from_secs(2),
             cancel_tx: None,
         }
     }
 
-    pub fn start<F>(&mut self, callback: F) -> Result<()>
+    pub fn with_mode(mut self, mode: WatchMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    fn create_watcher<H>(&self, handler: H) -> Result<Box<dyn Watcher + Send>>
     where
-        F: Fn(Vec<PathBuf>) + Send + Sync + 'static,
+        H: notify::EventHandler + Clone,
     {
-        let (tx, mut rx) = mpsc::channel(100);
-        let (cancel_tx, mut cancel_rx) = mpsc::channel::<()>(1);
-        let debounce_duration = self.debounce_duration;
+        let poll = match self.mode {
+            WatchMode::Poll => true,
+            WatchMode::Native => false,
+            WatchMode::Auto => self.paths.iter().any(|p| native_events_unreliable(p)),
+        };
 
-        let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
-            if let Ok(event) = res {
-                if event.kind.is_modify() || event.kind.is_create() || event.kind.is_remove() {
-                    for path in event.paths {
-                        if !is_ignored_file(&path) && is_code_file(&path) {
-                            let _ = tx.blocking_send(path);
-                        }
-                    }
+        if !poll {
+            match self.watch_all(RecommendedWatcher::new(handler.clone(), Config::default())) {
+                Ok(watcher) => return Ok(watcher),
+                Err(e) if self.mode == WatchMode::Auto => {
+                    warn!(error = %e, "Native file watching unavailable, polling instead");
                 }
+                Err(e) => return Err(e),
             }
-        })?;
+        }
+
+        info!(interval = ?self.poll_interval, "Polling for file changes");
+        self.watch_all(PollWatcher::new(
+            handler,
+            Config::default().with_poll_interval(self.poll_interval),
+        ))
+    }
 
+    fn watch_all<W: Watcher + Send + 'static>(
+        &self,
+        watcher: notify::Result<W>,
+    ) -> Result<Box<dyn Watcher + Send>> {
+        let mut watcher = watcher?;
         for path in &self.paths {
             if path.exists() {
                 watcher.watch(path, RecursiveMode::Recursive)?;
@@ -54,6 +354,39 @@ impl FileWatcher {
                 warn!("Path does not exist: {:?}", path);
             }
         }
+        Ok(Box::new(watcher))
+    }
+
+    pub fn start<F>(&mut self, callback: F) -> Result<()>
+    where
+        F: Fn(ChangeBatch) + Send + Sync + 'static,
+    {
+        let (tx, mut rx) = mpsc::channel(100);
+        let (cancel_tx, mut cancel_rx) = mpsc::channel::<()>(1);
+        let debounce_duration = self.debounce_duration;
+
+        let load_rules = {
+            let roots = self.paths.clone();
+            move || {
+                roots
+                    .iter()
+                    .map(|r| IgnoreRules::load(r))
+                    .collect::<Vec<_>>()
+            }
+        };
+        let rules: SharedRules = Arc::new(RwLock::new(load_rules()));
+
+        let handler = {
+            let rules = rules.clone();
+            move |res: notify::Result<Event>| {
+                if let Ok(event) = res {
+                    for change in classify(event, &rules) {
+                        let _ = tx.blocking_send(change);
+                    }
+                }
+            }
+        };
+        let watcher = self.create_watcher(SharedHandler(Arc::new(handler)))?;
 
         self.watcher = Some(watcher);
         self.cancel_tx = Some(cancel_tx);
@@ -61,7 +394,7 @@ impl FileWatcher {
         // Debounce logic in a background task
         tokio::spawn(async move {
             let mut last_event: Option<tokio::time::Instant> = None;
-            let mut pending_paths = HashSet::new();
+            let mut pending = PendingChanges::default();
 
             loop {
                 let sleep_duration = if last_event.is_some() {
@@ -72,23 +405,26 @@ impl FileWatcher {
 
                 tokio::select! {
                     received = rx.recv() => {
-                        if let Some(path) = received {
-                            pending_paths.insert(path);
+                        if let Some(change) = received {
+                            pending.push(change);
                             last_event = Some(tokio::time::Instant::now());
                         } else {
                             break;
                         }
                     }
                     _ = cancel_rx.recv() => {
-                        info!("Watcher debounce cancelled, discarding {} pending paths", pending_paths.len());
+                        info!("Watcher debounce cancelled, discarding {} pending paths", pending.paths.len());
                         break;
                     }
                     _ = tokio::time::sleep(sleep_duration) => {
                         if let Some(last) = last_event {
                             if last.elapsed() >= debounce_duration {
-                                let paths: Vec<PathBuf> = pending_paths.drain().collect();
-                                if !paths.is_empty() {
-                                    callback(paths);
+                                if pending.rescan {
+                                    info!("Ignore file changed, reloading ignore rules");
+                                    *rules.write().unwrap() = load_rules();
+                                }
+                                if !pending.is_empty() {
+                                    callback(pending.take(&rules));
                                 }
                                 last_event = None;
                             }
@@ -107,3 +443,114 @@ impl FileWatcher {
         info!("Stopped file watcher");
     }
 }
+
+/// Lets one event handler be given to whichever watcher ends up in use.
+#[derive(Clone)]
+struct SharedHandler<F>(Arc<F>);
+
+impl<F> notify::EventHandler for SharedHandler<F>
+where
+    F: Fn(notify::Result<Event>) + Send + Sync + 'static,
+{
+    fn handle_event(&mut self, event: notify::Result<Event>) {
+        (self.0)(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn rules_for(root: &Path) -> SharedRules {
+        Arc::new(RwLock::new(vec![IgnoreRules::load(root)]))
+    }
+
+    #[test]
+    fn test_rename_halves_are_paired() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("new.rs"), "fn a() {}").unwrap();
+        let rules = rules_for(root);
+
+        let mut pending = PendingChanges::default();
+        pending.push(Change::RenameFrom(7, root.join("old.rs")));
+        pending.push(Change::Path(root.join("old.rs")));
+        pending.push(Change::RenameTo(7, root.join("new.rs")));
+        // Backends that also report the paired event must not duplicate it
+        pending.push(Change::Rename(root.join("old.rs"), root.join("new.rs")));
+        pending.push(Change::RenameFrom(9, root.join("gone.rs")));
+
+        let batch = pending.take(&rules);
+        assert_eq!(
+            batch.renames,
+            vec![(root.join("old.rs"), root.join("new.rs"))]
+        );
+        assert_eq!(batch.paths, vec![root.join("gone.rs")]);
+        assert!(!batch.rescan);
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn test_classify_filters_and_expands() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("pkg/sub")).unwrap();
+        fs::write(root.join(".gitignore"), "out.rs\n").unwrap();
+        fs::write(root.join("pkg/a.rs"), "").unwrap();
+        fs::write(root.join("pkg/sub/b.py"), "").unwrap();
+        fs::write(root.join("pkg/image.png"), "").unwrap();
+        let rules = rules_for(root);
+
+        let event = |kind: EventKind, paths: Vec<PathBuf>| Event {
+            kind,
+            paths,
+            attrs: Default::default(),
+        };
+        let paths = |changes: Vec<Change>| -> Vec<PathBuf> {
+            let mut paths: Vec<PathBuf> = changes
+                .into_iter()
+                .filter_map(|c| match c {
+                    Change::Path(p) => Some(p),
+                    _ => None,
+                })
+                .collect();
+            paths.sort();
+            paths
+        };
+
+        let create = EventKind::Create(notify::event::CreateKind::Any);
+        let remove = EventKind::Remove(notify::event::RemoveKind::Any);
+        assert_eq!(
+            paths(classify(event(create, vec![root.join("pkg")]), &rules)),
+            vec![root.join("pkg/a.rs"), root.join("pkg/sub/b.py")]
+        );
+        assert!(classify(event(create, vec![root.join("out.rs")]), &rules).is_empty());
+        assert_eq!(
+            paths(classify(
+                event(remove, vec![root.join("deleted_dir")]),
+                &rules
+            )),
+            vec![root.join("deleted_dir")]
+        );
+        assert!(matches!(
+            classify(event(create, vec![root.join(".memoryignore")]), &rules)[..],
+            [Change::IgnoreFile]
+        ));
+        let modify = EventKind::Modify(ModifyKind::Any);
+        for git_path in [".git/HEAD", ".git/refs/heads/feature/x"] {
+            assert!(matches!(
+                classify(event(modify, vec![root.join(git_path)]), &rules)[..],
+                [Change::GitHead]
+            ));
+        }
+        assert!(classify(event(modify, vec![root.join(".git/index")]), &rules).is_empty());
+    }
+
+    #[test]
+    fn test_watch_mode_from_str() {
+        assert_eq!("poll".parse::<WatchMode>().unwrap(), WatchMode::Poll);
+        assert_eq!("Native".parse::<WatchMode>().unwrap(), WatchMode::Native);
+        assert!("inotify".parse::<WatchMode>().is_err());
+    }
+}
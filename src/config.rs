@@ -1,13 +1,20 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::atomic::AtomicU32;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use tokio::sync::{RwLock, Semaphore};
+use tokio::sync::{watch, RwLock, Semaphore};
+use tokio_util::sync::CancellationToken;
 
+use crate::codebase::embed_text::EmbeddingTextConfig;
+use crate::codebase::{PathMap, SymbolIndexRegistry, WatchMode};
 use crate::embedding::{AdaptiveEmbeddingQueue, EmbeddingService, EmbeddingStore};
 use crate::storage::SurrealStorage;
 
+/// Default limit on the size of files indexed (1 MiB).
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 1_048_576;
+
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     pub data_dir: PathBuf,
@@ -16,6 +23,27 @@ pub struct AppConfig {
     pub batch_size: usize,
     pub timeout_ms: u64,
     pub log_level: String,
+    /// Context prepended to code chunk/symbol text before embedding
+    pub embedding_text: EmbeddingTextConfig,
+    /// How the codebase watcher detects file changes
+    pub watch_mode: WatchMode,
+    /// CPU budget for parsing during indexing
+    pub index_budget: IndexBudget,
+    /// Server-to-host path prefixes, for clients outside the container
+    pub path_map: PathMap,
+    /// Files larger than this many bytes are not indexed; 0 = no limit
+    pub max_file_size: u64,
+    /// Commits of git history to index per project; 0 = history not indexed
+    pub history_depth: usize,
+}
+
+/// Limits on the CPU indexing may use, so it doesn't starve the editor.
+#[derive(Debug, Clone, Default)]
+pub struct IndexBudget {
+    /// Parser threads; all cores when unset
+    pub threads: Option<usize>,
+    /// Run parser threads only when a CPU is otherwise idle (Linux)
+    pub idle_only: bool,
 }
 
 impl Default for AppConfig {
@@ -29,13 +57,221 @@ impl Default for AppConfig {
             batch_size: 8,
             timeout_ms: 30000,
             log_level: "info".to_string(),
+            embedding_text: EmbeddingTextConfig::default(),
+            watch_mode: WatchMode::default(),
+            index_budget: IndexBudget::default(),
+            path_map: PathMap::default(),
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            history_depth: 0,
         }
     }
 }
 
+/// Work done by one indexing pipeline stage.
+#[derive(Default)]
+pub struct StageMetrics {
+    pub files: AtomicU32,
+    /// Time spent in the stage, summed over its workers
+    pub busy_micros: AtomicU64,
+}
+
+impl StageMetrics {
+    pub fn record(&self, files: u32, elapsed: Duration) {
+        self.files.fetch_add(files, Ordering::Relaxed);
+        self.busy_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    fn reset(&self) {
+        self.files.store(0, Ordering::Relaxed);
+        self.busy_micros.store(0, Ordering::Relaxed);
+    }
+
+    /// Files, busy time and throughput over `wall` seconds of the run.
+    pub fn snapshot(&self, wall: Duration) -> serde_json::Value {
+        let files = self.files.load(Ordering::Relaxed);
+        let busy = self.busy_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+        let wall = wall.as_secs_f64();
+        serde_json::json!({
+            "files": files,
+            "busy_secs": format!("{:.2}", busy),
+            "files_per_sec": format!("{:.1}", if wall > 0.0 { files as f64 / wall } else { 0.0 }),
+        })
+    }
+}
+
 pub struct IndexMonitor {
     pub total_files: AtomicU32,
     pub indexed_files: AtomicU32,
+    /// Read, hash, chunk and parse (parallel)
+    pub parse: StageMetrics,
+    /// Chunk and symbol writes
+    pub store: StageMetrics,
+    /// Cross-file reference resolution
+    pub relations: StageMetrics,
+    started: std::sync::Mutex<Option<Instant>>,
+    finished: std::sync::Mutex<Option<Instant>>,
+    /// Set while an indexing job runs; at most one per project
+    running: AtomicBool,
+    /// A full rescan asked for while a job ran, run by that job before it
+    /// ends. The lock also orders ending a job against deferring to it.
+    rescan_pending: std::sync::Mutex<bool>,
+    /// Cancellation for the running job, replaced when a job starts
+    cancel: std::sync::Mutex<CancellationToken>,
+    paused: watch::Sender<bool>,
+}
+
+/// Handle for a running indexing job. Dropping it marks the job finished.
+pub struct IndexJob {
+    monitor: Arc<IndexMonitor>,
+    /// Ended by `finish_or_rescan`, so dropping it must not end a later job
+    finished: bool,
+}
+
+impl IndexJob {
+    /// End the job, unless a rescan was deferred to it: then the job is
+    /// kept for the rescan and this returns true.
+    pub fn finish_or_rescan(&mut self) -> bool {
+        let mut pending = self.monitor.rescan_pending.lock().unwrap();
+        if std::mem::take(&mut *pending) {
+            *self.monitor.cancel.lock().unwrap() = CancellationToken::new();
+            self.monitor.paused.send_replace(false);
+            return true;
+        }
+        self.monitor.paused.send_replace(false);
+        self.monitor.running.store(false, Ordering::SeqCst);
+        self.finished = true;
+        false
+    }
+}
+
+impl Drop for IndexJob {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        // Dropped without finishing (its future cancelled): a rescan
+        // deferred to it is lost rather than run by an unrelated job
+        let mut pending = self.monitor.rescan_pending.lock().unwrap();
+        *pending = false;
+        self.monitor.paused.send_replace(false);
+        self.monitor.running.store(false, Ordering::SeqCst);
+    }
+}
+
+impl IndexMonitor {
+    /// Register a new job, or `None` if one is already running.
+    pub fn begin_job(self: &Arc<Self>) -> Option<IndexJob> {
+        if self.running.swap(true, Ordering::SeqCst) {
+            return None;
+        }
+        *self.cancel.lock().unwrap() = CancellationToken::new();
+        self.paused.send_replace(false);
+        Some(IndexJob {
+            monitor: self.clone(),
+            finished: false,
+        })
+    }
+
+    /// Register a job for a full rescan, or `None` if a job is already
+    /// running; that job then rescans before it ends.
+    pub fn begin_rescan(self: &Arc<Self>) -> Option<IndexJob> {
+        let mut pending = self.rescan_pending.lock().unwrap();
+        let job = self.begin_job();
+        if job.is_none() {
+            *pending = true;
+        }
+        job
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.paused.borrow()
+    }
+
+    /// Ask the running job to pause. Returns false if none is running.
+    pub fn pause(&self) -> bool {
+        if !self.is_running() {
+            return false;
+        }
+        self.paused.send_replace(true);
+        true
+    }
+
+    /// Let a paused job continue. Returns false if none is paused.
+    pub fn resume(&self) -> bool {
+        self.is_running() && self.paused.send_replace(false)
+    }
+
+    /// Ask the running job to stop. Returns false if none is running.
+    pub fn cancel(&self) -> bool {
+        if !self.is_running() {
+            return false;
+        }
+        self.cancel.lock().unwrap().cancel();
+        true
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.lock().unwrap().is_cancelled()
+    }
+
+    /// Wait while the job is paused. Returns false once it is cancelled.
+    pub async fn checkpoint(&self) -> bool {
+        let token = self.cancel.lock().unwrap().clone();
+        let mut paused = self.paused.subscribe();
+        loop {
+            if token.is_cancelled() {
+                return false;
+            }
+            if !*paused.borrow_and_update() {
+                return true;
+            }
+            tokio::select! {
+                _ = token.cancelled() => return false,
+                changed = paused.changed() => {
+                    if changed.is_err() {
+                        return true;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Start tracking a new run over `total_files` files.
+    pub fn start(&self, total_files: u32) {
+        self.total_files.store(total_files, Ordering::Relaxed);
+        self.indexed_files.store(0, Ordering::Relaxed);
+        self.parse.reset();
+        self.store.reset();
+        self.relations.reset();
+        *self.started.lock().unwrap() = Some(Instant::now());
+        *self.finished.lock().unwrap() = None;
+    }
+
+    pub fn finish(&self) {
+        *self.finished.lock().unwrap() = Some(Instant::now());
+    }
+
+    /// Per-stage throughput of the current (or last) run.
+    pub fn pipeline_stats(&self) -> Option<serde_json::Value> {
+        let started = (*self.started.lock().unwrap())?;
+        let wall = self
+            .finished
+            .lock()
+            .unwrap()
+            .unwrap_or_else(Instant::now)
+            .duration_since(started);
+        Some(serde_json::json!({
+            "elapsed_secs": format!("{:.1}", wall.as_secs_f64()),
+            "parse": self.parse.snapshot(wall),
+            "store": self.store.snapshot(wall),
+            "relations": self.relations.snapshot(wall),
+        }))
+    }
 }
 
 impl Default for IndexMonitor {
@@ -43,6 +279,15 @@ impl Default for IndexMonitor {
         Self {
             total_files: AtomicU32::new(0),
             indexed_files: AtomicU32::new(0),
+            parse: StageMetrics::default(),
+            store: StageMetrics::default(),
+            relations: StageMetrics::default(),
+            started: std::sync::Mutex::new(None),
+            finished: std::sync::Mutex::new(None),
+            running: AtomicBool::new(false),
+            rescan_pending: std::sync::Mutex::new(false),
+            cancel: std::sync::Mutex::new(CancellationToken::new()),
+            paused: watch::channel(false).0,
         }
     }
 }
@@ -94,6 +339,8 @@ pub struct AppState {
     pub embedding_store: Arc<EmbeddingStore>,
     pub embedding_queue: AdaptiveEmbeddingQueue,
     pub progress: IndexProgressTracker,
+    /// Per-project symbol indexes for cross-file resolution
+    pub symbol_indexes: SymbolIndexRegistry,
     /// Semaphore to limit concurrent DB operations (prevents SurrealKV channel exhaustion)
     pub db_semaphore: Arc<Semaphore>,
 }
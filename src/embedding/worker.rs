@@ -246,6 +246,7 @@ mod tests {
                 embedding_store: store,
                 embedding_queue: adaptive_queue,
                 progress: crate::config::IndexProgressTracker::new(),
+                symbol_indexes: crate::codebase::SymbolIndexRegistry::new(),
                 db_semaphore: Arc::new(tokio::sync::Semaphore::new(10)),
             }),
         );
@@ -0,0 +1,415 @@
+use petgraph::algo::astar;
+use petgraph::graph::{DiGraph, EdgeIndex, NodeIndex};
+use petgraph::visit::{EdgeFiltered, EdgeRef};
+use petgraph::Direction as EdgeDirection;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::types::{record_id_string, CodeRelationType, CodeSymbol, Direction, SymbolRelation};
+
+/// Directed graph of symbol relations, nodes keyed by record id
+/// (`code_symbols:...`).
+pub struct SymbolGraph {
+    graph: DiGraph<String, CodeRelationType>,
+    nodes: HashMap<String, NodeIndex>,
+}
+
+/// One node of a call hierarchy. A symbol is expanded at its first (closest)
+/// appearance only; later ones are `repeated` leaves, which also cuts cycles.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HierarchyNode {
+    pub id: String,
+    /// Edge from the parent; `None` at the root
+    pub relation_type: Option<CodeRelationType>,
+    pub repeated: bool,
+    pub children: Vec<HierarchyNode>,
+}
+
+/// A path between two symbols: `nodes[i]` reaches `nodes[i + 1]` through
+/// each of `relation_types[i]`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SymbolPath {
+    pub nodes: Vec<String>,
+    pub relation_types: Vec<Vec<CodeRelationType>>,
+}
+
+impl SymbolGraph {
+    pub fn new(relations: &[SymbolRelation]) -> Self {
+        let mut graph = DiGraph::new();
+        let mut nodes = HashMap::new();
+        let mut edges = HashSet::new();
+        for relation in relations {
+            let [from, to] = [&relation.from_symbol, &relation.to_symbol].map(|thing| {
+                let id = record_id_string(thing);
+                *nodes
+                    .entry(id.clone())
+                    .or_insert_with(|| graph.add_node(id))
+            });
+            if from != to && edges.insert((from, to, relation.relation_type)) {
+                graph.add_edge(from, to, relation.relation_type);
+            }
+        }
+        Self { graph, nodes }
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.nodes.contains_key(id)
+    }
+
+    /// Edges leaving `node` in `direction` (incoming edges lead to callers),
+    /// as (neighbor, relation type), ordered by neighbor id.
+    fn neighbors(
+        &self,
+        node: NodeIndex,
+        direction: Direction,
+    ) -> Vec<(NodeIndex, CodeRelationType)> {
+        let mut neighbors = Vec::new();
+        if matches!(direction, Direction::Outgoing | Direction::Both) {
+            neighbors.extend(
+                self.graph
+                    .edges_directed(node, EdgeDirection::Outgoing)
+                    .map(|e| (e.target(), *e.weight())),
+            );
+        }
+        if matches!(direction, Direction::Incoming | Direction::Both) {
+            neighbors.extend(
+                self.graph
+                    .edges_directed(node, EdgeDirection::Incoming)
+                    .map(|e| (e.source(), *e.weight())),
+            );
+        }
+        neighbors.sort_by(|a, b| self.graph[a.0].cmp(&self.graph[b.0]));
+        neighbors
+    }
+
+    /// Callees (`Outgoing`) or callers (`Incoming`) of `root` as a tree, at
+    /// most `depth` levels and `max_nodes` nodes deep. Returns the tree and
+    /// whether it was cut at `max_nodes`.
+    pub fn hierarchy(
+        &self,
+        root: &str,
+        direction: Direction,
+        depth: usize,
+        max_nodes: usize,
+    ) -> (HierarchyNode, bool) {
+        let Some(&start) = self.nodes.get(root) else {
+            return (self.leaf(root, None, false), false);
+        };
+        // Breadth first, so each symbol is expanded where it is closest
+        let mut children: HashMap<NodeIndex, Vec<(NodeIndex, CodeRelationType, bool)>> =
+            HashMap::new();
+        let mut expanded = HashSet::from([start]);
+        let mut queue = VecDeque::from([(start, 0)]);
+        let mut count = 1;
+        let mut truncated = false;
+        while let Some((node, level)) = queue.pop_front() {
+            if level == depth {
+                continue;
+            }
+            for (next, relation_type) in self.neighbors(node, direction) {
+                if count >= max_nodes {
+                    truncated = true;
+                    break;
+                }
+                count += 1;
+                let first = expanded.insert(next);
+                children
+                    .entry(node)
+                    .or_default()
+                    .push((next, relation_type, !first));
+                if first {
+                    queue.push_back((next, level + 1));
+                }
+            }
+        }
+        (self.build(start, None, false, &mut children), truncated)
+    }
+
+    fn leaf(
+        &self,
+        id: &str,
+        relation_type: Option<CodeRelationType>,
+        repeated: bool,
+    ) -> HierarchyNode {
+        HierarchyNode {
+            id: id.to_string(),
+            relation_type,
+            repeated,
+            children: Vec::new(),
+        }
+    }
+
+    fn build(
+        &self,
+        node: NodeIndex,
+        relation_type: Option<CodeRelationType>,
+        repeated: bool,
+        children: &mut HashMap<NodeIndex, Vec<(NodeIndex, CodeRelationType, bool)>>,
+    ) -> HierarchyNode {
+        let mut tree = self.leaf(&self.graph[node], relation_type, repeated);
+        if !repeated {
+            for (child, relation_type, repeated) in children.remove(&node).unwrap_or_default() {
+                tree.children
+                    .push(self.build(child, Some(relation_type), repeated, children));
+            }
+        }
+        tree
+    }
+
+    /// Up to `k` shortest paths from `from` to `to` of at most `max_len`
+    /// edges, shortest first (Yen's algorithm over A* searches).
+    pub fn paths(&self, from: &str, to: &str, max_len: usize, k: usize) -> Vec<SymbolPath> {
+        let (Some(&start), Some(&goal)) = (self.nodes.get(from), self.nodes.get(to)) else {
+            return Vec::new();
+        };
+        let shortest = |removed_nodes: &HashSet<NodeIndex>,
+                        removed_edges: &HashSet<EdgeIndex>,
+                        spur: NodeIndex| {
+            let view = EdgeFiltered::from_fn(&self.graph, |e| {
+                !removed_edges.contains(&e.id())
+                    && !removed_nodes.contains(&e.source())
+                    && !removed_nodes.contains(&e.target())
+            });
+            astar(&view, spur, |n| n == goal, |_| 1usize, |_| 0).map(|(_, path)| path)
+        };
+
+        let mut found: Vec<Vec<NodeIndex>> = Vec::new();
+        match shortest(&HashSet::new(), &HashSet::new(), start) {
+            Some(path) if path.len() > 1 && path.len() - 1 <= max_len => found.push(path),
+            _ => return Vec::new(),
+        }
+        let mut candidates: Vec<Vec<NodeIndex>> = Vec::new();
+        while found.len() < k {
+            let last = found.last().unwrap().clone();
+            for i in 0..last.len() - 1 {
+                let root = &last[..=i];
+                // Leave the paths sharing this root, through every relation
+                // type linking the hop, and never revisit the root
+                let removed_edges: HashSet<EdgeIndex> = found
+                    .iter()
+                    .filter(|p| p.len() > i + 1 && &p[..=i] == root)
+                    .flat_map(|p| self.graph.edges_connecting(p[i], p[i + 1]))
+                    .map(|e| e.id())
+                    .collect();
+                let removed_nodes: HashSet<NodeIndex> = root[..i].iter().copied().collect();
+                let Some(spur) = shortest(&removed_nodes, &removed_edges, last[i]) else {
+                    continue;
+                };
+                let path: Vec<NodeIndex> = root[..i].iter().copied().chain(spur).collect();
+                if path.len() - 1 <= max_len
+                    && !found.contains(&path)
+                    && !candidates.contains(&path)
+                {
+                    candidates.push(path);
+                }
+            }
+            let Some(best) = (0..candidates.len()).min_by_key(|&i| candidates[i].len()) else {
+                break;
+            };
+            found.push(candidates.swap_remove(best));
+        }
+
+        found
+            .into_iter()
+            .map(|path| SymbolPath {
+                relation_types: path
+                    .windows(2)
+                    .map(|w| {
+                        let mut types: Vec<CodeRelationType> = self
+                            .graph
+                            .edges_connecting(w[0], w[1])
+                            .map(|e| *e.weight())
+                            .collect();
+                        types.sort_by_key(|t| t.to_string());
+                        types
+                    })
+                    .collect(),
+                nodes: path.into_iter().map(|n| self.graph[n].clone()).collect(),
+            })
+            .collect()
+    }
+
+    /// Every symbol reachable from `seeds` in `direction` within `depth`
+    /// hops (unlimited when `None`), with its distance. Seeds are excluded.
+    pub fn reachable(
+        &self,
+        seeds: &[String],
+        direction: Direction,
+        depth: Option<usize>,
+    ) -> Vec<(String, usize)> {
+        let mut seen: HashSet<NodeIndex> = seeds
+            .iter()
+            .filter_map(|s| self.nodes.get(s).copied())
+            .collect();
+        let mut queue: VecDeque<(NodeIndex, usize)> = seen.iter().map(|&n| (n, 0)).collect();
+        let mut out = Vec::new();
+        while let Some((node, distance)) = queue.pop_front() {
+            if depth.is_some_and(|d| distance >= d) {
+                continue;
+            }
+            for (next, _) in self.neighbors(node, direction) {
+                if seen.insert(next) {
+                    out.push((self.graph[next].clone(), distance + 1));
+                    queue.push_back((next, distance + 1));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Whether a symbol is a test, by test-file naming (`tests/`, `test_*.py`,
+/// `*_test.go`, `*.spec.ts`, ...) or test naming of the symbol or its
+/// enclosing module (`test_*`, `TestFoo`, `mod tests`).
+pub fn is_test_symbol(symbol: &CodeSymbol) -> bool {
+    let path = symbol.file_path.to_lowercase();
+    let file = path.rsplit('/').next().unwrap_or(&path);
+    let stem = file.split('.').next().unwrap_or(file);
+    let test_file = path
+        .split('/')
+        .any(|dir| matches!(dir, "test" | "tests" | "__tests__" | "spec"))
+        || stem.starts_with("test_")
+        || stem.ends_with("_test")
+        || stem.ends_with("_tests")
+        || stem.ends_with("test") && file.ends_with(".java")
+        || file.contains(".test.")
+        || file.contains(".spec.");
+    let test_name = symbol.name.starts_with("test_")
+        || symbol.name.starts_with("Test")
+            && symbol.name[4..].starts_with(|c: char| c.is_uppercase() || c == '_')
+        || symbol.name.starts_with("test")
+            && symbol.name[4..].starts_with(|c: char| c.is_uppercase());
+    let in_test_module = symbol.parent.as_deref().is_some_and(|parent| {
+        parent
+            .split([':', '.'])
+            .any(|part| part == "tests" || part == "test")
+    });
+    test_file || test_name || in_test_module
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{edge, symbol};
+    use crate::types::SymbolType;
+
+    fn id(key: &str) -> String {
+        format!("code_symbols:{}", key)
+    }
+
+    // main -> a -> c -> d, main -> b -> c, b -> d, d -> a (cycle)
+    fn graph() -> SymbolGraph {
+        use CodeRelationType::Calls;
+        SymbolGraph::new(&[
+            edge("main", "a", Calls),
+            edge("main", "b", Calls),
+            edge("a", "c", Calls),
+            edge("b", "c", Calls),
+            edge("c", "d", Calls),
+            edge("b", "d", Calls),
+            edge("d", "a", Calls),
+        ])
+    }
+
+    #[test]
+    fn test_hierarchy() {
+        let graph = graph();
+        let (tree, truncated) = graph.hierarchy(&id("main"), Direction::Outgoing, 5, 100);
+        assert!(!truncated);
+        let names: Vec<&str> = tree.children.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(names, vec![id("a"), id("b")]);
+        // c is expanded under a and repeated under b; d, two calls away
+        // through b, is expanded there and its call back to a is a leaf
+        let (a, b) = (&tree.children[0], &tree.children[1]);
+        assert_eq!(a.children[0].id, id("c"));
+        assert!(a.children[0].children[0].repeated);
+        assert!(b.children[0].repeated && b.children[0].id == id("c"));
+        let d = &b.children[1];
+        assert!(!d.repeated && d.id == id("d"));
+        let back = &d.children[0];
+        assert!(back.repeated && back.id == id("a") && back.children.is_empty());
+
+        let (callers, _) = graph.hierarchy(&id("c"), Direction::Incoming, 1, 100);
+        assert_eq!(callers.children.len(), 2);
+        let (_, truncated) = graph.hierarchy(&id("main"), Direction::Outgoing, 5, 3);
+        assert!(truncated);
+    }
+
+    #[test]
+    fn test_paths() {
+        let graph = graph();
+        let paths = graph.paths(&id("main"), &id("d"), 10, 5);
+        let nodes: Vec<Vec<String>> = paths.iter().map(|p| p.nodes.clone()).collect();
+        assert_eq!(nodes[0], vec![id("main"), id("b"), id("d")]);
+        assert_eq!(nodes.len(), 3);
+        assert!(nodes[1..].iter().all(|p| p.len() == 4));
+        assert_eq!(
+            paths[0].relation_types,
+            vec![vec![CodeRelationType::Calls]; 2]
+        );
+
+        assert_eq!(graph.paths(&id("main"), &id("d"), 2, 5).len(), 1);
+        assert!(graph.paths(&id("d"), &id("main"), 10, 5).is_empty());
+    }
+
+    #[test]
+    fn test_paths_over_parallel_edges() {
+        use CodeRelationType::{Calls, References};
+        // a both calls and references b; the detour a -> c -> b is the
+        // second path, not the first one again through the other edge
+        let graph = SymbolGraph::new(&[
+            edge("a", "b", Calls),
+            edge("a", "b", References),
+            edge("a", "c", Calls),
+            edge("c", "b", Calls),
+        ]);
+        let paths = graph.paths(&id("a"), &id("b"), 10, 5);
+        let nodes: Vec<Vec<String>> = paths.iter().map(|p| p.nodes.clone()).collect();
+        assert_eq!(
+            nodes,
+            vec![vec![id("a"), id("b")], vec![id("a"), id("c"), id("b")]]
+        );
+        assert_eq!(paths[0].relation_types, vec![vec![Calls, References]]);
+        assert_eq!(paths[1].relation_types, vec![vec![Calls]; 2]);
+    }
+
+    #[test]
+    fn test_reachable_and_tests() {
+        let graph = graph();
+        let mut callers = graph.reachable(&[id("c")], Direction::Incoming, None);
+        callers.sort();
+        assert_eq!(
+            callers,
+            vec![(id("a"), 1), (id("b"), 1), (id("d"), 2), (id("main"), 2)]
+        );
+        assert_eq!(
+            graph
+                .reachable(&[id("c")], Direction::Incoming, Some(1))
+                .len(),
+            2
+        );
+
+        let symbol = |name: &str, path: &str, parent: Option<&str>| {
+            let mut s = symbol(name, name, SymbolType::Function, (1, 2));
+            s.file_path = path.to_string();
+            s.parent = parent.map(str::to_string);
+            s
+        };
+        assert!(is_test_symbol(&symbol("run", "tests/api.rs", None)));
+        assert!(is_test_symbol(&symbol("run", "src/auth_test.go", None)));
+        assert!(is_test_symbol(&symbol("run", "web/login.spec.ts", None)));
+        assert!(is_test_symbol(&symbol("TestLogin", "auth.go", None)));
+        assert!(is_test_symbol(&symbol(
+            "login",
+            "src/auth.rs",
+            Some("auth::tests")
+        )));
+        assert!(!is_test_symbol(&symbol(
+            "login",
+            "src/auth.rs",
+            Some("auth")
+        )));
+        assert!(!is_test_symbol(&symbol("Testament", "src/latest.rs", None)));
+        assert!(!is_test_symbol(&symbol("testify", "src/contest.py", None)));
+    }
+}
@@ -2,19 +2,23 @@
 //!
 //! - `rrf`: Reciprocal Rank Fusion for hybrid search merging
 //! - `ppr`: Personalized PageRank for graph-aware ranking
+//! - `callgraph`: call hierarchies, call paths and change impact over code symbols
 
+pub mod callgraph;
 pub mod leiden;
 pub mod ppr;
 pub mod rrf;
 pub mod traversal;
 
+pub use callgraph::{is_test_symbol, HierarchyNode, SymbolGraph, SymbolPath};
 pub use leiden::detect_communities;
 pub use ppr::{
     apply_hub_dampening, personalized_page_rank, PPR_DAMPING, PPR_MAX_ITER, PPR_TOLERANCE,
 };
 pub use rrf::{
-    rrf_merge, RrfScores, DEFAULT_BM25_WEIGHT, DEFAULT_CODE_BM25_WEIGHT, DEFAULT_CODE_PPR_WEIGHT,
-    DEFAULT_CODE_VECTOR_WEIGHT, DEFAULT_PPR_WEIGHT, DEFAULT_VECTOR_WEIGHT, RRF_K,
+    rrf_merge, RrfScores, DEFAULT_BM25_WEIGHT, DEFAULT_CODE_BM25_WEIGHT,
+    DEFAULT_CODE_HISTORY_WEIGHT, DEFAULT_CODE_PPR_WEIGHT, DEFAULT_CODE_VECTOR_WEIGHT,
+    DEFAULT_PPR_WEIGHT, DEFAULT_VECTOR_WEIGHT, RRF_K,
 };
 pub use traversal::{
     GraphTraversalStorage, GraphTraverser, TraversalConfig, TraversalResult, TraversalStrategy,
@@ -17,6 +17,8 @@ pub const DEFAULT_PPR_WEIGHT: f32 = 0.45;
 pub const DEFAULT_CODE_VECTOR_WEIGHT: f32 = 0.50;
 pub const DEFAULT_CODE_BM25_WEIGHT: f32 = 0.20;
 pub const DEFAULT_CODE_PPR_WEIGHT: f32 = 0.30;
+/// Boost for files that change often (recall_code), when history is indexed
+pub const DEFAULT_CODE_HISTORY_WEIGHT: f32 = 0.10;
 
 /// Individual score components for a merged result
 #[derive(Debug, Clone, Default)]
@@ -4,7 +4,9 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
-use memory_mcp::config::{AppConfig, AppState};
+use memory_mcp::codebase::embed_text::EmbeddingTextConfig;
+use memory_mcp::codebase::{PathMap, WatchMode};
+use memory_mcp::config::{AppConfig, AppState, IndexBudget};
 use memory_mcp::embedding::{
     EmbeddingConfig, EmbeddingService, EmbeddingStore, EmbeddingWorker, ModelType,
 };
@@ -45,8 +47,56 @@ struct Cli {
     #[arg(long, env, default_value = "0")]
     idle_timeout: u64,
 
+    /// Context prepended to code before embedding: comma-separated subset of
+    /// path,language,container,doc, or "none" to embed raw code.
+    #[arg(
+        long,
+        env = "EMBEDDING_CONTEXT",
+        default_value = "path,language,container,doc"
+    )]
+    embedding_context: String,
+
+    /// How the codebase watcher detects changes: auto, native or poll.
+    /// auto polls on Docker Desktop bind mounts and network filesystems,
+    /// where native events don't arrive.
+    #[arg(long, env = "WATCH_MODE", default_value = "auto")]
+    watch_mode: String,
+
+    /// Maximum parser threads used while indexing. Defaults to all cores.
+    #[arg(long, env = "INDEX_THREADS")]
+    index_threads: Option<usize>,
+
+    /// Parse only on otherwise idle CPU time so indexing never competes with
+    /// the editor (Linux).
+    #[arg(long, env = "INDEX_IDLE")]
+    index_idle: bool,
+
+    /// Skip files larger than this many bytes when indexing. 0 = no limit.
+    #[arg(long, env = "MAX_FILE_SIZE", default_value_t = memory_mcp::config::DEFAULT_MAX_FILE_SIZE)]
+    max_file_size: u64,
+
+    /// Index this many commits of git history (messages, authors, changed
+    /// files) for search and file churn. 0 = off.
+    #[arg(long, env = "HISTORY_DEPTH", default_value_t = 0)]
+    history_depth: usize,
+
+    /// Map server paths to host paths in results, as comma-separated
+    /// server=host prefixes (e.g. /project=/Users/me/repo). index_project
+    /// accepts either form.
+    #[arg(long, env = "PATH_MAP", default_value = "")]
+    path_map: String,
+
     #[arg(long)]
     list_models: bool,
+
+    /// Import a SCIP index (index.scip) into --project-id, wait for its
+    /// embeddings and exit
+    #[arg(long, requires = "project_id")]
+    import_scip: Option<PathBuf>,
+
+    /// Indexed project to import into
+    #[arg(long)]
+    project_id: Option<String>,
 }
 
 fn default_data_dir() -> PathBuf {
@@ -96,6 +146,18 @@ async fn main() -> anyhow::Result<()> {
     );
 
     let model: ModelType = cli.model.parse().map_err(|e: String| anyhow::anyhow!(e))?;
+    let embedding_text: EmbeddingTextConfig = cli
+        .embedding_context
+        .parse()
+        .map_err(|e: String| anyhow::anyhow!(e))?;
+    let watch_mode: WatchMode = cli
+        .watch_mode
+        .parse()
+        .map_err(|e: String| anyhow::anyhow!(e))?;
+    let path_map: PathMap = cli
+        .path_map
+        .parse()
+        .map_err(|e: String| anyhow::anyhow!(e))?;
 
     if model.requires_license_agreement() {
         tracing::warn!(
@@ -145,12 +207,22 @@ async fn main() -> anyhow::Result<()> {
             batch_size: cli.batch_size,
             timeout_ms: cli.timeout,
             log_level: cli.log_level,
+            embedding_text,
+            watch_mode,
+            index_budget: IndexBudget {
+                threads: cli.index_threads.filter(|&n| n > 0),
+                idle_only: cli.index_idle,
+            },
+            path_map,
+            max_file_size: cli.max_file_size,
+            history_depth: cli.history_depth,
         },
         storage: storage.clone(),
         embedding: embedding.clone(),
         embedding_store: embedding_store.clone(),
         embedding_queue: adaptive_queue,
         progress: memory_mcp::config::IndexProgressTracker::new(),
+        symbol_indexes: memory_mcp::codebase::SymbolIndexRegistry::new(),
         db_semaphore: Arc::new(tokio::sync::Semaphore::new(10)),
     });
 
@@ -170,6 +242,16 @@ async fn main() -> anyhow::Result<()> {
     let monitor_state = state.clone();
     tokio::spawn(memory_mcp::embedding::run_completion_monitor(monitor_state));
 
+    if let (Some(path), Some(project_id)) = (&cli.import_scip, &cli.project_id) {
+        let stats = memory_mcp::codebase::import_scip(state.clone(), project_id, path).await?;
+        while state.embedding_queue.metrics().get_queue_depth() > 0 {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        state.storage.shutdown().await?;
+        return Ok(());
+    }
+
     let server = MemoryMcpServer::new(state.clone());
 
     // Auto-start codebase manager if /project exists
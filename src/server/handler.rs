@@ -200,7 +200,7 @@ impl MemoryMcpServer {
     }
 
     #[tool(
-        description = "Index a project directory for code search. Returns status if already indexed. Use delete_project to re-index. TIP: Use path='/project' for Docker environments."
+        description = "Index a project directory for code search. Returns status if already indexed. Use delete_project to re-index. TIP: Use path='/project' for Docker environments; host paths covered by --path-map are accepted too. Pass git_ref to index a branch/tag/commit of a git repository side by side with the working tree (project '<name>@<git_ref>'; unchanged content reuses embeddings)."
     )]
     async fn index_project(
         &self,
@@ -211,6 +211,18 @@ impl MemoryMcpServer {
             .map_err(to_rpc_error)
     }
 
+    #[tool(
+        description = "Import a SCIP index (`index.scip` from rust-analyzer, scip-typescript, scip-java, ...) into an indexed project. Symbols and call/reference/implements edges of the covered files are replaced with the compiler's, marked `source: scip`; they take precedence over tree-sitter until a covered file changes and is reindexed."
+    )]
+    async fn import_scip(
+        &self,
+        params: Parameters<ImportScipParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        logic::code::import_scip(&self.state, params.0)
+            .await
+            .map_err(to_rpc_error)
+    }
+
     #[tool(
         description = "Search indexed code using semantic similarity. Requires project to be fully indexed. Returns matching code chunks."
     )]
@@ -224,7 +236,7 @@ impl MemoryMcpServer {
     }
 
     #[tool(
-        description = "Best quality code retrieval. Combines semantic vectors, keyword matching (BM25), and code symbol graph analysis (calls/imports/inheritance) using Reciprocal Rank Fusion. Returns ranked code chunks with score breakdown per channel. Use as default for any code retrieval task."
+        description = "Best quality code retrieval. Combines semantic vectors, keyword matching (BM25), and code symbol graph analysis (calls/imports/inheritance) using Reciprocal Rank Fusion. Returns ranked code chunks with score breakdown per channel; with git history indexed, frequently changed files rank higher and results carry their change history. Use as default for any code retrieval task."
     )]
     async fn recall_code(
         &self,
@@ -236,7 +248,55 @@ impl MemoryMcpServer {
     }
 
     #[tool(
-        description = "Get indexing status for a project. Statuses: indexing, completed, failed."
+        description = "Code similar to a chunk (`chunk_id` from a search result) or to a `snippet`, by embedding similarity above `threshold` (default 0.8). Each result carries `score` and `token_similarity`, the share of tokens it has in common with the query code."
+    )]
+    async fn find_similar_code(
+        &self,
+        params: Parameters<FindSimilarCodeParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        logic::analysis::find_similar_code(&self.state, params.0)
+            .await
+            .map_err(to_rpc_error)
+    }
+
+    #[tool(
+        description = "Copy-pasted code in a project: clusters of chunks whose embeddings are at least `threshold` similar (default 0.92), each pair confirmed by token overlap (`min_token_similarity`, default 0.5). Clusters list file and line ranges, largest first. At most 5000 chunks, the first in file order, are compared (`compared_chunks`, `chunks_truncated`)."
+    )]
+    async fn find_clones(
+        &self,
+        params: Parameters<FindClonesParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        logic::analysis::find_clones(&self.state, params.0)
+            .await
+            .map_err(to_rpc_error)
+    }
+
+    #[tool(
+        description = "Search git commit messages by keyword to find why code changed. Returns hash, author, date, message, changed files and changed symbols. Requires HISTORY_DEPTH > 0."
+    )]
+    async fn search_commits(
+        &self,
+        params: Parameters<SearchCommitsParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        logic::history::search_commits(&self.state, params.0)
+            .await
+            .map_err(to_rpc_error)
+    }
+
+    #[tool(
+        description = "Change history of a file (optionally narrowed to one symbol): commits newest first, plus churn (commit count, authors, last modified). Requires HISTORY_DEPTH > 0."
+    )]
+    async fn get_file_history(
+        &self,
+        params: Parameters<GetFileHistoryParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        logic::history::get_file_history(&self.state, params.0)
+            .await
+            .map_err(to_rpc_error)
+    }
+
+    #[tool(
+        description = "Get indexing status for a project. Statuses: indexing, paused, cancelled, embedding_pending, completed, failed. For git repositories, includes the indexed commit and branch. Includes per-stage throughput (parse, store, relations) of the current or last run."
     )]
     async fn get_index_status(
         &self,
@@ -267,6 +327,40 @@ impl MemoryMcpServer {
             .map_err(to_rpc_error)
     }
 
+    #[tool(
+        description = "Pause a running index_project job after the files in flight are stored. Use resume_indexing to continue."
+    )]
+    async fn pause_indexing(
+        &self,
+        params: Parameters<PauseIndexingParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        logic::code::pause_indexing(&self.state, params.0)
+            .await
+            .map_err(to_rpc_error)
+    }
+
+    #[tool(description = "Resume a paused index_project job.")]
+    async fn resume_indexing(
+        &self,
+        params: Parameters<ResumeIndexingParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        logic::code::resume_indexing(&self.state, params.0)
+            .await
+            .map_err(to_rpc_error)
+    }
+
+    #[tool(
+        description = "Stop a running index_project job (e.g. one started on the wrong directory). Files already indexed are kept; calling index_project again continues where it stopped."
+    )]
+    async fn cancel_indexing(
+        &self,
+        params: Parameters<CancelIndexingParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        logic::code::cancel_indexing(&self.state, params.0)
+            .await
+            .map_err(to_rpc_error)
+    }
+
     #[tool(description = "Delete a project and all its indexed code chunks.")]
     async fn delete_project(
         &self,
@@ -277,7 +371,9 @@ impl MemoryMcpServer {
             .map_err(to_rpc_error)
     }
 
-    #[tool(description = "Search for code symbols (functions, classes) by name.")]
+    #[tool(
+        description = "Search for code symbols (functions, classes) by name, qualified name (e.g. `auth::TokenValidator::check`), signature or doc comment. Results include the qualified name, parent and doc comment. `file_path` is relative to the project root (as is `path_prefix`); `absolute_path` is the path on the client's machine."
+    )]
     async fn search_symbols(
         &self,
         params: Parameters<SearchSymbolsParams>,
@@ -297,6 +393,66 @@ impl MemoryMcpServer {
             .map_err(to_rpc_error)
     }
 
+    #[tool(
+        description = "Transitive call hierarchy of a symbol as a tree: callers (`direction: incoming`, default) or callees (`outgoing`), `depth` levels deep (default 3). `relation_types` picks the edges followed (default calls; also references, implements, extends, imports, contains). A symbol reached again is a `repeated` leaf."
+    )]
+    async fn get_call_hierarchy(
+        &self,
+        params: Parameters<GetCallHierarchyParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        logic::navigation::get_call_hierarchy(&self.state, params.0)
+            .await
+            .map_err(to_rpc_error)
+    }
+
+    #[tool(
+        description = "How one symbol reaches another: the shortest call paths from `from_symbol_id` to `to_symbol_id` (up to `max_paths`, each at most `max_length` edges), shortest first."
+    )]
+    async fn find_call_paths(
+        &self,
+        params: Parameters<FindCallPathsParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        logic::navigation::find_call_paths(&self.state, params.0)
+            .await
+            .map_err(to_rpc_error)
+    }
+
+    #[tool(
+        description = "Type hierarchy of a trait, interface or class as trees over implements/extends edges: `supertypes`, `subtypes` or `both` (default), `depth` levels deep (default 3). Each type lists the methods it defines; a method whose name matches a member of a supertype on the path carries `overrides` (in the supertype tree, members carry `overridden_by`). A Rust implementor is reported as its struct or enum, with the `impl` block as `location`."
+    )]
+    async fn get_type_hierarchy(
+        &self,
+        params: Parameters<GetTypeHierarchyParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        logic::navigation::get_type_hierarchy(&self.state, params.0)
+            .await
+            .map_err(to_rpc_error)
+    }
+
+    #[tool(
+        description = "Dead code report: functions, methods, types and traits with no inbound calls, imports, implements, extends or references edges, grouped by file. Entry points (`main`, or `entry_points`), tests, public API, trait impls and framework-annotated symbols are excluded unless the matching `include_*` flag is set. `confidence` reflects how many of the project's references resolved unambiguously."
+    )]
+    async fn find_unreferenced_symbols(
+        &self,
+        params: Parameters<FindUnreferencedSymbolsParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        logic::analysis::find_unreferenced_symbols(&self.state, params.0)
+            .await
+            .map_err(to_rpc_error)
+    }
+
+    #[tool(
+        description = "Impact analysis: every symbol transitively affected by changing the given `files` or `symbol_ids` (their callers, referencers, implementors and importers), with distance. `tests` lists the affected tests to run."
+    )]
+    async fn impact_of_change(
+        &self,
+        params: Parameters<ImpactOfChangeParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        logic::navigation::impact_of_change(&self.state, params.0)
+            .await
+            .map_err(to_rpc_error)
+    }
+
     #[tool(description = "Find all symbols called by a given symbol.")]
     async fn get_callees(
         &self,
@@ -307,6 +463,30 @@ impl MemoryMcpServer {
             .map_err(to_rpc_error)
     }
 
+    #[tool(
+        description = "Go to definition: the symbol referenced (or defined) at a file position. `line` is 1-based, `column` 0-based. References to libraries return the name, `reason` (external, ambiguous, not_found) and module instead."
+    )]
+    async fn find_definition(
+        &self,
+        params: Parameters<FindDefinitionParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        logic::navigation::find_definition(&self.state, params.0)
+            .await
+            .map_err(to_rpc_error)
+    }
+
+    #[tool(
+        description = "Find every reference to a symbol, with file, line and column. Give `symbol_id`, or `project_id` with `file_path`, `line` (1-based) and `column` (0-based) of a use or the definition."
+    )]
+    async fn find_references(
+        &self,
+        params: Parameters<FindReferencesParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        logic::navigation::find_references(&self.state, params.0)
+            .await
+            .map_err(to_rpc_error)
+    }
+
     #[tool(description = "Get related symbols (functions, classes) via graph traversal.")]
     async fn get_related_symbols(
         &self,
@@ -318,7 +498,65 @@ impl MemoryMcpServer {
     }
 
     #[tool(
-        description = "Get detailed statistics for an indexed project including symbol/chunk counts and embedding progress."
+        description = "Repository map for getting oriented in a project: the most important files and their key signatures, ranked by PageRank over the symbol graph and cut to `max_tokens` (default 1024). `focus_paths` ranks toward what those files or directories use. `communities` groups files that work together, with their top files and symbols."
+    )]
+    async fn get_repo_map(
+        &self,
+        params: Parameters<GetRepoMapParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        logic::analysis::get_repo_map(&self.state, params.0)
+            .await
+            .map_err(to_rpc_error)
+    }
+
+    #[tool(
+        description = "Outline of one file: its symbols nested by enclosing type or module, with signatures and line ranges. Imports are left out."
+    )]
+    async fn get_file_outline(
+        &self,
+        params: Parameters<GetFileOutlineParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        logic::source::get_file_outline(&self.state, params.0)
+            .await
+            .map_err(to_rpc_error)
+    }
+
+    #[tool(
+        description = "Source text of a file or a line range of it, for when the file can't be read directly (e.g. the server runs in a container). Read from the project root, or pieced together from the indexed chunks when the root is not readable; `source` says which."
+    )]
+    async fn get_code(
+        &self,
+        params: Parameters<GetCodeParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        logic::source::get_code(&self.state, params.0)
+            .await
+            .map_err(to_rpc_error)
+    }
+
+    #[tool(description = "Get an indexed code chunk by id, with its stored source text.")]
+    async fn get_chunk(
+        &self,
+        params: Parameters<GetChunkParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        logic::source::get_chunk(&self.state, params.0)
+            .await
+            .map_err(to_rpc_error)
+    }
+
+    #[tool(
+        description = "Source text of a symbol's definition, read like get_code over the symbol's line range."
+    )]
+    async fn get_symbol_source(
+        &self,
+        params: Parameters<GetSymbolSourceParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        logic::source::get_symbol_source(&self.state, params.0)
+            .await
+            .map_err(to_rpc_error)
+    }
+
+    #[tool(
+        description = "Get detailed statistics for an indexed project including symbol/chunk counts and embedding progress. Lists files left out of the index with the reason (too_large, generated, minified, binary)."
     )]
     async fn get_project_stats(
         &self,
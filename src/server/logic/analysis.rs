@@ -0,0 +1,687 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use rmcp::model::CallToolResult;
+use serde_json::json;
+
+use crate::config::AppState;
+use crate::server::params::{
+    FindClonesParams, FindSimilarCodeParams, FindUnreferencedSymbolsParams, GetRepoMapParams,
+};
+use crate::storage::StorageBackend;
+use crate::types::{CodeRelationType, CodeSymbol, SymbolType};
+
+use super::code::HostPaths;
+use super::{error_response, normalize_limit, success_json};
+
+pub async fn find_similar_code(
+    state: &Arc<AppState>,
+    params: FindSimilarCodeParams,
+) -> anyhow::Result<CallToolResult> {
+    use crate::codebase::clones::{token_counts, token_similarity, tokens};
+
+    let threshold = params.threshold.unwrap_or(0.8);
+    let limit = normalize_limit(params.limit);
+    let (source, embedding) = match (&params.chunk_id, &params.snippet) {
+        (Some(id), None) => match state.storage.get_code_chunk(id).await {
+            Ok(Some(chunk)) => match chunk.embedding.clone() {
+                Some(embedding) => (Some(chunk), embedding),
+                None => return Ok(error_response("Chunk is not embedded yet")),
+            },
+            Ok(None) => return Ok(error_response(format!("Chunk not found: {}", id))),
+            Err(e) => return Ok(error_response(e)),
+        },
+        (None, Some(snippet)) => {
+            crate::ensure_embedding_ready!(state);
+            (None, state.embedding.embed(snippet).await?)
+        }
+        _ => return Ok(error_response("Pass exactly one of chunk_id or snippet")),
+    };
+    let text = match &source {
+        Some(chunk) => chunk.content.as_str(),
+        None => params.snippet.as_deref().unwrap_or_default(),
+    };
+    let query_tokens = tokens(text);
+    let query_counts = token_counts(&query_tokens);
+
+    // One extra for the source chunk, which finds itself
+    let mut results = match state
+        .storage
+        .vector_search_code(&embedding, params.project_id.as_deref(), limit + 1)
+        .await
+    {
+        Ok(results) => results,
+        Err(e) => return Ok(error_response(e)),
+    };
+    results.retain(|r| {
+        r.score >= threshold
+            && source.as_ref().is_none_or(|chunk| {
+                !(chunk.file_path == r.file_path
+                    && chunk.project_id == r.project_id
+                    && chunk.start_line <= r.end_line
+                    && r.start_line <= chunk.end_line)
+            })
+    });
+    results.truncate(limit);
+    HostPaths::new(state).fill_chunks(&mut results).await;
+
+    let results: Vec<serde_json::Value> = results
+        .iter()
+        .map(|r| {
+            let mut value = json!(r);
+            let counts = token_counts(&tokens(&r.content));
+            value["token_similarity"] = json!(token_similarity(&query_counts, &counts));
+            value
+        })
+        .collect();
+    Ok(success_json(json!({
+        "results": results,
+        "count": results.len(),
+        "threshold": threshold,
+    })))
+}
+
+/// Most chunks `find_clones` compares pairwise.
+const MAX_CLONE_CHUNKS: usize = 5000;
+
+pub async fn find_clones(
+    state: &Arc<AppState>,
+    params: FindClonesParams,
+) -> anyhow::Result<CallToolResult> {
+    let threshold = params.threshold.unwrap_or(0.92);
+    let min_token_similarity = params.min_token_similarity.unwrap_or(0.5).clamp(0.0, 1.0);
+    let min_lines = params.min_lines.unwrap_or(5);
+    let limit = normalize_limit(params.limit);
+    let embedded = match state
+        .storage
+        .count_embedded_chunks(&params.project_id)
+        .await
+    {
+        Ok(0) => {
+            return Ok(error_response(format!(
+                "No embedded chunks for project: {}",
+                params.project_id
+            )))
+        }
+        Ok(count) => count,
+        Err(e) => return Ok(error_response(e)),
+    };
+    // Every pair is compared, so only the first chunks in file order are
+    // loaded; one more tells whether any were left out
+    let mut chunks = match state
+        .storage
+        .get_embedded_chunks(&params.project_id, min_lines, MAX_CLONE_CHUNKS + 1)
+        .await
+    {
+        Ok(chunks) => chunks,
+        Err(e) => return Ok(error_response(e)),
+    };
+    let chunks_truncated = chunks.len() > MAX_CLONE_CHUNKS;
+    chunks.truncate(MAX_CLONE_CHUNKS);
+
+    let (chunks, groups) = tokio::task::spawn_blocking(move || {
+        let groups = crate::codebase::clones::find_clone_groups(
+            &chunks,
+            threshold,
+            min_token_similarity,
+            min_lines,
+        );
+        (chunks, groups)
+    })
+    .await?;
+
+    let mut paths = HostPaths::new(state);
+    let mut clusters = Vec::new();
+    for group in groups.iter().take(limit) {
+        let mut members = Vec::new();
+        for &i in &group.members {
+            let chunk = &chunks[i];
+            members.push(json!({
+                "id": chunk.id.as_ref().map(|id| crate::types::record_key_to_string(&id.key)),
+                "file_path": chunk.file_path,
+                "absolute_path": paths.absolute(&params.project_id, &chunk.file_path).await,
+                "start_line": chunk.start_line,
+                "end_line": chunk.end_line,
+                "name": chunk.name,
+            }));
+        }
+        clusters.push(json!({
+            "size": members.len(),
+            "similarity": group.similarity,
+            "token_similarity": group.token_similarity,
+            "chunks": members,
+        }));
+    }
+    Ok(success_json(json!({
+        "project_id": params.project_id,
+        "clusters": clusters,
+        "count": groups.len(),
+        "embedded_chunks": embedded,
+        "compared_chunks": chunks.len(),
+        "chunks_truncated": chunks_truncated,
+        "truncated": groups.len() > limit,
+    })))
+}
+
+pub async fn find_unreferenced_symbols(
+    state: &Arc<AppState>,
+    params: FindUnreferencedSymbolsParams,
+) -> anyhow::Result<CallToolResult> {
+    use crate::codebase::deadcode::{self, Confidence, DeadCodeOptions, USE_RELATIONS};
+    use crate::codebase::source::Source;
+
+    let limit = params.limit.unwrap_or(200).clamp(1, 1000);
+    let symbols = match state.storage.get_project_symbols(&params.project_id).await {
+        Ok(symbols) if symbols.is_empty() => {
+            return Ok(error_response(format!(
+                "No indexed symbols for project: {}",
+                params.project_id
+            )))
+        }
+        Ok(symbols) => symbols,
+        Err(e) => return Ok(error_response(e)),
+    };
+    let mut types = USE_RELATIONS.to_vec();
+    types.push(CodeRelationType::Contains);
+    let relations = match state
+        .storage
+        .get_project_relations(&params.project_id, &types)
+        .await
+    {
+        Ok(relations) => relations,
+        Err(e) => return Ok(error_response(e)),
+    };
+    let ambiguous = match state
+        .storage
+        .get_unresolved_names(&params.project_id, "ambiguous")
+        .await
+    {
+        Ok(names) => names,
+        Err(e) => return Ok(error_response(e)),
+    };
+    let ambiguous_count = match state
+        .storage
+        .count_unresolved_references(&params.project_id)
+        .await
+    {
+        Ok(counts) => counts
+            .into_iter()
+            .find(|(reason, _)| reason == "ambiguous")
+            .map_or(0, |(_, count)| count as usize),
+        Err(e) => return Ok(error_response(e)),
+    };
+    let resolved = relations
+        .iter()
+        .filter(|r| r.relation_type != CodeRelationType::Contains)
+        .count();
+    let confidence = Confidence::from_resolution(resolved, ambiguous_count);
+
+    // Declaration lines and annotations come from the files themselves
+    let mut paths = HostPaths::new(state);
+    let sources = match paths.root(&params.project_id).await {
+        Some(root) => {
+            let files: std::collections::BTreeSet<String> =
+                symbols.iter().map(|s| s.file_path.clone()).collect();
+            tokio::task::spawn_blocking(move || {
+                files
+                    .into_iter()
+                    .filter_map(|file| {
+                        let path = crate::codebase::server_path(&root, &file);
+                        match crate::codebase::source::read_source(&path, 0) {
+                            Ok(Source::Text(text)) => Some((file, text)),
+                            _ => None,
+                        }
+                    })
+                    .collect()
+            })
+            .await?
+        }
+        None => HashMap::new(),
+    };
+
+    let defaults = DeadCodeOptions::default();
+    let options = DeadCodeOptions {
+        entry_points: params.entry_points.unwrap_or(defaults.entry_points),
+        include_tests: params.include_tests.unwrap_or(false),
+        include_public: params.include_public.unwrap_or(false),
+        include_annotated: params.include_annotated.unwrap_or(false),
+    };
+    let report = deadcode::find_unreferenced(
+        &symbols,
+        &relations,
+        &sources,
+        &ambiguous.into_iter().collect(),
+        confidence,
+        &options,
+    );
+
+    let total = report.unreferenced.len();
+    let mut by_file: std::collections::BTreeMap<&str, Vec<&CodeSymbol>> = Default::default();
+    let mut levels: HashMap<String, Confidence> = HashMap::new();
+    for &(i, level) in report.unreferenced.iter().take(limit) {
+        let symbol = &symbols[i];
+        by_file.entry(&symbol.file_path).or_default().push(symbol);
+        levels.insert(symbol.unique_key(), level);
+    }
+    let mut files = Vec::new();
+    for (file_path, mut found) in by_file {
+        found.sort_by_key(|s| s.start_line);
+        let symbols: Vec<serde_json::Value> = found
+            .iter()
+            .map(|s| {
+                json!({
+                    "id": format!("code_symbols:{}", s.unique_key()),
+                    "name": s.name,
+                    "qualified_name": s.qualified_name,
+                    "symbol_type": s.symbol_type,
+                    "start_line": s.start_line,
+                    "end_line": s.end_line,
+                    "confidence": levels[&s.unique_key()],
+                })
+            })
+            .collect();
+        files.push(json!({
+            "file_path": file_path,
+            "absolute_path": paths.absolute(&params.project_id, file_path).await,
+            "symbols": symbols,
+        }));
+    }
+    Ok(success_json(json!({
+        "project_id": params.project_id,
+        "confidence": confidence,
+        "references": { "resolved": resolved, "ambiguous": ambiguous_count },
+        "files": files,
+        "count": total,
+        "excluded": report.excluded,
+        "truncated": total > limit,
+    })))
+}
+
+pub async fn get_repo_map(
+    state: &Arc<AppState>,
+    params: GetRepoMapParams,
+) -> anyhow::Result<CallToolResult> {
+    use crate::codebase::deadcode::USE_RELATIONS;
+    use crate::codebase::repomap;
+
+    const COMMUNITY_FILES: usize = 5;
+    const COMMUNITY_SYMBOLS: usize = 5;
+    const MAX_COMMUNITIES: usize = 20;
+    let max_tokens = params.max_tokens.unwrap_or(1024).clamp(64, 8192);
+    let symbols: Vec<CodeSymbol> = match state.storage.get_project_symbols(&params.project_id).await
+    {
+        Ok(symbols) => symbols
+            .into_iter()
+            .filter(|s| s.symbol_type != SymbolType::Import)
+            .collect(),
+        Err(e) => return Ok(error_response(e)),
+    };
+    if symbols.is_empty() {
+        return Ok(error_response(format!(
+            "No indexed symbols for project: {}",
+            params.project_id
+        )));
+    }
+    let relations = match state
+        .storage
+        .get_project_relations(&params.project_id, USE_RELATIONS)
+        .await
+    {
+        Ok(relations) => relations,
+        Err(e) => return Ok(error_response(e)),
+    };
+    let mut paths = HostPaths::new(state);
+    let mut focus = Vec::new();
+    for path in params.focus_paths.iter().flatten() {
+        focus.push(paths.relative(Some(&params.project_id), path).await);
+    }
+
+    let map = repomap::rank(&symbols, &relations, &focus);
+    let (outline, shown, truncated) = repomap::render(&map, &symbols, max_tokens);
+    let files: Vec<serde_json::Value> = shown
+        .iter()
+        .map(|(f, shown_symbols)| {
+            let file = &map.files[*f];
+            json!({
+                "file_path": file.file_path,
+                "rank": file.rank,
+                "symbols": shown_symbols
+                    .iter()
+                    .map(|&s| json!({
+                        "id": format!("code_symbols:{}", symbols[s].unique_key()),
+                        "name": symbols[s].name,
+                        "symbol_type": symbols[s].symbol_type,
+                        "start_line": symbols[s].start_line,
+                    }))
+                    .collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    let communities: Vec<serde_json::Value> = map
+        .communities
+        .iter()
+        .take(MAX_COMMUNITIES)
+        .map(|community| {
+            let mut top: Vec<(usize, f32)> = community
+                .files
+                .iter()
+                .flat_map(|&f| map.files[f].symbols.iter().take(COMMUNITY_SYMBOLS).copied())
+                .collect();
+            top.sort_by(|a, b| b.1.total_cmp(&a.1));
+            json!({
+                "rank": community.rank,
+                "file_count": community.files.len(),
+                "files": community
+                    .files
+                    .iter()
+                    .take(COMMUNITY_FILES)
+                    .map(|&f| &map.files[f].file_path)
+                    .collect::<Vec<_>>(),
+                "top_symbols": top
+                    .iter()
+                    .take(COMMUNITY_SYMBOLS)
+                    .map(|&(s, _)| symbols[s].qualified_name.as_deref().unwrap_or(&symbols[s].name))
+                    .collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    Ok(success_json(json!({
+        "project_id": params.project_id,
+        "map": outline,
+        "tokens": repomap::estimate_tokens(&outline),
+        "max_tokens": max_tokens,
+        "files": files,
+        "total_files": map.files.len(),
+        "truncated": truncated,
+        "focused": map.focused,
+        "communities": communities,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{response_json, TestContext};
+    use std::fs;
+
+    #[tokio::test]
+    async fn test_find_unreferenced_symbols() {
+        let ctx = TestContext::new().await;
+        let root = ctx._temp_dir.path().join("dead");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join("main.rs"),
+            r#"fn main() {
+    let n = load();
+}
+
+fn load() -> u32 {
+    1
+}
+
+fn stale() -> u32 {
+    2
+}
+
+pub fn api() {}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn checks() {}
+}
+"#,
+        )
+        .unwrap();
+        // Windows-1252: read as the indexer reads it, so `pub` is seen
+        fs::write(root.join("menu.rs"), b"// caf\xe9\npub fn order() {}\n").unwrap();
+        crate::codebase::index_project(ctx.state.clone(), &root)
+            .await
+            .unwrap();
+
+        let json = response_json(
+            &find_unreferenced_symbols(
+                &ctx.state,
+                FindUnreferencedSymbolsParams {
+                    project_id: "dead".to_string(),
+                    entry_points: None,
+                    include_tests: None,
+                    include_public: None,
+                    include_annotated: None,
+                    limit: None,
+                },
+            )
+            .await
+            .unwrap(),
+        );
+        assert_eq!(json["confidence"], "high");
+        assert_eq!(json["count"], 1);
+        let file = &json["files"][0];
+        assert_eq!(file["file_path"], "main.rs");
+        assert_eq!(file["symbols"][0]["name"], "stale");
+        assert_eq!(file["symbols"][0]["confidence"], "high");
+        assert_eq!(json["excluded"]["entry_point"], 1);
+        assert_eq!(json["excluded"]["public"], 2);
+        assert_eq!(json["excluded"]["test"], 1);
+
+        let json = response_json(
+            &find_unreferenced_symbols(
+                &ctx.state,
+                FindUnreferencedSymbolsParams {
+                    project_id: "dead".to_string(),
+                    entry_points: Some(vec![]),
+                    include_tests: Some(true),
+                    include_public: Some(true),
+                    include_annotated: None,
+                    limit: None,
+                },
+            )
+            .await
+            .unwrap(),
+        );
+        let main = json["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|f| f["file_path"] == "main.rs")
+            .unwrap();
+        let names: Vec<&str> = main["symbols"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["main", "stale", "api", "checks"]);
+    }
+
+    #[tokio::test]
+    async fn test_find_similar_code_and_clones() {
+        let ctx = TestContext::new().await;
+        let root = ctx._temp_dir.path().join("clones");
+        fs::create_dir_all(&root).unwrap();
+        let sum = |name: &str| {
+            format!(
+                "fn {}(values: &[u32]) -> u32 {{\n    let mut total = 0;\n    for v in values {{\n        total += v;\n    }}\n    total\n}}\n",
+                name
+            )
+        };
+        fs::write(root.join("a.rs"), sum("sum_all")).unwrap();
+        fs::write(root.join("b.rs"), sum("add_up")).unwrap();
+        fs::write(
+            root.join("c.rs"),
+            "fn greet(names: &[String]) -> String {\n    let mut out = String::new();\n    out.push_str(\"hello \");\n    out.push_str(&names.join(\", \"));\n    out\n}\n",
+        )
+        .unwrap();
+        crate::codebase::index_project(ctx.state.clone(), &root)
+            .await
+            .unwrap();
+
+        // The embedding queue does not run in tests: give every chunk the
+        // same direction, so only the token check tells them apart
+        let mut chunks = Vec::new();
+        for file in ["a.rs", "b.rs", "c.rs"] {
+            chunks.extend(
+                ctx.state
+                    .storage
+                    .get_chunks_by_path("clones", file)
+                    .await
+                    .unwrap(),
+            );
+        }
+        let mut direction = vec![0.0; 768];
+        direction[0] = 1.0;
+        let updates: Vec<(String, Vec<f32>)> = chunks
+            .iter()
+            .map(|c| {
+                let id = crate::types::record_id_string(c.id.as_ref().unwrap());
+                (id, direction.clone())
+            })
+            .collect();
+        ctx.state
+            .storage
+            .batch_update_chunk_embeddings(&updates)
+            .await
+            .unwrap();
+
+        let json = response_json(
+            &find_clones(
+                &ctx.state,
+                FindClonesParams {
+                    project_id: "clones".to_string(),
+                    threshold: None,
+                    min_token_similarity: None,
+                    min_lines: None,
+                    limit: None,
+                },
+            )
+            .await
+            .unwrap(),
+        );
+        assert_eq!(json["count"], 1);
+        assert_eq!(json["compared_chunks"], 3);
+        assert_eq!(json["chunks_truncated"], false);
+        let cluster = &json["clusters"][0];
+        let files: Vec<&str> = cluster["chunks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["file_path"].as_str().unwrap())
+            .collect();
+        assert_eq!(files, vec!["a.rs", "b.rs"]);
+        assert_eq!(cluster["chunks"][0]["start_line"], 1);
+        assert_eq!(cluster["chunks"][0]["end_line"], 7);
+
+        let chunk_id = cluster["chunks"][0]["id"].as_str().unwrap().to_string();
+        let json = response_json(
+            &find_similar_code(
+                &ctx.state,
+                FindSimilarCodeParams {
+                    chunk_id: Some(chunk_id.clone()),
+                    snippet: None,
+                    threshold: None,
+                    project_id: Some("clones".to_string()),
+                    limit: None,
+                },
+            )
+            .await
+            .unwrap(),
+        );
+        let results = json["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r["file_path"] != "a.rs"));
+        let tokens = |file: &str| {
+            results.iter().find(|r| r["file_path"] == file).unwrap()["token_similarity"]
+                .as_f64()
+                .unwrap()
+        };
+        assert!(tokens("b.rs") > 0.9 && tokens("c.rs") < 0.5);
+
+        let json = response_json(
+            &find_similar_code(
+                &ctx.state,
+                FindSimilarCodeParams {
+                    chunk_id: Some(chunk_id),
+                    snippet: Some("fn x() {}".to_string()),
+                    threshold: None,
+                    project_id: None,
+                    limit: None,
+                },
+            )
+            .await
+            .unwrap(),
+        );
+        assert!(json["error"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_get_repo_map() {
+        let ctx = TestContext::new().await;
+        let root = ctx._temp_dir.path().join("mapped");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(
+            root.join("src/store.rs"),
+            "pub fn load(key: &str) -> u32 {\n    key.len() as u32\n}\n\npub fn save(key: &str, value: u32) {\n    let _ = (key, value);\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/api.rs"),
+            "pub fn get(key: &str) -> u32 {\n    load(key)\n}\n\npub fn put(key: &str) {\n    save(key, load(key));\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/cli.rs"),
+            "fn main() {\n    put(\"a\");\n    println!(\"{}\", get(\"a\") + load(\"b\"));\n}\n",
+        )
+        .unwrap();
+        crate::codebase::index_project(ctx.state.clone(), &root)
+            .await
+            .unwrap();
+
+        let map = |max_tokens: Option<usize>, focus_paths: Option<Vec<String>>| {
+            let state = ctx.state.clone();
+            async move {
+                response_json(
+                    &get_repo_map(
+                        &state,
+                        GetRepoMapParams {
+                            project_id: "mapped".to_string(),
+                            max_tokens,
+                            focus_paths,
+                        },
+                    )
+                    .await
+                    .unwrap(),
+                )
+            }
+        };
+        let json = map(None, None).await;
+        assert!(json["map"].as_str().unwrap().starts_with("src/store.rs\n"));
+        assert_eq!(json["total_files"], 3);
+        assert_eq!(json["truncated"], false);
+        assert_eq!(json["focused"], false);
+        assert_eq!(json["files"][0]["file_path"], "src/store.rs");
+        assert!(json["files"][0]["symbols"][0]["id"]
+            .as_str()
+            .unwrap()
+            .starts_with("code_symbols:"));
+        assert!(!json["communities"].as_array().unwrap().is_empty());
+
+        let json = map(Some(64), Some(vec!["src/cli.rs".to_string()])).await;
+        assert_eq!(json["focused"], true);
+        assert!(json["tokens"].as_u64().unwrap() <= 64);
+
+        let json = response_json(
+            &get_repo_map(
+                &ctx.state,
+                GetRepoMapParams {
+                    project_id: "missing".to_string(),
+                    max_tokens: None,
+                    focus_paths: None,
+                },
+            )
+            .await
+            .unwrap(),
+        );
+        assert!(json["error"].is_string());
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use rmcp::model::CallToolResult;
@@ -6,22 +8,117 @@ use serde_json::json;
 use crate::config::AppState;
 use crate::graph::{
     apply_hub_dampening, personalized_page_rank, rrf_merge, DEFAULT_CODE_BM25_WEIGHT,
-    DEFAULT_CODE_PPR_WEIGHT, DEFAULT_CODE_VECTOR_WEIGHT, PPR_DAMPING, PPR_MAX_ITER, PPR_TOLERANCE,
+    DEFAULT_CODE_HISTORY_WEIGHT, DEFAULT_CODE_PPR_WEIGHT, DEFAULT_CODE_VECTOR_WEIGHT, PPR_DAMPING,
+    PPR_MAX_ITER, PPR_TOLERANCE,
 };
 use crate::server::params::{
-    DeleteProjectParams, GetCalleesParams, GetCallersParams, GetIndexStatusParams,
-    GetProjectStatsParams, IndexProjectParams, ListProjectsParams, RecallCodeParams,
+    CancelIndexingParams, DeleteProjectParams, GetCalleesParams, GetCallersParams,
+    GetIndexStatusParams, GetProjectStatsParams, ImportScipParams, IndexProjectParams,
+    ListProjectsParams, PauseIndexingParams, RecallCodeParams, ResumeIndexingParams,
     SearchCodeParams, SearchSymbolsParams,
 };
 use crate::storage::StorageBackend;
+use crate::types::{CodeSymbol, Direction, FileChurn, ScoredCodeChunk};
 
 use super::{error_response, normalize_limit, strip_symbol_embeddings, success_json};
 
+/// Turns stored project-relative paths into paths the client can open,
+/// through the project root and the configured path map.
+pub(super) struct HostPaths<'a> {
+    state: &'a AppState,
+    roots: HashMap<String, Option<PathBuf>>,
+}
+
+impl<'a> HostPaths<'a> {
+    pub(super) fn new(state: &'a AppState) -> Self {
+        Self {
+            state,
+            roots: HashMap::new(),
+        }
+    }
+
+    pub(super) async fn root(&mut self, project_id: &str) -> Option<PathBuf> {
+        if !self.roots.contains_key(project_id) {
+            let root = match self.state.storage.get_index_status(project_id).await {
+                Ok(Some(status)) => status.root.map(PathBuf::from),
+                _ => None,
+            };
+            self.roots.insert(project_id.to_string(), root);
+        }
+        self.roots[project_id].clone()
+    }
+
+    pub(super) async fn absolute(&mut self, project_id: &str, file_path: &str) -> Option<String> {
+        let root = self.root(project_id).await?;
+        let path = crate::codebase::server_path(&root, file_path);
+        Some(
+            self.state
+                .config
+                .path_map
+                .to_host(&path)
+                .to_string_lossy()
+                .to_string(),
+        )
+    }
+
+    /// A project file read from the project root and decoded as the indexer
+    /// decodes it. `None` when the root or the file is not readable here.
+    pub(super) async fn read(&mut self, project_id: &str, file_path: &str) -> Option<String> {
+        let root = self.root(project_id).await?;
+        let bytes = tokio::fs::read(crate::codebase::server_path(&root, file_path))
+            .await
+            .ok()?;
+        crate::codebase::source::decode(&bytes)
+    }
+
+    /// Project-relative form of a path given by the client, which may be
+    /// relative already or absolute on either side of the path map.
+    pub(super) async fn relative(&mut self, project_id: Option<&str>, path: &str) -> String {
+        let absolute = Path::new(path);
+        match project_id {
+            Some(project_id) if absolute.is_absolute() => match self.root(project_id).await {
+                Some(root) => {
+                    let server = self.state.config.path_map.to_server(absolute);
+                    crate::codebase::relative_path(&root, &server)
+                }
+                None => path.to_string(),
+            },
+            _ => path.to_string(),
+        }
+    }
+
+    pub(super) async fn fill_chunks(&mut self, chunks: &mut [ScoredCodeChunk]) {
+        for chunk in chunks {
+            if let Some(project_id) = chunk.project_id.clone() {
+                chunk.absolute_path = self.absolute(&project_id, &chunk.file_path).await;
+            }
+        }
+    }
+
+    /// Symbols as JSON with embeddings stripped and `absolute_path` added.
+    pub(super) async fn symbols_json(
+        &mut self,
+        mut symbols: Vec<CodeSymbol>,
+    ) -> Vec<serde_json::Value> {
+        strip_symbol_embeddings(&mut symbols);
+        let mut out = Vec::with_capacity(symbols.len());
+        for symbol in symbols {
+            let absolute = self.absolute(&symbol.project_id, &symbol.file_path).await;
+            let mut value = json!(symbol);
+            value["absolute_path"] = json!(absolute);
+            out.push(value);
+        }
+        out
+    }
+}
+
 pub async fn index_project(
     state: &Arc<AppState>,
     params: IndexProjectParams,
 ) -> anyhow::Result<CallToolResult> {
-    let path = std::path::Path::new(&params.path);
+    // Clients outside the container may pass the host path
+    let server_path = state.config.path_map.to_server(Path::new(&params.path));
+    let path = server_path.as_path();
 
     if !path.exists() {
         return Ok(error_response(format!(
@@ -30,28 +127,46 @@ pub async fn index_project(
         )));
     }
 
-    let project_id = path
+    let name = path
         .file_name()
         .and_then(|n| n.to_str())
-        .unwrap_or("unknown")
-        .to_string();
+        .unwrap_or("unknown");
+    // A git ref is indexed from the object database as its own project
+    let git_ref = params.git_ref.filter(|r| !r.is_empty());
+    let project_id = match &git_ref {
+        Some(rev) => format!("{}@{}", name, rev),
+        None => name.to_string(),
+    };
 
     let force = params.force.unwrap_or(false);
 
     // Check current status
     if let Ok(Some(status)) = state.storage.get_index_status(&project_id).await {
+        let running = match state.progress.get(&project_id).await {
+            Some(monitor) => monitor.is_running(),
+            None => false,
+        };
         match status.status {
-            crate::types::IndexState::Indexing => {
+            _ if running => {
                 // Already indexing - return current progress
                 return Ok(success_json(json!({
                     "project_id": project_id,
-                    "status": "indexing",
+                    "status": status.status.to_string(),
                     "total_files": status.total_files,
                     "indexed_files": status.indexed_files,
                     "total_chunks": status.total_chunks,
                     "message": "Indexing already in progress"
                 })));
             }
+            crate::types::IndexState::Indexing
+            | crate::types::IndexState::Paused
+            | crate::types::IndexState::Cancelled => {
+                tracing::info!(
+                    project_id = %project_id,
+                    status = %status.status,
+                    "Resuming unfinished indexing"
+                );
+            }
             crate::types::IndexState::Completed | crate::types::IndexState::EmbeddingPending => {
                 if !force {
                     return Ok(success_json(json!({
@@ -60,7 +175,7 @@ pub async fn index_project(
                         "total_files": status.total_files,
                         "indexed_files": status.indexed_files,
                         "total_chunks": status.total_chunks,
-                        "message": "Project already indexed. File changes are tracked incrementally. Use force=true to re-index from scratch."
+                        "message": "Project already indexed. File changes are tracked incrementally. Use force=true to re-scan now; unchanged files are kept."
                     })));
                 }
                 tracing::info!(project_id = %project_id, "Force re-indexing project");
@@ -77,11 +192,14 @@ pub async fn index_project(
 
     // Spawn indexing in background
     let state_clone = state.clone();
-    let path_clone = params.path.clone();
+    let path_clone = server_path.clone();
 
     tokio::spawn(async move {
-        let path = std::path::Path::new(&path_clone);
-        match crate::codebase::index_project(state_clone, path).await {
+        let result = match git_ref {
+            Some(rev) => crate::codebase::index_git_ref(state_clone, &path_clone, &rev).await,
+            None => crate::codebase::index_project(state_clone, &path_clone).await,
+        };
+        match result {
             Ok(status) => {
                 tracing::info!(
                     project_id = %status.project_id,
@@ -104,6 +222,25 @@ pub async fn index_project(
     })))
 }
 
+pub async fn import_scip(
+    state: &Arc<AppState>,
+    params: ImportScipParams,
+) -> anyhow::Result<CallToolResult> {
+    let path = state.config.path_map.to_server(Path::new(&params.path));
+    match crate::codebase::import_scip(state.clone(), &params.project_id, &path).await {
+        Ok(stats) => Ok(success_json(json!({
+            "project_id": params.project_id,
+            "tool": stats.tool,
+            "files": stats.files,
+            "symbols": stats.symbols,
+            "matched_symbols": stats.matched_symbols,
+            "relations": stats.relations,
+            "unresolved": stats.unresolved,
+        }))),
+        Err(e) => Ok(error_response(e)),
+    }
+}
+
 pub async fn search_code(
     state: &Arc<AppState>,
     params: SearchCodeParams,
@@ -130,13 +267,15 @@ pub async fn search_code(
     let query_embedding = state.embedding.embed(&params.query).await?;
 
     let limit = normalize_limit(params.limit);
-    let results = state
+    let mut results = state
         .storage
         .vector_search_code(&query_embedding, params.project_id.as_deref(), limit)
         .await
         .unwrap_or_default();
+    let mut paths = HostPaths::new(state);
 
     if !results.is_empty() {
+        paths.fill_chunks(&mut results).await;
         return Ok(success_json(json!({
             "results": results,
             "count": results.len(),
@@ -151,14 +290,17 @@ pub async fn search_code(
         .bm25_search_code(&params.query, params.project_id.as_deref(), limit)
         .await
     {
-        Ok(fallback) => Ok(success_json(json!({
-            "results": fallback,
-            "count": fallback.len(),
-            "query": params.query,
-            "note": "fallback to text search",
-            "is_partial": is_partial,
-            "message": indexing_message
-        }))),
+        Ok(mut fallback) => {
+            paths.fill_chunks(&mut fallback).await;
+            Ok(success_json(json!({
+                "results": fallback,
+                "count": fallback.len(),
+                "query": params.query,
+                "note": "fallback to text search",
+                "is_partial": is_partial,
+                "message": indexing_message
+            })))
+        }
         Err(e) => Ok(error_response(e)),
     }
 }
@@ -169,7 +311,6 @@ pub async fn recall_code(
     params: RecallCodeParams,
 ) -> anyhow::Result<CallToolResult> {
     use petgraph::graph::{DiGraph, NodeIndex};
-    use std::collections::HashMap;
 
     crate::ensure_embedding_ready!(state);
 
@@ -198,23 +339,28 @@ pub async fn recall_code(
     let vector_weight = params.vector_weight.unwrap_or(DEFAULT_CODE_VECTOR_WEIGHT);
     let bm25_weight = params.bm25_weight.unwrap_or(DEFAULT_CODE_BM25_WEIGHT);
     let ppr_weight = params.ppr_weight.unwrap_or(DEFAULT_CODE_PPR_WEIGHT);
+    let history_weight = params.history_weight.unwrap_or(DEFAULT_CODE_HISTORY_WEIGHT);
 
     let project_id = params.project_id.as_deref();
 
     // 1. Vector search on code_chunks
-    let vector_results = state
+    let mut vector_results = state
         .storage
         .vector_search_code(&query_embedding, project_id, fetch_limit)
         .await
         .unwrap_or_default();
 
     // 2. BM25 (CONTAINS fallback) search on code_chunks
-    let bm25_results = state
+    let mut bm25_results = state
         .storage
         .bm25_search_code(&params.query, project_id, fetch_limit)
         .await
         .unwrap_or_default();
 
+    let mut paths = HostPaths::new(state);
+    paths.fill_chunks(&mut vector_results).await;
+    paths.fill_chunks(&mut bm25_results).await;
+
     let vector_tuples: Vec<_> = vector_results
         .iter()
         .map(|r| (r.id.clone(), r.score))
@@ -371,7 +517,7 @@ pub async fn recall_code(
     };
 
     // 4. RRF merge
-    let merged = rrf_merge(
+    let mut merged = rrf_merge(
         &vector_tuples,
         &bm25_tuples,
         &ppr_tuples,
@@ -381,8 +527,8 @@ pub async fn recall_code(
         limit,
     );
 
-    // 5. Build response with score breakdown
-    let mut content_map: HashMap<String, &crate::types::ScoredCodeChunk> = HashMap::new();
+    // 5. Look up the merged chunks
+    let mut content_map: HashMap<String, &ScoredCodeChunk> = HashMap::new();
     for r in &vector_results {
         content_map.insert(r.id.clone(), r);
     }
@@ -390,23 +536,66 @@ pub async fn recall_code(
         content_map.entry(r.id.clone()).or_insert(r);
     }
 
+    // 6. Git history: files that change often rank a little higher
+    let mut churn: HashMap<(String, String), FileChurn> = HashMap::new();
+    let mut project_paths: HashMap<String, Vec<String>> = HashMap::new();
+    for chunk in merged.iter().filter_map(|(id, _)| content_map.get(id)) {
+        if let Some(project_id) = &chunk.project_id {
+            project_paths
+                .entry(project_id.clone())
+                .or_default()
+                .push(chunk.file_path.clone());
+        }
+    }
+    for (project_id, paths) in project_paths {
+        if let Ok(found) = state.storage.get_file_churn(&project_id, &paths).await {
+            churn.extend(
+                found
+                    .into_iter()
+                    .map(|(path, c)| ((project_id.clone(), path), c)),
+            );
+        }
+    }
+    let churn_of = |chunk: &ScoredCodeChunk| {
+        let project_id = chunk.project_id.clone().unwrap_or_default();
+        churn.get(&(project_id, chunk.file_path.clone()))
+    };
+    let max_commits = churn.values().map(|c| c.commits).max().unwrap_or(0);
+    if history_weight > 0.0 && max_commits > 0 {
+        for (id, scores) in merged.iter_mut() {
+            if let Some(c) = content_map.get(id).and_then(|chunk| churn_of(chunk)) {
+                scores.combined_score *= churn_boost(c.commits, max_commits, history_weight);
+            }
+        }
+        merged.sort_by(|a, b| {
+            b.1.combined_score
+                .partial_cmp(&a.1.combined_score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+    }
+
+    // 7. Build response with score breakdown
     let results: Vec<serde_json::Value> = merged
         .into_iter()
         .filter_map(|(id, scores)| {
             content_map.get(&id).map(|chunk| {
                 json!({
                     "id": id,
+                    "project_id": chunk.project_id,
                     "file_path": chunk.file_path,
+                    "absolute_path": chunk.absolute_path,
                     "content": chunk.content,
                     "language": chunk.language,
                     "start_line": chunk.start_line,
                     "end_line": chunk.end_line,
                     "chunk_type": chunk.chunk_type,
                     "name": chunk.name,
+                    "context": chunk.context,
                     "score": scores.combined_score,
                     "vector_score": scores.vector_score,
                     "bm25_score": scores.bm25_score,
                     "ppr_score": scores.ppr_score,
+                    "history": churn_of(chunk),
                 })
             })
         })
@@ -419,21 +608,30 @@ pub async fn recall_code(
         "weights": {
             "vector": vector_weight,
             "bm25": bm25_weight,
-            "ppr": ppr_weight
+            "ppr": ppr_weight,
+            "history": history_weight
         },
         "is_partial": is_partial,
         "message": indexing_message
     })))
 }
 
+/// Score multiplier for a file changed in `commits` of the indexed commits,
+/// log-scaled against the busiest file among the results.
+fn churn_boost(commits: u32, max_commits: u32, weight: f32) -> f32 {
+    let scale = (1.0 + commits as f32).ln() / (1.0 + max_commits as f32).ln();
+    1.0 + weight * scale
+}
+
 pub async fn get_index_status(
     state: &Arc<AppState>,
     params: GetIndexStatusParams,
 ) -> anyhow::Result<CallToolResult> {
     match state.storage.get_index_status(&params.project_id).await {
         Ok(Some(mut status)) => {
+            let monitor = state.progress.get(&params.project_id).await;
             if status.status == crate::types::IndexState::Indexing {
-                if let Some(monitor) = state.progress.get(&params.project_id).await {
+                if let Some(monitor) = &monitor {
                     let indexed = monitor
                         .indexed_files
                         .load(std::sync::atomic::Ordering::Relaxed);
@@ -489,9 +687,22 @@ pub async fn get_index_status(
                 0.0
             };
 
+            let host_root = status.root.as_ref().map(|root| {
+                state
+                    .config
+                    .path_map
+                    .to_host(Path::new(root))
+                    .to_string_lossy()
+                    .to_string()
+            });
+
             Ok(success_json(json!({
                 "project_id": status.project_id,
                 "status": status.status.to_string(),
+                "root": status.root,
+                "host_root": host_root,
+                "commit": status.commit,
+                "branch": status.branch,
                 "total_files": status.total_files,
                 "indexed_files": status.indexed_files,
                 "started_at": status.started_at,
@@ -519,7 +730,12 @@ pub async fn get_index_status(
                 "overall_progress": {
                     "percent": format!("{:.1}", overall_progress),
                     "is_complete": status.status == crate::types::IndexState::Completed || (embedded_chunks >= total_chunks && embedded_symbols >= total_symbols && total_chunks > 0)
-                }
+                },
+
+                "job_running": monitor.as_ref().is_some_and(|m| m.is_running()),
+
+                // Per-stage throughput of the current or last indexing run
+                "pipeline": monitor.and_then(|m| m.pipeline_stats())
             })))
         }
         Ok(None) => Ok(error_response(format!(
@@ -566,6 +782,7 @@ pub async fn list_projects(
                 enriched.push(json!({
                     "id": project_id,
                     "status": status_str,
+                    "root": status.as_ref().and_then(|s| s.root.clone()),
                     "chunks": chunks,
                     "symbols": symbols,
                     "embedded_chunks": embedded_chunks,
@@ -593,6 +810,11 @@ pub async fn delete_project(
 
     let _ = state.storage.delete_index_status(&params.project_id).await;
     let _ = state.storage.delete_file_hashes(&params.project_id).await;
+    let _ = state
+        .storage
+        .delete_project_commits(&params.project_id)
+        .await;
+    state.symbol_indexes.remove(&params.project_id).await;
 
     match state
         .storage
@@ -607,6 +829,74 @@ pub async fn delete_project(
     }
 }
 
+pub async fn pause_indexing(
+    state: &Arc<AppState>,
+    params: PauseIndexingParams,
+) -> anyhow::Result<CallToolResult> {
+    match state.progress.get(&params.project_id).await {
+        Some(monitor) if monitor.pause() => Ok(success_json(json!({
+            "project_id": params.project_id,
+            "status": "paused",
+            "message": "Indexing pauses once the files in flight are stored. Use resume_indexing to continue."
+        }))),
+        _ => Ok(error_response(format!(
+            "No indexing job running for project: {}",
+            params.project_id
+        ))),
+    }
+}
+
+pub async fn resume_indexing(
+    state: &Arc<AppState>,
+    params: ResumeIndexingParams,
+) -> anyhow::Result<CallToolResult> {
+    let monitor = state.progress.get(&params.project_id).await;
+    if let Some(monitor) = &monitor {
+        if monitor.resume() {
+            return Ok(success_json(json!({
+                "project_id": params.project_id,
+                "status": "indexing",
+                "message": "Indexing resumed"
+            })));
+        }
+        if monitor.is_running() {
+            return Ok(error_response(format!(
+                "Indexing of {} is not paused",
+                params.project_id
+            )));
+        }
+    }
+
+    // No live job: a cancelled or interrupted run is continued by index_project
+    match state.storage.get_index_status(&params.project_id).await {
+        Ok(Some(status)) if status.status.is_unfinished() => Ok(error_response(format!(
+            "No indexing job running for project: {}. Its last run is {}; call index_project with the project path to continue where it stopped.",
+            params.project_id, status.status
+        ))),
+        _ => Ok(error_response(format!(
+            "No paused indexing job for project: {}",
+            params.project_id
+        ))),
+    }
+}
+
+pub async fn cancel_indexing(
+    state: &Arc<AppState>,
+    params: CancelIndexingParams,
+) -> anyhow::Result<CallToolResult> {
+    match state.progress.get(&params.project_id).await {
+        Some(monitor) if monitor.cancel() => Ok(success_json(json!({
+            "project_id": params.project_id,
+            "status": "cancelling",
+            "message": "Indexing stops once the files in flight are stored. Files already indexed are kept; index_project continues from there."
+        }))),
+        _ => Ok(error_response(format!(
+            "No indexing job running for project: {}",
+            params.project_id
+        ))),
+    }
+}
+
 pub async fn search_symbols(
     state: &Arc<AppState>,
     params: SearchSymbolsParams,
@@ -614,6 +904,12 @@ pub async fn search_symbols(
     let limit = params.limit.unwrap_or(20).clamp(1, 100);
     let offset = params.offset.unwrap_or(0);
 
+    let mut paths = HostPaths::new(state);
+    let path_prefix = match params.path_prefix.as_deref() {
+        Some(prefix) => Some(paths.relative(params.project_id.as_deref(), prefix).await),
+        None => None,
+    };
+
     match state
         .storage
         .search_symbols(
@@ -622,13 +918,13 @@ pub async fn search_symbols(
             limit,
             offset,
             params.symbol_type.as_deref(),
-            params.path_prefix.as_deref(),
+            path_prefix.as_deref(),
         )
         .await
     {
-        Ok((mut symbols, total)) => {
+        Ok((symbols, total)) => {
             let count = symbols.len();
-            strip_symbol_embeddings(&mut symbols);
+            let symbols = paths.symbols_json(symbols).await;
 
             let has_more = offset + count < total as usize;
 
@@ -643,7 +939,7 @@ pub async fn search_symbols(
                 "filters": {
                     "project_id": params.project_id,
                     "symbol_type": params.symbol_type,
-                    "path_prefix": params.path_prefix
+                    "path_prefix": path_prefix
                 }
             })))
         }
@@ -656,8 +952,8 @@ pub async fn get_callers(
     params: GetCallersParams,
 ) -> anyhow::Result<CallToolResult> {
     match state.storage.get_symbol_callers(&params.symbol_id).await {
-        Ok(mut callers) => {
-            strip_symbol_embeddings(&mut callers);
+        Ok(callers) => {
+            let callers = HostPaths::new(state).symbols_json(callers).await;
             Ok(success_json(json!({
                 "results": callers,
                 "count": callers.len(),
@@ -673,8 +969,8 @@ pub async fn get_callees(
     params: GetCalleesParams,
 ) -> anyhow::Result<CallToolResult> {
     match state.storage.get_symbol_callees(&params.symbol_id).await {
-        Ok(mut callees) => {
-            strip_symbol_embeddings(&mut callees);
+        Ok(callees) => {
+            let callees = HostPaths::new(state).symbols_json(callees).await;
             Ok(success_json(json!({
                 "results": callees,
                 "count": callees.len(),
@@ -689,8 +985,6 @@ pub async fn get_related_symbols(
     state: &Arc<AppState>,
     params: crate::server::params::GetRelatedSymbolsParams,
 ) -> anyhow::Result<CallToolResult> {
-    use crate::types::Direction;
-
     let depth = params.depth.unwrap_or(1).min(3);
     let direction: Direction = params
         .direction
@@ -703,8 +997,8 @@ pub async fn get_related_symbols(
         .get_related_symbols(&params.symbol_id, depth, direction)
         .await
     {
-        Ok((mut symbols, relations)) => {
-            strip_symbol_embeddings(&mut symbols);
+        Ok((symbols, relations)) => {
+            let symbols = HostPaths::new(state).symbols_json(symbols).await;
             Ok(success_json(json!({
                 "symbols": symbols,
                 "relations": relations,
@@ -752,6 +1046,26 @@ pub async fn get_project_stats(
         .await
         .unwrap_or(0);
 
+    let total_relations = state
+        .storage
+        .count_symbol_relations(&params.project_id)
+        .await
+        .unwrap_or(0);
+    let unresolved: serde_json::Map<String, serde_json::Value> = state
+        .storage
+        .count_unresolved_references(&params.project_id)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(reason, count)| (reason, json!(count)))
+        .collect();
+
+    let mut skipped = serde_json::Map::new();
+    for file in &status.skipped_files {
+        let count = skipped.entry(file.reason.to_string()).or_insert(json!(0));
+        *count = json!(count.as_u64().unwrap_or(0) + 1);
+    }
+
     let vector_progress = if total_chunks > 0 {
         (embedded_chunks as f32 / total_chunks as f32) * 100.0
     } else {
@@ -768,7 +1082,8 @@ pub async fn get_project_stats(
         "status": status.status.to_string(),
         "files": {
             "total": status.total_files,
-            "indexed": status.indexed_files
+            "indexed": status.indexed_files,
+            "skipped": skipped
         },
         "chunks": {
             "total": total_chunks,
@@ -780,9 +1095,14 @@ pub async fn get_project_stats(
             "embedded": embedded_symbols,
             "progress_percent": format!("{:.1}", graph_progress)
         },
+        "relations": {
+            "total": total_relations,
+            "unresolved": unresolved
+        },
         "started_at": status.started_at,
         "completed_at": status.completed_at,
-        "failed_files": status.failed_files
+        "failed_files": status.failed_files,
+        "skipped_files": status.skipped_files
     })))
 }
 
@@ -792,6 +1112,31 @@ mod tests {
     use crate::test_utils::TestContext;
     use std::fs;
 
+    #[test]
+    fn test_churn_boost() {
+        assert_eq!(churn_boost(10, 10, 0.1), 1.1);
+        assert!(churn_boost(1, 10, 0.1) < churn_boost(5, 10, 0.1));
+    }
+
+    #[tokio::test]
+    async fn test_host_paths_read_decodes() {
+        let ctx = TestContext::new().await;
+        let root = ctx._temp_dir.path().join("latin");
+        fs::create_dir_all(&root).unwrap();
+        // "café" in Windows-1252
+        fs::write(root.join("menu.rs"), b"// caf\xe9\nfn order() {}\n").unwrap();
+        crate::codebase::index_project(ctx.state.clone(), &root)
+            .await
+            .unwrap();
+
+        let mut paths = HostPaths::new(&ctx.state);
+        assert_eq!(
+            paths.read("latin", "menu.rs").await.unwrap(),
+            "// café\nfn order() {}\n"
+        );
+        assert!(paths.read("latin", "missing.rs").await.is_none());
+    }
+
     #[tokio::test]
     async fn test_code_logic_flow() {
         let ctx = TestContext::new().await;
@@ -807,6 +1152,7 @@ mod tests {
         let index_params = IndexProjectParams {
             path: project_path.to_string_lossy().to_string(),
             force: None,
+            git_ref: None,
         };
 
         // 1. Trigger Indexing
@@ -859,6 +1205,19 @@ mod tests {
                 &t.text[..std::cmp::min(500, t.text.len())]
             );
             assert!(t.text.contains("Hello"));
+
+            let json: serde_json::Value = serde_json::from_str(&t.text).unwrap();
+            let hit = &json["results"][0];
+            assert_eq!(hit["file_path"], "main.rs");
+            assert_eq!(
+                hit["absolute_path"],
+                project_path
+                    .canonicalize()
+                    .unwrap()
+                    .join("main.rs")
+                    .to_string_lossy()
+                    .as_ref()
+            );
         } else {
             panic!("Expected text content");
         }
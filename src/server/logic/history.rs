@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use rmcp::model::CallToolResult;
+use serde_json::json;
+
+use crate::config::AppState;
+use crate::server::params::{GetFileHistoryParams, SearchCommitsParams};
+use crate::storage::StorageBackend;
+use crate::types::GitCommit;
+
+use super::code::HostPaths;
+use super::{error_response, normalize_limit, success_json};
+
+/// A commit as returned to clients.
+fn commit_json(commit: &GitCommit) -> serde_json::Value {
+    json!({
+        "hash": commit.hash,
+        "short_hash": commit.hash.get(..7).unwrap_or(&commit.hash),
+        "project_id": commit.project_id,
+        "author": commit.author,
+        "email": commit.email,
+        "date": commit.date,
+        "message": commit.message,
+        "files": commit.files,
+        "symbols": commit.symbols,
+    })
+}
+
+/// Hint for empty history results when the server isn't indexing history.
+fn history_hint(state: &AppState, found: usize) -> Option<&'static str> {
+    (found == 0 && state.config.history_depth == 0)
+        .then_some("Git history is not indexed. Start the server with HISTORY_DEPTH > 0.")
+}
+
+pub async fn search_commits(
+    state: &Arc<AppState>,
+    params: SearchCommitsParams,
+) -> anyhow::Result<CallToolResult> {
+    let limit = normalize_limit(params.limit);
+    match state
+        .storage
+        .search_commits(&params.query, params.project_id.as_deref(), limit)
+        .await
+    {
+        Ok(commits) => Ok(success_json(json!({
+            "results": commits.iter().map(commit_json).collect::<Vec<_>>(),
+            "count": commits.len(),
+            "query": params.query,
+            "message": history_hint(state, commits.len()),
+        }))),
+        Err(e) => Ok(error_response(e)),
+    }
+}
+
+pub async fn get_file_history(
+    state: &Arc<AppState>,
+    params: GetFileHistoryParams,
+) -> anyhow::Result<CallToolResult> {
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    let mut paths = HostPaths::new(state);
+    let file_path = paths
+        .relative(Some(&params.project_id), &params.file_path)
+        .await;
+    let absolute_path = paths.absolute(&params.project_id, &file_path).await;
+
+    // A symbol filter applies after the fetch, so fetch the whole history
+    let fetch = match params.symbol {
+        Some(_) => state.config.history_depth.max(limit),
+        None => limit,
+    };
+    let commits = match state
+        .storage
+        .get_file_commits(&params.project_id, &file_path, fetch)
+        .await
+    {
+        Ok(commits) => commits,
+        Err(e) => return Ok(error_response(e)),
+    };
+    let commits: Vec<GitCommit> = match params.symbol.as_deref() {
+        Some(symbol) => commits
+            .into_iter()
+            .filter(|c| c.symbols.iter().any(|s| symbol_matches(s, symbol)))
+            .take(limit)
+            .collect(),
+        None => commits,
+    };
+    let churn = state
+        .storage
+        .get_file_churn(&params.project_id, std::slice::from_ref(&file_path))
+        .await
+        .unwrap_or_default()
+        .remove(&file_path);
+
+    Ok(success_json(json!({
+        "project_id": params.project_id,
+        "file_path": file_path,
+        "absolute_path": absolute_path,
+        "symbol": params.symbol,
+        "churn": churn,
+        "commits": commits.iter().map(commit_json).collect::<Vec<_>>(),
+        "count": commits.len(),
+        "message": history_hint(state, commits.len()),
+    })))
+}
+
+/// Whether a stored qualified name refers to `symbol`, given either
+/// qualified (`auth::login`) or as a bare name (`login`).
+fn symbol_matches(qualified: &str, symbol: &str) -> bool {
+    qualified == symbol
+        || qualified
+            .strip_suffix(symbol)
+            .is_some_and(|rest| rest.ends_with("::") || rest.ends_with('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbol_matches() {
+        assert!(symbol_matches("auth::login", "login"));
+        assert!(symbol_matches("auth::login", "auth::login"));
+        assert!(symbol_matches("app.Session.login", "Session.login"));
+        assert!(!symbol_matches("auth::relogin", "login"));
+    }
+}
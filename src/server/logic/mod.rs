@@ -1,7 +1,11 @@
+pub mod analysis;
 pub mod code;
 pub mod graph;
+pub mod history;
 pub mod memory;
+pub mod navigation;
 pub mod search;
+pub mod source;
 pub mod system;
 
 use rmcp::model::{CallToolResult, Content};
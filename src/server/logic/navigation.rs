@@ -0,0 +1,1131 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use rmcp::model::CallToolResult;
+use serde_json::json;
+
+use crate::codebase::detect_language;
+use crate::config::AppState;
+use crate::graph::{is_test_symbol, HierarchyNode, SymbolGraph};
+use crate::server::params::{
+    FindCallPathsParams, FindDefinitionParams, FindReferencesParams, GetCallHierarchyParams,
+    GetTypeHierarchyParams, ImpactOfChangeParams,
+};
+use crate::storage::StorageBackend;
+use crate::types::{
+    record_id_string, AppError, CodeRelationType, CodeSymbol, Direction, Language,
+    ReferenceOccurrence, SymbolType,
+};
+
+use super::code::HostPaths;
+use super::{error_response, success_json};
+
+/// Symbol id in the `code_symbols:...` form edges and occurrences use.
+pub(super) fn symbol_record(id: &str) -> String {
+    match id.strip_prefix("code_symbols:") {
+        Some(_) => id.to_string(),
+        None => format!("code_symbols:{}", id),
+    }
+}
+
+/// Requested edge types, or `default` when none are given.
+fn relation_types(
+    names: Option<&[String]>,
+    default: &[CodeRelationType],
+) -> Result<Vec<CodeRelationType>, String> {
+    match names {
+        Some(names) if !names.is_empty() => names
+            .iter()
+            .map(|name| {
+                serde_json::from_value(json!(name.to_lowercase()))
+                    .map_err(|_| format!("Unknown relation type: {}", name))
+            })
+            .collect(),
+        _ => Ok(default.to_vec()),
+    }
+}
+
+/// Compact symbol descriptions by id, for graph results. Ids without a
+/// stored symbol (a file's top-level code) are left out.
+async fn symbol_summaries(
+    state: &AppState,
+    paths: &mut HostPaths<'_>,
+    ids: Vec<String>,
+) -> Result<HashMap<String, (CodeSymbol, serde_json::Value)>, AppError> {
+    let mut out = HashMap::new();
+    for symbol in state.storage.get_symbols_by_ids(&ids).await? {
+        let id = format!("code_symbols:{}", symbol.unique_key());
+        let absolute = paths.absolute(&symbol.project_id, &symbol.file_path).await;
+        let summary = json!({
+            "id": id,
+            "name": symbol.name,
+            "qualified_name": symbol.qualified_name,
+            "symbol_type": symbol.symbol_type,
+            "file_path": symbol.file_path,
+            "absolute_path": absolute,
+            "start_line": symbol.start_line,
+            "end_line": symbol.end_line,
+        });
+        out.insert(id, (symbol, summary));
+    }
+    Ok(out)
+}
+
+/// Load the project graph around a symbol. Errors are tool responses.
+async fn symbol_graph(
+    state: &AppState,
+    symbol_id: &str,
+    types: Option<&[String]>,
+) -> Result<(CodeSymbol, SymbolGraph), CallToolResult> {
+    let types = relation_types(types, &[CodeRelationType::Calls]).map_err(error_response)?;
+    let symbol = state
+        .storage
+        .get_symbols_by_ids(&[symbol_id.to_string()])
+        .await
+        .map_err(error_response)?
+        .pop()
+        .ok_or_else(|| error_response(format!("Symbol not found: {}", symbol_id)))?;
+    let relations = state
+        .storage
+        .get_project_relations(&symbol.project_id, &types)
+        .await
+        .map_err(error_response)?;
+    Ok((symbol, SymbolGraph::new(&relations)))
+}
+
+fn hierarchy_json(
+    node: &HierarchyNode,
+    summaries: &HashMap<String, (CodeSymbol, serde_json::Value)>,
+) -> serde_json::Value {
+    let mut value = match summaries.get(&node.id) {
+        Some((_, summary)) => summary.clone(),
+        None => json!({ "id": node.id, "name": "global" }),
+    };
+    value["relation_type"] = json!(node.relation_type);
+    if node.repeated {
+        value["repeated"] = json!(true);
+    }
+    value["children"] = node
+        .children
+        .iter()
+        .map(|child| hierarchy_json(child, summaries))
+        .collect();
+    value
+}
+
+pub async fn get_call_hierarchy(
+    state: &Arc<AppState>,
+    params: GetCallHierarchyParams,
+) -> anyhow::Result<CallToolResult> {
+    const MAX_NODES: usize = 500;
+    let depth = params.depth.unwrap_or(3).clamp(1, 10);
+    let direction = match params.direction.as_deref().unwrap_or("incoming") {
+        "incoming" => Direction::Incoming,
+        "outgoing" => Direction::Outgoing,
+        "both" => Direction::Both,
+        other => {
+            return Ok(error_response(format!(
+                "Unknown direction: {} (expected incoming, outgoing or both)",
+                other
+            )))
+        }
+    };
+    let symbol_id = symbol_record(&params.symbol_id);
+    let (_, graph) = match symbol_graph(state, &symbol_id, params.relation_types.as_deref()).await {
+        Ok(loaded) => loaded,
+        Err(response) => return Ok(response),
+    };
+
+    let (tree, truncated) = graph.hierarchy(&symbol_id, direction, depth, MAX_NODES);
+    let mut ids = Vec::new();
+    let mut stack = vec![&tree];
+    while let Some(node) = stack.pop() {
+        ids.push(node.id.clone());
+        stack.extend(&node.children);
+    }
+    let mut paths = HostPaths::new(state);
+    let summaries = match symbol_summaries(state, &mut paths, ids).await {
+        Ok(summaries) => summaries,
+        Err(e) => return Ok(error_response(e)),
+    };
+    Ok(success_json(json!({
+        "direction": direction,
+        "depth": depth,
+        "tree": hierarchy_json(&tree, &summaries),
+        "truncated": truncated,
+    })))
+}
+
+pub async fn find_call_paths(
+    state: &Arc<AppState>,
+    params: FindCallPathsParams,
+) -> anyhow::Result<CallToolResult> {
+    let max_length = params.max_length.unwrap_or(6).clamp(1, 12);
+    let max_paths = params.max_paths.unwrap_or(3).clamp(1, 10);
+    let from = symbol_record(&params.from_symbol_id);
+    let to = symbol_record(&params.to_symbol_id);
+    let (_, graph) = match symbol_graph(state, &from, params.relation_types.as_deref()).await {
+        Ok(loaded) => loaded,
+        Err(response) => return Ok(response),
+    };
+
+    let found = graph.paths(&from, &to, max_length, max_paths);
+    let ids = found.iter().flat_map(|p| p.nodes.clone()).collect();
+    let mut paths = HostPaths::new(state);
+    let summaries = match symbol_summaries(state, &mut paths, ids).await {
+        Ok(summaries) => summaries,
+        Err(e) => return Ok(error_response(e)),
+    };
+    let results: Vec<serde_json::Value> = found
+        .iter()
+        .map(|path| {
+            let symbols: Vec<serde_json::Value> = path
+                .nodes
+                .iter()
+                .map(|id| match summaries.get(id) {
+                    Some((_, summary)) => summary.clone(),
+                    None => json!({ "id": id, "name": "global" }),
+                })
+                .collect();
+            json!({
+                "length": path.relation_types.len(),
+                "symbols": symbols,
+                "relation_types": path.relation_types,
+            })
+        })
+        .collect();
+    Ok(success_json(json!({
+        "paths": results,
+        "count": results.len(),
+        "max_length": max_length,
+    })))
+}
+
+/// Methods a type defines, in source order, through its `Contains` edges.
+fn type_methods<'a>(
+    id: &str,
+    contains: &HashMap<String, Vec<String>>,
+    summaries: &'a HashMap<String, (CodeSymbol, serde_json::Value)>,
+) -> Vec<&'a (CodeSymbol, serde_json::Value)> {
+    let mut methods: Vec<_> = contains
+        .get(id)
+        .into_iter()
+        .flatten()
+        .filter_map(|member| summaries.get(member))
+        .filter(|(symbol, _)| {
+            matches!(
+                symbol.symbol_type,
+                SymbolType::Function | SymbolType::Method
+            )
+        })
+        .collect();
+    methods.sort_by_key(|(symbol, _)| symbol.start_line);
+    methods
+}
+
+/// A type hierarchy tree with each type's methods. `path` holds the
+/// methods of the types between the root and `node`, nearest last; a
+/// method matching one of them by name `overrides` it in the subtype tree
+/// and is `overridden_by` it in the supertype tree.
+fn type_hierarchy_json(
+    node: &HierarchyNode,
+    subtypes: bool,
+    path: &mut Vec<Vec<serde_json::Value>>,
+    contains: &HashMap<String, Vec<String>>,
+    summaries: &HashMap<String, (CodeSymbol, serde_json::Value)>,
+) -> serde_json::Value {
+    let mut value = match summaries.get(&node.id) {
+        Some((_, summary)) => summary.clone(),
+        None => json!({ "id": node.id }),
+    };
+    value["relation_type"] = json!(node.relation_type);
+    if node.repeated {
+        value["repeated"] = json!(true);
+    }
+    let key = if subtypes {
+        "overrides"
+    } else {
+        "overridden_by"
+    };
+    let methods: Vec<serde_json::Value> = type_methods(&node.id, contains, summaries)
+        .into_iter()
+        .map(|(symbol, summary)| {
+            let mut method = json!({
+                "id": summary["id"],
+                "name": symbol.name,
+                "start_line": symbol.start_line,
+            });
+            let matched: Vec<&serde_json::Value> = path
+                .iter()
+                .rev()
+                .flatten()
+                .filter(|other| other["name"] == symbol.name)
+                .map(|other| &other["id"])
+                .collect();
+            if subtypes {
+                if let Some(first) = matched.first() {
+                    method[key] = (*first).clone();
+                }
+            } else if !matched.is_empty() {
+                method[key] = json!(matched);
+            }
+            method
+        })
+        .collect();
+
+    path.push(methods.clone());
+    value["children"] = node
+        .children
+        .iter()
+        .map(|child| type_hierarchy_json(child, subtypes, path, contains, summaries))
+        .collect();
+    path.pop();
+    value["methods"] = json!(methods);
+    value
+}
+
+/// Rust implementors are `impl` blocks named after their type. Report the
+/// struct or enum the block implements instead, with the block as
+/// `location`; blocks for types outside the index are left as they are.
+async fn resolve_impl_blocks(
+    state: &AppState,
+    paths: &mut HostPaths<'_>,
+    summaries: &mut HashMap<String, (CodeSymbol, serde_json::Value)>,
+) -> Result<(), AppError> {
+    let is_impl_block = |symbol: &CodeSymbol| {
+        symbol.symbol_type == SymbolType::Class
+            && detect_language(Path::new(&symbol.file_path)) == Language::Rust
+    };
+    let mut names: HashMap<&str, Vec<String>> = HashMap::new();
+    for (symbol, _) in summaries.values() {
+        if is_impl_block(symbol) {
+            names
+                .entry(&symbol.project_id)
+                .or_default()
+                .push(symbol.name.clone());
+        }
+    }
+    let mut types: HashMap<(String, String), Vec<CodeSymbol>> = HashMap::new();
+    for (project_id, mut names) in names {
+        names.sort();
+        names.dedup();
+        for target in state
+            .storage
+            .get_symbols_by_name(project_id, &names, &[SymbolType::Struct, SymbolType::Enum])
+            .await?
+        {
+            types
+                .entry((target.project_id.clone(), target.name.clone()))
+                .or_default()
+                .push(target);
+        }
+    }
+
+    for (symbol, summary) in summaries.values_mut() {
+        if !is_impl_block(symbol) {
+            continue;
+        }
+        let Some(found) = types.get(&(symbol.project_id.clone(), symbol.name.clone())) else {
+            continue;
+        };
+        // Prefer the type declared next to the block
+        let Some(target) = found
+            .iter()
+            .find(|t| t.file_path == symbol.file_path)
+            .or_else(|| found.first())
+        else {
+            continue;
+        };
+        let absolute = paths.absolute(&target.project_id, &target.file_path).await;
+        let location = json!({
+            "id": summary["id"],
+            "file_path": summary["file_path"],
+            "absolute_path": summary["absolute_path"],
+            "start_line": summary["start_line"],
+            "end_line": summary["end_line"],
+        });
+        *summary = json!({
+            "id": format!("code_symbols:{}", target.unique_key()),
+            "name": target.name,
+            "qualified_name": target.qualified_name,
+            "symbol_type": target.symbol_type,
+            "file_path": target.file_path,
+            "absolute_path": absolute,
+            "start_line": target.start_line,
+            "end_line": target.end_line,
+            "location": location,
+        });
+    }
+    Ok(())
+}
+
+pub async fn get_type_hierarchy(
+    state: &Arc<AppState>,
+    params: GetTypeHierarchyParams,
+) -> anyhow::Result<CallToolResult> {
+    const MAX_NODES: usize = 500;
+    let depth = params.depth.unwrap_or(3).clamp(1, 10);
+    let (supertypes, subtypes) = match params.direction.as_deref().unwrap_or("both") {
+        "supertypes" => (true, false),
+        "subtypes" => (false, true),
+        "both" => (true, true),
+        other => {
+            return Ok(error_response(format!(
+                "Unknown direction: {} (expected supertypes, subtypes or both)",
+                other
+            )))
+        }
+    };
+    let symbol_id = symbol_record(&params.symbol_id);
+    let symbol = match state
+        .storage
+        .get_symbols_by_ids(std::slice::from_ref(&symbol_id))
+        .await
+    {
+        Ok(mut found) => match found.pop() {
+            Some(symbol) => symbol,
+            None => return Ok(error_response(format!("Symbol not found: {}", symbol_id))),
+        },
+        Err(e) => return Ok(error_response(e)),
+    };
+    let types = [
+        CodeRelationType::Implements,
+        CodeRelationType::Extends,
+        CodeRelationType::Contains,
+    ];
+    let relations = match state
+        .storage
+        .get_project_relations(&symbol.project_id, &types)
+        .await
+    {
+        Ok(relations) => relations,
+        Err(e) => return Ok(error_response(e)),
+    };
+    let (members, inheritance): (Vec<_>, Vec<_>) = relations
+        .into_iter()
+        .partition(|r| r.relation_type == CodeRelationType::Contains);
+    let graph = SymbolGraph::new(&inheritance);
+    let mut contains: HashMap<String, Vec<String>> = HashMap::new();
+    for relation in &members {
+        contains
+            .entry(record_id_string(&relation.from_symbol))
+            .or_default()
+            .push(record_id_string(&relation.to_symbol));
+    }
+
+    // Supertypes are what a type implements or extends
+    let trees: Vec<(bool, (HierarchyNode, bool))> = [
+        (false, supertypes, Direction::Outgoing),
+        (true, subtypes, Direction::Incoming),
+    ]
+    .into_iter()
+    .filter(|(_, wanted, _)| *wanted)
+    .map(|(down, _, direction)| {
+        (
+            down,
+            graph.hierarchy(&symbol_id, direction, depth, MAX_NODES),
+        )
+    })
+    .collect();
+
+    let mut ids = Vec::new();
+    for (_, (tree, _)) in &trees {
+        let mut stack = vec![tree];
+        while let Some(node) = stack.pop() {
+            ids.push(node.id.clone());
+            ids.extend(contains.get(&node.id).into_iter().flatten().cloned());
+            stack.extend(&node.children);
+        }
+    }
+    let mut paths = HostPaths::new(state);
+    let mut summaries = match symbol_summaries(state, &mut paths, ids).await {
+        Ok(summaries) => summaries,
+        Err(e) => return Ok(error_response(e)),
+    };
+    if let Err(e) = resolve_impl_blocks(state, &mut paths, &mut summaries).await {
+        return Ok(error_response(e));
+    }
+
+    let mut result = json!({ "depth": depth, "truncated": false });
+    for (down, (tree, truncated)) in &trees {
+        let key = if *down { "subtypes" } else { "supertypes" };
+        result[key] = type_hierarchy_json(tree, *down, &mut Vec::new(), &contains, &summaries);
+        if *truncated {
+            result["truncated"] = json!(true);
+        }
+    }
+    Ok(success_json(result))
+}
+
+pub async fn impact_of_change(
+    state: &Arc<AppState>,
+    params: ImpactOfChangeParams,
+) -> anyhow::Result<CallToolResult> {
+    const MAX_AFFECTED: usize = 1000;
+    let types = match relation_types(
+        params.relation_types.as_deref(),
+        &[
+            CodeRelationType::Calls,
+            CodeRelationType::References,
+            CodeRelationType::Implements,
+            CodeRelationType::Extends,
+            CodeRelationType::Imports,
+        ],
+    ) {
+        Ok(types) => types,
+        Err(e) => return Ok(error_response(e)),
+    };
+    let mut paths = HostPaths::new(state);
+
+    let mut seeds: Vec<String> = params
+        .symbol_ids
+        .iter()
+        .flatten()
+        .map(|id| symbol_record(id))
+        .collect();
+    for file in params.files.iter().flatten() {
+        let file_path = paths.relative(Some(&params.project_id), file).await;
+        match state
+            .storage
+            .get_symbols_by_path(&params.project_id, &file_path)
+            .await
+        {
+            Ok(symbols) => seeds.extend(
+                symbols
+                    .iter()
+                    .map(|s| format!("code_symbols:{}", s.unique_key())),
+            ),
+            Err(e) => return Ok(error_response(e)),
+        }
+    }
+    if seeds.is_empty() {
+        return Ok(error_response(
+            "No indexed symbols in the given files or symbol_ids",
+        ));
+    }
+
+    let relations = match state
+        .storage
+        .get_project_relations(&params.project_id, &types)
+        .await
+    {
+        Ok(relations) => relations,
+        Err(e) => return Ok(error_response(e)),
+    };
+    let graph = SymbolGraph::new(&relations);
+    // A change reaches whatever calls, references or builds on it
+    let mut reached = graph.reachable(&seeds, Direction::Incoming, params.depth);
+    let truncated = reached.len() > MAX_AFFECTED;
+    reached.truncate(MAX_AFFECTED);
+
+    let ids = seeds
+        .iter()
+        .cloned()
+        .chain(reached.iter().map(|(id, _)| id.clone()))
+        .collect();
+    let summaries = match symbol_summaries(state, &mut paths, ids).await {
+        Ok(summaries) => summaries,
+        Err(e) => return Ok(error_response(e)),
+    };
+
+    let mut affected = Vec::new();
+    let mut tests = Vec::new();
+    let mut files = std::collections::BTreeSet::new();
+    let changed = seeds.iter().map(|id| (id, 0));
+    for (id, distance) in changed.chain(reached.iter().map(|(id, d)| (id, *d))) {
+        let Some((symbol, summary)) = summaries.get(id) else {
+            continue;
+        };
+        let mut summary = summary.clone();
+        summary["distance"] = json!(distance);
+        if is_test_symbol(symbol) {
+            summary["is_test"] = json!(true);
+            tests.push(summary.clone());
+        }
+        if distance > 0 {
+            files.insert(symbol.file_path.clone());
+            affected.push(summary);
+        }
+    }
+    Ok(success_json(json!({
+        "changed": seeds.len(),
+        "affected": affected,
+        "affected_count": affected.len(),
+        "files": files,
+        "tests": tests,
+        "truncated": truncated,
+    })))
+}
+
+/// What sits at a file position.
+enum Located {
+    /// A definition, reached through a reference to it or by its own name
+    Symbol(Box<CodeSymbol>, Option<ReferenceOccurrence>),
+    /// A reference with no definition in the project
+    Unresolved(ReferenceOccurrence),
+    Nothing,
+}
+
+async fn locate(
+    state: &AppState,
+    paths: &mut HostPaths<'_>,
+    project_id: &str,
+    file_path: &str,
+    line: u32,
+    column: u32,
+) -> Result<Located, AppError> {
+    let occurrences = state
+        .storage
+        .get_line_occurrences(project_id, file_path, line)
+        .await?;
+    let word = paths
+        .read(project_id, file_path)
+        .await
+        .and_then(|text| word_at(&text, line, column));
+
+    // Imports and SQL in strings are recorded at the statement's column, so
+    // fall back to the name under the cursor
+    let occurrence = occurrences.iter().find(|o| o.covers(column)).or_else(|| {
+        let word = word.as_deref()?;
+        occurrences.iter().find(|o| o.name == word)
+    });
+    if let Some(occurrence) = occurrence {
+        let symbol = match &occurrence.to_symbol {
+            Some(id) => state
+                .storage
+                .get_symbols_by_ids(std::slice::from_ref(id))
+                .await?
+                .pop(),
+            None => None,
+        };
+        return Ok(match symbol {
+            Some(symbol) => Located::Symbol(Box::new(symbol), Some(occurrence.clone())),
+            None => Located::Unresolved(occurrence.clone()),
+        });
+    }
+
+    // The cursor is on a definition: innermost symbol named by the word, or
+    // starting on the line when the file can't be read
+    let defined = state
+        .storage
+        .get_symbols_by_path(project_id, file_path)
+        .await?
+        .into_iter()
+        .filter(|s| s.start_line <= line && line <= s.end_line)
+        .filter(|s| match word.as_deref() {
+            Some(word) => s.name == word,
+            None => s.start_line == line,
+        })
+        .min_by_key(|s| s.end_line - s.start_line);
+    Ok(match defined {
+        Some(symbol) => Located::Symbol(Box::new(symbol), None),
+        None => Located::Nothing,
+    })
+}
+
+/// Identifier around a 1-based line and 0-based column.
+fn word_at(text: &str, line: u32, column: u32) -> Option<String> {
+    let text = text.lines().nth(line.checked_sub(1)? as usize)?;
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    let mut column = (column as usize).min(text.len());
+    while !text.is_char_boundary(column) {
+        column -= 1;
+    }
+    let start = text[..column]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_word(*c))
+        .last()
+        .map_or(column, |(i, _)| i);
+    let end = text[column..]
+        .char_indices()
+        .find(|(_, c)| !is_word(*c))
+        .map_or(text.len(), |(i, _)| column + i);
+    Some(text[start..end].to_string()).filter(|w| !w.is_empty())
+}
+
+fn occurrence_json(
+    occurrence: &ReferenceOccurrence,
+    absolute_path: Option<String>,
+) -> serde_json::Value {
+    json!({
+        "name": occurrence.name,
+        "file_path": occurrence.file_path,
+        "absolute_path": absolute_path,
+        "line": occurrence.line,
+        "column": occurrence.column,
+        "from_symbol": occurrence.from_symbol,
+        "relation_type": occurrence.relation_type,
+        "source": occurrence.source,
+    })
+}
+
+pub async fn find_definition(
+    state: &Arc<AppState>,
+    params: FindDefinitionParams,
+) -> anyhow::Result<CallToolResult> {
+    let mut paths = HostPaths::new(state);
+    let file_path = paths
+        .relative(Some(&params.project_id), &params.file_path)
+        .await;
+    let located = match locate(
+        state,
+        &mut paths,
+        &params.project_id,
+        &file_path,
+        params.line,
+        params.column,
+    )
+    .await
+    {
+        Ok(located) => located,
+        Err(e) => return Ok(error_response(e)),
+    };
+
+    match located {
+        Located::Symbol(symbol, occurrence) => {
+            let reference = match &occurrence {
+                Some(o) => {
+                    let absolute = paths.absolute(&params.project_id, &o.file_path).await;
+                    occurrence_json(o, absolute)
+                }
+                None => serde_json::Value::Null,
+            };
+            let definition = paths.symbols_json(vec![*symbol]).await.remove(0);
+            Ok(success_json(json!({
+                "definition": definition,
+                "reference": reference,
+                "on_definition": occurrence.is_none(),
+            })))
+        }
+        Located::Unresolved(occurrence) => Ok(success_json(json!({
+            "definition": null,
+            "name": occurrence.name,
+            "reason": occurrence.reason,
+            "module": occurrence.module,
+        }))),
+        Located::Nothing => Ok(error_response(format!(
+            "No indexed symbol or reference at {}:{}:{}",
+            file_path, params.line, params.column
+        ))),
+    }
+}
+
+pub async fn find_references(
+    state: &Arc<AppState>,
+    params: FindReferencesParams,
+) -> anyhow::Result<CallToolResult> {
+    let limit = params.limit.unwrap_or(100).clamp(1, 1000);
+    let mut paths = HostPaths::new(state);
+
+    let symbol = match (
+        &params.symbol_id,
+        &params.project_id,
+        &params.file_path,
+        params.line,
+    ) {
+        (Some(id), ..) => match state
+            .storage
+            .get_symbols_by_ids(std::slice::from_ref(id))
+            .await
+        {
+            Ok(mut symbols) => symbols.pop(),
+            Err(e) => return Ok(error_response(e)),
+        },
+        (None, Some(project_id), Some(file_path), Some(line)) => {
+            let file_path = paths.relative(Some(project_id), file_path).await;
+            let column = params.column.unwrap_or(0);
+            match locate(state, &mut paths, project_id, &file_path, line, column).await {
+                Ok(Located::Symbol(symbol, _)) => Some(*symbol),
+                Ok(Located::Unresolved(o)) => {
+                    return Ok(error_response(format!(
+                        "`{}` has no definition in the project ({})",
+                        o.name,
+                        o.reason.as_deref().unwrap_or("unresolved")
+                    )))
+                }
+                Ok(Located::Nothing) => None,
+                Err(e) => return Ok(error_response(e)),
+            }
+        }
+        _ => {
+            return Ok(error_response(
+                "Give symbol_id, or project_id with file_path, line and column",
+            ))
+        }
+    };
+    let Some(symbol) = symbol else {
+        return Ok(error_response("No symbol found"));
+    };
+
+    let symbol_id = format!("code_symbols:{}", symbol.unique_key());
+    let occurrences = match state
+        .storage
+        .get_symbol_occurrences(&symbol_id, limit)
+        .await
+    {
+        Ok(occurrences) => occurrences,
+        Err(e) => return Ok(error_response(e)),
+    };
+    let mut references = Vec::with_capacity(occurrences.len());
+    for occurrence in &occurrences {
+        let absolute = paths
+            .absolute(&occurrence.project_id, &occurrence.file_path)
+            .await;
+        references.push(occurrence_json(occurrence, absolute));
+    }
+    let symbol = paths.symbols_json(vec![symbol]).await.remove(0);
+    Ok(success_json(json!({
+        "symbol": symbol,
+        "references": references,
+        "count": references.len(),
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{response_json, TestContext};
+    use std::fs;
+
+    #[tokio::test]
+    async fn test_call_hierarchy_paths_and_impact() {
+        let ctx = TestContext::new().await;
+        let root = ctx._temp_dir.path().join("calls");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join("lib.rs"),
+            r#"fn parse(input: &str) -> u32 {
+    input.len() as u32
+}
+
+fn load(input: &str) -> u32 {
+    parse(input)
+}
+
+fn run() -> u32 {
+    load("x") + parse("y")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        let n = load("ab");
+        assert_eq!(n, 2);
+    }
+}
+"#,
+        )
+        .unwrap();
+        crate::codebase::index_project(ctx.state.clone(), &root)
+            .await
+            .unwrap();
+        let symbols = ctx
+            .state
+            .storage
+            .get_symbols_by_path("calls", "lib.rs")
+            .await
+            .unwrap();
+        let id = |name: &str| {
+            let symbol = symbols.iter().find(|s| s.name == name).unwrap();
+            format!("code_symbols:{}", symbol.unique_key())
+        };
+
+        let json = response_json(
+            &get_call_hierarchy(
+                &ctx.state,
+                GetCallHierarchyParams {
+                    symbol_id: id("parse"),
+                    direction: None,
+                    depth: None,
+                    relation_types: None,
+                },
+            )
+            .await
+            .unwrap(),
+        );
+        let tree = &json["tree"];
+        assert_eq!(tree["name"], "parse");
+        let callers: Vec<&str> = tree["children"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(callers.len(), 2);
+        assert!(callers.contains(&"load") && callers.contains(&"run"));
+
+        let json = response_json(
+            &get_call_hierarchy(
+                &ctx.state,
+                GetCallHierarchyParams {
+                    symbol_id: id("parse"),
+                    direction: Some("callers".to_string()),
+                    depth: None,
+                    relation_types: None,
+                },
+            )
+            .await
+            .unwrap(),
+        );
+        assert!(json["error"].is_string());
+
+        let json = response_json(
+            &find_call_paths(
+                &ctx.state,
+                FindCallPathsParams {
+                    from_symbol_id: id("run"),
+                    to_symbol_id: id("parse"),
+                    max_length: None,
+                    max_paths: None,
+                    relation_types: None,
+                },
+            )
+            .await
+            .unwrap(),
+        );
+        let lengths: Vec<u64> = json["paths"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["length"].as_u64().unwrap())
+            .collect();
+        assert_eq!(lengths, vec![1, 2]);
+        assert_eq!(json["paths"][1]["symbols"][1]["name"], "load");
+
+        let json = response_json(
+            &impact_of_change(
+                &ctx.state,
+                ImpactOfChangeParams {
+                    project_id: "calls".to_string(),
+                    files: None,
+                    symbol_ids: Some(vec![id("parse")]),
+                    depth: None,
+                    relation_types: None,
+                },
+            )
+            .await
+            .unwrap(),
+        );
+        let affected: Vec<(&str, u64)> = json["affected"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|a| (a["name"].as_str().unwrap(), a["distance"].as_u64().unwrap()))
+            .collect();
+        assert!(affected.contains(&("load", 1)) && affected.contains(&("run", 1)));
+        assert!(affected.contains(&("test_load", 2)));
+        assert_eq!(json["tests"].as_array().unwrap().len(), 1);
+        assert_eq!(json["tests"][0]["name"], "test_load");
+    }
+
+    #[tokio::test]
+    async fn test_type_hierarchy() {
+        let ctx = TestContext::new().await;
+        let root = ctx._temp_dir.path().join("types");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join("lib.rs"),
+            r#"pub trait Store {
+    fn get(&self) -> u32;
+    fn put(&mut self, v: u32) {}
+}
+
+pub struct Mem { v: u32 }
+
+impl Store for Mem {
+    fn get(&self) -> u32 { self.v }
+    fn put(&mut self, v: u32) { self.v = v; }
+}
+
+pub struct Disk;
+
+impl Store for Disk {
+    fn get(&self) -> u32 { 0 }
+    fn flush(&self) {}
+}
+"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join("shapes.ts"),
+            r#"class Base {
+  name(): string { return 'b'; }
+  size(): number { return 0; }
+}
+
+class Square extends Base {
+  name(): string { return 's'; }
+}
+"#,
+        )
+        .unwrap();
+        crate::codebase::index_project(ctx.state.clone(), &root)
+            .await
+            .unwrap();
+        let symbols = ctx
+            .state
+            .storage
+            .get_project_symbols("types")
+            .await
+            .unwrap();
+        let id = |name: &str| {
+            let symbol = symbols.iter().find(|s| s.name == name).unwrap();
+            format!("code_symbols:{}", symbol.unique_key())
+        };
+
+        let json = response_json(
+            &get_type_hierarchy(
+                &ctx.state,
+                GetTypeHierarchyParams {
+                    symbol_id: id("Store"),
+                    direction: Some("subtypes".to_string()),
+                    depth: None,
+                },
+            )
+            .await
+            .unwrap(),
+        );
+        assert!(json.get("supertypes").is_none());
+        let tree = &json["subtypes"];
+        assert_eq!(tree["name"], "Store");
+        assert_eq!(tree["methods"].as_array().unwrap().len(), 2);
+        let implementors = tree["children"].as_array().unwrap();
+        assert_eq!(implementors.len(), 2);
+        let overrides = |name: &str, line: u64| -> Vec<(String, bool)> {
+            let implementor = implementors.iter().find(|c| c["name"] == name).unwrap();
+            assert_eq!(implementor["relation_type"], "implements");
+            let declared = symbols
+                .iter()
+                .find(|s| s.name == name && s.symbol_type == SymbolType::Struct)
+                .unwrap();
+            assert_eq!(
+                implementor["id"],
+                format!("code_symbols:{}", declared.unique_key())
+            );
+            assert_eq!(implementor["location"]["start_line"], line);
+            implementor["methods"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|m| {
+                    (
+                        m["name"].as_str().unwrap().to_string(),
+                        m.get("overrides").is_some(),
+                    )
+                })
+                .collect()
+        };
+        assert_eq!(
+            overrides("Mem", 8),
+            vec![("get".to_string(), true), ("put".to_string(), true)]
+        );
+        assert_eq!(
+            overrides("Disk", 15),
+            vec![("get".to_string(), true), ("flush".to_string(), false)]
+        );
+
+        let json = response_json(
+            &get_type_hierarchy(
+                &ctx.state,
+                GetTypeHierarchyParams {
+                    symbol_id: id("Square"),
+                    direction: None,
+                    depth: None,
+                },
+            )
+            .await
+            .unwrap(),
+        );
+        assert_eq!(json["subtypes"]["children"].as_array().unwrap().len(), 0);
+        let base = &json["supertypes"]["children"][0];
+        assert_eq!(base["name"], "Base");
+        assert_eq!(base["relation_type"], "extends");
+        let methods = base["methods"].as_array().unwrap();
+        assert_eq!(methods[0]["name"], "name");
+        assert_eq!(
+            methods[0]["overridden_by"][0],
+            json["supertypes"]["methods"][0]["id"]
+        );
+        assert!(methods[1].get("overridden_by").is_none());
+
+        let json = response_json(
+            &get_type_hierarchy(
+                &ctx.state,
+                GetTypeHierarchyParams {
+                    symbol_id: id("Store"),
+                    direction: Some("sideways".to_string()),
+                    depth: None,
+                },
+            )
+            .await
+            .unwrap(),
+        );
+        assert!(json["error"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_find_definition_and_references() {
+        let ctx = TestContext::new().await;
+        let root = ctx._temp_dir.path().join("nav");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join("lib.rs"),
+            "fn helper() -> u32 {\n    1\n}\n\nfn run() -> u32 {\n    helper() + helper()\n}\n",
+        )
+        .unwrap();
+        crate::codebase::index_project(ctx.state.clone(), &root)
+            .await
+            .unwrap();
+        let at = |line, column| FindDefinitionParams {
+            project_id: "nav".to_string(),
+            file_path: "lib.rs".to_string(),
+            line,
+            column,
+        };
+
+        // On the second call, inside the name
+        let json = response_json(&find_definition(&ctx.state, at(6, 17)).await.unwrap());
+        assert_eq!(json["definition"]["name"], "helper");
+        assert_eq!(json["definition"]["start_line"], 1);
+        assert_eq!(json["reference"]["column"], 15);
+        assert_eq!(json["on_definition"], false);
+
+        // On the definition itself
+        let json = response_json(&find_definition(&ctx.state, at(1, 4)).await.unwrap());
+        assert_eq!(json["definition"]["name"], "helper");
+        assert_eq!(json["on_definition"], true);
+
+        let json = response_json(&find_definition(&ctx.state, at(2, 4)).await.unwrap());
+        assert!(json["error"].is_string());
+
+        let json = response_json(
+            &find_references(
+                &ctx.state,
+                FindReferencesParams {
+                    symbol_id: None,
+                    project_id: Some("nav".to_string()),
+                    file_path: Some("lib.rs".to_string()),
+                    line: Some(1),
+                    column: Some(5),
+                    limit: None,
+                },
+            )
+            .await
+            .unwrap(),
+        );
+        assert_eq!(json["symbol"]["name"], "helper");
+        let positions: Vec<(u64, u64)> = json["references"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| (r["line"].as_u64().unwrap(), r["column"].as_u64().unwrap()))
+            .collect();
+        assert_eq!(positions, vec![(6, 4), (6, 15)]);
+        assert_eq!(json["references"][0]["from_symbol"], "run");
+    }
+}
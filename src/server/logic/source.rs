@@ -0,0 +1,531 @@
+use std::collections::HashMap;
+use std::path::{Component, Path};
+use std::sync::Arc;
+
+use rmcp::model::CallToolResult;
+use serde_json::json;
+
+use crate::config::AppState;
+use crate::server::params::{
+    GetChunkParams, GetCodeParams, GetFileOutlineParams, GetSymbolSourceParams,
+};
+use crate::storage::StorageBackend;
+use crate::types::{record_id_string, AppError, CodeSymbol, SymbolType};
+
+use super::code::HostPaths;
+use super::navigation::symbol_record;
+use super::{error_response, success_json};
+
+/// Default and ceiling of the `max_chars` budget of the source tools.
+const DEFAULT_MAX_CHARS: usize = 20_000;
+const MAX_CHARS: usize = 200_000;
+
+pub async fn get_file_outline(
+    state: &Arc<AppState>,
+    params: GetFileOutlineParams,
+) -> anyhow::Result<CallToolResult> {
+    let max_chars = params.max_chars.unwrap_or(DEFAULT_MAX_CHARS).min(MAX_CHARS);
+    let mut paths = HostPaths::new(state);
+    let file_path = paths.relative(Some(&params.project_id), &params.path).await;
+    let mut symbols: Vec<CodeSymbol> = match state
+        .storage
+        .get_symbols_by_path(&params.project_id, &file_path)
+        .await
+    {
+        Ok(symbols) => symbols
+            .into_iter()
+            .filter(|s| s.symbol_type != SymbolType::Import)
+            .collect(),
+        Err(e) => return Ok(error_response(e)),
+    };
+    if symbols.is_empty() {
+        return Ok(error_response(format!(
+            "No indexed symbols for {} in project {}",
+            file_path, params.project_id
+        )));
+    }
+
+    // Enclosing symbols sort before what they contain; keep them in that
+    // order until the budget runs out
+    symbols.sort_by_key(|s| (s.start_line, std::cmp::Reverse(s.end_line)));
+    let mut used = 0;
+    let kept = symbols
+        .iter()
+        .take_while(|s| {
+            used += s.name.len() + s.signature.as_ref().map_or(0, String::len);
+            used <= max_chars
+        })
+        .count();
+    let truncated = kept < symbols.len();
+    symbols.truncate(kept);
+
+    Ok(success_json(json!({
+        "project_id": params.project_id,
+        "file_path": file_path,
+        "absolute_path": paths.absolute(&params.project_id, &file_path).await,
+        "symbols": outline_tree(&symbols),
+        "count": kept,
+        "truncated": truncated,
+    })))
+}
+
+/// Symbols, sorted by line with enclosing ones first, nested under the
+/// nearest earlier symbol whose qualified name is their parent.
+fn outline_tree(symbols: &[CodeSymbol]) -> Vec<serde_json::Value> {
+    let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, symbol) in symbols.iter().enumerate() {
+        if let Some(name) = &symbol.qualified_name {
+            by_name.entry(name).or_default().push(i);
+        }
+    }
+    let mut children = vec![Vec::new(); symbols.len()];
+    let mut roots = Vec::new();
+    for (i, symbol) in symbols.iter().enumerate() {
+        // Prefer the candidate that encloses the symbol, e.g. an impl
+        // block over the struct it implements
+        let candidates = symbol.parent.as_deref().and_then(|p| by_name.get(p));
+        let parent = candidates.and_then(|candidates| {
+            let earlier = &candidates[..candidates.partition_point(|&c| c < i)];
+            earlier
+                .iter()
+                .rfind(|&&c| symbols[c].end_line >= symbol.end_line)
+                .or(earlier.first())
+                .copied()
+        });
+        match parent {
+            Some(parent) => children[parent].push(i),
+            None => roots.push(i),
+        }
+    }
+
+    fn node(i: usize, symbols: &[CodeSymbol], children: &[Vec<usize>]) -> serde_json::Value {
+        let symbol = &symbols[i];
+        json!({
+            "id": format!("code_symbols:{}", symbol.unique_key()),
+            "name": symbol.name,
+            "symbol_type": symbol.symbol_type,
+            "signature": symbol.signature,
+            "start_line": symbol.start_line,
+            "end_line": symbol.end_line,
+            "children": children[i]
+                .iter()
+                .map(|&c| node(c, symbols, children))
+                .collect::<Vec<_>>(),
+        })
+    }
+    roots.iter().map(|&i| node(i, symbols, &children)).collect()
+}
+
+pub async fn get_code(
+    state: &Arc<AppState>,
+    params: GetCodeParams,
+) -> anyhow::Result<CallToolResult> {
+    let max_chars = params.max_chars.unwrap_or(DEFAULT_MAX_CHARS).min(MAX_CHARS);
+    let start = params.start_line.unwrap_or(1).max(1);
+    if params.end_line.is_some_and(|end| end < start) {
+        return Ok(error_response("end_line is before start_line"));
+    }
+    let mut paths = HostPaths::new(state);
+    let file_path = paths.relative(Some(&params.project_id), &params.path).await;
+    let inside = Path::new(&file_path)
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if !inside {
+        return Ok(error_response(format!(
+            "Path is outside the project: {}",
+            params.path
+        )));
+    }
+
+    let lines = match read_lines(
+        state,
+        &mut paths,
+        &params.project_id,
+        &file_path,
+        start,
+        params.end_line,
+    )
+    .await
+    {
+        Ok(Some(lines)) => lines,
+        Ok(None) => {
+            return Ok(error_response(format!(
+                "Source not available for {} in project {}",
+                file_path, params.project_id
+            )))
+        }
+        Err(e) => return Ok(error_response(e)),
+    };
+    let mut value = lines.into_json(max_chars);
+    value["project_id"] = json!(params.project_id);
+    value["absolute_path"] = json!(paths.absolute(&params.project_id, &file_path).await);
+    value["file_path"] = json!(file_path);
+    Ok(success_json(value))
+}
+
+pub async fn get_chunk(
+    state: &Arc<AppState>,
+    params: GetChunkParams,
+) -> anyhow::Result<CallToolResult> {
+    let max_chars = params.max_chars.unwrap_or(DEFAULT_MAX_CHARS).min(MAX_CHARS);
+    let chunk = match state.storage.get_code_chunk(&params.id).await {
+        Ok(Some(chunk)) => chunk,
+        Ok(None) => return Ok(error_response(format!("Chunk not found: {}", params.id))),
+        Err(e) => return Ok(error_response(e)),
+    };
+    let absolute_path = match &chunk.project_id {
+        Some(project_id) => {
+            HostPaths::new(state)
+                .absolute(project_id, &chunk.file_path)
+                .await
+        }
+        None => None,
+    };
+    let (content, truncated) = clip(chunk.content, max_chars);
+    Ok(success_json(json!({
+        "id": chunk.id.as_ref().map(record_id_string),
+        "project_id": chunk.project_id,
+        "file_path": chunk.file_path,
+        "absolute_path": absolute_path,
+        "language": chunk.language,
+        "chunk_type": chunk.chunk_type,
+        "name": chunk.name,
+        "start_line": chunk.start_line,
+        "end_line": chunk.end_line,
+        "content": content,
+        "truncated": truncated,
+    })))
+}
+
+pub async fn get_symbol_source(
+    state: &Arc<AppState>,
+    params: GetSymbolSourceParams,
+) -> anyhow::Result<CallToolResult> {
+    let max_chars = params.max_chars.unwrap_or(DEFAULT_MAX_CHARS).min(MAX_CHARS);
+    let symbol_id = symbol_record(&params.symbol_id);
+    let symbol = match state
+        .storage
+        .get_symbols_by_ids(std::slice::from_ref(&symbol_id))
+        .await
+    {
+        Ok(mut found) => match found.pop() {
+            Some(symbol) => symbol,
+            None => return Ok(error_response(format!("Symbol not found: {}", symbol_id))),
+        },
+        Err(e) => return Ok(error_response(e)),
+    };
+    let mut paths = HostPaths::new(state);
+    let lines = match read_lines(
+        state,
+        &mut paths,
+        &symbol.project_id,
+        &symbol.file_path,
+        symbol.start_line,
+        Some(symbol.end_line),
+    )
+    .await
+    {
+        Ok(Some(lines)) => lines,
+        Ok(None) => {
+            return Ok(error_response(format!(
+                "Source not available for {} in project {}",
+                symbol.file_path, symbol.project_id
+            )))
+        }
+        Err(e) => return Ok(error_response(e)),
+    };
+    let mut value = lines.into_json(max_chars);
+    value["symbol"] = json!({
+        "id": symbol_id,
+        "name": symbol.name,
+        "symbol_type": symbol.symbol_type,
+        "qualified_name": symbol.qualified_name,
+        "signature": symbol.signature,
+    });
+    value["project_id"] = json!(symbol.project_id);
+    value["absolute_path"] = json!(paths.absolute(&symbol.project_id, &symbol.file_path).await);
+    value["file_path"] = json!(symbol.file_path);
+    Ok(success_json(value))
+}
+
+/// A line range of a project file and where it was read from.
+struct SourceLines {
+    text: String,
+    start_line: u32,
+    end_line: u32,
+    /// Lines in the file, known when it was read from disk
+    total_lines: Option<u32>,
+    /// `file` or `chunks`
+    source: &'static str,
+    /// False when the stored chunks leave lines of the range uncovered
+    complete: bool,
+}
+
+impl SourceLines {
+    /// Clip the text to `max_chars`; `next_line` resumes a clipped read.
+    fn into_json(self, max_chars: usize) -> serde_json::Value {
+        let (content, truncated) = clip(self.text, max_chars);
+        let next_line = truncated.then(|| self.start_line + content.matches('\n').count() as u32);
+        json!({
+            "start_line": self.start_line,
+            "end_line": self.end_line,
+            "total_lines": self.total_lines,
+            "source": self.source,
+            "complete": self.complete,
+            "content": content,
+            "truncated": truncated,
+            "next_line": next_line,
+        })
+    }
+}
+
+/// Lines `start..=end` (1-based, `end` defaulting to the last line) of a
+/// project file. Read from the project root when it is readable here, else
+/// pieced together from the stored chunks, with uncovered lines left empty.
+/// `None` when neither has the file.
+async fn read_lines(
+    state: &AppState,
+    paths: &mut HostPaths<'_>,
+    project_id: &str,
+    file_path: &str,
+    start: u32,
+    end: Option<u32>,
+) -> Result<Option<SourceLines>, AppError> {
+    if let Some(text) = paths.read(project_id, file_path).await {
+        let lines: Vec<&str> = text.lines().collect();
+        let total = lines.len() as u32;
+        let end = end.unwrap_or(total).min(total);
+        let text = match start <= end {
+            true => lines[start as usize - 1..end as usize].join("\n"),
+            false => String::new(),
+        };
+        return Ok(Some(SourceLines {
+            text,
+            start_line: start,
+            end_line: end,
+            total_lines: Some(total),
+            source: "file",
+            complete: true,
+        }));
+    }
+
+    let chunks = state
+        .storage
+        .get_chunks_by_path(project_id, file_path)
+        .await?;
+    if chunks.is_empty() {
+        return Ok(None);
+    }
+    let mut lines = std::collections::BTreeMap::new();
+    for chunk in &chunks {
+        for (i, line) in chunk.content.lines().enumerate() {
+            let number = chunk.start_line + i as u32;
+            // A chunk starts at its node, which may be past the indentation
+            // of its first line
+            if i > 0 {
+                lines.insert(number, line);
+            } else {
+                lines.entry(number).or_insert(line);
+            }
+        }
+    }
+    let last = lines.keys().next_back().copied().unwrap_or(0);
+    let end = end.unwrap_or(last).min(last);
+    let range: Vec<Option<&str>> = (start..=end).map(|n| lines.get(&n).copied()).collect();
+    Ok(Some(SourceLines {
+        text: range
+            .iter()
+            .map(|line| line.unwrap_or(""))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        start_line: start,
+        end_line: end,
+        total_lines: None,
+        source: "chunks",
+        complete: range.iter().all(Option::is_some),
+    }))
+}
+
+/// Cut `text` to at most `max_chars` characters, at a line break when one
+/// falls in the second half of the budget.
+fn clip(text: String, max_chars: usize) -> (String, bool) {
+    let Some((cut, _)) = text.char_indices().nth(max_chars) else {
+        return (text, false);
+    };
+    let cut = match text[..cut].rfind('\n') {
+        Some(newline) if newline >= cut / 2 => newline + 1,
+        _ => cut,
+    };
+    (text[..cut].to_string(), true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{response_json, TestContext};
+    use std::fs;
+
+    #[tokio::test]
+    async fn test_file_outline_and_source() {
+        let ctx = TestContext::new().await;
+        let root = ctx._temp_dir.path().join("sourced");
+        fs::create_dir_all(&root).unwrap();
+        let text = "use std::fmt;\n\npub struct Counter {\n    count: u32,\n}\n\nimpl Counter {\n    pub fn bump(&mut self) -> u32 {\n        self.count += 1;\n        self.count\n    }\n}\n\nfn helper() -> u32 {\n    1\n}\n";
+        fs::write(root.join("lib.rs"), text).unwrap();
+        crate::codebase::index_project(ctx.state.clone(), &root)
+            .await
+            .unwrap();
+
+        let outline = |max_chars: Option<usize>| {
+            let state = ctx.state.clone();
+            async move {
+                response_json(
+                    &get_file_outline(
+                        &state,
+                        GetFileOutlineParams {
+                            project_id: "sourced".to_string(),
+                            path: "lib.rs".to_string(),
+                            max_chars,
+                        },
+                    )
+                    .await
+                    .unwrap(),
+                )
+            }
+        };
+        let json = outline(None).await;
+        let names = |nodes: &serde_json::Value| -> Vec<String> {
+            nodes
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|n| n["name"].as_str().unwrap().to_string())
+                .collect()
+        };
+        let symbols = &json["symbols"];
+        assert!(!names(symbols).contains(&"fmt".to_string()));
+        assert!(names(symbols).contains(&"helper".to_string()));
+        let bump = symbols
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|n| n["children"].as_array().unwrap())
+            .find(|n| n["name"] == "bump")
+            .unwrap();
+        assert_eq!(bump["start_line"], 8);
+        assert_eq!(bump["end_line"], 11);
+        assert!(bump["signature"].as_str().unwrap().contains("fn bump"));
+        assert_eq!(json["truncated"], false);
+        let json = outline(Some(10)).await;
+        assert_eq!(json["truncated"], true);
+
+        let code = |path: &str, start_line: Option<u32>, end_line: Option<u32>, max_chars| {
+            let (state, path) = (ctx.state.clone(), path.to_string());
+            async move {
+                response_json(
+                    &get_code(
+                        &state,
+                        GetCodeParams {
+                            project_id: "sourced".to_string(),
+                            path,
+                            start_line,
+                            end_line,
+                            max_chars,
+                        },
+                    )
+                    .await
+                    .unwrap(),
+                )
+            }
+        };
+        let json = code("lib.rs", Some(8), Some(11), None).await;
+        assert_eq!(json["source"], "file");
+        assert_eq!(json["total_lines"], 16);
+        assert_eq!(
+            json["content"],
+            "    pub fn bump(&mut self) -> u32 {\n        self.count += 1;\n        self.count\n    }"
+        );
+        let json = code("lib.rs", None, None, Some(20)).await;
+        assert_eq!(json["truncated"], true);
+        assert_eq!(json["content"], "use std::fmt;\n\n");
+        assert_eq!(json["next_line"], 3);
+        assert!(code("../secret", None, None, None).await["error"].is_string());
+        // Decoded as the indexer decodes it, not dropped as non-UTF-8
+        fs::write(root.join("latin.rs"), b"// caf\xe9\n").unwrap();
+        let json = code("latin.rs", None, None, None).await;
+        assert_eq!(json["source"], "file");
+        assert_eq!(json["content"], "// café");
+
+        let bump_id = bump["id"].as_str().unwrap().to_string();
+        let source = |max_chars| {
+            let (state, symbol_id) = (ctx.state.clone(), bump_id.clone());
+            async move {
+                response_json(
+                    &get_symbol_source(
+                        &state,
+                        GetSymbolSourceParams {
+                            symbol_id,
+                            max_chars,
+                        },
+                    )
+                    .await
+                    .unwrap(),
+                )
+            }
+        };
+        let json = source(None).await;
+        assert_eq!(json["symbol"]["name"], "bump");
+        assert!(json["content"]
+            .as_str()
+            .unwrap()
+            .starts_with("    pub fn bump"));
+
+        // Without the file, the text comes from the stored chunks
+        fs::remove_file(root.join("lib.rs")).unwrap();
+        let json = source(None).await;
+        assert_eq!(json["source"], "chunks");
+        assert_eq!(json["complete"], true);
+        assert!(json["content"]
+            .as_str()
+            .unwrap()
+            .contains("        self.count += 1;\n"));
+        let json = code("lib.rs", Some(14), Some(16), None).await;
+        assert_eq!(json["source"], "chunks");
+        assert_eq!(json["content"], "fn helper() -> u32 {\n    1\n}");
+
+        let chunks = ctx
+            .state
+            .storage
+            .get_chunks_by_path("sourced", "lib.rs")
+            .await
+            .unwrap();
+        let chunk_id = record_id_string(chunks[0].id.as_ref().unwrap());
+        let json = response_json(
+            &get_chunk(
+                &ctx.state,
+                GetChunkParams {
+                    id: chunk_id.clone(),
+                    max_chars: Some(5),
+                },
+            )
+            .await
+            .unwrap(),
+        );
+        assert_eq!(json["id"], chunk_id);
+        assert_eq!(json["file_path"], "lib.rs");
+        assert_eq!(json["content"].as_str().unwrap().chars().count(), 5);
+        assert_eq!(json["truncated"], true);
+        let json = response_json(
+            &get_chunk(
+                &ctx.state,
+                GetChunkParams {
+                    id: "code_chunks:missing".to_string(),
+                    max_chars: None,
+                },
+            )
+            .await
+            .unwrap(),
+        );
+        assert!(json["error"].is_string());
+    }
+}
@@ -83,6 +83,7 @@ pub async fn reset_all_memory(
     }
 
     state.storage.reset_db().await?;
+    state.symbol_indexes.clear().await;
 
     Ok(success_json(json!({
         "reset": true,
@@ -92,6 +92,9 @@ pub struct RecallCodeParams {
     /// Weight for graph (PageRank) channel (default: 0.30)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ppr_weight: Option<f32>,
+    /// Boost for frequently changed files when git history is indexed (default: 0.10)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history_weight: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -155,12 +158,47 @@ pub struct GetStatusParams {
     pub _placeholder: bool,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SearchCommitsParams {
+    /// Keywords to find in commit messages
+    pub query: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
+    /// Max results to return (default: 10)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GetFileHistoryParams {
+    pub project_id: String,
+    /// File path, relative to the project root or absolute
+    pub file_path: String,
+    /// Only commits that changed this symbol (name or qualified name)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    /// Max commits to return (default: 20)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct IndexProjectParams {
     pub path: String,
     /// Force full re-index even if project is already indexed (default: false)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub force: Option<bool>,
+    /// Index this branch, tag or commit from git objects instead of the
+    /// working tree, as project `<name>@<git_ref>`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_ref: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ImportScipParams {
+    pub project_id: String,
+    /// Path to an `index.scip` file
+    pub path: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -172,6 +210,40 @@ pub struct SearchCodeParams {
     pub limit: Option<usize>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FindSimilarCodeParams {
+    /// Chunk to compare against (id from a code search result)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk_id: Option<String>,
+    /// Code to compare against, instead of `chunk_id`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+    /// Minimum cosine similarity (default: 0.8)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FindClonesParams {
+    pub project_id: String,
+    /// Minimum cosine similarity of chunk embeddings (default: 0.92)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<f32>,
+    /// Minimum token overlap confirming a pair, 0 to 1 (default: 0.5)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_token_similarity: Option<f32>,
+    /// Ignore chunks shorter than this many lines (default: 5)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_lines: Option<u32>,
+    /// Max clusters to return
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GetIndexStatusParams {
     pub project_id: String,
@@ -188,6 +260,21 @@ pub struct DeleteProjectParams {
     pub project_id: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PauseIndexingParams {
+    pub project_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ResumeIndexingParams {
+    pub project_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CancelIndexingParams {
+    pub project_id: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ResetAllMemoryParams {
     pub confirm: bool,
@@ -233,6 +320,172 @@ pub struct GetRelatedSymbolsParams {
     pub direction: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GetCallHierarchyParams {
+    pub symbol_id: String,
+    /// `incoming` (callers, default), `outgoing` (callees) or `both`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction: Option<String>,
+    /// Levels to expand (default: 3, max: 10)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth: Option<usize>,
+    /// Edge types to follow (default: ["calls"])
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relation_types: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GetTypeHierarchyParams {
+    pub symbol_id: String,
+    /// `supertypes`, `subtypes` or `both` (default)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction: Option<String>,
+    /// Levels to expand (default: 3, max: 10)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FindCallPathsParams {
+    pub from_symbol_id: String,
+    pub to_symbol_id: String,
+    /// Max edges per path (default: 6, max: 12)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+    /// Number of shortest paths to return (default: 3, max: 10)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_paths: Option<usize>,
+    /// Edge types to follow (default: ["calls"])
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relation_types: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ImpactOfChangeParams {
+    pub project_id: String,
+    /// Changed files, relative to the project root or absolute
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub files: Option<Vec<String>>,
+    /// Changed symbols
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol_ids: Option<Vec<String>>,
+    /// Max hops from a changed symbol (default: unlimited)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth: Option<usize>,
+    /// Edge types that propagate a change (default: calls, references,
+    /// implements, extends, imports)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relation_types: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FindUnreferencedSymbolsParams {
+    pub project_id: String,
+    /// Names or qualified names that are always reachable
+    /// (default: ["main", "init", "constructor"])
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_points: Option<Vec<String>>,
+    /// Report unreferenced tests too (default: false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_tests: Option<bool>,
+    /// Report exported/public symbols too (default: false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_public: Option<bool>,
+    /// Report symbols with framework attributes or decorators too (default: false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_annotated: Option<bool>,
+    /// Max symbols to report (default: 200, max: 1000)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FindDefinitionParams {
+    pub project_id: String,
+    /// File path, relative to the project root or absolute
+    pub file_path: String,
+    /// 1-based line
+    pub line: u32,
+    /// 0-based column
+    pub column: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FindReferencesParams {
+    /// Symbol to find references to; or give a position instead
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol_id: Option<String>,
+    /// Project of `file_path`, required with a position
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
+    /// File path, relative to the project root or absolute
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_path: Option<String>,
+    /// 1-based line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    /// 0-based column
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<u32>,
+    /// Max references to return (default: 100)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GetRepoMapParams {
+    pub project_id: String,
+    /// Token budget of the outline (default: 1024, max: 8192)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<usize>,
+    /// Files or directories to rank toward, relative to the project root or absolute
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focus_paths: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GetFileOutlineParams {
+    pub project_id: String,
+    /// File path, relative to the project root or absolute
+    pub path: String,
+    /// Character budget for names and signatures (default: 20000, max: 200000)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_chars: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GetCodeParams {
+    pub project_id: String,
+    /// File path, relative to the project root or absolute
+    pub path: String,
+    /// 1-based first line (default: 1)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<u32>,
+    /// 1-based last line, inclusive (default: end of file)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<u32>,
+    /// Character budget for the returned text (default: 20000, max: 200000)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_chars: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GetChunkParams {
+    /// Chunk id from a code search result
+    pub id: String,
+    /// Character budget for the returned text (default: 20000, max: 200000)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_chars: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GetSymbolSourceParams {
+    pub symbol_id: String,
+    /// Character budget for the returned text (default: 20000, max: 200000)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_chars: Option<usize>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GetProjectStatsParams {
     pub project_id: String,
@@ -9,8 +9,9 @@ use surrealdb::Surreal;
 use super::StorageBackend;
 use crate::graph::GraphTraversalStorage;
 use crate::types::{
-    CodeChunk, CodeRelationType, CodeSymbol, Direction, Entity, IndexStatus, Memory, MemoryUpdate,
-    Relation, ScoredCodeChunk, SearchResult, SurrealValue, SymbolRelation,
+    CodeChunk, CodeRelationType, CodeSymbol, Direction, Entity, FileChurn, GitCommit, IndexStatus,
+    Memory, MemoryUpdate, ReferenceOccurrence, Relation, ScoredCodeChunk, SearchResult,
+    SurrealValue, SymbolRelation, SymbolType, UnresolvedReference,
 };
 use crate::Result;
 
@@ -94,6 +95,11 @@ impl SurrealStorage {
     }
 }
 
+/// Relations per `create_symbol_relations_batch` query
+const RELATION_BATCH_SIZE: usize = 500;
+/// Newest matching commits ranked per `search_commits` result
+const COMMIT_CANDIDATES_PER_RESULT: usize = 10;
+
 fn generate_id() -> String {
     use std::sync::atomic::{AtomicU64, Ordering};
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -236,6 +242,13 @@ fn value_to_symbol_relations(value: surrealdb_types::Value) -> Vec<SymbolRelatio
                 Some(Value::String(s)) => s.to_string(),
                 _ => String::new(),
             };
+            let source = match obj.get("source") {
+                Some(Value::String(s)) => {
+                    serde_json::from_value(serde_json::Value::String(s.to_string()))
+                        .unwrap_or_default()
+                }
+                _ => Default::default(),
+            };
             let created_at = match obj.get("created_at") {
                 Some(Value::Datetime(d)) => *d,
                 _ => Default::default(),
@@ -249,6 +262,7 @@ fn value_to_symbol_relations(value: surrealdb_types::Value) -> Vec<SymbolRelatio
                 file_path,
                 line_number,
                 project_id,
+                source,
                 created_at,
             });
         }
@@ -471,6 +485,7 @@ impl StorageBackend for SurrealStorage {
         let query = r#"
             SELECT 
                 meta::id(id) AS id,
+                project_id,
                 file_path,
                 content,
                 language,
@@ -478,6 +493,7 @@ impl StorageBackend for SurrealStorage {
                 end_line,
                 chunk_type,
                 name,
+                context,
                 vector::similarity::cosine(embedding, $vec) AS score 
             FROM code_chunks
             WHERE embedding IS NOT NONE
@@ -552,6 +568,7 @@ impl StorageBackend for SurrealStorage {
         let sql = r#"
             SELECT 
                 meta::id(id) AS id,
+                project_id,
                 file_path,
                 content,
                 language,
@@ -559,6 +576,7 @@ impl StorageBackend for SurrealStorage {
                 end_line,
                 chunk_type,
                 name,
+                context,
                 1.0f AS score 
             FROM code_chunks 
             WHERE string::lowercase(content) CONTAINS string::lowercase($query)
@@ -887,6 +905,30 @@ impl StorageBackend for SurrealStorage {
         Ok(chunks)
     }
 
+    async fn get_code_chunk(&self, id: &str) -> Result<Option<CodeChunk>> {
+        let key = id.strip_prefix("code_chunks:").unwrap_or(id);
+        let chunk: Option<CodeChunk> = self.db.select(("code_chunks", key)).await?;
+        Ok(chunk)
+    }
+
+    async fn get_embedded_chunks(
+        &self,
+        project_id: &str,
+        min_lines: u32,
+        limit: usize,
+    ) -> Result<Vec<CodeChunk>> {
+        let sql = "SELECT * FROM code_chunks WHERE project_id = $project_id AND embedding IS NOT NONE AND end_line - start_line + 1 >= $min_lines ORDER BY file_path, start_line LIMIT $limit";
+        let mut response = self
+            .db
+            .query(sql)
+            .bind(("project_id", project_id.to_string()))
+            .bind(("min_lines", min_lines))
+            .bind(("limit", limit))
+            .await?;
+        let chunks: Vec<CodeChunk> = response.take(0)?;
+        Ok(chunks)
+    }
+
     async fn get_index_status(&self, project_id: &str) -> Result<Option<IndexStatus>> {
         let sql = "SELECT * FROM index_status WHERE project_id = $project_id LIMIT 1";
         let mut response = self
@@ -902,6 +944,9 @@ impl StorageBackend for SurrealStorage {
         let sql = r#"
             UPDATE index_status SET 
                 status = $status,
+                root = $root,
+                commit = $commit,
+                branch = $branch,
                 total_files = $total_files,
                 indexed_files = $indexed_files,
                 total_chunks = $total_chunks,
@@ -910,6 +955,7 @@ impl StorageBackend for SurrealStorage {
                 completed_at = $completed_at,
                 error_message = $error_message,
                 failed_files = $failed_files,
+                skipped_files = $skipped_files,
                 failed_embeddings = $failed_embeddings
             WHERE project_id = $project_id
         "#;
@@ -919,6 +965,9 @@ impl StorageBackend for SurrealStorage {
             .query(sql)
             .bind(("project_id", status.project_id.clone()))
             .bind(("status", status.status.clone()))
+            .bind(("root", status.root.clone()))
+            .bind(("commit", status.commit.clone()))
+            .bind(("branch", status.branch.clone()))
             .bind(("total_files", status.total_files))
             .bind(("indexed_files", status.indexed_files))
             .bind(("total_chunks", status.total_chunks))
@@ -927,6 +976,7 @@ impl StorageBackend for SurrealStorage {
             .bind(("completed_at", status.completed_at))
             .bind(("error_message", status.error_message.clone()))
             .bind(("failed_files", status.failed_files.clone()))
+            .bind(("skipped_files", status.skipped_files.clone()))
             .bind(("failed_embeddings", status.failed_embeddings))
             .await?;
 
@@ -980,6 +1030,24 @@ impl StorageBackend for SurrealStorage {
         }))
     }
 
+    async fn get_file_hashes(&self, project_id: &str) -> Result<HashMap<String, String>> {
+        let sql = "SELECT file_path, content_hash FROM file_hashes WHERE project_id = $project_id";
+        let mut response = self
+            .db
+            .query(sql)
+            .bind(("project_id", project_id.to_string()))
+            .await?;
+        let result: Vec<serde_json::Value> = response.take(0)?;
+        Ok(result
+            .into_iter()
+            .filter_map(|v| {
+                let path = v.get("file_path")?.as_str()?.to_string();
+                let hash = v.get("content_hash")?.as_str()?.to_string();
+                Some((path, hash))
+            })
+            .collect())
+    }
+
     async fn set_file_hash(&self, project_id: &str, file_path: &str, hash: &str) -> Result<()> {
         let sql = r#"
             UPSERT file_hashes SET
@@ -1018,6 +1086,191 @@ impl StorageBackend for SurrealStorage {
         Ok(())
     }
 
+    async fn rename_file(&self, project_id: &str, from: &str, to: &str) -> Result<usize> {
+        let sql = r#"
+            BEGIN TRANSACTION;
+            DELETE file_hashes WHERE project_id = $project_id AND file_path = $to;
+            UPDATE file_hashes SET file_path = $to
+                WHERE project_id = $project_id AND file_path = $from;
+            UPDATE code_chunks SET file_path = $to
+                WHERE project_id = $project_id AND file_path = $from
+                RETURN VALUE id;
+            COMMIT TRANSACTION;
+        "#;
+        let mut response = self
+            .db
+            .query(sql)
+            .bind(("project_id", project_id.to_string()))
+            .bind(("from", from.to_string()))
+            .bind(("to", to.to_string()))
+            .await?;
+        // Statement 0 is BEGIN
+        let moved: Vec<surrealdb_types::RecordId> = response.take(3)?;
+        Ok(moved.len())
+    }
+
+    async fn create_commits_batch(&self, commits: Vec<GitCommit>) -> Result<usize> {
+        if commits.is_empty() {
+            return Ok(0);
+        }
+        // Typed insert keeps `date` a datetime (see create_code_symbols_batch)
+        let created: Vec<GitCommit> = self.db.insert("git_commits").content(commits).await?;
+        Ok(created.len())
+    }
+
+    async fn get_commit_hashes(&self, project_id: &str) -> Result<HashSet<String>> {
+        let sql = "SELECT VALUE hash FROM git_commits WHERE project_id = $project_id";
+        let mut response = self
+            .db
+            .query(sql)
+            .bind(("project_id", project_id.to_string()))
+            .await?;
+        let hashes: Vec<String> = response.take(0)?;
+        Ok(hashes.into_iter().collect())
+    }
+
+    async fn search_commits(
+        &self,
+        query: &str,
+        project_id: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<GitCommit>> {
+        // TODO: SurrealDB v3.0.0 FULLTEXT @@ + search::score(0) is broken.
+        // Match any query word, then rank the newest matches by how many of
+        // them a message has.
+        let terms: Vec<String> = query
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .filter(|t| t.len() > 1)
+            .map(str::to_lowercase)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        if terms.is_empty() {
+            return Ok(vec![]);
+        }
+        let sql = r#"
+            SELECT * FROM git_commits
+            WHERE string::lowercase(message) CONTAINSANY $terms
+              AND ($project_id IS NONE OR project_id = $project_id)
+            ORDER BY date DESC
+            LIMIT $candidates
+        "#;
+        let mut response = self
+            .db
+            .query(sql)
+            .bind(("terms", terms.clone()))
+            .bind(("project_id", project_id.map(String::from)))
+            .bind((
+                "candidates",
+                limit.saturating_mul(COMMIT_CANDIDATES_PER_RESULT),
+            ))
+            .await?;
+        let commits: Vec<GitCommit> = response.take(0)?;
+
+        let mut scored: Vec<(usize, GitCommit)> = commits
+            .into_iter()
+            .map(|commit| {
+                let message = commit.message.to_lowercase();
+                let hits = terms
+                    .iter()
+                    .filter(|t| message.contains(t.as_str()))
+                    .count();
+                (hits, commit)
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| b.1.date.cmp(&a.1.date)));
+        Ok(scored
+            .into_iter()
+            .take(limit)
+            .map(|(_, commit)| commit)
+            .collect())
+    }
+
+    async fn get_file_commits(
+        &self,
+        project_id: &str,
+        file_path: &str,
+        limit: usize,
+    ) -> Result<Vec<GitCommit>> {
+        let sql = r#"
+            SELECT * FROM git_commits
+            WHERE project_id = $project_id AND files CONTAINS $file_path
+            ORDER BY date DESC
+            LIMIT $limit
+        "#;
+        let mut response = self
+            .db
+            .query(sql)
+            .bind(("project_id", project_id.to_string()))
+            .bind(("file_path", file_path.to_string()))
+            .bind(("limit", limit))
+            .await?;
+        let commits: Vec<GitCommit> = response.take(0)?;
+        Ok(commits)
+    }
+
+    async fn get_file_churn(
+        &self,
+        project_id: &str,
+        file_paths: &[String],
+    ) -> Result<HashMap<String, FileChurn>> {
+        if file_paths.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let sql = r#"
+            SELECT hash, email, date, files FROM git_commits
+            WHERE project_id = $project_id AND files CONTAINSANY $file_paths
+            ORDER BY date DESC
+        "#;
+        let mut response = self
+            .db
+            .query(sql)
+            .bind(("project_id", project_id.to_string()))
+            .bind(("file_paths", file_paths.to_vec()))
+            .await?;
+
+        #[derive(serde::Deserialize, SurrealValue)]
+        struct Touch {
+            hash: String,
+            email: String,
+            date: Datetime,
+            files: Vec<String>,
+        }
+
+        let touches: Vec<Touch> = response.take(0)?;
+        let wanted: HashSet<&String> = file_paths.iter().collect();
+        let mut authors: HashMap<&str, HashSet<&str>> = HashMap::new();
+        let mut churn: HashMap<String, FileChurn> = HashMap::new();
+        // Newest first, so the first commit seen for a file is its last change
+        for touch in &touches {
+            for path in touch.files.iter().filter(|p| wanted.contains(p)) {
+                authors.entry(path).or_default().insert(&touch.email);
+                churn
+                    .entry(path.clone())
+                    .and_modify(|c| c.commits += 1)
+                    .or_insert_with(|| FileChurn {
+                        commits: 1,
+                        authors: 0,
+                        last_modified: touch.date,
+                        last_commit: touch.hash.clone(),
+                    });
+            }
+        }
+        for (path, c) in churn.iter_mut() {
+            c.authors = authors.get(path.as_str()).map_or(0, |a| a.len() as u32);
+        }
+        Ok(churn)
+    }
+
+    async fn delete_project_commits(&self, project_id: &str) -> Result<()> {
+        let sql = "DELETE FROM git_commits WHERE project_id = $project_id";
+        self.db
+            .query(sql)
+            .bind(("project_id", project_id.to_string()))
+            .await?;
+        Ok(())
+    }
+
     async fn create_code_symbol(&self, mut symbol: CodeSymbol) -> Result<String> {
         let key = symbol.unique_key();
         let id = ("code_symbols", key.as_str());
@@ -1113,7 +1366,7 @@ impl StorageBackend for SurrealStorage {
     }
 
     async fn create_symbol_relation(&self, relation: SymbolRelation) -> Result<String> {
-        let sql = "RELATE $from->symbol_relation->$to SET relation_type = $rtype, project_id = $pid, file_path = $fpath, line_number = $lnum, created_at = $cat";
+        let sql = "RELATE $from->symbol_relation->$to SET relation_type = $rtype, project_id = $pid, file_path = $fpath, line_number = $lnum, source = $source, created_at = $cat";
         let from = relation.from_symbol.clone();
         let to = relation.to_symbol.clone();
 
@@ -1126,15 +1379,123 @@ impl StorageBackend for SurrealStorage {
             .bind(("pid", relation.project_id))
             .bind(("fpath", relation.file_path))
             .bind(("lnum", relation.line_number as i64))
+            .bind(("source", relation.source.to_string()))
             .bind(("cat", relation.created_at))
             .await?;
         Ok("relation_created".to_string())
     }
 
+    async fn create_symbol_relations_batch(&self, relations: Vec<SymbolRelation>) -> Result<usize> {
+        if relations.is_empty() {
+            return Ok(0);
+        }
+
+        let sql = r#"
+            FOR $r IN $rows {
+                RELATE (type::record($r.from))->symbol_relation->(type::record($r.to))
+                    SET relation_type = $r.relation_type, project_id = $r.project_id,
+                        file_path = $r.file_path, line_number = $r.line_number,
+                        source = $r.source;
+            };
+        "#;
+        let count = relations.len();
+        for batch in relations.chunks(RELATION_BATCH_SIZE) {
+            let rows: Vec<_> = batch
+                .iter()
+                .map(|r| {
+                    serde_json::json!({
+                        "from": crate::types::record_id_string(&r.from_symbol),
+                        "to": crate::types::record_id_string(&r.to_symbol),
+                        "relation_type": r.relation_type.to_string(),
+                        "project_id": r.project_id,
+                        "file_path": r.file_path,
+                        "line_number": r.line_number,
+                        "source": r.source.to_string(),
+                    })
+                })
+                .collect();
+            // A failed RELATE only shows up in the statement result
+            self.db.query(sql).bind(("rows", rows)).await?.check()?;
+        }
+        Ok(count)
+    }
+
+    async fn create_unresolved_references_batch(
+        &self,
+        references: Vec<UnresolvedReference>,
+    ) -> Result<usize> {
+        if references.is_empty() {
+            return Ok(0);
+        }
+        let created: Vec<UnresolvedReference> = self
+            .db
+            .insert("unresolved_reference")
+            .content(references)
+            .await?;
+        Ok(created.len())
+    }
+
+    async fn create_reference_occurrences_batch(
+        &self,
+        occurrences: Vec<ReferenceOccurrence>,
+    ) -> Result<usize> {
+        let mut count = 0;
+        for batch in occurrences.chunks(RELATION_BATCH_SIZE) {
+            let created: Vec<ReferenceOccurrence> = self
+                .db
+                .insert("reference_occurrence")
+                .content(batch.to_vec())
+                .await?;
+            count += created.len();
+        }
+        Ok(count)
+    }
+
+    async fn get_line_occurrences(
+        &self,
+        project_id: &str,
+        file_path: &str,
+        line: u32,
+    ) -> Result<Vec<ReferenceOccurrence>> {
+        let sql = r#"
+            SELECT * FROM reference_occurrence
+            WHERE project_id = $project_id AND file_path = $file_path AND line = $line
+            ORDER BY column
+        "#;
+        let mut response = self
+            .db
+            .query(sql)
+            .bind(("project_id", project_id.to_string()))
+            .bind(("file_path", file_path.to_string()))
+            .bind(("line", line as i64))
+            .await?;
+        Ok(response.take(0)?)
+    }
+
+    async fn get_symbol_occurrences(
+        &self,
+        symbol_id: &str,
+        limit: usize,
+    ) -> Result<Vec<ReferenceOccurrence>> {
+        let sql = r#"
+            SELECT * FROM reference_occurrence WHERE to_symbol = $symbol
+            ORDER BY file_path, line, column LIMIT $limit
+        "#;
+        let mut response = self
+            .db
+            .query(sql)
+            .bind(("symbol", symbol_id.to_string()))
+            .bind(("limit", limit as i64))
+            .await?;
+        Ok(response.take(0)?)
+    }
+
     async fn delete_project_symbols(&self, project_id: &str) -> Result<usize> {
         let sql = r#"
             BEGIN TRANSACTION;
             DELETE symbol_relation WHERE project_id = $project_id;
+            DELETE unresolved_reference WHERE project_id = $project_id;
+            DELETE reference_occurrence WHERE project_id = $project_id;
             DELETE code_symbols WHERE project_id = $project_id;
             COMMIT TRANSACTION;
         "#;
@@ -1158,6 +1519,14 @@ impl StorageBackend for SurrealStorage {
                 SELECT id FROM code_symbols
                 WHERE project_id = $project_id AND file_path = $file_path
             );
+            DELETE unresolved_reference WHERE project_id = $project_id AND file_path = $file_path;
+            DELETE reference_occurrence WHERE project_id = $project_id AND file_path = $file_path;
+            -- Other files' references to these symbols no longer resolve
+            UPDATE reference_occurrence SET to_symbol = NONE, reason = 'not_found'
+                WHERE to_symbol IN (
+                    SELECT VALUE string::concat('code_symbols:', meta::id(id)) FROM code_symbols
+                    WHERE project_id = $project_id AND file_path = $file_path
+                );
             DELETE code_symbols WHERE project_id = $project_id AND file_path = $file_path;
             COMMIT TRANSACTION;
         "#;
@@ -1170,6 +1539,138 @@ impl StorageBackend for SurrealStorage {
         Ok(0)
     }
 
+    async fn get_symbols_by_path(
+        &self,
+        project_id: &str,
+        file_path: &str,
+    ) -> Result<Vec<CodeSymbol>> {
+        let sql =
+            "SELECT * FROM code_symbols WHERE project_id = $project_id AND file_path = $file_path";
+        let mut response = self
+            .db
+            .query(sql)
+            .bind(("project_id", project_id.to_string()))
+            .bind(("file_path", file_path.to_string()))
+            .await?;
+        let symbols: Vec<CodeSymbol> = response.take(0)?;
+        Ok(symbols)
+    }
+
+    async fn get_symbols_by_ids(&self, ids: &[String]) -> Result<Vec<CodeSymbol>> {
+        let things = ids
+            .iter()
+            .map(|id| id.strip_prefix("code_symbols:").unwrap_or(id))
+            .filter_map(|key| crate::types::ThingId::new("code_symbols", key).ok())
+            .map(|t| t.to_thing())
+            .collect::<Vec<_>>();
+        if things.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut response = self
+            .db
+            .query("SELECT * FROM code_symbols WHERE id IN $ids")
+            .bind(("ids", things))
+            .await?;
+        Ok(response.take(0)?)
+    }
+
+    async fn get_symbols_by_name(
+        &self,
+        project_id: &str,
+        names: &[String],
+        symbol_types: &[SymbolType],
+    ) -> Result<Vec<CodeSymbol>> {
+        if names.is_empty() {
+            return Ok(vec![]);
+        }
+        let types: Vec<String> = symbol_types.iter().map(|t| t.to_string()).collect();
+        let sql = "SELECT * FROM code_symbols WHERE project_id = $project_id AND name IN $names AND symbol_type IN $types ORDER BY file_path, start_line";
+        let mut response = self
+            .db
+            .query(sql)
+            .bind(("project_id", project_id.to_string()))
+            .bind(("names", names.to_vec()))
+            .bind(("types", types))
+            .await?;
+        Ok(response.take(0)?)
+    }
+
+    async fn delete_outgoing_relations_by_path(
+        &self,
+        project_id: &str,
+        file_path: &str,
+    ) -> Result<()> {
+        let sql = r#"
+            BEGIN TRANSACTION;
+            DELETE symbol_relation WHERE (project_id = $project_id AND file_path = $file_path)
+                OR in IN (
+                    SELECT VALUE id FROM code_symbols
+                    WHERE project_id = $project_id AND file_path = $file_path
+                );
+            DELETE unresolved_reference WHERE project_id = $project_id AND file_path = $file_path;
+            DELETE reference_occurrence WHERE project_id = $project_id AND file_path = $file_path;
+            COMMIT TRANSACTION;
+        "#;
+        let _ = self
+            .db
+            .query(sql)
+            .bind(("project_id", project_id.to_string()))
+            .bind(("file_path", file_path.to_string()))
+            .await?;
+        Ok(())
+    }
+
+    async fn remove_symbol(&self, symbol_id: &str, replacement: Option<&str>) -> Result<usize> {
+        let old = parse_thing(symbol_id)?;
+
+        let mut relinked = 0;
+        if let Some(replacement) = replacement {
+            let new = parse_thing(replacement)?;
+            let mut response = self
+                .db
+                .query("SELECT * FROM symbol_relation WHERE `out` = $id AND `in` != $id")
+                .bind(("id", old.clone()))
+                .await?;
+            let raw: surrealdb_types::Value = response.take(0)?;
+            let relations = value_to_symbol_relations(raw)
+                .into_iter()
+                .map(|edge| {
+                    SymbolRelation::new(
+                        edge.from_symbol,
+                        new.clone(),
+                        edge.relation_type,
+                        edge.file_path,
+                        edge.line_number,
+                        edge.project_id,
+                    )
+                    .with_source(edge.source)
+                })
+                .collect();
+            relinked = self.create_symbol_relations_batch(relations).await?;
+        }
+
+        let sql = r#"
+            BEGIN TRANSACTION;
+            IF $new != NONE {
+                UPDATE reference_occurrence SET to_symbol = $new WHERE to_symbol = $old;
+            } ELSE {
+                UPDATE reference_occurrence SET to_symbol = NONE, reason = 'not_found'
+                    WHERE to_symbol = $old;
+            };
+            DELETE symbol_relation WHERE `in` = $id OR `out` = $id;
+            DELETE $id;
+            COMMIT TRANSACTION;
+        "#;
+        let _ = self
+            .db
+            .query(sql)
+            .bind(("old", crate::types::record_id_string(&old)))
+            .bind(("new", replacement.map(|r| r.to_string())))
+            .bind(("id", old))
+            .await?;
+        Ok(relinked)
+    }
+
     async fn get_project_symbols(&self, project_id: &str) -> Result<Vec<CodeSymbol>> {
         let sql = "SELECT * FROM code_symbols WHERE project_id = $project_id";
         let mut response = self
@@ -1317,6 +1818,24 @@ impl StorageBackend for SurrealStorage {
         Ok((symbols, relations))
     }
 
+    async fn get_project_relations(
+        &self,
+        project_id: &str,
+        relation_types: &[CodeRelationType],
+    ) -> Result<Vec<SymbolRelation>> {
+        let types: Vec<String> = relation_types.iter().map(|t| t.to_string()).collect();
+        let sql = "SELECT * FROM symbol_relation WHERE project_id = $project_id AND relation_type IN $types";
+        let mut response = self
+            .db
+            .query(sql)
+            .bind(("project_id", project_id.to_string()))
+            .bind(("types", types))
+            .await?;
+        // Use Value intermediary to bypass SurrealValue RecordId bug
+        let raw: surrealdb_types::Value = response.take(0)?;
+        Ok(value_to_symbol_relations(raw))
+    }
+
     async fn get_code_subgraph(
         &self,
         symbol_ids: &[String],
@@ -1395,7 +1914,8 @@ impl StorageBackend for SurrealStorage {
     ) -> Result<(Vec<CodeSymbol>, u32)> {
         let limit = limit.clamp(1, 100);
 
-        let mut conditions = vec!["(string::lowercase(name) CONTAINS string::lowercase($query) OR string::lowercase(signature) CONTAINS string::lowercase($query))".to_string()];
+        // signature, qualified_name and doc are NONE on many symbols
+        let mut conditions = vec!["(string::lowercase(name) CONTAINS string::lowercase($query) OR string::lowercase(signature ?? '') CONTAINS string::lowercase($query) OR string::lowercase(qualified_name ?? '') CONTAINS string::lowercase($query) OR string::lowercase(doc ?? '') CONTAINS string::lowercase($query))".to_string()];
 
         if project_id.is_some() {
             conditions.push("project_id = $project_id".to_string());
@@ -1519,11 +2039,11 @@ impl StorageBackend for SurrealStorage {
         Ok(result.map(|r| r.count).unwrap_or(0))
     }
 
-    async fn count_symbol_relations(&self, project_id: &str) -> Result<u32> {
+    async fn count_unresolved_references(&self, project_id: &str) -> Result<Vec<(String, u32)>> {
         let sql = r#"
-            SELECT count() FROM symbol_relation 
-            WHERE project_id = $project_id 
-            GROUP ALL
+            SELECT reason, count() AS count FROM unresolved_reference
+            WHERE project_id = $project_id
+            GROUP BY reason
         "#;
         let mut response = self
             .db
@@ -1532,64 +2052,51 @@ impl StorageBackend for SurrealStorage {
             .await?;
 
         #[derive(serde::Deserialize, SurrealValue)]
-        struct CountResult {
+        struct ReasonCount {
+            reason: String,
             count: u32,
         }
 
-        let result: Option<CountResult> = response.take(0)?;
-        Ok(result.map(|r| r.count).unwrap_or(0))
+        let rows: Vec<ReasonCount> = response.take(0)?;
+        Ok(rows.into_iter().map(|r| (r.reason, r.count)).collect())
     }
 
-    async fn find_symbol_by_name(
-        &self,
-        project_id: &str,
-        name: &str,
-    ) -> Result<Option<CodeSymbol>> {
+    async fn get_unresolved_names(&self, project_id: &str, reason: &str) -> Result<Vec<String>> {
         let sql = r#"
-            SELECT * FROM code_symbols 
-            WHERE project_id = $project_id AND name = $name 
-            LIMIT 1
+            SELECT VALUE name FROM unresolved_reference
+            WHERE project_id = $project_id AND reason = $reason
         "#;
         let mut response = self
             .db
             .query(sql)
             .bind(("project_id", project_id.to_string()))
-            .bind(("name", name.to_string()))
+            .bind(("reason", reason.to_string()))
             .await?;
-
-        let symbols: Vec<CodeSymbol> = response.take(0)?;
-        Ok(symbols.into_iter().next())
+        let mut names: Vec<String> = response.take(0)?;
+        names.sort();
+        names.dedup();
+        Ok(names)
     }
 
-    async fn find_symbol_by_name_with_context(
-        &self,
-        project_id: &str,
-        name: &str,
-        prefer_file: Option<&str>,
-    ) -> Result<Option<CodeSymbol>> {
-        // Try same file first for better resolution
-        if let Some(file) = prefer_file {
-            let sql = r#"
-            SELECT * FROM code_symbols 
-            WHERE project_id = $project_id AND name = $name AND file_path = $file
-            LIMIT 1
+    async fn count_symbol_relations(&self, project_id: &str) -> Result<u32> {
+        let sql = r#"
+            SELECT count() FROM symbol_relation 
+            WHERE project_id = $project_id 
+            GROUP ALL
         "#;
-            let mut response = self
-                .db
-                .query(sql)
-                .bind(("project_id", project_id.to_string()))
-                .bind(("name", name.to_string()))
-                .bind(("file", file.to_string()))
-                .await?;
+        let mut response = self
+            .db
+            .query(sql)
+            .bind(("project_id", project_id.to_string()))
+            .await?;
 
-            let symbols: Vec<CodeSymbol> = response.take(0)?;
-            if let Some(sym) = symbols.into_iter().next() {
-                return Ok(Some(sym));
-            }
+        #[derive(serde::Deserialize, SurrealValue)]
+        struct CountResult {
+            count: u32,
         }
 
-        // Fallback to any file in project
-        self.find_symbol_by_name(project_id, name).await
+        let result: Option<CountResult> = response.take(0)?;
+        Ok(result.map(|r| r.count).unwrap_or(0))
     }
 
     async fn health_check(&self) -> Result<bool> {
@@ -1608,6 +2115,9 @@ impl StorageBackend for SurrealStorage {
             "code_chunks",
             "code_symbols",
             "symbol_relation",
+            "unresolved_reference",
+            "reference_occurrence",
+            "git_commits",
             "index_status",
         ];
         for table in &tables {
@@ -1853,6 +2363,163 @@ mod tests {
         let callers = storage.get_symbol_callers(&callee_id).await.unwrap();
         assert_eq!(callers.len(), 1, "Should find 1 caller");
         assert_eq!(callers[0].name, "main");
+
+        // 5. Search tolerates symbols without signature or docs
+        let (found, total) = storage
+            .search_symbols("help", Some("test_project"), 10, 0, None, None)
+            .await
+            .unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(found[0].name, "helper");
+    }
+
+    #[tokio::test]
+    async fn test_symbol_relations_batch() {
+        let (storage, _tmp) = setup_test_db().await;
+        use crate::types::{CodeRelationType, CodeSymbol, SymbolRelation, SymbolType};
+
+        let symbol = |name: &str, line: u32| {
+            CodeSymbol::new(
+                name.to_string(),
+                SymbolType::Function,
+                "lib.rs".to_string(),
+                line,
+                line + 2,
+                "test_project".to_string(),
+            )
+        };
+        let symbols = vec![symbol("main", 1), symbol("a", 5), symbol("b", 9)];
+        let ids = storage.create_code_symbols_batch(symbols).await.unwrap();
+        let thing = |id: &str| parse_thing(id).unwrap();
+
+        let relations = ids[1..]
+            .iter()
+            .map(|callee| {
+                SymbolRelation::new(
+                    thing(&ids[0]),
+                    thing(callee),
+                    CodeRelationType::Calls,
+                    "lib.rs".to_string(),
+                    2,
+                    "test_project".to_string(),
+                )
+            })
+            .collect();
+        let created = storage
+            .create_symbol_relations_batch(relations)
+            .await
+            .unwrap();
+        assert_eq!(created, 2);
+
+        let mut callees: Vec<String> = storage
+            .get_symbol_callees(&ids[0])
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        callees.sort();
+        assert_eq!(callees, vec!["a", "b"]);
+        assert_eq!(
+            storage
+                .count_symbol_relations("test_project")
+                .await
+                .unwrap(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_symbols_by_name() {
+        let (storage, _tmp) = setup_test_db().await;
+        use crate::types::{CodeSymbol, SymbolType};
+
+        let symbol = |name: &str, symbol_type: SymbolType, file_path: &str, project_id: &str| {
+            CodeSymbol::new(
+                name.to_string(),
+                symbol_type,
+                file_path.to_string(),
+                1,
+                3,
+                project_id.to_string(),
+            )
+        };
+        storage
+            .create_code_symbols_batch(vec![
+                symbol("Config", SymbolType::Struct, "b.rs", "p"),
+                symbol("Config", SymbolType::Class, "b.rs", "p"),
+                symbol("load_config", SymbolType::Function, "a.rs", "p"),
+                symbol("ConfigError", SymbolType::Enum, "a.rs", "p"),
+                symbol("Config", SymbolType::Enum, "a.rs", "other"),
+            ])
+            .await
+            .unwrap();
+
+        let found = storage
+            .get_symbols_by_name(
+                "p",
+                &["Config".to_string()],
+                &[SymbolType::Struct, SymbolType::Enum],
+            )
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].symbol_type, SymbolType::Struct);
+        assert_eq!(found[0].file_path, "b.rs");
+    }
+
+    #[tokio::test]
+    async fn test_delete_symbols_by_path_unlinks_occurrences() {
+        let (storage, _tmp) = setup_test_db().await;
+        use crate::types::{CodeRelationType, CodeSymbol, ReferenceOccurrence, SymbolType};
+
+        let helper = CodeSymbol::new(
+            "helper".to_string(),
+            SymbolType::Function,
+            "helper.rs".to_string(),
+            1,
+            3,
+            "p".to_string(),
+        );
+        let helper_id = storage.create_code_symbol(helper).await.unwrap();
+        let occurrence = ReferenceOccurrence {
+            id: None,
+            project_id: "p".to_string(),
+            file_path: "main.rs".to_string(),
+            name: "helper".to_string(),
+            relation_type: CodeRelationType::Calls,
+            line: 2,
+            column: 4,
+            from_symbol: "global".to_string(),
+            to_symbol: Some(helper_id.clone()),
+            reason: None,
+            module: None,
+            source: Default::default(),
+        };
+        storage
+            .create_reference_occurrences_batch(vec![occurrence])
+            .await
+            .unwrap();
+        assert_eq!(
+            storage
+                .get_symbol_occurrences(&helper_id, 10)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        storage
+            .delete_symbols_by_path("p", "helper.rs")
+            .await
+            .unwrap();
+        let found = storage
+            .get_line_occurrences("p", "main.rs", 2)
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].to_symbol, None);
+        assert_eq!(found[0].reason.as_deref(), Some("not_found"));
     }
 
     #[tokio::test]
@@ -1937,6 +2604,7 @@ mod tests {
                 end_line: 3,
                 chunk_type: ChunkType::Function,
                 name: Some(format!("test_{}", i)),
+                context: None,
                 embedding: Some(vec![0.1; 768]),
                 content_hash: format!("hash_{}", i),
                 project_id: Some("test_project".to_string()),
@@ -1957,6 +2625,61 @@ mod tests {
         assert_eq!(results.len(), 50);
     }
 
+    #[tokio::test]
+    async fn test_rename_file() {
+        let (storage, _tmp) = setup_test_db().await;
+
+        let chunks: Vec<CodeChunk> = (0..2)
+            .map(|i| CodeChunk {
+                id: None,
+                file_path: "src/old.rs".to_string(),
+                content: format!("fn part_{}() {{}}", i),
+                language: Language::Rust,
+                start_line: i * 10 + 1,
+                end_line: i * 10 + 5,
+                chunk_type: ChunkType::Function,
+                name: Some(format!("part_{}", i)),
+                context: None,
+                embedding: None,
+                content_hash: format!("part_hash_{}", i),
+                project_id: Some("rename_project".to_string()),
+                indexed_at: Datetime::default(),
+            })
+            .collect();
+        storage.create_code_chunks_batch(chunks).await.unwrap();
+        storage
+            .set_file_hash("rename_project", "src/old.rs", "abc")
+            .await
+            .unwrap();
+
+        let moved = storage
+            .rename_file("rename_project", "src/old.rs", "src/new.rs")
+            .await
+            .unwrap();
+        assert_eq!(moved, 2);
+        assert_eq!(
+            storage
+                .get_chunks_by_path("rename_project", "src/new.rs")
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            storage
+                .get_file_hash("rename_project", "src/new.rs")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("abc")
+        );
+        assert!(storage
+            .get_file_hash("rename_project", "src/old.rs")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
     #[tokio::test]
     async fn test_batch_update_embeddings() {
         let (storage, _tmp) = setup_test_db().await;
@@ -1971,6 +2694,7 @@ mod tests {
                 end_line: 3,
                 chunk_type: ChunkType::Function,
                 name: Some(format!("embed_{}", i)),
+                context: None,
                 embedding: None,
                 content_hash: format!("embed_hash_{}", i),
                 project_id: Some("embed_project".to_string()),
@@ -5,11 +5,12 @@
 
 use crate::types::Datetime;
 use async_trait::async_trait;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::types::{
-    CodeChunk, CodeSymbol, Direction, Entity, IndexStatus, Memory, MemoryUpdate, Relation,
-    ScoredCodeChunk, SearchResult, SymbolRelation,
+    CodeChunk, CodeRelationType, CodeSymbol, Direction, Entity, FileChurn, GitCommit, IndexStatus,
+    Memory, MemoryUpdate, ReferenceOccurrence, Relation, ScoredCodeChunk, SearchResult,
+    SymbolRelation, SymbolType, UnresolvedReference,
 };
 use crate::Result;
 
@@ -162,6 +163,18 @@ pub trait StorageBackend: Send + Sync {
     async fn get_chunks_by_path(&self, project_id: &str, file_path: &str)
         -> Result<Vec<CodeChunk>>;
 
+    /// Get a code chunk by id (`code_chunks:...` or the bare key)
+    async fn get_code_chunk(&self, id: &str) -> Result<Option<CodeChunk>>;
+
+    /// Embedded chunks of a project at least `min_lines` long, with their
+    /// embeddings, at most `limit` in file order
+    async fn get_embedded_chunks(
+        &self,
+        project_id: &str,
+        min_lines: u32,
+        limit: usize,
+    ) -> Result<Vec<CodeChunk>>;
+
     /// Get indexing status for a project
     async fn get_index_status(&self, project_id: &str) -> Result<Option<IndexStatus>>;
 
@@ -181,6 +194,9 @@ pub trait StorageBackend: Send + Sync {
     /// Get stored file hash for incremental index comparison
     async fn get_file_hash(&self, project_id: &str, file_path: &str) -> Result<Option<String>>;
 
+    /// All stored file hashes for a project, keyed by path
+    async fn get_file_hashes(&self, project_id: &str) -> Result<HashMap<String, String>>;
+
     /// Set/update file hash after indexing
     async fn set_file_hash(&self, project_id: &str, file_path: &str, hash: &str) -> Result<()>;
 
@@ -190,6 +206,10 @@ pub trait StorageBackend: Send + Sync {
     /// Delete file hash for a specific file (used when file is deleted)
     async fn delete_file_hash(&self, project_id: &str, file_path: &str) -> Result<()>;
 
+    /// Move a file's chunks (with their embeddings) and stored hash to a new
+    /// path. Returns the number of chunks moved.
+    async fn rename_file(&self, project_id: &str, from: &str, to: &str) -> Result<usize>;
+
     // ─────────────────────────────────────────────────────────────────────────
     // Code Graph operations
     // ─────────────────────────────────────────────────────────────────────────
@@ -213,12 +233,76 @@ pub trait StorageBackend: Send + Sync {
     /// Create a relation between code symbols
     async fn create_symbol_relation(&self, relation: SymbolRelation) -> Result<String>;
 
+    /// Create many relations between code symbols in one round trip
+    async fn create_symbol_relations_batch(&self, relations: Vec<SymbolRelation>) -> Result<usize>;
+
+    /// Record references that could not be resolved to a project symbol
+    async fn create_unresolved_references_batch(
+        &self,
+        references: Vec<UnresolvedReference>,
+    ) -> Result<usize>;
+
+    /// Record reference positions, resolved or not
+    async fn create_reference_occurrences_batch(
+        &self,
+        occurrences: Vec<ReferenceOccurrence>,
+    ) -> Result<usize>;
+
+    /// Reference occurrences on one line of a file, by column
+    async fn get_line_occurrences(
+        &self,
+        project_id: &str,
+        file_path: &str,
+        line: u32,
+    ) -> Result<Vec<ReferenceOccurrence>>;
+
+    /// Occurrences resolved to a symbol, by file and position
+    async fn get_symbol_occurrences(
+        &self,
+        symbol_id: &str,
+        limit: usize,
+    ) -> Result<Vec<ReferenceOccurrence>>;
+
     /// Delete all symbols for a project
     async fn delete_project_symbols(&self, project_id: &str) -> Result<usize>;
 
     /// Delete all symbols for a specific file
     async fn delete_symbols_by_path(&self, project_id: &str, file_path: &str) -> Result<usize>;
 
+    /// Get the symbols defined in one file
+    async fn get_symbols_by_path(
+        &self,
+        project_id: &str,
+        file_path: &str,
+    ) -> Result<Vec<CodeSymbol>>;
+
+    /// Get symbols by record id (`code_symbols:...` or bare key). Missing
+    /// ids are skipped.
+    async fn get_symbols_by_ids(&self, ids: &[String]) -> Result<Vec<CodeSymbol>>;
+
+    /// Get the symbols of a project named exactly one of `names` with one
+    /// of `symbol_types`
+    async fn get_symbols_by_name(
+        &self,
+        project_id: &str,
+        names: &[String],
+        symbol_types: &[SymbolType],
+    ) -> Result<Vec<CodeSymbol>>;
+
+    /// Delete the relations a file's code creates (edges out of its symbols or
+    /// its top-level code), its unresolved references and its reference
+    /// occurrences. Edges pointing into the file from elsewhere are kept.
+    async fn delete_outgoing_relations_by_path(
+        &self,
+        project_id: &str,
+        file_path: &str,
+    ) -> Result<()>;
+
+    /// Delete a symbol and its edges. Inbound edges and occurrences are first
+    /// re-pointed at `replacement` when one is given; otherwise occurrences
+    /// become unresolved. Returns the number of edges re-linked.
+    async fn remove_symbol(&self, symbol_id: &str, replacement: Option<&str>) -> Result<usize>;
+
     /// Get all symbols for a project (for building cross-file SymbolIndex)
     async fn get_project_symbols(&self, project_id: &str) -> Result<Vec<CodeSymbol>>;
 
@@ -236,6 +320,13 @@ pub trait StorageBackend: Send + Sync {
         direction: Direction,
     ) -> Result<(Vec<CodeSymbol>, Vec<SymbolRelation>)>;
 
+    /// All edges of a project with one of `relation_types`
+    async fn get_project_relations(
+        &self,
+        project_id: &str,
+        relation_types: &[CodeRelationType],
+    ) -> Result<Vec<SymbolRelation>>;
+
     /// Get code subgraph for a set of symbol IDs (for recall_code PageRank)
     async fn get_code_subgraph(
         &self,
@@ -253,6 +344,42 @@ pub trait StorageBackend: Send + Sync {
         path_prefix: Option<&str>,
     ) -> Result<(Vec<CodeSymbol>, u32)>;
 
+    // ─────────────────────────────────────────────────────────────────────────
+    // Git history
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Store commits not yet indexed for their project
+    async fn create_commits_batch(&self, commits: Vec<GitCommit>) -> Result<usize>;
+
+    /// Hashes of the commits already indexed for a project
+    async fn get_commit_hashes(&self, project_id: &str) -> Result<HashSet<String>>;
+
+    /// Search commit messages by keyword, best matches (then newest) first
+    async fn search_commits(
+        &self,
+        query: &str,
+        project_id: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<GitCommit>>;
+
+    /// Commits that touched a file, newest first
+    async fn get_file_commits(
+        &self,
+        project_id: &str,
+        file_path: &str,
+        limit: usize,
+    ) -> Result<Vec<GitCommit>>;
+
+    /// Change frequency and last change of each path that has history
+    async fn get_file_churn(
+        &self,
+        project_id: &str,
+        file_paths: &[String],
+    ) -> Result<HashMap<String, FileChurn>>;
+
+    /// Delete a project's indexed history
+    async fn delete_project_commits(&self, project_id: &str) -> Result<()>;
+
     // ─────────────────────────────────────────────────────────────────────────
     // Statistics & Counts
     // ─────────────────────────────────────────────────────────────────────────
@@ -272,20 +399,11 @@ pub trait StorageBackend: Send + Sync {
     /// Count symbol relations for a project (useful for debugging graph)
     async fn count_symbol_relations(&self, project_id: &str) -> Result<u32>;
 
-    /// Find a symbol by name across the project (for cross-file resolution)
-    async fn find_symbol_by_name(
-        &self,
-        project_id: &str,
-        name: &str,
-    ) -> Result<Option<crate::types::symbol::CodeSymbol>>;
+    /// Count unresolved references per reason (`external`, `ambiguous`, `not_found`)
+    async fn count_unresolved_references(&self, project_id: &str) -> Result<Vec<(String, u32)>>;
 
-    /// Find symbol by name with file preference for better resolution
-    async fn find_symbol_by_name_with_context(
-        &self,
-        project_id: &str,
-        name: &str,
-        prefer_file: Option<&str>,
-    ) -> Result<Option<CodeSymbol>>;
+    /// Distinct names of unresolved references with the given reason
+    async fn get_unresolved_names(&self, project_id: &str, reason: &str) -> Result<Vec<String>>;
 
     // ─────────────────────────────────────────────────────────────────────────
     // System
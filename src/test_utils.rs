@@ -1,3 +1,5 @@
+use rmcp::model::CallToolResult;
+use std::path::Path;
 use std::sync::Arc;
 use tempfile::TempDir;
 
@@ -7,6 +9,7 @@ use crate::embedding::{
     ModelType,
 };
 use crate::storage::SurrealStorage;
+use crate::types::{CodeRelationType, CodeSymbol, SymbolRelation, SymbolType, Thing};
 
 pub struct TestContext {
     pub state: Arc<AppState>,
@@ -15,6 +18,11 @@ pub struct TestContext {
 
 impl TestContext {
     pub async fn new() -> Self {
+        Self::with_config(|_| {}).await
+    }
+
+    /// Context whose config is adjusted by `configure` before use.
+    pub async fn with_config(configure: impl FnOnce(&mut AppConfig)) -> Self {
         let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
         let db_path = temp_dir.path();
 
@@ -52,7 +60,7 @@ impl TestContext {
         let (queue_tx, _queue_rx) = tokio::sync::mpsc::channel(1000);
         let adaptive_queue = AdaptiveEmbeddingQueue::with_defaults(queue_tx, metrics);
 
-        let config = AppConfig {
+        let mut config = AppConfig {
             data_dir: db_path.to_path_buf(),
             model: "mock".to_string(),
             cache_size: 100,
@@ -60,7 +68,14 @@ impl TestContext {
 
             timeout_ms: 5000,
             log_level: "debug".to_string(),
+            embedding_text: Default::default(),
+            watch_mode: Default::default(),
+            index_budget: Default::default(),
+            path_map: Default::default(),
+            max_file_size: crate::config::DEFAULT_MAX_FILE_SIZE,
+            history_depth: 0,
         };
+        configure(&mut config);
 
         let state = Arc::new(AppState {
             config,
@@ -69,6 +84,7 @@ impl TestContext {
             embedding_store,
             embedding_queue: adaptive_queue,
             progress: crate::config::IndexProgressTracker::new(),
+            symbol_indexes: crate::codebase::SymbolIndexRegistry::new(),
             db_semaphore: Arc::new(tokio::sync::Semaphore::new(10)),
         });
 
@@ -78,3 +94,150 @@ impl TestContext {
         }
     }
 }
+
+/// The JSON payload of a tool result.
+pub fn response_json(result: &CallToolResult) -> serde_json::Value {
+    match &result.content[0].raw {
+        rmcp::model::RawContent::Text(t) => serde_json::from_str(&t.text).unwrap(),
+        _ => panic!("Expected text content"),
+    }
+}
+
+/// A symbol in `lib.rs` of project `p` whose record id is `code_symbols:<key>`.
+pub fn symbol(key: &str, name: &str, symbol_type: SymbolType, lines: (u32, u32)) -> CodeSymbol {
+    let mut s = CodeSymbol::new(
+        name.to_string(),
+        symbol_type,
+        "lib.rs".to_string(),
+        lines.0,
+        lines.1,
+        "p".to_string(),
+    );
+    s.id = Some(Thing::new("code_symbols", key));
+    s
+}
+
+/// A relation between the symbols keyed `from` and `to`, as [`symbol`] ids them.
+pub fn edge(from: &str, to: &str, relation_type: CodeRelationType) -> SymbolRelation {
+    SymbolRelation::new(
+        Thing::new("code_symbols", from),
+        Thing::new("code_symbols", to),
+        relation_type,
+        "lib.rs".to_string(),
+        1,
+        "p".to_string(),
+    )
+}
+
+/// A git repository built with gix from the files on disk, so tests that
+/// need commits do not depend on a git CLI being installed.
+pub struct TestRepo {
+    repo: gix::Repository,
+    author: gix::actor::Signature,
+}
+
+impl TestRepo {
+    /// Initialize a repository at `root` with `main` checked out.
+    pub fn init(root: &Path) -> Self {
+        let mut repo = gix::init(root).expect("Failed to init repository");
+        // Reflog entries of branch and HEAD updates need a committer
+        repo.committer_or_set_generic_fallback()
+            .expect("Failed to set committer");
+        let repo = Self {
+            repo,
+            author: gix::actor::Signature {
+                name: "test".into(),
+                email: "test@example.com".into(),
+                time: gix::date::Time::default(),
+            },
+        };
+        repo.switch("main");
+        repo
+    }
+
+    /// Author and committer of the following commits.
+    pub fn with_author(mut self, name: &str, email: &str) -> Self {
+        self.author.name = name.into();
+        self.author.email = email.into();
+        self
+    }
+
+    /// Commit the work tree as it is on disk to the checked out branch,
+    /// returning the commit id.
+    pub fn commit(&self, message: &str) -> String {
+        self.commit_at(message, "2024-01-01T00:00:00+00:00")
+    }
+
+    /// [`TestRepo::commit`] with an ISO 8601 commit date.
+    pub fn commit_at(&self, message: &str, date: &str) -> String {
+        let mut author = self.author.clone();
+        author.time = gix::date::parse(date, None).expect("Invalid commit date");
+        let mut time = gix::date::parse::TimeBuf::default();
+        let signature = author.to_ref(&mut time);
+        let tree = self.write_tree(self.repo.workdir().expect("Bare repository"));
+        let parent = self.repo.head_id().ok().map(|id| id.detach());
+        self.repo
+            .commit_as(signature, signature, "HEAD", message, tree, parent)
+            .expect("Failed to commit")
+            .to_string()
+    }
+
+    /// Create `branch` at the current commit and check it out.
+    pub fn checkout_new_branch(&self, branch: &str) {
+        let head = self.repo.head_id().expect("No commit to branch from");
+        self.repo
+            .reference(
+                format!("refs/heads/{}", branch),
+                head,
+                gix::refs::transaction::PreviousValue::MustNotExist,
+                "branch",
+            )
+            .expect("Failed to create branch");
+        self.switch(branch);
+    }
+
+    /// Point HEAD at `branch`, which may not exist yet. The work tree is
+    /// left as it is.
+    fn switch(&self, branch: &str) {
+        use gix::refs::transaction::{Change, LogChange, PreviousValue, RefEdit};
+        let target = format!("refs/heads/{}", branch);
+        self.repo
+            .edit_reference(RefEdit {
+                change: Change::Update {
+                    log: LogChange::default(),
+                    expected: PreviousValue::Any,
+                    new: gix::refs::Target::Symbolic(target.try_into().unwrap()),
+                },
+                name: "HEAD".try_into().unwrap(),
+                deref: false,
+            })
+            .expect("Failed to update HEAD");
+    }
+
+    fn write_tree(&self, dir: &Path) -> gix::ObjectId {
+        use gix::objs::tree::{Entry, EntryKind};
+        let mut entries = Vec::new();
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_string_lossy().to_string();
+            let (kind, oid) = if name == ".git" {
+                continue;
+            } else if path.is_dir() {
+                (EntryKind::Tree, self.write_tree(&path))
+            } else {
+                let blob = self.repo.write_blob(std::fs::read(&path).unwrap());
+                (EntryKind::Blob, blob.unwrap().detach())
+            };
+            entries.push(Entry {
+                mode: kind.into(),
+                filename: name.into(),
+                oid,
+            });
+        }
+        entries.sort();
+        self.repo
+            .write_object(&gix::objs::Tree { entries })
+            .expect("Failed to write tree")
+            .detach()
+    }
+}
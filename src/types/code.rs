@@ -25,6 +25,11 @@ pub struct CodeChunk {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
 
+    /// Signatures of enclosing containers (`impl TokenValidator`), outermost
+    /// first, one per line. Set for chunks cut out of a larger class or impl.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub embedding: Option<Vec<f32>>,
 
@@ -45,6 +50,8 @@ pub enum ChunkType {
     Struct,
     Module,
     Impl,
+    /// Documentation section delimited by headings
+    Section,
     #[default]
     Other,
 }
@@ -59,10 +66,70 @@ pub enum Language {
     Go,
     Java,
     Dart,
+    Markdown,
+    ReStructuredText,
+    Text,
+    Yaml,
+    Toml,
+    Json,
+    Sql,
+    Protobuf,
+    GraphQL,
     #[default]
     Unknown,
 }
 
+impl Language {
+    /// Lowercase name, matching the serialized form.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Language::Rust => "rust",
+            Language::Python => "python",
+            Language::JavaScript => "javascript",
+            Language::TypeScript => "typescript",
+            Language::Go => "go",
+            Language::Java => "java",
+            Language::Dart => "dart",
+            Language::Markdown => "markdown",
+            Language::ReStructuredText => "restructuredtext",
+            Language::Text => "text",
+            Language::Yaml => "yaml",
+            Language::Toml => "toml",
+            Language::Json => "json",
+            Language::Sql => "sql",
+            Language::Protobuf => "protobuf",
+            Language::GraphQL => "graphql",
+            Language::Unknown => "unknown",
+        }
+    }
+
+    /// Prose formats chunked by heading hierarchy instead of by syntax tree.
+    pub fn is_document(&self) -> bool {
+        matches!(
+            self,
+            Language::Markdown | Language::ReStructuredText | Language::Text
+        )
+    }
+
+    /// Config and schema formats chunked by key, table or definition.
+    pub fn is_structured(&self) -> bool {
+        matches!(
+            self,
+            Language::Yaml
+                | Language::Toml
+                | Language::Json
+                | Language::Sql
+                | Language::Protobuf
+                | Language::GraphQL
+        )
+    }
+
+    /// Schema formats whose definitions become symbols.
+    pub fn is_schema(&self) -> bool {
+        matches!(self, Language::Sql | Language::Protobuf | Language::GraphQL)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
 pub struct IndexStatus {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -71,6 +138,18 @@ pub struct IndexStatus {
     pub project_id: String,
     pub status: IndexState,
 
+    /// Absolute project root on the server; stored paths are relative to it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+
+    /// Git commit the index reflects, for projects inside a repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+
+    /// Branch (or ref, for snapshots) of `commit`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+
     #[serde(default)]
     pub total_files: u32,
 
@@ -95,10 +174,76 @@ pub struct IndexStatus {
     #[serde(default)]
     pub failed_files: Vec<String>,
 
+    /// Files left out on purpose (too large, binary, generated), with why
+    #[serde(default)]
+    pub skipped_files: Vec<SkippedFile>,
+
     #[serde(default)]
     pub failed_embeddings: u32,
 }
 
+/// A commit from the project's git history, searchable by its message.
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct GitCommit {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Thing>,
+
+    pub project_id: String,
+    pub hash: String,
+    pub author: String,
+    pub email: String,
+    pub date: Datetime,
+    pub message: String,
+
+    /// Project-relative paths the commit added, modified or deleted
+    #[serde(default)]
+    pub files: Vec<String>,
+
+    /// Qualified names of the symbols whose definitions the commit changed
+    #[serde(default)]
+    pub symbols: Vec<String>,
+}
+
+/// How often and how recently a file changed, over the indexed history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileChurn {
+    pub commits: u32,
+    pub authors: u32,
+    pub last_modified: Datetime,
+    pub last_commit: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SurrealValue)]
+pub struct SkippedFile {
+    pub path: String,
+    pub reason: SkipReason,
+}
+
+/// Why a scanned file was not indexed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// Over the configured maximum file size
+    TooLarge,
+    /// Carries a generated-code marker
+    Generated,
+    /// Minified bundle
+    Minified,
+    /// Not text in any supported encoding
+    Binary,
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::TooLarge => write!(f, "too_large"),
+            SkipReason::Generated => write!(f, "generated"),
+            SkipReason::Minified => write!(f, "minified"),
+            SkipReason::Binary => write!(f, "binary"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum IndexState {
@@ -106,6 +251,20 @@ pub enum IndexState {
     EmbeddingPending,
     Completed,
     Failed,
+    /// Paused by request; resumes in place
+    Paused,
+    /// Stopped by request; the next run continues from the stored files
+    Cancelled,
+}
+
+impl IndexState {
+    /// Whether a run stopped part-way, leaving stored files to be relinked.
+    pub fn is_unfinished(&self) -> bool {
+        matches!(
+            self,
+            IndexState::Indexing | IndexState::Paused | IndexState::Cancelled
+        )
+    }
 }
 
 impl std::fmt::Display for IndexState {
@@ -115,6 +274,8 @@ impl std::fmt::Display for IndexState {
             IndexState::EmbeddingPending => write!(f, "embedding_pending"),
             IndexState::Completed => write!(f, "completed"),
             IndexState::Failed => write!(f, "failed"),
+            IndexState::Paused => write!(f, "paused"),
+            IndexState::Cancelled => write!(f, "cancelled"),
         }
     }
 }
@@ -125,6 +286,9 @@ impl IndexStatus {
             id: None,
             project_id,
             status: IndexState::Indexing,
+            root: None,
+            commit: None,
+            branch: None,
             total_files: 0,
             indexed_files: 0,
             total_chunks: 0,
@@ -133,6 +297,7 @@ impl IndexStatus {
             completed_at: None,
             error_message: None,
             failed_files: Vec::new(),
+            skipped_files: Vec::new(),
             failed_embeddings: 0,
         }
     }
@@ -71,8 +71,10 @@ impl_string_surreal_value!(
     ChunkType,
     Language,
     IndexState,
+    SkipReason,
     SymbolType,
     CodeRelationType,
+    RelationSource,
     Direction,
 );
 
@@ -85,13 +87,22 @@ pub fn record_key_to_string(key: &RecordIdKey) -> String {
     }
 }
 
-pub use code::{ChunkType, CodeChunk, IndexState, IndexStatus, Language};
+/// `table:key` form of a record id, as tools accept and string fields store it.
+pub fn record_id_string(id: &RecordId) -> String {
+    format!("{}:{}", id.table.as_str(), record_key_to_string(&id.key))
+}
+
+pub use code::{
+    ChunkType, CodeChunk, FileChurn, GitCommit, IndexState, IndexStatus, Language, SkipReason,
+    SkippedFile,
+};
 pub use embedding_state::{EmbedResult, EmbedTarget, EmbeddingState};
 pub use entity::{Direction, Entity, Relation};
 pub use error::{AppError, Result};
 pub use memory::{Memory, MemoryType, MemoryUpdate};
 pub use search::{CodeSearchResult, RecallResult, ScoredCodeChunk, ScoredMemory, SearchResult};
 pub use symbol::{
-    CodeReference, CodeRelationType, CodeSymbol, ScoredSymbol, SymbolRelation, SymbolType,
+    CodeReference, CodeRelationType, CodeSymbol, ReferenceOccurrence, RelationSource, ScoredSymbol,
+    SymbolRelation, SymbolType, UnresolvedReference,
 };
 pub use thing_id::ThingId;
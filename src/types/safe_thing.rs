@@ -3,65 +3,75 @@
 //! This module provides factory functions for creating SurrealDB Things
 //! with validated, safe IDs that won't cause panics or SQL injection.
 
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
-
+use super::symbol::SymbolType;
 use super::{RecordId, Thing};
 
 /// Creates a safe Thing for a code symbol using a deterministic hash.
 ///
-/// The hash is computed from (project_id, file_path, name, line) to ensure:
+/// The hash is computed from (project_id, file_path, qualified_name, kind,
+/// overload) to ensure:
 /// 1. Deterministic: same input → same ID
 /// 2. Safe: only hex characters, no special chars
-/// 3. Unique: different symbols get different IDs
+/// 3. Stable: editing lines above a symbol does not change its ID
 ///
 /// # Arguments
 /// * `project_id` - The project identifier
 /// * `file_path` - Path to the source file
-/// * `name` - Symbol name (may contain `::`, special chars)
-/// * `line` - Line number where symbol is defined
+/// * `qualified_name` - Qualified symbol name (may contain `::`, special chars)
+/// * `symbol_type` - Kind of symbol, so `struct Foo` and `impl Foo` differ
+/// * `overload` - Ordinal among same-named symbols of the same kind in the file
 ///
 /// # Example
 /// ```ignore
-/// let thing = symbol_thing("myproject", "src/lib.rs", "std::io::Read", 42);
+/// let thing = symbol_thing("myproject", "src/lib.rs", "crate::io::Read", SymbolType::Trait, 0);
 /// // Returns Thing { tb: "code_symbols", id: "a1b2c3d4e5f67890" }
 /// ```
-pub fn symbol_thing(project_id: &str, file_path: &str, name: &str, line: u32) -> Thing {
-    let safe_id = symbol_hash(project_id, file_path, name, line);
+pub fn symbol_thing(
+    project_id: &str,
+    file_path: &str,
+    qualified_name: &str,
+    symbol_type: SymbolType,
+    overload: u32,
+) -> Thing {
+    let safe_id = symbol_hash(project_id, file_path, qualified_name, symbol_type, overload);
     RecordId::new("code_symbols", safe_id)
 }
 
-/// Creates a safe Thing for a symbol relation endpoint.
-///
-/// Uses the same hashing as `symbol_thing` to ensure relations
-/// can be created between symbols consistently.
-pub fn symbol_relation_thing(project_id: &str, file_path: &str, name: &str, line: u32) -> Thing {
-    symbol_thing(project_id, file_path, name, line)
-}
-
 /// Computes a deterministic hash for a symbol.
 ///
+/// Stored edges and occurrences in other files point at this id, so it is
+/// built from explicitly encoded fields with blake3 rather than `Hash`, whose
+/// output may change across Rust releases or with the order of `SymbolType`
+/// variants. Each field is length-prefixed so `("ab", "c")` and `("a", "bc")`
+/// differ.
+///
 /// Returns a 16-character hex string that is safe for SurrealDB IDs.
-pub fn symbol_hash(project_id: &str, file_path: &str, name: &str, line: u32) -> String {
-    let mut hasher = DefaultHasher::new();
-    project_id.hash(&mut hasher);
-    file_path.hash(&mut hasher);
-    name.hash(&mut hasher);
-    line.hash(&mut hasher);
-    format!("{:016x}", hasher.finish())
+pub fn symbol_hash(
+    project_id: &str,
+    file_path: &str,
+    qualified_name: &str,
+    symbol_type: SymbolType,
+    overload: u32,
+) -> String {
+    let kind = serde_json::to_value(symbol_type)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default();
+    let mut hasher = blake3::Hasher::new();
+    for field in [project_id, file_path, qualified_name, kind.as_str()] {
+        hasher.update(&(field.len() as u64).to_le_bytes());
+        hasher.update(field.as_bytes());
+    }
+    hasher.update(&overload.to_le_bytes());
+    hasher.finalize().to_hex()[..16].to_string()
 }
 
-/// Creates a safe Thing for a code reference (caller/callee relationship).
+/// Creates a Thing standing for a file's top-level code.
 ///
-/// For references, we hash based on the containing symbol's context
-/// since references don't have their own unique identity.
-pub fn reference_thing(
-    project_id: &str,
-    file_path: &str,
-    from_symbol: &str,
-    from_line: u32,
-) -> Thing {
-    symbol_thing(project_id, file_path, from_symbol, from_line)
+/// References made outside any symbol (module-level calls, imports) use this
+/// as their source so they still attach to the file.
+pub fn file_scope_thing(project_id: &str, file_path: &str) -> Thing {
+    symbol_thing(project_id, file_path, "global", SymbolType::Module, 0)
 }
 
 #[cfg(test)]
@@ -71,7 +81,7 @@ mod tests {
     #[test]
     fn test_symbol_thing_with_colons() {
         // Should not panic with special characters
-        let thing = symbol_thing("project", "file.rs", "std::io::Read", 42);
+        let thing = symbol_thing("project", "file.rs", "std::io::Read", SymbolType::Trait, 0);
         assert_eq!(thing.table.as_str(), "code_symbols");
         // ID should be all hex characters
         assert!(crate::types::record_key_to_string(&thing.key)
@@ -81,8 +91,8 @@ mod tests {
 
     #[test]
     fn test_symbol_thing_deterministic() {
-        let thing1 = symbol_thing("p", "f.rs", "func", 10);
-        let thing2 = symbol_thing("p", "f.rs", "func", 10);
+        let thing1 = symbol_thing("p", "f.rs", "func", SymbolType::Function, 0);
+        let thing2 = symbol_thing("p", "f.rs", "func", SymbolType::Function, 0);
         assert_eq!(
             crate::types::record_key_to_string(&thing1.key),
             crate::types::record_key_to_string(&thing2.key)
@@ -91,8 +101,8 @@ mod tests {
 
     #[test]
     fn test_symbol_thing_different_for_different_inputs() {
-        let thing1 = symbol_thing("p", "f.rs", "func1", 10);
-        let thing2 = symbol_thing("p", "f.rs", "func2", 10);
+        let thing1 = symbol_thing("p", "f.rs", "func1", SymbolType::Function, 0);
+        let thing2 = symbol_thing("p", "f.rs", "func2", SymbolType::Function, 0);
         assert_ne!(
             crate::types::record_key_to_string(&thing1.key),
             crate::types::record_key_to_string(&thing2.key)
@@ -100,9 +110,14 @@ mod tests {
     }
 
     #[test]
-    fn test_symbol_thing_different_lines() {
-        let thing1 = symbol_thing("p", "f.rs", "func", 10);
-        let thing2 = symbol_thing("p", "f.rs", "func", 20);
+    fn test_symbol_thing_distinguishes_overloads_and_kinds() {
+        let thing1 = symbol_thing("p", "f.rs", "func", SymbolType::Function, 0);
+        let thing2 = symbol_thing("p", "f.rs", "func", SymbolType::Function, 1);
+        let thing3 = symbol_thing("p", "f.rs", "func", SymbolType::Struct, 0);
+        assert_ne!(
+            crate::types::record_key_to_string(&thing1.key),
+            crate::types::record_key_to_string(&thing3.key)
+        );
         assert_ne!(
             crate::types::record_key_to_string(&thing1.key),
             crate::types::record_key_to_string(&thing2.key)
@@ -111,10 +126,30 @@ mod tests {
 
     #[test]
     fn test_symbol_hash_length() {
-        let hash = symbol_hash("project", "file.rs", "symbol", 1);
+        let hash = symbol_hash("project", "file.rs", "symbol", SymbolType::Function, 0);
         assert_eq!(hash.len(), 16);
     }
 
+    #[test]
+    fn test_symbol_hash_is_pinned() {
+        // Stored ids must survive toolchain upgrades and new SymbolType
+        // variants; changing this value orphans every indexed edge
+        assert_eq!(
+            symbol_hash(
+                "project",
+                "src/lib.rs",
+                "crate::io::Read",
+                SymbolType::Trait,
+                0
+            ),
+            "a2b53610dd985d81"
+        );
+        assert_ne!(
+            symbol_hash("p", "ab", "c", SymbolType::Function, 0),
+            symbol_hash("p", "a", "bc", SymbolType::Function, 0)
+        );
+    }
+
     #[test]
     fn test_special_characters_in_name() {
         // Various problematic characters that could cause issues
@@ -134,7 +169,7 @@ mod tests {
         ];
 
         for name in names {
-            let thing = symbol_thing("p", "f.rs", name, 1);
+            let thing = symbol_thing("p", "f.rs", name, SymbolType::Function, 0);
             // Should not panic and ID should be safe
             assert!(crate::types::record_key_to_string(&thing.key)
                 .chars()
@@ -143,8 +178,8 @@ mod tests {
     }
 
     #[test]
-    fn test_reference_thing() {
-        let thing = reference_thing("project", "file.rs", "caller_func", 100);
+    fn test_file_scope_thing() {
+        let thing = file_scope_thing("project", "file.rs");
         assert_eq!(thing.table.as_str(), "code_symbols");
     }
 }
@@ -42,7 +42,13 @@ pub struct CodeSearchResult {
 #[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
 pub struct ScoredCodeChunk {
     pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
+    /// Relative to the project root
     pub file_path: String,
+    /// Path as the client sees it, filled in for results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub absolute_path: Option<String>,
     pub content: String,
     pub language: Language,
     pub start_line: u32,
@@ -50,5 +56,7 @@ pub struct ScoredCodeChunk {
     pub chunk_type: ChunkType,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
     pub score: f32,
 }
@@ -17,6 +17,10 @@ pub enum SymbolType {
     Module,
     Trait,
     Import,
+    /// SQL table or view
+    Table,
+    /// SQL column
+    Field,
 }
 
 impl std::fmt::Display for SymbolType {
@@ -31,11 +35,13 @@ impl std::fmt::Display for SymbolType {
             SymbolType::Module => write!(f, "module"),
             SymbolType::Trait => write!(f, "trait"),
             SymbolType::Import => write!(f, "import"),
+            SymbolType::Table => write!(f, "table"),
+            SymbolType::Field => write!(f, "field"),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum CodeRelationType {
     Calls,
@@ -43,6 +49,7 @@ pub enum CodeRelationType {
     Contains,
     Implements,
     Extends,
+    References,
 }
 
 impl std::fmt::Display for CodeRelationType {
@@ -53,6 +60,7 @@ impl std::fmt::Display for CodeRelationType {
             CodeRelationType::Contains => write!(f, "contains"),
             CodeRelationType::Implements => write!(f, "implements"),
             CodeRelationType::Extends => write!(f, "extends"),
+            CodeRelationType::References => write!(f, "references"),
         }
     }
 }
@@ -72,6 +80,18 @@ pub struct CodeSymbol {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub signature: Option<String>,
 
+    /// Module path plus enclosing symbols, e.g. `crate::auth::TokenValidator::check`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub qualified_name: Option<String>,
+
+    /// Qualified name of the innermost enclosing symbol
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+
+    /// Doc comment or docstring, markers stripped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub embedding: Option<Vec<f32>>,
 
@@ -97,6 +117,9 @@ impl CodeSymbol {
             end_line,
             project_id,
             signature: None,
+            qualified_name: None,
+            parent: None,
+            doc: None,
             embedding: None,
             indexed_at: Datetime::default(),
         }
@@ -107,49 +130,53 @@ impl CodeSymbol {
         self
     }
 
-    pub fn unique_key(&self) -> String {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-
-        let mut hasher = DefaultHasher::new();
-        self.project_id.hash(&mut hasher);
-        self.file_path.hash(&mut hasher);
-        self.name.hash(&mut hasher);
-        self.start_line.hash(&mut hasher);
+    pub fn with_doc(mut self, doc: String) -> Self {
+        self.doc = Some(doc);
+        self
+    }
 
-        format!("{:016x}", hasher.finish())
+    /// Record key: the assigned ID if the parser set one, otherwise derived
+    /// from the qualified (or plain) name as the first of its kind.
+    pub fn unique_key(&self) -> String {
+        match &self.id {
+            Some(id) => crate::types::record_key_to_string(&id.key),
+            None => crate::types::safe_thing::symbol_hash(
+                &self.project_id,
+                &self.file_path,
+                self.qualified_name.as_deref().unwrap_or(&self.name),
+                self.symbol_type,
+                0,
+            ),
+        }
     }
 }
 
 /// Reference to a symbol with full context for Thing creation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SymbolRef {
     pub name: String,
     pub file_path: String,
     pub line: u32,
+    pub symbol_type: SymbolType,
+    /// Record key of the symbol in `code_symbols`
+    pub key: String,
 }
 
 impl SymbolRef {
-    pub fn new(name: String, file_path: String, line: u32) -> Self {
-        Self {
-            name,
-            file_path,
-            line,
-        }
-    }
-
     /// Create SymbolRef from an existing CodeSymbol
     pub fn from_symbol(symbol: &CodeSymbol) -> Self {
         Self {
             name: symbol.name.clone(),
             file_path: symbol.file_path.clone(),
             line: symbol.start_line,
+            symbol_type: symbol.symbol_type,
+            key: symbol.unique_key(),
         }
     }
 
     /// Convert to SurrealDB Thing for relation creation
-    pub fn to_thing(&self, project_id: &str) -> Thing {
-        crate::types::safe_thing::symbol_thing(project_id, &self.file_path, &self.name, self.line)
+    pub fn to_thing(&self) -> Thing {
+        super::RecordId::new("code_symbols", self.key.as_str())
     }
 }
 
@@ -163,6 +190,9 @@ pub struct CodeReference {
     pub file_path: String,
     pub line: u32,
     pub column: u32,
+    /// Source module of an `Imports` reference, as written in the import
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
 }
 
 impl CodeReference {
@@ -181,6 +211,7 @@ pub struct CodeReferenceBuilder {
     file_path: Option<String>,
     line: Option<u32>,
     column: Option<u32>,
+    module: Option<String>,
 }
 
 impl CodeReferenceBuilder {
@@ -224,6 +255,11 @@ impl CodeReferenceBuilder {
         self
     }
 
+    pub fn module(mut self, module: impl Into<String>) -> Self {
+        self.module = Some(module.into());
+        self
+    }
+
     pub fn build(self) -> CodeReference {
         CodeReference {
             name: self.name.expect("name is required"),
@@ -234,6 +270,27 @@ impl CodeReferenceBuilder {
             file_path: self.file_path.expect("file_path is required"),
             line: self.line.expect("line is required"),
             column: self.column.expect("column is required"),
+            module: self.module,
+        }
+    }
+}
+
+/// What produced a code graph edge.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationSource {
+    /// Name-based resolution of tree-sitter parse results
+    #[default]
+    TreeSitter,
+    /// Compiler-accurate references from an imported SCIP index
+    Scip,
+}
+
+impl std::fmt::Display for RelationSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RelationSource::TreeSitter => write!(f, "tree_sitter"),
+            RelationSource::Scip => write!(f, "scip"),
         }
     }
 }
@@ -255,6 +312,9 @@ pub struct SymbolRelation {
     pub line_number: u32,
     pub project_id: String,
 
+    #[serde(default)]
+    pub source: RelationSource,
+
     #[serde(default = "default_datetime")]
     pub created_at: Datetime,
 }
@@ -276,9 +336,82 @@ impl SymbolRelation {
             file_path,
             line_number,
             project_id,
+            source: RelationSource::TreeSitter,
             created_at: Datetime::default(),
         }
     }
+
+    pub fn with_source(mut self, source: RelationSource) -> Self {
+        self.source = source;
+        self
+    }
+}
+
+/// A reference that could not be tied to a definition in the project: a call
+/// into a library, a name imported from a package, or one with several equally
+/// plausible targets.
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct UnresolvedReference {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Thing>,
+
+    pub project_id: String,
+    pub file_path: String,
+    /// Enclosing symbol of the reference, or `global`
+    pub from_symbol: String,
+    pub name: String,
+    pub relation_type: CodeRelationType,
+    pub line: u32,
+
+    /// Imported module, when the name came from an import
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+
+    /// `external`, `ambiguous` or `not_found`
+    pub reason: String,
+
+    #[serde(default = "default_datetime")]
+    pub created_at: Datetime,
+}
+
+/// One use of a name in the source, at its exact position. Edges keep a
+/// single line per (from, to) pair; occurrences keep every use, resolved or
+/// not, for position-based navigation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SurrealValue)]
+pub struct ReferenceOccurrence {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Thing>,
+
+    pub project_id: String,
+    pub file_path: String,
+    pub name: String,
+    pub relation_type: CodeRelationType,
+    /// 1-based
+    pub line: u32,
+    /// 0-based
+    pub column: u32,
+
+    /// Enclosing symbol of the reference, or `global`
+    pub from_symbol: String,
+    /// Record id (`code_symbols:...`) of the definition, when resolved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to_symbol: Option<String>,
+    /// Why `to_symbol` is missing: `external`, `ambiguous` or `not_found`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Imported module, when the name came from an import
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+
+    #[serde(default)]
+    pub source: RelationSource,
+}
+
+impl ReferenceOccurrence {
+    /// Whether `column` on the occurrence's line falls on its name
+    pub fn covers(&self, column: u32) -> bool {
+        self.column <= column && column < self.column + self.name.chars().count() as u32
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]